@@ -1,6 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 
-use slothjs::runtime::{EsprimaParser, Runtime};
+use slothjs::runtime::{EsprimaParser, NodejsParser, Runtime};
+use slothjs::Backend;
 
 fn esprima_eval() {
     let parser = EsprimaParser::new();
@@ -8,10 +9,54 @@ fn esprima_eval() {
 }
 
 fn benchmark_esprimaparser(c: &mut Criterion) {
-    c.bench_function("Runtime::<EsprimaParser>::load()", |b| {
-        b.iter(|| esprima_eval())
-    });
+    c.bench_function("Runtime::<EsprimaParser>::load()", |b| b.iter(esprima_eval));
 }
 
-criterion_group!(benchmarks, benchmark_esprimaparser);
+/// A Richards/DeltaBlue-like workload: constructors, prototype methods,
+/// property reads and writes in a loop.
+const PROPERTIES: &str = r#"
+function Task(id, priority, next) {
+    this.id = id; this.priority = priority; this.next = next; this.count = 0;
+}
+Task.prototype.run = function(packet) { this.count += packet.size; return this.next; };
+Task.prototype.isHeld = function() { return this.count > 1000000; };
+
+function Packet(link, size) { this.link = link; this.size = size; this.data = {a: 0, b: 0}; }
+Packet.prototype.add = function(k) { this.data.a += k; this.data.b = this.data.a - k; return this; };
+
+function Point(x, y) { this.x = x; this.y = y; }
+Point.prototype.plus = function(p) { return new Point(this.x + p.x, this.y + p.y); };
+
+function schedule(rounds) {
+    var tasks = null;
+    for (var i = 0; i < 10; i++) { tasks = new Task(i, i % 3, tasks); }
+    var packet = new Packet(null, 1), origin = new Point(0, 0), step = new Point(1, 2);
+    var total = 0;
+    for (var r = 0; r < rounds; r++) {
+        var task = tasks;
+        while (task !== null) {
+            task = task.isHeld() ? task.next : task.run(packet.add(r));
+        }
+        origin = origin.plus(step);
+        total += origin.x + packet.data.b;
+    }
+    return total + tasks.count;
+}
+schedule(200)
+"#;
+
+fn benchmark_properties(c: &mut Criterion) {
+    let mut group = c.benchmark_group("properties");
+    for backend in [Backend::TreeWalker, Backend::Bytecode] {
+        let mut js = Runtime::load(Box::new(NodejsParser::new())).expect("Runtime::load");
+        js.set_backend(backend);
+        let program = js.parse(PROPERTIES).expect("parse");
+        group.bench_function(format!("{:?}", backend), |b| {
+            b.iter(|| js.heap.run(&program).expect("run"))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benchmarks, benchmark_esprimaparser, benchmark_properties);
 criterion_main!(benchmarks);
@@ -6,13 +6,19 @@
 //! - `-J`, `--json` select [`JSONParser`] (deserialization of JSON ESTree)
 //! - without flags: if [`NodejsParser::NODE`] works, [`NodejsParser`] is used,
 //!   otherwise it falls back to [`EsprimaParser`]. Check the parser with `--debug`.
+//!
+//! Selecting a backend:
+//! - `-B`, `--bytecode` compile to bytecode and run it on the VM instead of walking the AST
+//!
+//! Limits:
+//! - `--fuel <STEPS>` aborts each evaluation after that many loop iterations and calls
+//! - `--memory-limit <BYTES>` aborts when the objects and strings take more memory
 
 // TODO: `-e` to evaluate snippets from command line
 // TODO: interpret the sources
 // TODO: `-j` for JSON output
 // TODO: readline, more human-friendly editing
 // TODO: tab completion?
-// TODO: register native `console.log` and alike
 
 use std::io;
 use std::io::prelude::*;
@@ -22,7 +28,7 @@ use atty::{self, Stream};
 
 use clap::Parser;
 use slothjs::runtime::{EsprimaParser, JSONParser, NodejsParser, Parser as JSParser, Runtime};
-use slothjs::source;
+use slothjs::{source, Backend};
 
 /// Reads stdin, parses and interprets it as one block.
 pub fn batch_main(sljs: &mut Runtime) -> io::Result<()> {
@@ -94,6 +100,18 @@ struct Args {
     /// Expect ESTree AST as JSON input
     #[clap(short = 'J', long, action)]
     json: bool,
+
+    /// Run programs on the bytecode VM instead of the tree walker
+    #[clap(short = 'B', long, action)]
+    bytecode: bool,
+
+    /// Abort each evaluation after this many steps (loop iterations and calls)
+    #[clap(long)]
+    fuel: Option<u64>,
+
+    /// Abort when objects and strings take more than this many bytes
+    #[clap(long)]
+    memory_limit: Option<usize>,
 }
 
 impl Args {
@@ -109,12 +127,7 @@ impl Args {
                     Box::new(EsprimaParser::new())
                 }
             }
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Only one of -E/-N/-J can be set",
-                ))
-            }
+            _ => return Err(io::Error::other("Only one of -E/-N/-J can be set")),
         })
     }
 }
@@ -128,6 +141,11 @@ fn main() -> io::Result<()> {
     }
 
     let mut sljs = Runtime::load(parser)?;
+    if args.bytecode {
+        sljs.set_backend(Backend::Bytecode);
+    }
+    sljs.set_fuel(args.fuel);
+    sljs.set_memory_limit(args.memory_limit);
 
     if atty::is(Stream::Stdin) {
         repl_main(&mut sljs)
@@ -33,12 +33,126 @@ impl fmt::Display for Statement {
     }
 }
 
+impl Function {
+    /// Reconstructs the source text of the function, e.g. for `Function.prototype.toString()`.
+    pub fn to_source_text(&self) -> String {
+        self.to_source().join("\n")
+    }
+}
+
+const INDENT: &str = "    ";
+
 trait ToSource {
-    const INDENT: &'static str = "    ";
+    const INDENT: &'static str = INDENT;
 
     fn to_source(&self) -> Vec<String>;
 }
 
+/// A one-line piece of source.
+fn text(s: &str) -> Vec<String> {
+    vec![s.to_string()]
+}
+
+/// Glues pieces of source together: the first line of each piece continues
+/// the last line of the previous piece.
+fn concat(pieces: Vec<Vec<String>>) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for piece in pieces {
+        let mut piece = piece.into_iter();
+        if let Some(first) = piece.next() {
+            lines.last_mut().unwrap().push_str(&first);
+        }
+        lines.extend(piece);
+    }
+    lines
+}
+
+/// Glues `items` together, separated by `sep`.
+fn join(items: Vec<Vec<String>>, sep: &str) -> Vec<String> {
+    let mut pieces = vec![];
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            pieces.push(text(sep));
+        }
+        pieces.push(item);
+    }
+    concat(pieces)
+}
+
+fn indented(lines: Vec<String>) -> impl Iterator<Item = String> {
+    (lines.into_iter()).map(|line| format!("{}{}", INDENT, line))
+}
+
+/// `header` followed by a statement: a block continues the header line,
+/// anything else goes on its own indented lines.
+fn with_body(header: Vec<String>, body: &Statement) -> Vec<String> {
+    if let Stmt::Block(block) = &body.stmt {
+        concat(vec![header, text(" "), block.to_source()])
+    } else {
+        let mut lines = header;
+        lines.extend(indented(body.to_source()));
+        lines
+    }
+}
+
+/// Binding power of an expression: an operand that binds weaker than
+/// its operator requires must be parenthesized.
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Sequence(_) => 1,
+        Expr::Assign(_) | Expr::LogicalAssign(_) => 2,
+        Expr::Conditional(_) => 3,
+        Expr::LogicalOp(logexpr) => match logexpr.1 {
+            BoolOp::Or | BoolOp::Nullish => 4,
+            BoolOp::And => 5,
+        },
+        Expr::BinaryOp(binexpr) => binop_precedence(&binexpr.1),
+        Expr::Unary(_) => 15,
+        // `(a?.b).c` is not `a?.b.c`
+        Expr::Update(_) | Expr::Chain(_) => 16,
+        Expr::Call(_) | Expr::New(_) => 17,
+        Expr::Member(_) => 18,
+        Expr::Literal(_)
+        | Expr::Identifier(_)
+        | Expr::Variable(_)
+        | Expr::Array(_)
+        | Expr::Object(_)
+        | Expr::Function(_)
+        | Expr::This => 20,
+    }
+}
+
+fn binop_precedence(op: &BinOp) -> u8 {
+    use BinOp::*;
+    match op {
+        Pipe => 6,
+        Hat => 7,
+        Ampersand => 8,
+        EqEq | NotEq | EqEqEq | NotEqEq => 9,
+        Less | Greater | LtEq | GtEq | In | InstanceOf => 10,
+        LtLt | GtGt | GtGtGt => 11,
+        Plus | Minus => 12,
+        Star | Slash | Percent => 13,
+        StarStar => 14,
+    }
+}
+
+/// The source of `expr` used as an operand that requires the binding power `level`.
+fn operand(expr: &Expression, level: u8) -> Vec<String> {
+    let mut source = expr.to_source();
+    if precedence(&expr.expr) < level {
+        source[0].insert(0, '(');
+        source.last_mut().unwrap().push(')');
+    }
+    source
+}
+
+/// The source of a comma-separated list of `exprs`.
+fn operand_list(exprs: &[Expression]) -> Vec<String> {
+    let items = exprs.iter().map(|e| operand(e, 2)).collect();
+    join(items, ", ")
+}
+
 impl ToSource for Literal {
     fn to_source(&self) -> Vec<String> {
         vec![self.to_string()]
@@ -58,6 +172,7 @@ impl ToSource for BinOp {
             Plus => "+",
             Minus => "-",
             Star => "*",
+            StarStar => "**",
             Slash => "/",
             Percent => "%",
             EqEq => "==",
@@ -83,77 +198,233 @@ impl ToSource for BinOp {
 
 impl ToSource for MemberExpression {
     fn to_source(&self) -> Vec<String> {
-        let MemberExpression(object, attr, computed) = self;
-        let objsrc = object.to_source();
-        let mut s = String::new();
-        if objsrc.len() == 1 {
-            s.push_str(objsrc[0].as_str());
-        } else {
-            todo!(
-                "MemberExpression::to_source(): {} lines in object",
-                objsrc.len()
-            );
-        }
+        let MemberExpression(object, attr, computed, _) = self;
+        let objsrc = operand(object, 17);
 
         if *computed {
-            let attrsrc = attr.to_source();
-            s.push('[');
-            if attrsrc.len() == 1 {
-                s.push_str(attrsrc[0].as_str());
-            } else {
-                todo!(
-                    "MemberExpression::to_source(): {} lines in attr",
-                    attrsrc.len()
-                );
-            }
-            s.push(']');
+            concat(vec![objsrc, text("["), attr.to_source(), text("]")])
         } else if let Expr::Identifier(ident) = &attr.expr {
-            s.push('.');
-            s.push_str(ident.as_str());
+            concat(vec![objsrc, text("."), text(ident.as_str())])
         } else {
             panic!(
                 "MemberExpression::to_source(): non-computed non-identifiter attr {:?}",
                 attr.expr
             );
         }
-        vec![s]
     }
 }
 
 impl ToSource for CallExpression {
     fn to_source(&self) -> Vec<String> {
         let CallExpression(callee, arguments) = self;
+        let calleesrc = operand(callee, 17);
+        concat(vec![
+            calleesrc,
+            text("("),
+            operand_list(arguments),
+            text(")"),
+        ])
+    }
+}
 
-        let mut s = String::new();
-        let calleesrc = callee.to_source();
-        if calleesrc.len() != 1 {
-            todo!(
-                "CallExpression::to_source(): {} lines in callee",
-                calleesrc.len()
-            );
+impl ToSource for ChainExpression {
+    fn to_source(&self) -> Vec<String> {
+        let ChainExpression(object, links) = self;
+        let mut parts = vec![operand(object, 17)];
+        for link in links.iter() {
+            let optional = if link.optional { "?." } else { "" };
+            parts.push(match &link.kind {
+                LinkKind::Property(name) => {
+                    let dot = if link.optional { "?." } else { "." };
+                    text(&format!("{}{}", dot, name.as_str()))
+                }
+                LinkKind::Computed(key) => {
+                    concat(vec![text(optional), text("["), key.to_source(), text("]")])
+                }
+                LinkKind::Call(arguments) => concat(vec![
+                    text(optional),
+                    text("("),
+                    operand_list(arguments),
+                    text(")"),
+                ]),
+            });
+        }
+        concat(parts)
+    }
+}
+
+impl ToSource for ObjectExpression {
+    fn to_source(&self) -> Vec<String> {
+        fn is_valid_identifier(s: &str) -> bool {
+            let is_start = |c: char| c.is_alphabetic() || c == '_' || c == '$';
+
+            let mut it = s.chars();
+            match it.next() {
+                Some(c) => is_start(c) && it.all(|c| is_start(c) || c.is_numeric()),
+                None => false,
+            }
         }
-        s.push_str(calleesrc[0].as_str());
-
-        s.push('(');
-        for arg in arguments.iter() {
-            let argsrc = arg.to_source();
-            if argsrc.len() != 1 {
-                todo!(
-                    "CallExpression::to_source(): {} lines in argument: {:?}",
-                    argsrc.len(),
-                    arg
-                );
+
+        if self.0.is_empty() {
+            return text("{}");
+        }
+
+        let entries = (self.0.iter())
+            .map(|property| {
+                let keysrc = match &property.key {
+                    ObjectKey::Identifier(name) if is_valid_identifier(name) => text(name),
+                    ObjectKey::Identifier(name) => vec![Literal::from(name.as_str()).to_string()],
+                    ObjectKey::Computed(expr) => {
+                        concat(vec![text("["), expr.to_source(), text("]")])
+                    }
+                };
+                let func = match &property.value.expr {
+                    Expr::Function(FunctionExpression { func }) => Some(func),
+                    _ => None,
+                };
+                match (property.kind, func) {
+                    (PropertyKind::Init, _) | (_, None) if property.shorthand => keysrc,
+                    (PropertyKind::Init, _) | (_, None) => {
+                        concat(vec![keysrc, text(": "), operand(&property.value, 2)])
+                    }
+                    (kind, Some(func)) => {
+                        let prefix = match kind {
+                            PropertyKind::Get => "get ",
+                            PropertyKind::Set => "set ",
+                            _ if func.is_async && func.is_generator => "async *",
+                            _ if func.is_async => "async ",
+                            _ if func.is_generator => "*",
+                            _ => "",
+                        };
+                        concat(vec![text(prefix), keysrc, func.signature()])
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if entries.iter().all(|entry| entry.len() == 1) {
+            concat(vec![text("{ "), join(entries, ", "), text(" }")])
+        } else {
+            let mut lines = text("{");
+            for mut entry in entries {
+                entry.last_mut().unwrap().push(',');
+                lines.extend(indented(entry));
             }
-            s.push_str(argsrc[0].as_str());
-            s.push_str(", ");
+            lines.push(String::from("}"));
+            lines
+        }
+    }
+}
+
+impl ToSource for UnaryExpression {
+    fn to_source(&self) -> Vec<String> {
+        let UnaryExpression(op, argument) = self;
+        let opsrc = match op {
+            UnOp::Exclamation => "!",
+            UnOp::Minus => "-",
+            UnOp::Plus => "+",
+            UnOp::Tilde => "~",
+            UnOp::Typeof => "typeof ",
+            UnOp::Void => "void ",
+            UnOp::Delete => "delete ",
+        };
+        let argsrc = operand(argument, 15);
+        // `- -x` must not become `--x`
+        let sep = match (opsrc, argsrc[0].chars().next()) {
+            ("-", Some('-')) | ("+", Some('+')) => " ",
+            _ => "",
+        };
+        concat(vec![text(opsrc), text(sep), argsrc])
+    }
+}
+
+impl ToSource for UpdateExpression {
+    fn to_source(&self) -> Vec<String> {
+        let UpdateExpression(op, prefix, argument) = self;
+        let opsrc = match op {
+            UpdOp::Increment => text("++"),
+            UpdOp::Decrement => text("--"),
+        };
+        if *prefix {
+            concat(vec![opsrc, operand(argument, 16)])
+        } else {
+            concat(vec![operand(argument, 17), opsrc])
+        }
+    }
+}
+
+impl ToSource for AssignmentExpression {
+    fn to_source(&self) -> Vec<String> {
+        let AssignmentExpression(left, modop, right) = self;
+        let opsrc = match modop {
+            None => text(" = "),
+            Some(op) => concat(vec![text(" "), op.to_source(), text("= ")]),
+        };
+        concat(vec![operand(left, 17), opsrc, operand(right, 2)])
+    }
+}
+
+impl ToSource for LogicalAssignmentExpression {
+    fn to_source(&self) -> Vec<String> {
+        let LogicalAssignmentExpression(left, op, right) = self;
+        let opsrc = match op {
+            BoolOp::And => text(" &&= "),
+            BoolOp::Or => text(" ||= "),
+            BoolOp::Nullish => text(" ??= "),
+        };
+        concat(vec![operand(left, 17), opsrc, operand(right, 2)])
+    }
+}
+
+impl ToSource for ConditionalExpression {
+    fn to_source(&self) -> Vec<String> {
+        concat(vec![
+            operand(&self.condexpr, 4),
+            text(" ? "),
+            operand(&self.thenexpr, 2),
+            text(" : "),
+            operand(&self.elseexpr, 2),
+        ])
+    }
+}
+
+impl ToSource for NewExpression {
+    fn to_source(&self) -> Vec<String> {
+        let NewExpression(callee, arguments) = self;
+        concat(vec![
+            text("new "),
+            operand(callee, 18),
+            text("("),
+            operand_list(arguments),
+            text(")"),
+        ])
+    }
+}
+
+impl ToSource for Function {
+    fn to_source(&self) -> Vec<String> {
+        let mut header = String::new();
+        if self.is_async {
+            header.push_str("async ");
+        }
+        header.push_str("function");
+        if self.is_generator {
+            header.push('*');
         }
-        if s.ends_with(", ") {
-            s.pop();
-            s.pop();
+        header.push(' ');
+        if let Some(id) = &self.id {
+            header.push_str(id.as_str());
         }
-        s.push(')');
+        concat(vec![vec![header], self.signature()])
+    }
+}
 
-        vec![s]
+impl Function {
+    /// The parameters and the body, e.g. for a method of an object literal.
+    fn signature(&self) -> Vec<String> {
+        let params = self.params.iter().map(|p| p.as_str()).collect::<Vec<_>>();
+        let header = format!("({}) ", params.join(", "));
+        concat(vec![vec![header], self.body.to_source()])
     }
 }
 
@@ -162,45 +433,70 @@ impl ToSource for Expr {
         match self {
             Expr::Literal(lit) => lit.to_source(),
             Expr::Identifier(id) => id.to_source(),
+            Expr::Variable(var) => var.name.to_source(),
             Expr::BinaryOp(binop) => {
                 let BinaryExpression(left, op, right) = binop.as_ref();
-                let left = left.to_source();
-                let op = op.to_source();
-                let right = right.to_source();
-                let s = format!("{} {} {}", left[0], op[0], right[0]);
-                vec![s]
+                let level = binop_precedence(op);
+                // `**` is right-associative and its base cannot be a unary expression
+                let (left_level, right_level) = match op {
+                    BinOp::StarStar => (16, level),
+                    _ => (level, level + 1),
+                };
+                let op = concat(vec![text(" "), op.to_source(), text(" ")]);
+                concat(vec![
+                    operand(left, left_level),
+                    op,
+                    operand(right, right_level),
+                ])
             }
-            Expr::Array(arrexpr) => {
-                if arrexpr.0.is_empty() {
-                    vec!["[]".to_string()]
-                } else {
-                    let mut s = String::new();
-                    s.push('[');
-                    for expr in arrexpr.0.iter() {
-                        let e = expr.to_source();
-                        s.push_str(e[0].as_str());
-                        s.push_str(", ");
+            Expr::LogicalOp(logop) => {
+                let LogicalExpression(left, op, right) = logop.as_ref();
+                let level = precedence(self);
+                // `??` does not mix with `&&` and `||` without parentheses
+                let operand = |expr: &Expression, level: u8| match &expr.expr {
+                    Expr::LogicalOp(inner)
+                        if (inner.1 == BoolOp::Nullish) != (*op == BoolOp::Nullish) =>
+                    {
+                        operand(expr, 20)
                     }
-                    s.pop();
-                    s.pop();
-                    s.push(']');
-                    vec![s]
-                }
+                    _ => operand(expr, level),
+                };
+                let opsrc = match op {
+                    BoolOp::And => text(" && "),
+                    BoolOp::Or => text(" || "),
+                    BoolOp::Nullish => text(" ?? "),
+                };
+                concat(vec![operand(left, level), opsrc, operand(right, level + 1)])
+            }
+            Expr::Array(ArrayExpression(elements)) => {
+                let items = (elements.iter())
+                    .map(|elem| elem.as_ref().map_or(vec![], |e| operand(e, 2)))
+                    .collect();
+                // a trailing hole needs its own comma: `[1, ,]` has two elements
+                let trailing = match elements.last() {
+                    Some(None) => ",",
+                    _ => "",
+                };
+                concat(vec![
+                    text("["),
+                    join(items, ", "),
+                    text(trailing),
+                    text("]"),
+                ])
             }
+            Expr::Object(objexpr) => objexpr.to_source(),
             Expr::Member(membexpr) => membexpr.to_source(),
             Expr::Call(callexpr) => callexpr.to_source(),
+            Expr::Chain(chain) => chain.to_source(),
             Expr::This => vec!["this".to_string()],
-            /*
-            Expr::Unary() => TODO
-            Expr::Conditional() => TODO
-            Expr::Update() => TODO
-            Expr::Function() => TODO
-            Expr::Sequence() => TODO
-            Expr::Assign() => TODO
-            Expr::New() => TODO
-            Expr::LogicalOp() => TODO
-            */
-            other => todo!("{:?}.to_source()", other),
+            Expr::Unary(unexpr) => unexpr.to_source(),
+            Expr::Conditional(condexpr) => condexpr.to_source(),
+            Expr::Update(updexpr) => updexpr.to_source(),
+            Expr::Function(funcexpr) => funcexpr.func.to_source(),
+            Expr::Sequence(seqexpr) => operand_list(&seqexpr.0),
+            Expr::Assign(assignexpr) => assignexpr.to_source(),
+            Expr::LogicalAssign(assignexpr) => assignexpr.to_source(),
+            Expr::New(newexpr) => newexpr.to_source(),
         }
     }
 }
@@ -296,6 +592,126 @@ impl ToSource for BlockStatement {
     }
 }
 
+impl ToSource for VariableDeclaration {
+    /// Gives the declaration without the trailing `;`
+    fn to_source(&self) -> Vec<String> {
+        let kind = match self.kind {
+            DeclarationKind::Var => "var ",
+            DeclarationKind::Let => "let ",
+            DeclarationKind::Const => "const ",
+        };
+        let declarators = (self.declarations.iter())
+            .map(|decl| match &decl.init {
+                Some(init) => concat(vec![
+                    text(decl.name.as_str()),
+                    text(" = "),
+                    operand(init, 2),
+                ]),
+                None => text(decl.name.as_str()),
+            })
+            .collect();
+        concat(vec![text(kind), join(declarators, ", ")])
+    }
+}
+
+impl ToSource for SwitchStatement {
+    fn to_source(&self) -> Vec<String> {
+        let header = concat(vec![
+            text("switch ("),
+            self.discriminant.to_source(),
+            text(") {"),
+        ]);
+        let mut lines = header;
+        for case in self.cases.iter() {
+            let label = match &case.test {
+                Some(test) => concat(vec![text("case "), test.to_source(), text(":")]),
+                None => text("default:"),
+            };
+            lines.extend(indented(label));
+            for stmt in case.consequent.iter() {
+                lines.extend(indented(indented(stmt.to_source()).collect()));
+            }
+        }
+        lines.push(String::from("}"));
+        lines
+    }
+}
+
+impl ToSource for ForStatement {
+    fn to_source(&self) -> Vec<String> {
+        let test = match &self.test {
+            Some(test) => test.to_source(),
+            None => text("true"),
+        };
+
+        // `do { body } while (test)` is `for (body; test; ) body`
+        if self.init == self.body && self.init.stmt != Stmt::Empty {
+            let header = text("do");
+            let lines = with_body(header, &self.body);
+            let whiletest = concat(vec![text("while ("), test, text(");")]);
+            return match &self.body.stmt {
+                Stmt::Block(_) => concat(vec![lines, text(" "), whiletest]),
+                _ => [lines, whiletest].concat(),
+            };
+        }
+
+        if self.init.stmt == Stmt::Empty && self.update.is_none() && self.test.is_some() {
+            let header = concat(vec![text("while ("), test, text(")")]);
+            return with_body(header, &self.body);
+        }
+
+        let init = match &self.init.stmt {
+            Stmt::Empty => text(""),
+            Stmt::Variable(var) => var.to_source(),
+            Stmt::Expr(exprstmt) => exprstmt.expression.to_source(),
+            other => panic!("ForStatement::to_source(): unexpected init {:?}", other),
+        };
+        let mut pieces = vec![text("for ("), init, text(";")];
+        if let Some(test) = &self.test {
+            pieces.extend([text(" "), test.to_source()]);
+        }
+        pieces.push(text(";"));
+        if let Some(update) = &self.update {
+            pieces.extend([text(" "), update.to_source()]);
+        }
+        pieces.push(text(")"));
+        with_body(concat(pieces), &self.body)
+    }
+}
+
+impl ToSource for ForInStatement {
+    fn to_source(&self) -> Vec<String> {
+        let left = match &self.left {
+            ForInTarget::Var(var) => var.to_source(),
+            ForInTarget::Expr(expr) => expr.to_source(),
+        };
+        let header = concat(vec![
+            text("for ("),
+            left,
+            text(" in "),
+            self.right.to_source(),
+            text(")"),
+        ]);
+        with_body(header, &self.body)
+    }
+}
+
+impl ToSource for TryStatement {
+    fn to_source(&self) -> Vec<String> {
+        let mut pieces = vec![text("try "), self.block.to_source()];
+        if let Some(handler) = &self.handler {
+            let param = handler.param.as_str();
+            pieces.push(vec![format!(" catch ({}) ", param)]);
+            pieces.push(handler.body.to_source());
+        }
+        if let Some(finalizer) = &self.finalizer {
+            pieces.push(text(" finally "));
+            pieces.push(finalizer.to_source());
+        }
+        concat(pieces)
+    }
+}
+
 impl ToSource for Stmt {
     fn to_source(&self) -> Vec<String> {
         use Stmt::*;
@@ -304,38 +720,42 @@ impl ToSource for Stmt {
             Block(blockstmt) => blockstmt.to_source(),
             Expr(exprstmt) => {
                 let mut lines = exprstmt.expression.to_source();
+                // these would be parsed as a declaration/a block otherwise
+                if lines[0].starts_with("function") || lines[0].starts_with('{') {
+                    lines[0].insert(0, '(');
+                    lines.last_mut().unwrap().push(')');
+                }
                 let len = lines.len();
                 lines[len - 1].push(';');
                 lines
             }
             If(ifstmt) => ifstmt.to_source(),
-            //Switch() => TODO
-            //For() => TODO
-            //ForIn() => TODO
-            Return(retstmt) => {
-                let mut s = String::from("return");
-                if let Some(retexpr) = &retstmt.0 {
-                    s.push(' ');
-                    let retsrc = retexpr.to_source();
-                    if retsrc.len() != 1 {
-                        todo!(
-                            "Return::to_source(): {} lines in return expression",
-                            retsrc.len()
-                        );
-                    }
-                    s.push_str(retsrc[0].as_str());
-                }
-                s.push(';');
-                vec![s]
+            Switch(switchstmt) => switchstmt.to_source(),
+            For(forstmt) => forstmt.to_source(),
+            ForIn(forinstmt) => forinstmt.to_source(),
+            Return(retstmt) => match &retstmt.0 {
+                Some(retexpr) => concat(vec![text("return "), retexpr.to_source(), text(";")]),
+                None => text("return;"),
+            },
+            Break(BreakStatement(None)) => text("break;"),
+            Break(BreakStatement(Some(label))) => vec![format!("break {};", label.as_str())],
+            Continue(ContinueStatement(None)) => text("continue;"),
+            Continue(ContinueStatement(Some(label))) => {
+                vec![format!("continue {};", label.as_str())]
+            }
+            Label(labelstmt) => {
+                let LabelStatement(label, body) = labelstmt.as_ref();
+                concat(vec![
+                    vec![format!("{}: ", label.as_str())],
+                    body.to_source(),
+                ])
+            }
+            Throw(ThrowStatement(expr)) => {
+                concat(vec![text("throw "), expr.to_source(), text(";")])
             }
-            //Break() => TODO
-            //Continue() => TODO
-            //Label() => TODO
-            //Throw() => TODO
-            //Try() => TODO
-            //Variable() => TODO
-            //Function() => TODO
-            _ => todo!("{:?}.to_source()", self),
+            Try(trystmt) => trystmt.to_source(),
+            Variable(var) => concat(vec![var.to_source(), text(";")]),
+            Function(funcdecl) => funcdecl.function.func.to_source(),
         }
     }
 }
@@ -377,6 +797,7 @@ mod test {
 
         assert_print!("true", Literal::from(true));
         assert_print!("\"string\"", Literal::from("string"));
+        assert_print!("-12n", Literal::from(crate::BigInt::from(-12i64)));
         //assert_eq!( Literal::from(r#"\"quotes\""#) r#"\"\\\"quotes\\\"\""#]);
 
         assert_print!("test", id("test"));
@@ -397,6 +818,10 @@ mod test {
             r#"[2, "a", 2 + "a"]"#,
             array(vec![lit(2), lit("a"), add(2, "a")]),
         );
+        {
+            let holes = ArrayExpression(vec![None, Some(lit(1)), None]);
+            assert_print!("[, 1, ,]", Expression::from(Expr::Array(holes)));
+        }
 
         assert_print!("sqr(12)", call(id("sqr"), vec![lit(12)]));
         assert_print!(
@@ -407,11 +832,37 @@ mod test {
         assert_print!("a.b.c", memb(memb(id("a"), "b"), "c"));
         assert_print!("a[b].c", memb(index(id("a"), id("b")), "c"));
         assert_print!("a[i + 1]", index(id("a"), add(id("i"), 1)));
+
+        assert_print!(
+            "(a + b) * c",
+            binary(BinOp::Star, add(id("a"), id("b")), id("c"))
+        );
+        assert_print!(
+            "a - (b - c)",
+            binary(
+                BinOp::Minus,
+                id("a"),
+                binary(BinOp::Minus, id("b"), id("c"))
+            )
+        );
+        assert_print!("+ +x", plus(plus(id("x"))));
+        assert_print!("{}", empty_object());
+        assert_print!(
+            r#"{ a: 1, "b c": [] }"#,
+            ObjectExpression(vec![
+                (ObjectKey::Identifier("a".into()), lit(1)).into(),
+                (ObjectKey::Identifier("b c".into()), empty_array()).into(),
+            ])
+        );
     }
 
     #[test]
     fn display_stmt() {
         assert_print!(";", Statement::from(Stmt::Empty));
+        assert_print!(
+            "var x = 1, y = 2;",
+            Statement::from(var([("x", lit(1)), ("y", lit(2))].iter()))
+        );
 
         assert_print!(
             r#"1 in ["a", "b"];"#,
@@ -438,14 +889,13 @@ mod test {
                 test: binary(BinOp::Less, id("n"), lit(2)),
                 consequent: Statement::from(block(vec![
                     call(memb(id("console"), "log"), vec![lit("base")]).into(),
-                    return_(lit(1)).into(),
+                    return_(lit(1)),
                 ])),
                 alternate: Some(Statement::from(block(vec![return_(binary(
                     BinOp::Star,
                     id("n"),
                     call(id("fact"), vec![binary(BinOp::Minus, id("n"), 1)]),
-                ))
-                .into()]))),
+                ))]))),
             };
 
             #[rustfmt::skip]
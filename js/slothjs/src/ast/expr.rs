@@ -4,7 +4,8 @@
 
 use crate::prelude::*;
 
-use crate::{source, JSON};
+use crate::shape::PropertyCache;
+use crate::{source, BigInt, JSON};
 
 use super::stmt::{BlockStatement, FunctionDeclaration};
 
@@ -46,13 +47,16 @@ where
 pub enum Expr {
     Literal(Literal),
     Identifier(Identifier),
+    Variable(Variable),
     BinaryOp(Box<BinaryExpression>),
     LogicalOp(Box<LogicalExpression>),
     Call(Box<CallExpression>),
     Array(ArrayExpression),
     Object(ObjectExpression),
     Member(Box<MemberExpression>),
+    Chain(Box<ChainExpression>),
     Assign(Box<AssignmentExpression>),
+    LogicalAssign(Box<LogicalAssignmentExpression>),
     Conditional(Box<ConditionalExpression>),
     Unary(Box<UnaryExpression>),
     Update(Box<UpdateExpression>),
@@ -113,22 +117,32 @@ impl From<CallExpression> for Expr {
     }
 }
 
+/// A primitive literal; BigInt literals have a `null` JSON value like in ESTree.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Literal(JSON); // TODO: change to JSValue
+pub struct Literal(JSON, Option<BigInt>); // TODO: change to JSValue
 
 impl Literal {
     pub fn to_value(&self) -> JSValue {
+        if let Some(n) = &self.1 {
+            return JSValue::from(n.clone());
+        }
         JSValue::try_from(&self.0).expect("primitive JSON")
     }
 
     pub fn to_json(&self) -> JSON {
         self.0.clone()
     }
+
+    pub fn as_bigint(&self) -> Option<&BigInt> {
+        self.1.as_ref()
+    }
 }
 
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.0.is_null() {
+        if let Some(n) = &self.1 {
+            write!(f, "{}n", n)
+        } else if self.0.is_null() {
             write!(f, "null")
         } else if let Some(b) = self.0.as_bool() {
             write!(f, "{}", b)
@@ -146,33 +160,39 @@ impl From<JSON> for Literal {
     fn from(json: JSON) -> Literal {
         assert!(!json.is_array());
         assert!(!json.is_object());
-        Literal(json)
+        Literal(json, None)
+    }
+}
+
+impl From<BigInt> for Literal {
+    fn from(n: BigInt) -> Self {
+        Literal(JSON::Null, Some(n))
     }
 }
 
 impl From<bool> for Literal {
     fn from(b: bool) -> Self {
-        Literal(JSON::from(b))
+        Literal(JSON::from(b), None)
     }
 }
 impl From<f64> for Literal {
     fn from(n: f64) -> Self {
-        Literal(JSON::from(n))
+        Literal(JSON::from(n), None)
     }
 }
 impl From<i64> for Literal {
     fn from(n: i64) -> Self {
-        Literal(JSON::from(n))
+        Literal(JSON::from(n), None)
     }
 }
 impl From<&str> for Literal {
     fn from(s: &str) -> Self {
-        Literal(JSON::from(s))
+        Literal(JSON::from(s), None)
     }
 }
 impl From<JSString> for Literal {
     fn from(s: JSString) -> Self {
-        Literal(JSON::String(s.to_string()))
+        Literal(JSON::String(s.to_string()), None)
     }
 }
 
@@ -197,6 +217,58 @@ impl From<JSString> for Identifier {
     }
 }
 
+/// Where a variable lives at runtime, see [`crate::resolve`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Slot {
+    /// Looked up by name: globals and variables declared by `eval`.
+    #[default]
+    Dynamic,
+    /// A slot of the current frame.
+    Local(u32),
+    /// A slot of the current frame that holds a cell shared with closures.
+    Boxed(u32),
+    /// A cell captured by the current closure.
+    Captured(u32),
+}
+
+/// A reference to a variable, resolved to its [`Slot`].
+/// The resolver makes these from `Expr::Identifier`s in variable positions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variable {
+    pub name: Identifier,
+    pub slot: Slot,
+}
+
+impl From<Identifier> for Variable {
+    fn from(name: Identifier) -> Variable {
+        Variable {
+            name,
+            slot: Slot::Dynamic,
+        }
+    }
+}
+
+/// The frame layout of a [`Function`] or a [`super::Program`], computed by [`crate::resolve`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+    /// the names of the frame slots, e.g. for `eval`
+    pub names: Vec<JSString>,
+    /// which slots hold cells shared with closures
+    pub boxed: Vec<bool>,
+    /// the variables of the enclosing frame captured by the closure, by index of `Slot::Captured`
+    pub captures: Vec<(Identifier, Slot)>,
+    pub params: Vec<Slot>,
+    pub arguments: Slot,
+    /// is it the frame of strict mode code?
+    pub strict: bool,
+}
+
+impl Layout {
+    pub fn size(&self) -> usize {
+        self.names.len()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BinaryExpression(pub Expression, pub BinOp, pub Expression);
 
@@ -213,10 +285,41 @@ pub struct UpdateExpression(pub UpdOp, pub bool, pub Expression);
 pub struct CallExpression(pub Expression, pub Vec<Expression>);
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ArrayExpression(pub Vec<Expression>);
+/// `None` elements are holes: `[1, , 3]`
+pub struct ArrayExpression(pub Vec<Option<Expression>>);
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct ObjectExpression(pub Vec<(ObjectKey, Expression)>);
+pub struct ObjectExpression(pub Vec<ObjectProperty>);
+
+/// A property of an [`ObjectExpression`]: `key: value`, `key`, `key() {}`, `get key() {}`
+/// or `set key(v) {}`. The `value` of a method or an accessor is a function expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectProperty {
+    pub key: ObjectKey,
+    pub value: Expression,
+    pub kind: PropertyKind,
+    /// `{x}` for `{x: x}`
+    pub shorthand: bool,
+}
+
+impl From<(ObjectKey, Expression)> for ObjectProperty {
+    fn from((key, value): (ObjectKey, Expression)) -> Self {
+        ObjectProperty {
+            key,
+            value,
+            kind: PropertyKind::Init,
+            shorthand: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropertyKind {
+    Init,
+    Method,
+    Get,
+    Set,
+}
 
 /// Describes an [`ObjectExpression`] key: `ObjectKey::Computed` or `ObjectKey::Identifier`
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -238,8 +341,31 @@ where
     }
 }
 
+/// `object.property` or `object[property]` (if computed), with the cache of this access.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemberExpression(pub Expression, pub Expression, pub bool, pub PropertyCache);
+
+/// An optional chain: `object` followed by its links, e.g. `object?.a.b?.[key]?.(arg)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainExpression(pub Expression, pub Vec<ChainLink>);
+
+/// A link of a [`ChainExpression`]; an `optional` one short-circuits the whole chain
+/// to `undefined` when the value before it is `null` or `undefined`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainLink {
+    pub optional: bool,
+    pub kind: LinkKind,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct MemberExpression(pub Expression, pub Expression, pub bool);
+pub enum LinkKind {
+    /// `.name`
+    Property(Identifier),
+    /// `[key]`
+    Computed(Expression),
+    /// `(arguments)`
+    Call(Vec<Expression>),
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SequenceExpression(pub Vec<Expression>);
@@ -247,6 +373,10 @@ pub struct SequenceExpression(pub Vec<Expression>);
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AssignmentExpression(pub Expression, pub Option<BinOp>, pub Expression);
 
+/// `target &&= value`, `target ||= value` or `target ??= value`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogicalAssignmentExpression(pub Expression, pub BoolOp, pub Expression);
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ConditionalExpression {
     pub condexpr: Expression,
@@ -263,9 +393,12 @@ pub struct Function {
     pub functions: Vec<FunctionDeclaration>, // the set of declared functions
     pub free_variables: HashSet<Identifier>,
     pub body: BlockStatement,
+    pub layout: Rc<Layout>,
     pub is_generator: bool,
     pub is_expression: bool,
     pub is_async: bool,
+    /// is it strict mode code, by its own "use strict" or by an enclosing one?
+    pub is_strict: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -280,11 +413,12 @@ pub type Pattern = Identifier;
 pub struct NewExpression(pub Expression, pub Vec<Expression>);
 
 /// Lists all possible binary operation for [`BinaryExpression`]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BinOp {
     Plus,
     Minus,
     Star,
+    StarStar,
     Slash,
     Percent,
     EqEq,
@@ -305,15 +439,16 @@ pub enum BinOp {
     InstanceOf,
 }
 
-/// Lists all boolean operations (`&&`, `||`) for [`LogicalExpression`]
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Lists all boolean operations (`&&`, `||`, `??`) for [`LogicalExpression`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BoolOp {
     And,
     Or,
+    Nullish,
 }
 
 /// Lists all unary operations for [`UnaryExpression`]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnOp {
     Exclamation,
     Minus,
@@ -325,7 +460,7 @@ pub enum UnOp {
 }
 
 /// Lists all update operations (`++`, `--`) for [`UpdateExpression`]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpdOp {
     Increment,
     Decrement,
@@ -352,7 +487,7 @@ pub fn lit<V>(value: V) -> Expression
 where
     JSON: From<V>,
 {
-    Expression::from(Literal(JSON::from(value)))
+    Expression::from(Literal(JSON::from(value), None))
 }
 
 /// make an [`Identifier`] from `name` (JS: `name`)
@@ -365,7 +500,10 @@ pub fn array<E>(exprs: Vec<E>) -> Expression
 where
     Expression: From<E>,
 {
-    let exprs = exprs.into_iter().map(Expression::from).collect();
+    let exprs = exprs
+        .into_iter()
+        .map(|e| Some(Expression::from(e)))
+        .collect();
     let expr = Expr::Array(ArrayExpression(exprs));
     Expression { expr, loc: None }
 }
@@ -381,7 +519,7 @@ where
     ObjectKey: From<K>,
 {
     let pairs = (pairs.into_iter())
-        .map(|(k, v)| (ObjectKey::from(k), v))
+        .map(|(k, v)| ObjectProperty::from((ObjectKey::from(k), v)))
         .collect();
     Expression::from(ObjectExpression(pairs))
 }
@@ -431,7 +569,7 @@ where
         expr: Expr::Identifier(attr),
         loc: None,
     };
-    MemberExpression(object, attr, false).into()
+    MemberExpression(object, attr, false, PropertyCache::default()).into()
 }
 
 /// make a computed  [`MemberExpression`](`object`, `attr`) (JS: `object[attr]`)
@@ -440,7 +578,7 @@ where
     Expression: From<E>,
 {
     let object = Expression::from(object);
-    MemberExpression(object, attr, true).into()
+    MemberExpression(object, attr, true, PropertyCache::default()).into()
 }
 
 /// make a [`CallExpression`] with `callee` and `arguments` (JS: `callee(arguments...)`)
@@ -13,9 +13,26 @@ pub struct Program {
     pub body: BlockStatement,
     pub variables: HashSet<Identifier>, // The set of scope variables
     pub functions: Vec<FunctionDeclaration>,
+    pub layout: Rc<Layout>,
+    /// does it begin with a "use strict" directive?
+    pub is_strict: bool,
 }
 
 impl Program {
+    /// Makes a [`Program`] with its declarations hoisted and its variables resolved,
+    /// see [`crate::resolve`].
+    pub fn new(body: BlockStatement, is_strict: bool) -> Program {
+        let mut program = Program {
+            body,
+            variables: HashSet::new(),
+            functions: vec![],
+            layout: Rc::default(),
+            is_strict,
+        };
+        crate::resolve::program(&mut program);
+        program
+    }
+
     pub fn from_stmt<V>(val: V) -> Program
     where
         Statement: From<V>,
@@ -43,12 +60,9 @@ impl PartialEq for Program {
 
 impl Eq for Program {}
 
+/// Makes a sloppy mode [`Program`], see [`Program::new()`].
 impl From<BlockStatement> for Program {
     fn from(blockstmt: BlockStatement) -> Program {
-        Program {
-            body: blockstmt,
-            variables: HashSet::new(), // TODO: block analysis
-            functions: vec![],         // TODO: block analysis
-        }
+        Program::new(blockstmt, false)
     }
 }
@@ -6,7 +6,7 @@ use crate::prelude::*;
 
 use crate::source;
 
-use super::expr::{Expression, FunctionExpression, Identifier, Pattern};
+use super::expr::{Expression, FunctionExpression, Identifier, Pattern, Slot};
 
 /// `Statement` represents an [`Stmt`] together with its source span, if any.
 #[derive(Clone, Debug)]
@@ -58,7 +58,7 @@ pub enum Stmt {
     Continue(ContinueStatement),
     Label(Box<LabelStatement>),
     Throw(ThrowStatement),
-    Try(TryStatement),
+    Try(Box<TryStatement>),
 
     // TODO: move declarations out?
     Variable(VariableDeclaration),
@@ -105,6 +105,7 @@ pub struct ExpressionStatement {
 pub struct VariableDeclarator {
     pub name: Pattern,
     pub init: Option<Box<Expression>>,
+    pub slot: Slot,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -125,6 +126,7 @@ pub struct VariableDeclaration {
 pub struct FunctionDeclaration {
     pub id: Identifier, // might be different from function.id
     pub function: FunctionExpression,
+    pub slot: Slot,
 }
 
 // ==============================================
@@ -132,6 +134,8 @@ pub struct FunctionDeclaration {
 pub struct BlockStatement {
     pub body: Vec<Statement>,
     pub bindings: HashSet<Identifier>,
+    /// the slots of `bindings`, reset on entering the block
+    pub slots: Vec<Slot>,
 }
 
 impl PartialEq for BlockStatement {
@@ -145,8 +149,9 @@ impl From<Vec<Statement>> for BlockStatement {
     fn from(body: Vec<Statement>) -> BlockStatement {
         BlockStatement {
             body,
-            bindings: HashSet::new(),
-        } // TODO: bindings analysis
+            bindings: HashSet::new(), // filled by the resolver
+            slots: Vec::new(),
+        }
     }
 }
 
@@ -223,6 +228,7 @@ pub struct TryStatement {
 pub struct CatchClause {
     pub param: Pattern,
     pub body: BlockStatement,
+    pub slot: Slot,
 }
 
 /// make a [`BlockStatement`]
@@ -236,6 +242,7 @@ pub fn var<'a>(it: impl Iterator<Item = &'a (&'a str, Expression)>) -> VariableD
         .map(|(name, init)| VariableDeclarator {
             name: Identifier::from(*name),
             init: Some(Box::new(init.clone())),
+            slot: Slot::Dynamic,
         })
         .collect();
     VariableDeclaration {
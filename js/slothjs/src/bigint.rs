@@ -0,0 +1,735 @@
+//! Arbitrary precision integers for `BigInt` values.
+//!
+//! A [`BigInt`] is a sign and a magnitude of 32-bit limbs, least significant first.
+//! Bitwise operations see it as an infinite two's complement, like JavaScript does.
+use core::cmp::Ordering;
+use core::ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Sub};
+
+use crate::prelude::*;
+use crate::value::is_str_whitespace;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// little-endian limbs without trailing zeros: zero has none
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    /// Operations that would make a BigInt longer than this many bits fail.
+    pub const MAX_BITS: u64 = 1 << 30;
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        BigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The number of bits of the magnitude.
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// NumberToBigInt: `None` if `n` is not an integer.
+    pub fn from_f64(n: f64) -> Option<BigInt> {
+        if !n.is_finite() {
+            return None;
+        }
+        let int = BigInt::from_f64_trunc(n);
+        (int.to_f64() == n).then_some(int)
+    }
+
+    /// `n` (which must be finite) without its fraction.
+    fn from_f64_trunc(n: f64) -> BigInt {
+        let bits = n.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        if exponent == 0 {
+            return BigInt::zero(); // zeros and subnormals
+        }
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let shift = exponent - 1075;
+        let int = match shift {
+            0.. => BigInt::from(mantissa).shift_left(shift as u64),
+            -63..=-1 => BigInt::from(mantissa >> -shift),
+            _ => BigInt::zero(),
+        };
+        match n < 0.0 {
+            true => -&int,
+            false => int,
+        }
+    }
+
+    /// The nearest number, ties to even; too big ones are infinite.
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bits();
+        let magnitude = if bits <= 64 {
+            self.low_u64() as f64
+        } else {
+            // the top 64 bits with a sticky bit for the rest round just like all of them
+            let shift = bits - 64;
+            let top = self.shift_magnitude_right(shift);
+            let sticky = self.trailing_zeros() < shift;
+            let top = top.low_u64() | sticky as u64;
+            match shift {
+                0..=1023 => top as f64 * f64::from_bits((1023 + shift) << 52),
+                _ => f64::INFINITY,
+            }
+        };
+        match self.negative {
+            true => -magnitude,
+            false => magnitude,
+        }
+    }
+
+    fn low_u64(&self) -> u64 {
+        let limb = |i| self.magnitude.get(i).copied().unwrap_or(0) as u64;
+        limb(1) << 32 | limb(0)
+    }
+
+    fn trailing_zeros(&self) -> u64 {
+        let mut zeros = 0;
+        for &limb in self.magnitude.iter() {
+            if limb != 0 {
+                return zeros + limb.trailing_zeros() as u64;
+            }
+            zeros += 32;
+        }
+        zeros
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.bits() > 64 {
+            return None;
+        }
+        let low = self.low_u64();
+        match self.negative {
+            true if low <= i64::MIN.unsigned_abs() => Some((low as i64).wrapping_neg()),
+            false if low <= i64::MAX as u64 => Some(low as i64),
+            _ => None,
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        (!self.negative && self.bits() <= 64).then(|| self.low_u64())
+    }
+
+    /// StringToBigInt: decimal digits with an optional sign, or `0x`/`0o`/`0b` digits,
+    /// surrounded by whitespace. An empty string is `0n`.
+    pub fn parse(s: &str) -> Option<BigInt> {
+        let s = s.trim_matches(is_str_whitespace);
+        if s.is_empty() {
+            return Some(BigInt::zero());
+        }
+        let radix = match s.get(..2) {
+            Some("0x" | "0X") => 16,
+            Some("0o" | "0O") => 8,
+            Some("0b" | "0B") => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            return BigInt::from_str_radix(&s[2..], radix);
+        }
+        match s.as_bytes()[0] {
+            b'-' => BigInt::from_str_radix(&s[1..], 10).map(|n| -&n),
+            b'+' => BigInt::from_str_radix(&s[1..], 10),
+            _ => BigInt::from_str_radix(s, 10),
+        }
+    }
+
+    /// Parses non-empty `digits` without a sign or a prefix.
+    pub fn from_str_radix(digits: &str, radix: u32) -> Option<BigInt> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut magnitude = Vec::new();
+        for c in digits.chars() {
+            let digit = c.to_digit(radix)?;
+            mul_add_small(&mut magnitude, radix, digit);
+        }
+        Some(BigInt::from_parts(false, magnitude))
+    }
+
+    /// The digits in `radix` (2 to 36), with a `-` if negative.
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        // divide by the biggest power of `radix` that fits into a limb
+        let (mut chunk, mut chunk_digits) = (radix, 1);
+        while let Some(next) = chunk.checked_mul(radix) {
+            chunk = next;
+            chunk_digits += 1;
+        }
+
+        let mut digits = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let mut rem = div_rem_small(&mut magnitude, chunk);
+            for _ in 0..chunk_digits {
+                digits.push(char::from_digit(rem % radix, radix).unwrap());
+                rem /= radix;
+                if magnitude.is_empty() && rem == 0 {
+                    break;
+                }
+            }
+        }
+        if self.negative {
+            digits.push('-');
+        }
+        digits.iter().rev().collect()
+    }
+
+    /// `self / divisor` rounded towards zero, `None` for a zero `divisor`.
+    pub fn checked_div(&self, divisor: &BigInt) -> Option<BigInt> {
+        let (quotient, _) = self.div_rem(divisor)?;
+        Some(quotient)
+    }
+
+    /// `self % divisor` with the sign of `self`, `None` for a zero `divisor`.
+    pub fn checked_rem(&self, divisor: &BigInt) -> Option<BigInt> {
+        let (_, remainder) = self.div_rem(divisor)?;
+        Some(remainder)
+    }
+
+    fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        let quotient = BigInt::from_parts(self.negative != divisor.negative, quotient);
+        let remainder = BigInt::from_parts(self.negative, remainder);
+        Some((quotient, remainder))
+    }
+
+    /// `self ** exponent` for a non-negative `exponent`,
+    /// `None` if the result could be longer than `MAX_BITS`.
+    pub fn checked_pow(&self, exponent: &BigInt) -> Option<BigInt> {
+        if self.bits() <= 1 {
+            // 0, 1 and -1 stay small for any exponent
+            let odd = exponent.magnitude.first().is_some_and(|limb| limb & 1 == 1);
+            return Some(match exponent.is_zero() {
+                true => BigInt::from(1u64),
+                false => BigInt::from_parts(self.negative && odd, self.magnitude.clone()),
+            });
+        }
+        let mut exponent = exponent
+            .to_u64()
+            .filter(|&e| self.bits().saturating_mul(e) <= Self::MAX_BITS)?;
+        let mut result = BigInt::from(1u64);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        Some(result)
+    }
+
+    /// `self * 2^bits`
+    pub fn shift_left(&self, bits: u64) -> BigInt {
+        let (limbs, bits) = ((bits / 32) as usize, (bits % 32) as u32);
+        let mut magnitude = vec![0; limbs];
+        let mut carry = 0;
+        for &limb in self.magnitude.iter() {
+            magnitude.push(limb << bits | carry);
+            carry = if bits == 0 { 0 } else { limb >> (32 - bits) };
+        }
+        magnitude.push(carry);
+        BigInt::from_parts(self.negative, magnitude)
+    }
+
+    /// `self / 2^bits` rounded down, i.e. towards negative infinity for negative ones.
+    pub fn shift_right(&self, bits: u64) -> BigInt {
+        match self.negative {
+            // -(((|x| - 1) >> bits) + 1)
+            true => !&(!self).shift_magnitude_right(bits),
+            false => self.shift_magnitude_right(bits),
+        }
+    }
+
+    /// The magnitude shifted right, keeping the sign.
+    fn shift_magnitude_right(&self, bits: u64) -> BigInt {
+        let limbs = (bits / 32).min(self.magnitude.len() as u64) as usize;
+        let bits = (bits % 32) as u32;
+        let rest = &self.magnitude[limbs..];
+        let magnitude = (0..rest.len())
+            .map(|i| match bits {
+                0 => rest[i],
+                _ => rest[i] >> bits | rest.get(i + 1).map_or(0, |next| next << (32 - bits)),
+            })
+            .collect();
+        BigInt::from_parts(self.negative, magnitude)
+    }
+
+    /// `BigInt.asUintN(bits, self)`: `self` modulo `2^bits`.
+    pub fn as_uint_n(&self, bits: u64) -> BigInt {
+        if !self.negative && self.bits() <= bits {
+            return self.clone();
+        }
+        let limbs = bits.div_ceil(32) as usize;
+        let mut magnitude = self.twos_complement(usize::max(limbs, self.magnitude.len() + 1));
+        magnitude.truncate(limbs);
+        if !bits.is_multiple_of(32) {
+            if let Some(top) = magnitude.last_mut() {
+                *top &= (1 << (bits % 32)) - 1;
+            }
+        }
+        BigInt::from_parts(false, magnitude)
+    }
+
+    /// `BigInt.asIntN(bits, self)`: `self` wrapped into the signed `bits`-bit range.
+    pub fn as_int_n(&self, bits: u64) -> BigInt {
+        if bits == 0 {
+            return BigInt::zero();
+        }
+        if self.bits() < bits {
+            return self.clone();
+        }
+        let unsigned = self.as_uint_n(bits);
+        match unsigned.bits() == bits {
+            true => &unsigned - &BigInt::from(1u64).shift_left(bits),
+            false => unsigned,
+        }
+    }
+
+    /// `len` limbs of two's complement.
+    fn twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+        if self.negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (sum, overflow) = (!*limb).overflowing_add(carry as u32);
+                *limb = sum;
+                carry = overflow;
+            }
+        }
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> BigInt {
+        let negative = limbs.last().is_some_and(|top| top >> 31 == 1);
+        if negative {
+            let mut carry = true;
+            for limb in limbs.iter_mut() {
+                let (sum, overflow) = (!*limb).overflowing_add(carry as u32);
+                *limb = sum;
+                carry = overflow;
+            }
+        }
+        BigInt::from_parts(negative, limbs)
+    }
+
+    fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+        let len = usize::max(self.magnitude.len(), other.magnitude.len()) + 1;
+        let (a, b) = (self.twos_complement(len), other.twos_complement(len));
+        let limbs = a.iter().zip(b.iter()).map(|(&a, &b)| op(a, b)).collect();
+        BigInt::from_twos_complement(limbs)
+    }
+
+    /// Compares to a number exactly; `None` for `NaN`.
+    pub fn partial_cmp_f64(&self, n: f64) -> Option<Ordering> {
+        if n.is_nan() {
+            return None;
+        }
+        if n.is_infinite() {
+            return Some(if n > 0.0 {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            });
+        }
+        let int = BigInt::from_f64_trunc(n);
+        match self.cmp(&int) {
+            // the fraction of `n` decides
+            Ordering::Equal => (int.to_f64() - n).partial_cmp(&0.0),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+/// `magnitude * factor + addend` in place.
+fn mul_add_small(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in magnitude.iter_mut() {
+        let product = *limb as u64 * factor as u64 + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+    if carry != 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divides `magnitude` by `divisor` in place, gives the remainder.
+fn div_rem_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in magnitude.iter_mut().rev() {
+        let dividend = rem << 32 | *limb as u64;
+        *limb = (dividend / divisor as u64) as u32;
+        rem = dividend % divisor as u64;
+    }
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+    rem as u32
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+    for (i, &limb) in long.iter().enumerate() {
+        let s = limb as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        sum.push(s as u32);
+        carry = s >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// `a - b`, where `a >= b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = false;
+    for (i, &limb) in a.iter().enumerate() {
+        let (d, overflow1) = limb.overflowing_sub(b.get(i).copied().unwrap_or(0));
+        let (d, overflow2) = d.overflowing_sub(borrow as u32);
+        difference.push(d);
+        borrow = overflow1 || overflow2;
+    }
+    difference
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + product[i + j] as u64 + carry;
+            product[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// Long division of magnitudes (Knuth's algorithm D), `v` must not be zero.
+fn div_rem_magnitude(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitude(u, v) == Ordering::Less {
+        return (Vec::new(), u.to_vec());
+    }
+    if v.len() == 1 {
+        let mut quotient = u.to_vec();
+        let rem = div_rem_small(&mut quotient, v[0]);
+        return (quotient, vec![rem]);
+    }
+
+    // normalize: the top limb of the divisor gets its top bit set
+    let shift = v.last().unwrap().leading_zeros() as u64;
+    let vn = BigInt::from_parts(false, v.to_vec())
+        .shift_left(shift)
+        .magnitude;
+    let mut un = BigInt::from_parts(false, u.to_vec())
+        .shift_left(shift)
+        .magnitude;
+    if un.len() == u.len() {
+        un.push(0);
+    }
+    let n = vn.len();
+    let m = un.len() - n;
+    let mut quotient = vec![0u32; m];
+    let base = 1u64 << 32;
+    for j in (0..m).rev() {
+        let top = (un[j + n] as u64) << 32 | un[j + n - 1] as u64;
+        let mut qhat = top / vn[n - 1] as u64;
+        let mut rhat = top % vn[n - 1] as u64;
+        while qhat >= base || qhat * vn[n - 2] as u64 > (rhat << 32 | un[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u64;
+            if rhat >= base {
+                break;
+            }
+        }
+
+        // un[j..=j + n] -= qhat * vn
+        let mut borrow = 0i64;
+        for i in 0..n {
+            let p = qhat * vn[i] as u64;
+            let t = un[i + j] as i64 - borrow - (p & 0xffff_ffff) as i64;
+            un[i + j] = t as u32;
+            borrow = (p >> 32) as i64 - (t >> 32);
+        }
+        let t = un[j + n] as i64 - borrow;
+        un[j + n] = t as u32;
+
+        quotient[j] = qhat as u32;
+        if t < 0 {
+            // `qhat` was one too big: add `vn` back
+            quotient[j] = quotient[j].wrapping_sub(1);
+            let mut carry = 0u64;
+            for i in 0..n {
+                let t = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+    }
+    un.truncate(n);
+    let remainder = BigInt::from_parts(false, un).shift_magnitude_right(shift);
+    (quotient, remainder.magnitude)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> BigInt {
+        let mut int = BigInt::from(n.unsigned_abs());
+        int.negative = n < 0;
+        int
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(n: u64) -> BigInt {
+        BigInt::from_parts(false, vec![n as u32, (n >> 32) as u32])
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_radix(10))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Not for &BigInt {
+    type Output = BigInt;
+
+    /// `-self - 1`
+    fn not(self) -> BigInt {
+        &-self - &BigInt::from(1u64)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            let sum = add_magnitude(&self.magnitude, &other.magnitude);
+            return BigInt::from_parts(self.negative, sum);
+        }
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                let difference = sub_magnitude(&other.magnitude, &self.magnitude);
+                BigInt::from_parts(other.negative, difference)
+            }
+            _ => {
+                let difference = sub_magnitude(&self.magnitude, &other.magnitude);
+                BigInt::from_parts(self.negative, difference)
+            }
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let product = mul_magnitude(&self.magnitude, &other.magnitude);
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &BigInt {
+    type Output = BigInt;
+
+    fn bitxor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+}
+
+#[test]
+fn test_bigint_strings() {
+    let n = BigInt::parse("  -123456789012345678901234567890 ").unwrap();
+    assert_eq!(n.to_string(), "-123456789012345678901234567890");
+    assert_eq!(
+        BigInt::parse("0xff").unwrap().to_string_radix(2),
+        "11111111"
+    );
+    assert_eq!(BigInt::parse("").unwrap(), BigInt::zero());
+    assert_eq!(BigInt::from(-255i64).to_string_radix(16), "-ff");
+    assert_eq!(BigInt::from(u64::MAX).to_string_radix(36), "3w5e11264sgsf");
+    for invalid in ["1.5", "1e3", "-0x1", "0x", "12n", "-"] {
+        assert_eq!(BigInt::parse(invalid), None, "{:?}", invalid);
+    }
+}
+
+#[test]
+fn test_bigint_arithmetic() {
+    let big = |s| BigInt::parse(s).unwrap();
+    let a = big("123456789012345678901234567890");
+    let b = big("-987654321098765432109876543210");
+    assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+    assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+    assert_eq!(
+        (&a * &b).to_string(),
+        "-121932631137021795226185032733622923332237463801111263526900"
+    );
+    assert_eq!(b.checked_div(&a).unwrap().to_string(), "-8");
+    assert_eq!(
+        b.checked_rem(&a).unwrap().to_string(),
+        "-9000000000900000000090"
+    );
+    assert_eq!((&(&a * &b) - &big("17")).checked_div(&b), Some(a.clone()));
+    assert_eq!(a.checked_div(&BigInt::zero()), None);
+    // multi-limb division against u128
+    let mut seed = 0x2545_f491_4f6c_dd1du128;
+    let u128_int =
+        |n: u128| &BigInt::from((n >> 64) as u64).shift_left(64) + &BigInt::from(n as u64);
+    for _ in 0..1000 {
+        seed = seed
+            .wrapping_mul(0x5851_f42d_4c95_7f2d_1405_7b7e_f767_814f)
+            .wrapping_add(1);
+        let (x, y) = (seed, seed.rotate_left(17) >> (seed % 120));
+        let y = y.max(1);
+        let (q, r) = u128_int(x).div_rem(&u128_int(y)).unwrap();
+        assert_eq!((q, r), (u128_int(x / y), u128_int(x % y)), "{} / {}", x, y);
+    }
+
+    assert_eq!(BigInt::from(-5i64).shift_right(1), BigInt::from(-3i64));
+    assert_eq!(
+        BigInt::from(5i64).shift_left(100).shift_right(100),
+        BigInt::from(5i64)
+    );
+    assert_eq!(
+        &BigInt::from(-6i64) & &BigInt::from(5i64),
+        BigInt::from(0i64)
+    );
+    assert_eq!(
+        &BigInt::from(-6i64) | &BigInt::from(5i64),
+        BigInt::from(-1i64)
+    );
+    assert_eq!(
+        &BigInt::from(-6i64) ^ &BigInt::from(3i64),
+        BigInt::from(-7i64)
+    );
+    assert_eq!(!&BigInt::from(0i64), BigInt::from(-1i64));
+
+    assert_eq!(BigInt::from(-1i64).as_uint_n(64), BigInt::from(u64::MAX));
+    assert_eq!(BigInt::from(u64::MAX).as_int_n(64), BigInt::from(-1i64));
+    assert_eq!(BigInt::from(200i64).as_int_n(8), BigInt::from(-56i64));
+    assert_eq!(BigInt::from(-1i64).to_i64(), Some(-1));
+    assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+}
+
+#[test]
+fn test_bigint_numbers() {
+    assert_eq!(
+        BigInt::from_f64(1e21).unwrap().to_string(),
+        "1000000000000000000000"
+    );
+    assert_eq!(BigInt::from_f64(-0.0), Some(BigInt::zero()));
+    assert_eq!(BigInt::from_f64(1.5), None);
+    assert_eq!(BigInt::from_f64(f64::NAN), None);
+
+    let two53 = BigInt::from(1u64 << 53);
+    assert_eq!((&two53 + &BigInt::from(1u64)).to_f64(), 9007199254740992.0);
+    assert_eq!((&two53 + &BigInt::from(3u64)).to_f64(), 9007199254740996.0);
+    let big = BigInt::from(u64::MAX).shift_left(1000);
+    assert_eq!(big.to_f64(), f64::INFINITY);
+    assert_eq!((-&BigInt::from(u64::MAX)).to_f64(), -18446744073709551616.0);
+
+    assert_eq!(
+        BigInt::from(2i64).partial_cmp_f64(2.5),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        BigInt::from(-2i64).partial_cmp_f64(-2.5),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        BigInt::from(3i64).partial_cmp_f64(3.0),
+        Some(Ordering::Equal)
+    );
+    assert_eq!(BigInt::from(3i64).partial_cmp_f64(f64::NAN), None);
+}
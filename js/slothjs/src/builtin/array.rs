@@ -13,14 +13,17 @@ fn array_toString(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
     let array = array_object.as_array().ok_or_else(|| {
         Exception::attr_type_error(TypeError::INSTANCE_REQUIRED, call.this_ref, "Array")
     })?;
-    let array = array.storage.clone();
+    let array = array.to_vec();
 
     let mut s = String::new();
-    for val in array.iter() {
-        if !s.is_empty() {
+    for (i, val) in array.iter().enumerate() {
+        if i > 0 {
             s += ",";
         }
-        s += val.stringify(heap)?.as_ref();
+        // holes, `undefined` and `null` are empty
+        if !(val.is_undefined() || val.is_null()) {
+            s += val.stringify(heap)?.as_ref();
+        }
     }
     Ok(Interpreted::from(s))
 }
@@ -32,11 +35,10 @@ fn array_proto_push(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted>
     let array_object = heap.get_mut(call.this_ref);
     match &mut array_object.value {
         ObjectValue::Array(array) => {
-            array.storage.extend(arguments);
-            let length = array_object
-                .get_own_value("length")
-                .unwrap_or_else(|| JSValue::from(0));
-            Ok(Interpreted::from(length))
+            for argument in arguments {
+                array.push(argument)?;
+            }
+            Ok(Interpreted::from(array.len() as f64))
         }
         // TODO: generic object path
         _ => Err(Exception::type_error(
@@ -50,7 +52,7 @@ fn array_proto_pop(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted>
     let array_object = heap.get_mut(call.this_ref);
     match &mut array_object.value {
         ObjectValue::Array(array) => {
-            let value = array.storage.pop().unwrap_or(JSValue::Undefined);
+            let value = array.pop()?.unwrap_or(JSValue::UNDEFINED);
             Ok(Interpreted::from(value))
         }
         // TODO: generic object path
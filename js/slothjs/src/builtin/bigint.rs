@@ -0,0 +1,98 @@
+use crate::prelude::*;
+
+use crate::error::{RangeError, TypeError};
+use crate::value::PrimitiveHint;
+use crate::{
+    object::HostClass, BigInt, CallContext, Exception, Heap, Interpreted, JSRef, JSResult, JSValue,
+};
+
+pub static CLASS: HostClass = HostClass {
+    name: "BigInt",
+    constructor: bigint_constructor,
+    methods: &[
+        ("toString", bigint_proto_toString),
+        ("valueOf", bigint_proto_valueOf),
+    ],
+    static_methods: &[("asIntN", bigint_asIntN), ("asUintN", bigint_asUintN)],
+};
+
+/// `BigInt(value)` converts, `new BigInt()` is a TypeError.
+fn bigint_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if heap.smells_fresh(call.this_ref) {
+        return Err(Exception::type_error(
+            TypeError::NOT_CONSTRUCTOR,
+            call.this_ref,
+        ));
+    }
+
+    let value = call.arg_value(0, heap)?;
+    let value = value.to_primitive(PrimitiveHint::Number, heap)?;
+    let n = match value.as_number() {
+        Some(n) => match BigInt::from_f64(n) {
+            Some(n) => Rc::new(n),
+            None => return Err(Exception::range_error(RangeError::NOT_INTEGER, value)),
+        },
+        None => value.to_bigint_value(heap)?,
+    };
+    Ok(Interpreted::from(n))
+}
+
+fn object_to_bigint(this_ref: JSRef, heap: &Heap) -> JSResult<JSValue> {
+    match heap.get(this_ref).to_primitive() {
+        Some(value) if value.as_bigint().is_some() => Ok(value),
+        _ => Err(Exception::instance_required(this_ref, "BigInt")),
+    }
+}
+
+/// The `bits` argument of `asIntN()`/`asUintN()`, a ToIndex.
+fn bits_arg(call: &CallContext, heap: &mut Heap) -> JSResult<u64> {
+    let value = call.arg_value(0, heap)?;
+    let n = value.to_number(heap)?;
+    let n = if n.is_nan() { 0.0 } else { n.trunc() };
+    if !(0.0..=((1u64 << 53) - 1) as f64).contains(&n) {
+        return Err(Exception::range_error(RangeError::INVALID_LENGTH, value));
+    }
+    Ok(n as u64)
+}
+
+#[allow(non_snake_case)]
+fn bigint_proto_toString(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = object_to_bigint(call.this_ref, heap)?;
+    let radix = call.arg_value(0, heap)?;
+    let radix = match radix.is_undefined() {
+        true => 10.0,
+        false => radix.to_number(heap)?.trunc(),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(Exception::range_error(RangeError::INVALID_RADIX, radix));
+    }
+    let s = value.as_bigint().unwrap().to_string_radix(radix as u32);
+    Ok(Interpreted::from(s))
+}
+
+#[allow(non_snake_case)]
+fn bigint_proto_valueOf(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = object_to_bigint(call.this_ref, heap)?;
+    Ok(Interpreted::from(value))
+}
+
+#[allow(non_snake_case)]
+fn bigint_asIntN(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let bits = bits_arg(&call, heap)?;
+    let n = call.arg_value(1, heap)?.to_bigint_value(heap)?;
+    Ok(Interpreted::from(n.as_int_n(bits)))
+}
+
+#[allow(non_snake_case)]
+fn bigint_asUintN(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let bits = bits_arg(&call, heap)?;
+    let n = call.arg_value(1, heap)?.to_bigint_value(heap)?;
+    // a negative one would take all the `bits`
+    if n.is_negative() && bits > BigInt::MAX_BITS {
+        return Err(Exception::range_error(
+            RangeError::BIGINT_TOO_BIG,
+            JSValue::from(bits as f64),
+        ));
+    }
+    Ok(Interpreted::from(n.as_uint_n(bits)))
+}
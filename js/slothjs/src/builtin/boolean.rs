@@ -1,7 +1,6 @@
 //use crate::prelude::*;
 use crate::{
     object::HostClass, CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult,
-    JSValue,
 };
 
 pub static CLASS: HostClass = HostClass {
@@ -26,9 +25,9 @@ fn boolean_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpret
 }
 
 fn object_to_bool(this_ref: JSRef, heap: &Heap) -> JSResult<bool> {
-    match heap.get(this_ref).to_primitive() {
-        Some(JSValue::Bool(b)) => Ok(b),
-        _ => Err(Exception::instance_required(this_ref, "Boolean")),
+    match heap.get(this_ref).to_primitive().and_then(|v| v.as_bool()) {
+        Some(b) => Ok(b),
+        None => Err(Exception::instance_required(this_ref, "Boolean")),
     }
 }
 
@@ -0,0 +1,743 @@
+//! `ArrayBuffer`, the typed arrays (`Int8Array` .. `Float64Array`) and `DataView`.
+//!
+//! The bytes of an ArrayBuffer are shared with the views on it, so elements of a view
+//! can be read and written without going through the buffer object.
+use core::cell::RefCell;
+
+use crate::builtin::function::arraylike_values;
+use crate::builtin::map::iterate;
+use crate::error::{RangeError, TypeError};
+use crate::object::{HostClass, JSArray, ObjectValue};
+use crate::prelude::*;
+use crate::value::{number_to_int32, string_to_number};
+use crate::{CallContext, Exception, Heap, HostFn, Interpreted, JSObject, JSRef, JSResult};
+
+/// The bytes of an ArrayBuffer.
+pub type Bytes = Rc<RefCell<Vec<u8>>>;
+
+/// The type of elements of a typed array, also what a DataView reads or writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementType {
+    Int8,
+    Uint8,
+    /// like `Uint8`, but rounds and clamps numbers into `0..=255`
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl ElementType {
+    /// The name of the typed array class.
+    pub const fn name(self) -> &'static str {
+        match self {
+            ElementType::Int8 => "Int8Array",
+            ElementType::Uint8 => "Uint8Array",
+            ElementType::Uint8Clamped => "Uint8ClampedArray",
+            ElementType::Int16 => "Int16Array",
+            ElementType::Uint16 => "Uint16Array",
+            ElementType::Int32 => "Int32Array",
+            ElementType::Uint32 => "Uint32Array",
+            ElementType::Float32 => "Float32Array",
+            ElementType::Float64 => "Float64Array",
+        }
+    }
+
+    /// `BYTES_PER_ELEMENT`
+    pub const fn size(self) -> usize {
+        match self {
+            ElementType::Int8 | ElementType::Uint8 | ElementType::Uint8Clamped => 1,
+            ElementType::Int16 | ElementType::Uint16 => 2,
+            ElementType::Int32 | ElementType::Uint32 | ElementType::Float32 => 4,
+            ElementType::Float64 => 8,
+        }
+    }
+
+    /// Decodes an element from the start of `bytes`.
+    fn read(self, bytes: &[u8], little_endian: bool) -> JSNumber {
+        macro_rules! read {
+            ($t:ty) => {{
+                let raw = bytes[..core::mem::size_of::<$t>()].try_into().unwrap();
+                match little_endian {
+                    true => <$t>::from_le_bytes(raw) as JSNumber,
+                    false => <$t>::from_be_bytes(raw) as JSNumber,
+                }
+            }};
+        }
+        match self {
+            ElementType::Int8 => read!(i8),
+            ElementType::Uint8 | ElementType::Uint8Clamped => read!(u8),
+            ElementType::Int16 => read!(i16),
+            ElementType::Uint16 => read!(u16),
+            ElementType::Int32 => read!(i32),
+            ElementType::Uint32 => read!(u32),
+            ElementType::Float32 => read!(f32),
+            ElementType::Float64 => read!(f64),
+        }
+    }
+
+    /// Encodes `n` to the start of `bytes`; integers wrap around like `ToInt32()`.
+    fn write(self, bytes: &mut [u8], n: JSNumber, little_endian: bool) {
+        macro_rules! write {
+            ($value:expr) => {{
+                let value = $value;
+                let raw = match little_endian {
+                    true => value.to_le_bytes(),
+                    false => value.to_be_bytes(),
+                };
+                bytes[..raw.len()].copy_from_slice(&raw);
+            }};
+        }
+        let int = number_to_int32(n);
+        match self {
+            ElementType::Int8 => write!(int as i8),
+            ElementType::Uint8 => write!(int as u8),
+            ElementType::Uint8Clamped => write!(clamp_to_u8(n)),
+            ElementType::Int16 => write!(int as i16),
+            ElementType::Uint16 => write!(int as u16),
+            ElementType::Int32 => write!(int),
+            ElementType::Uint32 => write!(int as u32),
+            ElementType::Float32 => write!(n as f32),
+            ElementType::Float64 => write!(n),
+        }
+    }
+}
+
+/// Rounds half to even and clamps, like `Uint8ClampedArray` does.
+fn clamp_to_u8(n: JSNumber) -> u8 {
+    if n.is_nan() || n <= 0.0 {
+        return 0;
+    }
+    if n >= 255.0 {
+        return 255;
+    }
+    let int = n as u8;
+    let fraction = n - int as JSNumber;
+    match fraction > 0.5 || (fraction == 0.5 && int % 2 == 1) {
+        true => int + 1,
+        false => int,
+    }
+}
+
+/// ToNumber of a primitive value; objects must be converted before, they give `NaN`.
+pub(crate) fn primitive_number(value: &JSValue) -> JSNumber {
+    match value.unboxed() {
+        Unboxed::Number(n) => n,
+        Unboxed::Bool(b) => b as u8 as JSNumber,
+        Unboxed::String(s) => string_to_number(&s),
+        Unboxed::Ref(Heap::NULL) => 0.0,
+        _ => f64::NAN,
+    }
+}
+
+/// The range of bytes of an ArrayBuffer that a typed array or a DataView works on.
+#[derive(Clone, Debug)]
+pub struct ByteView {
+    /// the ArrayBuffer object
+    pub buffer: JSRef,
+    pub bytes: Bytes,
+    pub offset: usize,
+    /// in bytes
+    pub length: usize,
+}
+
+impl ByteView {
+    /// The number of `element`s in the view.
+    pub fn len(&self, element: ElementType) -> usize {
+        self.length / element.size()
+    }
+
+    /// Reads the element at `index`, typed arrays are little-endian.
+    pub fn element(&self, element: ElementType, index: usize) -> Option<JSNumber> {
+        if index >= self.len(element) {
+            return None;
+        }
+        let start = self.offset + index * element.size();
+        Some(element.read(&self.bytes.borrow()[start..], true))
+    }
+
+    /// Writes the element at `index`, writes out of bounds are ignored.
+    pub fn set_element(&self, element: ElementType, index: usize, n: JSNumber) {
+        if index < self.len(element) {
+            let start = self.offset + index * element.size();
+            element.write(&mut self.bytes.borrow_mut()[start..], n, true);
+        }
+    }
+
+    fn elements(&self, element: ElementType) -> Vec<JSValue> {
+        (0..self.len(element))
+            .filter_map(|i| self.element(element, i))
+            .map(JSValue::from)
+            .collect()
+    }
+}
+
+/// Allocates an ArrayBuffer of `length` zero bytes.
+fn alloc_buffer(length: usize, heap: &mut Heap) -> JSResult<(JSRef, Bytes)> {
+    heap.check_allocation(length)?;
+    let bytes = Rc::new(RefCell::new(vec![0; length]));
+    let mut object = JSObject::new();
+    object.proto = Heap::ARRAY_BUFFER_PROTO;
+    object.value = ObjectValue::ArrayBuffer(Rc::clone(&bytes));
+    Ok((heap.alloc(object), bytes))
+}
+
+/// ToIndex: a non-negative integer argument, `default` if it is `undefined`.
+fn index_arg(call: &CallContext, index: usize, default: usize, heap: &mut Heap) -> JSResult<usize> {
+    let value = call.arg_value(index, heap)?;
+    if value.is_undefined() {
+        return Ok(default);
+    }
+    let n = value.to_number(heap)?;
+    let n = if n.is_nan() { 0.0 } else { n };
+    if !(0.0..=JSArray::MAX_LENGTH as JSNumber).contains(&n) {
+        return Err(Exception::range_error(RangeError::INVALID_LENGTH, value));
+    }
+    Ok(n as usize)
+}
+
+/// A `begin`/`end` argument of `slice()` and such: negative ones count from `length`.
+fn relative_arg(
+    call: &CallContext,
+    index: usize,
+    length: usize,
+    default: usize,
+    heap: &mut Heap,
+) -> JSResult<usize> {
+    let value = call.arg_value(index, heap)?;
+    if value.is_undefined() {
+        return Ok(default);
+    }
+    let n = value.to_number(heap)?;
+    let n = if n.is_nan() { 0.0 } else { n };
+    let at = match n < 0.0 {
+        true => length as JSNumber + n,
+        false => n,
+    };
+    Ok(at.clamp(0.0, length as JSNumber) as usize)
+}
+
+fn out_of_bounds<V>(what: V) -> Exception
+where
+    Interpreted: From<V>,
+{
+    Exception::range_error(RangeError::OUT_OF_BOUNDS, what)
+}
+
+/*
+ *  ArrayBuffer
+ */
+
+pub static ARRAY_BUFFER_CLASS: HostClass = HostClass {
+    name: "ArrayBuffer",
+    constructor: array_buffer_constructor,
+    methods: &[("slice", array_buffer_proto_slice)],
+    static_methods: &[("isView", array_buffer_isView)],
+};
+
+fn array_buffer_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if !heap.smells_fresh(call.this_ref) {
+        return Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            "ArrayBuffer",
+        ));
+    }
+    let length = index_arg(&call, 0, 0, heap)?;
+    heap.check_allocation(length)?;
+    let bytes = Rc::new(RefCell::new(vec![0; length]));
+    heap.get_mut(call.this_ref).value = ObjectValue::ArrayBuffer(bytes);
+    Ok(Interpreted::VOID)
+}
+
+fn this_buffer(this_ref: JSRef, heap: &Heap) -> JSResult<Bytes> {
+    match &heap.get(this_ref).value {
+        ObjectValue::ArrayBuffer(bytes) => Ok(Rc::clone(bytes)),
+        _ => Err(Exception::instance_required(this_ref, "ArrayBuffer")),
+    }
+}
+
+#[allow(non_snake_case)]
+fn array_buffer_proto_byteLength(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let length = this_buffer(call.this_ref, heap)?.borrow().len();
+    Ok(Interpreted::from(length as i64))
+}
+
+fn array_buffer_proto_slice(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let bytes = this_buffer(call.this_ref, heap)?;
+    let length = bytes.borrow().len();
+    let begin = relative_arg(&call, 0, length, 0, heap)?;
+    let end = relative_arg(&call, 1, length, length, heap)?;
+    let copied = bytes.borrow()[begin..usize::max(begin, end)].to_vec();
+
+    heap.check_allocation(copied.len())?;
+    let mut object = JSObject::new();
+    object.proto = heap.get(call.this_ref).proto;
+    object.value = ObjectValue::ArrayBuffer(Rc::new(RefCell::new(copied)));
+    Ok(Interpreted::from(heap.alloc(object)))
+}
+
+#[allow(non_snake_case)]
+fn array_buffer_isView(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let is_view = match call.arg_value(0, heap)?.as_objref() {
+        Some(Heap::NULL) | None => false,
+        Some(objref) => matches!(
+            heap.get(objref).value,
+            ObjectValue::TypedArray(..) | ObjectValue::DataView(_)
+        ),
+    };
+    Ok(Interpreted::from(is_view))
+}
+
+/*
+ *  Typed arrays
+ */
+
+macro_rules! typed_array_constructors {
+    ($($element:ident => $constructor:ident,)*) => {
+        $(
+            fn $constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+                typed_array_constructor(ElementType::$element, call, heap)
+            }
+        )*
+
+        const TYPED_ARRAYS: &[(ElementType, HostFn)] = &[
+            $( (ElementType::$element, $constructor), )*
+        ];
+    };
+}
+
+typed_array_constructors! {
+    Int8 => int8_array_constructor,
+    Uint8 => uint8_array_constructor,
+    Uint8Clamped => uint8_clamped_array_constructor,
+    Int16 => int16_array_constructor,
+    Uint16 => uint16_array_constructor,
+    Int32 => int32_array_constructor,
+    Uint32 => uint32_array_constructor,
+    Float32 => float32_array_constructor,
+    Float64 => float64_array_constructor,
+}
+
+const TYPED_ARRAY_METHODS: &[(&str, HostFn)] = &[
+    ("fill", typed_array_proto_fill),
+    ("set", typed_array_proto_set),
+    ("slice", typed_array_proto_slice),
+    ("subarray", typed_array_proto_subarray),
+    ("toString", typed_array_proto_toString),
+];
+
+/// `new Uint8Array(length)`, `new Uint8Array(arrayLike)`
+/// or `new Uint8Array(buffer, byteOffset, length)`.
+fn typed_array_constructor(
+    element: ElementType,
+    call: CallContext,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    if !heap.smells_fresh(call.this_ref) {
+        return Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            element.name(),
+        ));
+    }
+    let size = element.size();
+    let argument = call.arg_value(0, heap)?;
+    let view = match argument.as_objref() {
+        Some(Heap::NULL) | None => {
+            let length = index_arg(&call, 0, 0, heap)?;
+            let byte_length = length
+                .checked_mul(size)
+                .ok_or_else(|| out_of_bounds(argument))?;
+            let (buffer, bytes) = alloc_buffer(byte_length, heap)?;
+            ByteView {
+                buffer,
+                bytes,
+                offset: 0,
+                length: byte_length,
+            }
+        }
+        Some(buffer) => match &heap.get(buffer).value {
+            ObjectValue::ArrayBuffer(bytes) => {
+                let bytes = Rc::clone(bytes);
+                let buffer_length = bytes.borrow().len();
+                let offset = index_arg(&call, 1, 0, heap)?;
+                if offset % size != 0 {
+                    let what = Interpreted::from(offset as JSNumber);
+                    return Err(Exception::range_error(RangeError::MISALIGNED, what));
+                }
+                let length = match call.arg_value(2, heap)?.is_undefined() {
+                    true if buffer_length % size != 0 => {
+                        let what = Interpreted::from(buffer_length as JSNumber);
+                        return Err(Exception::range_error(RangeError::MISALIGNED, what));
+                    }
+                    true => buffer_length.checked_sub(offset),
+                    false => index_arg(&call, 2, 0, heap)?.checked_mul(size),
+                };
+                match length {
+                    Some(length) if offset + length <= buffer_length => ByteView {
+                        buffer,
+                        bytes,
+                        offset,
+                        length,
+                    },
+                    _ => return Err(out_of_bounds(offset as JSNumber)),
+                }
+            }
+            ObjectValue::Array(_)
+            | ObjectValue::TypedArray(..)
+            | ObjectValue::Map(_)
+            | ObjectValue::Set(_) => {
+                let values = iterate(&argument, heap)?;
+                new_elements(element, values, heap)?
+            }
+            _ => {
+                let values = arraylike_values(&argument, heap)?;
+                new_elements(element, values, heap)?
+            }
+        },
+    };
+    heap.get_mut(call.this_ref).value = ObjectValue::TypedArray(element, view);
+    Ok(Interpreted::VOID)
+}
+
+/// A view on a new buffer with `values` converted to `element`s.
+fn new_elements(element: ElementType, values: Vec<JSValue>, heap: &mut Heap) -> JSResult<ByteView> {
+    let length = values.len() * element.size();
+    let (buffer, bytes) = alloc_buffer(length, heap)?;
+    let view = ByteView {
+        buffer,
+        bytes,
+        offset: 0,
+        length,
+    };
+    for (i, value) in values.iter().enumerate() {
+        let n = value.to_number(heap)?;
+        view.set_element(element, i, n);
+    }
+    Ok(view)
+}
+
+fn this_typed_array(this_ref: JSRef, heap: &Heap) -> JSResult<(ElementType, ByteView)> {
+    match &heap.get(this_ref).value {
+        ObjectValue::TypedArray(element, view) => Ok((*element, view.clone())),
+        _ => Err(Exception::instance_required(this_ref, "TypedArray")),
+    }
+}
+
+/// The view of a typed array or a DataView.
+fn this_view(this_ref: JSRef, heap: &Heap) -> JSResult<&ByteView> {
+    match &heap.get(this_ref).value {
+        ObjectValue::TypedArray(_, view) | ObjectValue::DataView(view) => Ok(view),
+        _ => Err(Exception::instance_required(this_ref, "TypedArray")),
+    }
+}
+
+#[allow(non_snake_case)]
+fn view_proto_byteLength(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let length = this_view(call.this_ref, heap)?.length;
+    Ok(Interpreted::from(length as i64))
+}
+
+#[allow(non_snake_case)]
+fn view_proto_byteOffset(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let offset = this_view(call.this_ref, heap)?.offset;
+    Ok(Interpreted::from(offset as i64))
+}
+
+fn view_proto_buffer(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let buffer = this_view(call.this_ref, heap)?.buffer;
+    Ok(Interpreted::from(buffer))
+}
+
+/// Getters shared by typed arrays and DataView.
+const VIEW_GETTERS: &[(&str, HostFn)] = &[
+    ("buffer", view_proto_buffer),
+    ("byteLength", view_proto_byteLength),
+    ("byteOffset", view_proto_byteOffset),
+];
+
+/// Makes a typed array like `this_ref` on `view`.
+fn alloc_typed_array(
+    this_ref: JSRef,
+    element: ElementType,
+    view: ByteView,
+    heap: &mut Heap,
+) -> JSRef {
+    let mut object = JSObject::new();
+    object.proto = heap.get(this_ref).proto;
+    object.value = ObjectValue::TypedArray(element, view);
+    heap.alloc(object)
+}
+
+/// `.fill(value, begin = 0, end = length)`
+fn typed_array_proto_fill(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let (element, view) = this_typed_array(call.this_ref, heap)?;
+    let n = call.arg_value(0, heap)?.to_number(heap)?;
+    let length = view.len(element);
+    let begin = relative_arg(&call, 1, length, 0, heap)?;
+    let end = relative_arg(&call, 2, length, length, heap)?;
+    for i in begin..end {
+        view.set_element(element, i, n);
+    }
+    Ok(Interpreted::from(call.this_ref))
+}
+
+/// `.set(source, offset = 0)` copies an array-like `source` into the array at `offset`.
+fn typed_array_proto_set(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let (element, view) = this_typed_array(call.this_ref, heap)?;
+    let source = call.arg_value(0, heap)?;
+    let offset = index_arg(&call, 1, 0, heap)?;
+    // the source may share the buffer: read all of it first
+    let values = match source.as_objref().map(|r| &heap.get(r).value) {
+        Some(ObjectValue::TypedArray(source_element, source_view)) => {
+            source_view.elements(*source_element)
+        }
+        _ => arraylike_values(&source, heap)?,
+    };
+    if offset + values.len() > view.len(element) {
+        return Err(out_of_bounds(offset as JSNumber));
+    }
+    for (i, value) in values.iter().enumerate() {
+        let n = value.to_number(heap)?;
+        view.set_element(element, offset + i, n);
+    }
+    Ok(Interpreted::VOID)
+}
+
+/// `.slice(begin = 0, end = length)` copies elements into a new array.
+fn typed_array_proto_slice(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let (element, view) = this_typed_array(call.this_ref, heap)?;
+    let length = view.len(element);
+    let begin = relative_arg(&call, 0, length, 0, heap)?;
+    let end = usize::max(begin, relative_arg(&call, 1, length, length, heap)?);
+
+    let size = element.size();
+    let from = view.offset + begin * size;
+    let copied = view.bytes.borrow()[from..from + (end - begin) * size].to_vec();
+    let (buffer, bytes) = alloc_buffer(copied.len(), heap)?;
+    let length = copied.len();
+    *bytes.borrow_mut() = copied;
+    let view = ByteView {
+        buffer,
+        bytes,
+        offset: 0,
+        length,
+    };
+    Ok(Interpreted::from(alloc_typed_array(
+        call.this_ref,
+        element,
+        view,
+        heap,
+    )))
+}
+
+/// `.subarray(begin = 0, end = length)` makes a new view on the same buffer.
+fn typed_array_proto_subarray(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let (element, view) = this_typed_array(call.this_ref, heap)?;
+    let length = view.len(element);
+    let begin = relative_arg(&call, 0, length, 0, heap)?;
+    let end = usize::max(begin, relative_arg(&call, 1, length, length, heap)?);
+    let view = ByteView {
+        offset: view.offset + begin * element.size(),
+        length: (end - begin) * element.size(),
+        ..view
+    };
+    Ok(Interpreted::from(alloc_typed_array(
+        call.this_ref,
+        element,
+        view,
+        heap,
+    )))
+}
+
+#[allow(non_snake_case)]
+fn typed_array_proto_toString(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let (element, view) = this_typed_array(call.this_ref, heap)?;
+    let mut s = String::new();
+    for (i, value) in view.elements(element).iter().enumerate() {
+        if i > 0 {
+            s += ",";
+        }
+        s += value.stringify(heap)?.as_ref();
+    }
+    Ok(Interpreted::from(s))
+}
+
+/*
+ *  DataView
+ */
+
+macro_rules! data_view_accessors {
+    ($($element:ident: $get:ident, $set:ident;)*) => {
+        $(
+            #[allow(non_snake_case)]
+            fn $get(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+                data_view_get(ElementType::$element, call, heap)
+            }
+
+            #[allow(non_snake_case)]
+            fn $set(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+                data_view_set(ElementType::$element, call, heap)
+            }
+        )*
+    };
+}
+
+data_view_accessors! {
+    Int8: data_view_proto_getInt8, data_view_proto_setInt8;
+    Uint8: data_view_proto_getUint8, data_view_proto_setUint8;
+    Int16: data_view_proto_getInt16, data_view_proto_setInt16;
+    Uint16: data_view_proto_getUint16, data_view_proto_setUint16;
+    Int32: data_view_proto_getInt32, data_view_proto_setInt32;
+    Uint32: data_view_proto_getUint32, data_view_proto_setUint32;
+    Float32: data_view_proto_getFloat32, data_view_proto_setFloat32;
+    Float64: data_view_proto_getFloat64, data_view_proto_setFloat64;
+}
+
+pub static DATA_VIEW_CLASS: HostClass = HostClass {
+    name: "DataView",
+    constructor: data_view_constructor,
+    methods: &[
+        ("getFloat32", data_view_proto_getFloat32),
+        ("getFloat64", data_view_proto_getFloat64),
+        ("getInt16", data_view_proto_getInt16),
+        ("getInt32", data_view_proto_getInt32),
+        ("getInt8", data_view_proto_getInt8),
+        ("getUint16", data_view_proto_getUint16),
+        ("getUint32", data_view_proto_getUint32),
+        ("getUint8", data_view_proto_getUint8),
+        ("setFloat32", data_view_proto_setFloat32),
+        ("setFloat64", data_view_proto_setFloat64),
+        ("setInt16", data_view_proto_setInt16),
+        ("setInt32", data_view_proto_setInt32),
+        ("setInt8", data_view_proto_setInt8),
+        ("setUint16", data_view_proto_setUint16),
+        ("setUint32", data_view_proto_setUint32),
+        ("setUint8", data_view_proto_setUint8),
+    ],
+    static_methods: &[],
+};
+
+/// `new DataView(buffer, byteOffset = 0, byteLength = rest of buffer)`
+fn data_view_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if !heap.smells_fresh(call.this_ref) {
+        return Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            "DataView",
+        ));
+    }
+    let argument = call.arg_value(0, heap)?;
+    let buffer = argument.as_objref().filter(|&r| r != Heap::NULL);
+    let bytes = match buffer.map(|buffer| &heap.get(buffer).value) {
+        Some(ObjectValue::ArrayBuffer(bytes)) => Rc::clone(bytes),
+        _ => return Err(Exception::instance_required(argument, "ArrayBuffer")),
+    };
+    let buffer_length = bytes.borrow().len();
+    let offset = index_arg(&call, 1, 0, heap)?;
+    if offset > buffer_length {
+        return Err(out_of_bounds(offset as JSNumber));
+    }
+    let length = index_arg(&call, 2, buffer_length - offset, heap)?;
+    if offset + length > buffer_length {
+        return Err(out_of_bounds(length as JSNumber));
+    }
+    let view = ByteView {
+        buffer: buffer.unwrap(),
+        bytes,
+        offset,
+        length,
+    };
+    heap.get_mut(call.this_ref).value = ObjectValue::DataView(view);
+    Ok(Interpreted::VOID)
+}
+
+/// The view of `this` and the byte offset of `element` at argument 0.
+fn data_view_at(
+    element: ElementType,
+    call: &CallContext,
+    heap: &mut Heap,
+) -> JSResult<(ByteView, usize)> {
+    let view = match &heap.get(call.this_ref).value {
+        ObjectValue::DataView(view) => view.clone(),
+        _ => return Err(Exception::instance_required(call.this_ref, "DataView")),
+    };
+    let at = index_arg(call, 0, 0, heap)?;
+    if at + element.size() > view.length {
+        return Err(out_of_bounds(at as JSNumber));
+    }
+    Ok((view, at))
+}
+
+/// `.getInt16(byteOffset, littleEndian = false)` and such.
+fn data_view_get(
+    element: ElementType,
+    call: CallContext,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let (view, at) = data_view_at(element, &call, heap)?;
+    let little_endian = call.arg_value(1, heap)?.boolify(heap);
+    let n = element.read(&view.bytes.borrow()[view.offset + at..], little_endian);
+    Ok(Interpreted::from(n))
+}
+
+/// `.setInt16(byteOffset, value, littleEndian = false)` and such.
+fn data_view_set(
+    element: ElementType,
+    call: CallContext,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let (view, at) = data_view_at(element, &call, heap)?;
+    let n = call.arg_value(1, heap)?.to_number(heap)?;
+    let little_endian = call.arg_value(2, heap)?.boolify(heap);
+    element.write(
+        &mut view.bytes.borrow_mut()[view.offset + at..],
+        n,
+        little_endian,
+    );
+    Ok(Interpreted::VOID)
+}
+
+pub fn init(heap: &mut Heap) -> JSResult<()> {
+    heap.init_class(Heap::ARRAY_BUFFER_PROTO, &ARRAY_BUFFER_CLASS)?;
+    heap.init_getter(
+        Heap::ARRAY_BUFFER_PROTO,
+        "byteLength",
+        array_buffer_proto_byteLength,
+    )?;
+
+    for &(element, constructor) in TYPED_ARRAYS.iter() {
+        let class = HostClass {
+            name: element.name(),
+            constructor,
+            methods: TYPED_ARRAY_METHODS,
+            static_methods: &[],
+        };
+        let proto = heap.alloc(JSObject::new());
+        heap.init_class(proto, &class)?;
+        for &(name, getter) in VIEW_GETTERS.iter() {
+            heap.init_getter(proto, name, getter)?;
+        }
+
+        let size = element.size() as JSNumber;
+        let constructor = heap
+            .get(proto)
+            .get_own_value("constructor")
+            .unwrap()
+            .to_ref()?;
+        heap.get_mut(constructor)
+            .set_system("BYTES_PER_ELEMENT", size)?;
+        heap.get_mut(proto).set_system("BYTES_PER_ELEMENT", size)?;
+    }
+
+    let proto = heap.alloc(JSObject::new());
+    heap.init_class(proto, &DATA_VIEW_CLASS)?;
+    for &(name, getter) in VIEW_GETTERS.iter() {
+        heap.init_getter(proto, name, getter)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,1007 @@
+//! The builtin `console` object.
+//!
+//! Everything printed by `console.*` goes to a [`ConsoleSink`] set on the [`Heap`]
+//! (or on [`crate::runtime::Runtime`]), values are formatted like Node's `util.inspect()`.
+
+use crate::prelude::*;
+use crate::value::{decimal_prefix, is_str_whitespace, number_to_string};
+use crate::{
+    object::ObjectValue, CallContext, Heap, HostFn, Interpreted, JSObject, JSRef, JSResult,
+};
+
+/// The severity of a `console` message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    /// `console.log()`, `console.table()`, `console.timeEnd()`
+    Log,
+    /// `console.info()`
+    Info,
+    /// `console.warn()`, `console.assert()`
+    Warn,
+    /// `console.error()`
+    Error,
+    /// `console.debug()`
+    Debug,
+}
+
+/// Where the output of `console` goes.
+///
+/// Any `FnMut(LogLevel, &str)` is a sink, e.g. to capture the output:
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use slothjs::{Heap, LogLevel};
+/// let output = Rc::new(RefCell::new(Vec::new()));
+/// let sink = Rc::clone(&output);
+///
+/// let mut heap = Heap::new();
+/// heap.set_console(Box::new(move |level: LogLevel, message: &str| {
+///     sink.borrow_mut().push((level, message.to_string()));
+/// }));
+/// ```
+pub trait ConsoleSink {
+    /// Writes out one formatted message (without a trailing newline).
+    fn write(&mut self, level: LogLevel, message: &str);
+
+    /// The current time in milliseconds for `console.time()`; `None` if there is no clock.
+    fn now(&self) -> Option<f64> {
+        #[cfg(feature = "std")]
+        {
+            let since_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH);
+            since_epoch.ok().map(|d| d.as_secs_f64() * 1000.0)
+        }
+        #[cfg(not(feature = "std"))]
+        None
+    }
+}
+
+impl<F> ConsoleSink for F
+where
+    F: FnMut(LogLevel, &str),
+{
+    fn write(&mut self, level: LogLevel, message: &str) {
+        self(level, message)
+    }
+}
+
+/// The default sink: `log`/`info`/`debug` go to stdout, `warn`/`error` go to stderr.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct StdConsole;
+
+#[cfg(feature = "std")]
+impl ConsoleSink for StdConsole {
+    fn write(&mut self, level: LogLevel, message: &str) {
+        match level {
+            LogLevel::Log | LogLevel::Info | LogLevel::Debug => println!("{}", message),
+            LogLevel::Warn | LogLevel::Error => eprintln!("{}", message),
+        }
+    }
+}
+
+/// The state of `console` on a [`Heap`]: its sink and running timers.
+pub(crate) struct Console {
+    sink: Box<dyn ConsoleSink>,
+    timers: HashMap<JSString, f64>,
+}
+
+impl Console {
+    pub(crate) fn new(sink: Box<dyn ConsoleSink>) -> Console {
+        Console {
+            sink,
+            timers: HashMap::new(),
+        }
+    }
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        #[cfg(feature = "std")]
+        let sink = Box::new(StdConsole);
+        #[cfg(not(feature = "std"))]
+        let sink = Box::new(|_: LogLevel, _: &str| ());
+        Console::new(sink)
+    }
+}
+
+impl fmt::Debug for Console {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Console({} timers)", self.timers.len())
+    }
+}
+
+/*
+ *  util.inspect()
+ */
+
+/// Formats `value` the way `util.inspect(value)` in Node does.
+pub fn inspect(value: &JSValue, heap: &Heap) -> String {
+    Inspector::new(heap).format_value(value, 0)
+}
+
+struct Inspector<'a> {
+    heap: &'a Heap,
+    /// how deep objects are expanded (`[Object]` below that)
+    depth: isize,
+    max_array_length: usize,
+    break_length: usize,
+
+    /// objects being formatted, to detect cycles
+    seen: Vec<JSRef>,
+    indentation: usize,
+    /// the depth of the object entered last
+    current_depth: isize,
+}
+
+/// Node's `compact`: this many innermost levels are combined on a single line.
+const COMPACT: isize = 3;
+
+impl<'a> Inspector<'a> {
+    fn new(heap: &'a Heap) -> Self {
+        Inspector {
+            heap,
+            depth: 2,
+            max_array_length: 100,
+            break_length: 80,
+            seen: Vec::new(),
+            indentation: 0,
+            current_depth: 0,
+        }
+    }
+
+    fn with_depth(mut self, depth: isize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    fn format_value(&mut self, value: &JSValue, recurse_times: isize) -> String {
+        match value.unboxed() {
+            Unboxed::Undefined => "undefined".to_string(),
+            Unboxed::Bool(b) => b.to_string(),
+            Unboxed::Number(n) => format_number(n),
+            Unboxed::String(s) => quote(s.as_str()),
+            Unboxed::BigInt(n) => format!("{}n", n),
+            Unboxed::Ref(Heap::NULL) => "null".to_string(),
+            Unboxed::Ref(objref) if self.seen.contains(&objref) => "[Circular]".to_string(),
+            Unboxed::Ref(objref) => self.format_object(objref, recurse_times),
+        }
+    }
+
+    fn format_object(&mut self, objref: JSRef, recurse_times: isize) -> String {
+        let heap = self.heap;
+        let object = heap.get(objref);
+        // like node, show the target of a proxy without calling its traps
+        if let Some(proxy) = object.as_proxy() {
+            return match proxy.target {
+                Heap::NULL => "<Revoked Proxy>".to_string(),
+                target => self.format_object(target, recurse_times),
+            };
+        }
+        let keys = enumerable_keys(object);
+        let constructor = constructor_name(object, heap);
+
+        let mut base = String::new();
+        let (open, close) = match &object.value {
+            ObjectValue::Array(array) => {
+                if array.is_empty() && keys.is_empty() {
+                    return "[]".to_string();
+                }
+                ("[".to_string(), "]")
+            }
+            ObjectValue::TypedArray(element, view) => {
+                (format!("{}({}) [", element.name(), view.len(*element)), "]")
+            }
+            ObjectValue::ArrayBuffer(_) => ("ArrayBuffer {".to_string(), "}"),
+            ObjectValue::DataView(_) => ("DataView {".to_string(), "}"),
+            ObjectValue::Map(map) => (format!("Map({}) {{", map.len()), "}"),
+            // like node, do not show what might be gone already
+            ObjectValue::WeakMap(_) | ObjectValue::WeakSet(_) => {
+                let name = constructor.clone().unwrap_or_default();
+                base = format!("{} {{ <items unknown> }}", name);
+                (format!("{} {{", name), "}")
+            }
+            ObjectValue::Set(set) => (format!("Set({}) {{", set.len()), "}"),
+            ObjectValue::HostFn(_) | ObjectValue::Closure(_) | ObjectValue::BoundFunction(_) => {
+                base = match object.get_own_value("name").and_then(|v| v.as_string()) {
+                    Some(name) if !name.is_empty() => {
+                        format!("[Function: {}]", name)
+                    }
+                    _ => "[Function (anonymous)]".to_string(),
+                };
+                ("{".to_string(), "}")
+            }
+            ObjectValue::Boolean(b) => {
+                base = format!("[Boolean: {}]", b);
+                ("{".to_string(), "}")
+            }
+            ObjectValue::Number(n) => {
+                base = format!("[Number: {}]", format_number(*n));
+                ("{".to_string(), "}")
+            }
+            ObjectValue::String(s) => {
+                base = format!("[String: {}]", quote(s.as_str()));
+                ("{".to_string(), "}")
+            }
+            ObjectValue::BigInt(n) => {
+                base = format!("[BigInt: {}n]", n);
+                ("{".to_string(), "}")
+            }
+            ObjectValue::None if objref.has_proto(Heap::ERROR_PROTO, heap) => {
+                let lookup = |name| match object.lookup_value(name, heap) {
+                    Some(value) => value.as_str().unwrap_or_default().to_string(),
+                    None => String::new(),
+                };
+                let (name, message) = (lookup("name"), lookup("message"));
+                base = match (name.is_empty(), message.is_empty()) {
+                    (_, true) => format!("[{}]", name),
+                    (true, false) => format!("[{}]", message),
+                    (false, false) => format!("[{}: {}]", name, message),
+                };
+                ("{".to_string(), "}")
+            }
+            ObjectValue::None
+            | ObjectValue::Scope(_)
+            | ObjectValue::Cell(_)
+            | ObjectValue::Proxy(_)
+            | ObjectValue::WeakRef(_)
+            | ObjectValue::FinalizationRegistry(_)
+            | ObjectValue::Accessor { .. } => {
+                let open = match &constructor {
+                    _ if object.proto == Heap::NULL => "[Object: null prototype] {".to_string(),
+                    Some(name) if name != "Object" => format!("{} {{", name),
+                    _ => "{".to_string(),
+                };
+                (open, "}")
+            }
+        };
+
+        let is_empty = match &object.value {
+            ObjectValue::Map(map) | ObjectValue::Set(map) => map.is_empty(),
+            ObjectValue::TypedArray(element, view) => view.len(*element) == 0,
+            ObjectValue::Array(_) | ObjectValue::ArrayBuffer(_) | ObjectValue::DataView(_) => false,
+            _ => true,
+        };
+        if is_empty && keys.is_empty() {
+            return if !base.is_empty() {
+                base
+            } else {
+                format!("{}{}", open, close)
+            };
+        }
+
+        if recurse_times > self.depth {
+            let name = constructor.unwrap_or_else(|| "Object".to_string());
+            return format!("[{}]", name);
+        }
+
+        let recurse_times = recurse_times + 1;
+        self.seen.push(objref);
+        self.current_depth = recurse_times;
+
+        let mut output = Vec::new();
+        let mut more_items = 0;
+        self.indentation += 2;
+        match &object.value {
+            ObjectValue::Array(array) => {
+                // holes are shown as runs of `<n empty items>`
+                let max_length = usize::min(array.len(), self.max_array_length);
+                let mut next = 0;
+                for (index, item) in array.elements() {
+                    if output.len() == max_length {
+                        break;
+                    }
+                    if index > next {
+                        output.push(empty_items(index - next));
+                        next = index;
+                        if output.len() == max_length {
+                            break;
+                        }
+                    }
+                    output.push(self.format_value(item, recurse_times));
+                    next += 1;
+                }
+                let remaining = array.len() - next;
+                if output.len() < max_length && remaining > 0 {
+                    output.push(empty_items(remaining));
+                } else {
+                    more_items = remaining;
+                }
+            }
+            ObjectValue::Map(map) => {
+                for (key, value) in map.iter() {
+                    let key = self.format_value(key, recurse_times);
+                    let value = self.format_value(value, recurse_times);
+                    output.push(format!("{} => {}", key, value));
+                }
+            }
+            ObjectValue::Set(set) => {
+                for (item, _) in set.iter() {
+                    output.push(self.format_value(item, recurse_times));
+                }
+            }
+            ObjectValue::TypedArray(element, view) => {
+                let length = view.len(*element);
+                for index in 0..usize::min(length, self.max_array_length) {
+                    let n = view.element(*element, index).unwrap_or(f64::NAN);
+                    output.push(format_number(n));
+                }
+                more_items = length - output.len();
+            }
+            ObjectValue::ArrayBuffer(bytes) => {
+                let bytes = bytes.borrow();
+                output.push(format!("[Uint8Contents]: <{}>", hex_bytes(&bytes)));
+                output.push(format!("byteLength: {}", bytes.len()));
+            }
+            ObjectValue::DataView(view) => {
+                output.push(format!("byteLength: {}", view.length));
+                output.push(format!("byteOffset: {}", view.offset));
+                let buffer = self.format_value(&JSValue::from(view.buffer), recurse_times);
+                output.push(format!("buffer: {}", buffer));
+            }
+            _ => (),
+        }
+        if more_items > 0 {
+            let plural = if more_items > 1 { "s" } else { "" };
+            output.push(format!("... {} more item{}", more_items, plural));
+        }
+        for key in keys.iter() {
+            // like node, do not call getters
+            let value = match heap.own_accessor(objref, key) {
+                Some((Some(_), Some(_))) => "[Getter/Setter]".to_string(),
+                Some((Some(_), None)) => "[Getter]".to_string(),
+                Some((None, _)) => "[Setter]".to_string(),
+                None => {
+                    let value = object.get_own_value(key).unwrap_or(JSValue::UNDEFINED);
+                    self.format_value(&value, recurse_times)
+                }
+            };
+            output.push(format!("{}: {}", format_key(key), value));
+        }
+        self.indentation -= 2;
+        self.seen.pop();
+
+        let entries = output.len();
+        if let ObjectValue::Array(array) = &object.value {
+            if entries > 6 {
+                let all_numbers =
+                    (0..entries).all(|i| array.get(i).is_some_and(|v| v.as_number().is_some()));
+                output = self.group_array_elements(output, more_items > 0, all_numbers);
+            }
+        }
+        if let ObjectValue::TypedArray(..) = &object.value {
+            if entries > 6 && keys.is_empty() {
+                output = self.group_array_elements(output, more_items > 0, true);
+            }
+        }
+
+        let open = if base.is_empty() {
+            open
+        } else {
+            format!("{} {}", base, open)
+        };
+        self.reduce_to_single_string(output, entries, &open, close, recurse_times)
+    }
+
+    /// Combines the `output` entries on one line if they fit, otherwise one per line.
+    fn reduce_to_single_string(
+        &self,
+        output: Vec<String>,
+        entries: usize,
+        open: &str,
+        close: &str,
+        recurse_times: isize,
+    ) -> String {
+        if self.current_depth - recurse_times < COMPACT && entries == output.len() {
+            let start = output.len() + self.indentation + width(open) + 10;
+            let total = (output.iter()).fold(output.len() + start, |acc, s| acc + width(s));
+            if total <= self.break_length {
+                let joined = output.join(", ");
+                if !joined.contains('\n') {
+                    return format!("{} {} {}", open, joined, close);
+                }
+            }
+        }
+        let indentation = format!("\n{}", " ".repeat(self.indentation));
+        let separator = format!(",{}  ", indentation);
+        format!(
+            "{}{}  {}{}{}",
+            open,
+            indentation,
+            output.join(&separator),
+            indentation,
+            close
+        )
+    }
+
+    /// Arranges many short array items into columns.
+    fn group_array_elements(
+        &self,
+        output: Vec<String>,
+        more_items: bool,
+        all_numbers: bool,
+    ) -> Vec<String> {
+        const SEPARATOR_SPACE: usize = 2; // a comma and a space
+        let output_length = if more_items {
+            output.len() - 1
+        } else {
+            output.len()
+        };
+
+        let data_len = (output.iter()).map(|s| width(s)).collect::<Vec<_>>();
+        let max_length = data_len[..output_length].iter().copied().max().unwrap_or(0);
+        let total_length =
+            (data_len[..output_length].iter()).sum::<usize>() + SEPARATOR_SPACE * output_length;
+        let actual_max = max_length + SEPARATOR_SPACE;
+
+        // at least three entries must fit next to each other,
+        // and a single entry must not be much longer than others
+        let fits = actual_max * 3 + self.indentation < self.break_length;
+        if !(fits && (total_length / actual_max > 5 || max_length <= 6)) {
+            return output;
+        }
+
+        const APPROX_CHAR_HEIGHTS: f64 = 2.5;
+        let average_bias = sqrt(actual_max as f64 - total_length as f64 / output.len() as f64);
+        let biased_max = f64::max(actual_max as f64 - 3.0 - average_bias, 1.0);
+        let columns = [
+            (sqrt(APPROX_CHAR_HEIGHTS * biased_max * output_length as f64) / biased_max + 0.5)
+                as usize,
+            (self.break_length - self.indentation) / actual_max,
+            COMPACT as usize * 4,
+            15,
+        ];
+        let columns = columns.into_iter().min().unwrap();
+        if columns <= 1 {
+            return output;
+        }
+
+        let max_line_length = (0..columns)
+            .map(|i| {
+                let column = (i..output_length).step_by(columns);
+                column.map(|j| data_len[j]).max().unwrap_or(0) + SEPARATOR_SPACE
+            })
+            .collect::<Vec<_>>();
+
+        let mut grouped = Vec::new();
+        for i in (0..output_length).step_by(columns) {
+            let max = usize::min(i + columns, output_length);
+            let mut line = String::new();
+            for j in i..max {
+                let last = j == max - 1;
+                let cell = if last {
+                    output[j].clone()
+                } else {
+                    format!("{}, ", output[j])
+                };
+                let padding = " ".repeat(max_line_length[j - i].saturating_sub(width(&cell)));
+                if all_numbers {
+                    let padding = if last {
+                        padding.get(SEPARATOR_SPACE..).unwrap_or("")
+                    } else {
+                        &padding
+                    };
+                    line.push_str(padding);
+                    line.push_str(&cell);
+                } else {
+                    line.push_str(&cell);
+                    if !last {
+                        line.push_str(&padding);
+                    }
+                }
+            }
+            grouped.push(line);
+        }
+        if more_items {
+            grouped.push(output[output_length].clone());
+        }
+        grouped
+    }
+}
+
+/// The number of columns `s` takes.
+fn width(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// `f64::sqrt()` is not in `core`.
+fn sqrt(x: f64) -> f64 {
+    if x <= 0.0 || !x.is_finite() {
+        return 0.0;
+    }
+    let mut guess = if x > 1.0 { x / 2.0 } else { 1.0 };
+    for _ in 0..64 {
+        let next = (guess + x / guess) / 2.0;
+        if next == guess {
+            break;
+        }
+        guess = next;
+    }
+    guess
+}
+
+fn format_number(n: JSNumber) -> String {
+    if n == 0.0 && n.is_sign_negative() {
+        "-0".to_string()
+    } else {
+        number_to_string(n)
+    }
+}
+
+/// Quotes a string with `'`, or with `"`/`` ` `` if that avoids escaping.
+fn quote(s: &str) -> String {
+    let quote = if !s.contains('\'') {
+        '\''
+    } else if !s.contains('"') {
+        '"'
+    } else if !s.contains('`') && !s.contains("${") {
+        '`'
+    } else {
+        '\''
+    };
+
+    let mut quoted = String::from(quote);
+    for c in s.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\u{b}' => quoted.push_str("\\v"),
+            '\\' => quoted.push_str("\\\\"),
+            c if c == quote => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c < ' ' || c == '\u{7f}' => {
+                let _ = write!(quoted, "\\x{:02X}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push(quote);
+    quoted
+}
+
+/// A run of holes in an array: `<3 empty items>`.
+fn empty_items(count: usize) -> String {
+    let plural = if count > 1 { "s" } else { "" };
+    format!("<{} empty item{}>", count, plural)
+}
+
+/// The contents of an ArrayBuffer as Node shows them: `01 02 ... 14 more bytes`.
+fn hex_bytes(bytes: &[u8]) -> String {
+    const MAX_BYTES: usize = 50;
+    let mut hex = (bytes.iter().take(MAX_BYTES))
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if bytes.len() > MAX_BYTES {
+        let more = bytes.len() - MAX_BYTES;
+        let plural = if more > 1 { "s" } else { "" };
+        hex += &format!(" ... {} more byte{}", more, plural);
+    }
+    hex
+}
+
+fn format_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = (chars.next()).is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+/// Own enumerable keys: integer keys in ascending order first, then the rest.
+// TODO: insertion order of properties is not tracked yet, so the rest is sorted.
+fn enumerable_keys(object: &JSObject) -> Vec<JSString> {
+    let mut keys = (object.properties.iter())
+        .filter(|(_, property)| property.access.enumerable())
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+    keys.sort_by(|a, b| {
+        let index = |s: &JSString| u32::from_str(s.as_str()).ok();
+        match (index(a), index(b)) {
+            (Some(i), Some(j)) => i.cmp(&j),
+            (Some(_), None) => core::cmp::Ordering::Less,
+            (None, Some(_)) => core::cmp::Ordering::Greater,
+            (None, None) => a.as_str().cmp(b.as_str()),
+        }
+    });
+    keys
+}
+
+/// The name of `object.constructor`, if any.
+fn constructor_name(object: &JSObject, heap: &Heap) -> Option<String> {
+    let constructor = object.lookup_value("constructor", heap)?.to_ref().ok()?;
+    let name = heap.get(constructor).get_own_value("name")?;
+    match name.as_str() {
+        Some(name) if !name.is_empty() => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/*
+ *  util.format()
+ */
+
+/// Formats `console` arguments like `util.format()`:
+/// substitutes `%s`/`%d`/`%i`/`%f`/`%o`/`%O`/`%c` in the first string argument,
+/// then appends the rest of the arguments separated by spaces.
+fn format_arguments(arguments: &[JSValue], heap: &mut Heap) -> JSResult<String> {
+    let mut output = String::new();
+    let mut rest = arguments;
+
+    if let Some((template, args)) = arguments
+        .split_first()
+        .and_then(|(first, args)| Some((first.as_string()?, args)))
+    {
+        let mut args = args.iter();
+        let mut chars = template.as_str().chars().peekable();
+        while let Some(c) = chars.next() {
+            let spec = match (c, chars.peek()) {
+                ('%', Some(&spec)) if "sdifoOc%".contains(spec) => spec,
+                _ => {
+                    output.push(c);
+                    continue;
+                }
+            };
+            chars.next();
+            if spec == '%' {
+                output.push('%');
+                continue;
+            }
+            let arg = match args.next() {
+                Some(arg) => arg,
+                None => {
+                    output.push('%');
+                    output.push(spec);
+                    continue;
+                }
+            };
+            let formatted = match spec {
+                's' => format_string(arg, heap)?,
+                'd' => format_number(arg.to_number(heap)?),
+                'i' => {
+                    let n = arg.to_number(heap)?;
+                    format_number(n - n % 1.0)
+                }
+                'f' => {
+                    let s = arg.stringify(heap)?;
+                    let s = s.trim_start_matches(is_str_whitespace);
+                    format_number(decimal_prefix(s).map_or(f64::NAN, |(n, _)| n))
+                }
+                'o' => Inspector::new(heap).with_depth(4).format_value(arg, 0),
+                'O' => inspect(arg, heap),
+                _ => String::new(), // %c: CSS is ignored
+            };
+            output.push_str(&formatted);
+        }
+        rest = args.as_slice();
+    } else if let [first, args @ ..] = arguments {
+        output = inspect(first, heap);
+        rest = args;
+    }
+
+    for arg in rest {
+        output.push(' ');
+        match arg.as_str() {
+            Some(s) => output.push_str(s),
+            None => output.push_str(&inspect(arg, heap)),
+        }
+    }
+    Ok(output)
+}
+
+/// `%s`: objects without a user-defined `toString()` are inspected shallowly.
+fn format_string(value: &JSValue, heap: &mut Heap) -> JSResult<String> {
+    match value.unboxed() {
+        Unboxed::Number(n) => Ok(format_number(n)),
+        Unboxed::Ref(objref) if objref != Heap::NULL => {
+            let to_string = heap.get(objref).lookup_value("toString", heap);
+            let is_builtin = match to_string.and_then(|v| v.as_objref()) {
+                Some(funcref) => !matches!(heap.get(funcref).value, ObjectValue::Closure(_)),
+                _ => true,
+            };
+            if is_builtin {
+                Ok(Inspector::new(heap).with_depth(0).format_value(value, 0))
+            } else {
+                Ok(value.stringify(heap)?.to_string())
+            }
+        }
+        _ => Ok(value.stringify(heap)?.to_string()),
+    }
+}
+
+fn argument_values(call: &CallContext, heap: &mut Heap) -> JSResult<Vec<JSValue>> {
+    (call.arguments.iter())
+        .map(|arg| arg.to_value(heap))
+        .collect()
+}
+
+fn write_arguments(level: LogLevel, call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let arguments = argument_values(&call, heap)?;
+    let message = format_arguments(&arguments, heap)?;
+    heap.console_mut().sink.write(level, &message);
+    Ok(Interpreted::VOID)
+}
+
+/*
+ *  console methods
+ */
+
+fn console_log(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    write_arguments(LogLevel::Log, call, heap)
+}
+
+fn console_info(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    write_arguments(LogLevel::Info, call, heap)
+}
+
+fn console_warn(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    write_arguments(LogLevel::Warn, call, heap)
+}
+
+fn console_error(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    write_arguments(LogLevel::Error, call, heap)
+}
+
+fn console_debug(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    write_arguments(LogLevel::Debug, call, heap)
+}
+
+/// `console.assert(value, ...message)` warns only if `value` is falsy.
+fn console_assert(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if call.arg_value(0, heap)?.boolify(heap) {
+        return Ok(Interpreted::VOID);
+    }
+    let mut arguments = argument_values(&call, heap)?;
+    if !arguments.is_empty() {
+        arguments.remove(0);
+    }
+    match arguments.first_mut() {
+        Some(message) if message.is_string() => {
+            let text = message.as_str().unwrap_or_default();
+            *message = JSValue::from(format!("Assertion failed: {}", text));
+        }
+        _ => arguments.insert(0, JSValue::from("Assertion failed")),
+    }
+    let message = format_arguments(&arguments, heap)?;
+    heap.console_mut().sink.write(LogLevel::Warn, &message);
+    Ok(Interpreted::VOID)
+}
+
+fn timer_label(call: &CallContext, heap: &mut Heap) -> JSResult<JSString> {
+    match call.arg_value(0, heap)? {
+        label if label.is_undefined() => Ok(JSString::from("default")),
+        label => label.stringify(heap),
+    }
+}
+
+fn console_time(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let label = timer_label(&call, heap)?;
+    let console = heap.console_mut();
+    if console.timers.contains_key(&label) {
+        let warning = format!(
+            "Warning: Label '{}' already exists for console.time()",
+            label
+        );
+        console.sink.write(LogLevel::Warn, &warning);
+    } else if let Some(now) = console.sink.now() {
+        console.timers.insert(label, now);
+    }
+    Ok(Interpreted::VOID)
+}
+
+#[allow(non_snake_case)]
+fn console_timeEnd(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let label = timer_label(&call, heap)?;
+    let console = heap.console_mut();
+    let elapsed = match (console.timers.remove(&label), console.sink.now()) {
+        (Some(start), Some(now)) => now - start,
+        _ => {
+            let warning = format!("Warning: No such label '{}' for console.timeEnd()", label);
+            console.sink.write(LogLevel::Warn, &warning);
+            return Ok(Interpreted::VOID);
+        }
+    };
+    let message = if elapsed < 1000.0 {
+        format!("{}: {:.3}ms", label, elapsed)
+    } else {
+        format!("{}: {:.3}s", label, elapsed / 1000.0)
+    };
+    console.sink.write(LogLevel::Log, &message);
+    Ok(Interpreted::VOID)
+}
+
+/// `console.table(data)` draws the properties of `data` items as a table.
+fn console_table(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let data = match call.arg_value(0, heap)?.as_objref() {
+        Some(objref) if objref != Heap::NULL => objref,
+        _ => return console_log(call, heap),
+    };
+
+    // a shallow inspection for cells
+    let cell = |value: &JSValue, heap: &Heap| {
+        let depth = match value.as_objref() {
+            Some(objref) if objref != Heap::NULL => {
+                let object = heap.get(objref);
+                let is_large = object.as_array().is_none() && enumerable_keys(object).len() > 2;
+                if is_large {
+                    -1
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        };
+        let mut inspector = Inspector::new(heap).with_depth(depth);
+        inspector.max_array_length = 3;
+        inspector.break_length = usize::MAX;
+        inspector.format_value(value, 0)
+    };
+
+    let object = heap.get(data);
+    let (index_header, rows): (&str, Vec<(String, JSValue)>) = match &object.value {
+        ObjectValue::Array(array) => {
+            let rows = (array.elements())
+                .map(|(i, item)| (i.to_string(), item.clone()))
+                .collect();
+            ("(index)", rows)
+        }
+        ObjectValue::Map(map) => {
+            let header = vec![
+                "(iteration index)".to_string(),
+                "Key".into(),
+                "Values".into(),
+            ];
+            let mut columns = vec![Vec::new(), Vec::new(), Vec::new()];
+            for (i, (key, value)) in map.iter().enumerate() {
+                columns[0].push(i.to_string());
+                columns[1].push(cell(key, heap));
+                columns[2].push(cell(value, heap));
+            }
+            let table = render_table(&header, columns);
+            heap.console_mut().sink.write(LogLevel::Log, &table);
+            return Ok(Interpreted::VOID);
+        }
+        ObjectValue::Set(set) => {
+            let header = vec!["(iteration index)".to_string(), "Values".into()];
+            let mut columns = vec![Vec::new(), Vec::new()];
+            for (i, (item, _)) in set.iter().enumerate() {
+                columns[0].push(i.to_string());
+                columns[1].push(cell(item, heap));
+            }
+            let table = render_table(&header, columns);
+            heap.console_mut().sink.write(LogLevel::Log, &table);
+            return Ok(Interpreted::VOID);
+        }
+        _ => {
+            let rows = (enumerable_keys(object).into_iter())
+                .map(|key| {
+                    let value = object.get_own_value(&key).unwrap_or(JSValue::UNDEFINED);
+                    (key.to_string(), value)
+                })
+                .collect();
+            ("(index)", rows)
+        }
+    };
+
+    // columns are the union of the row keys, primitive rows go to "Values"
+    let mut keys: Vec<JSString> = Vec::new();
+    let mut cells: Vec<HashMap<JSString, String>> = Vec::new();
+    let mut values: Vec<Option<String>> = Vec::new();
+    for (_, item) in rows.iter() {
+        let mut row = HashMap::new();
+        match item.as_objref() {
+            Some(itemref) if itemref != Heap::NULL => {
+                let item_object = heap.get(itemref);
+                let mut item_keys = enumerable_keys(item_object);
+                if let Some(array) = item_object.as_array() {
+                    let indices = array.elements().map(|(i, _)| JSString::from(i.to_string()));
+                    item_keys.splice(0..0, indices);
+                }
+                for key in item_keys {
+                    let value = item_object
+                        .get_own_value(&key)
+                        .unwrap_or(JSValue::UNDEFINED);
+                    row.insert(key.clone(), cell(&value, heap));
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+                values.push(None);
+            }
+            _ => values.push(Some(cell(item, heap))),
+        }
+        cells.push(row);
+    }
+
+    let mut header = vec![index_header.to_string()];
+    let mut columns = vec![rows.iter().map(|(index, _)| index.clone()).collect()];
+    for key in keys.iter() {
+        header.push(key.to_string());
+        let column = (cells.iter())
+            .map(|row| row.get(key).cloned().unwrap_or_default())
+            .collect();
+        columns.push(column);
+    }
+    if values.iter().any(Option::is_some) {
+        header.push("Values".to_string());
+        columns.push(values.into_iter().map(Option::unwrap_or_default).collect());
+    }
+
+    let table = render_table(&header, columns);
+    heap.console_mut().sink.write(LogLevel::Log, &table);
+    Ok(Interpreted::VOID)
+}
+
+/// Draws a box around `columns` of cells.
+fn render_table(header: &[String], columns: Vec<Vec<String>>) -> String {
+    let widths = (header.iter().zip(columns.iter()))
+        .map(|(head, column)| {
+            let widest = column.iter().map(|s| width(s)).max().unwrap_or(0);
+            usize::max(width(head), widest)
+        })
+        .collect::<Vec<_>>();
+    let rows_count = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+    let render_row = |row: &mut dyn Iterator<Item = &str>| {
+        let mut line = String::from("│ ");
+        for (i, cell) in row.enumerate() {
+            if i > 0 {
+                line.push_str(" │ ");
+            }
+            line.push_str(cell);
+            line.push_str(&" ".repeat(widths[i] - width(cell)));
+        }
+        line.push_str(" │");
+        line
+    };
+    let divider = |left: &str, middle: &str, right: &str| {
+        let parts = widths.iter().map(|w| "─".repeat(w + 2)).collect::<Vec<_>>();
+        format!("{}{}{}", left, parts.join(middle), right)
+    };
+
+    let mut lines = vec![
+        divider("┌", "┬", "┐"),
+        render_row(&mut header.iter().map(String::as_str)),
+        divider("├", "┼", "┤"),
+    ];
+    for j in 0..rows_count {
+        let mut row = (columns.iter()).map(|column| column.get(j).map_or("", String::as_str));
+        lines.push(render_row(&mut row));
+    }
+    lines.push(divider("└", "┴", "┘"));
+    lines.join("\n")
+}
+
+/*
+ *  init
+ */
+
+pub fn init(heap: &mut Heap) -> JSResult<()> {
+    let mut console = JSObject::new();
+
+    let methods: [(&str, HostFn); 9] = [
+        ("log", console_log),
+        ("info", console_info),
+        ("warn", console_warn),
+        ("error", console_error),
+        ("debug", console_debug),
+        ("assert", console_assert),
+        ("time", console_time),
+        ("timeEnd", console_timeEnd),
+        ("table", console_table),
+    ];
+    for (name, func) in methods {
+        console.set_hidden(name, heap.alloc_named_func(name, func))?;
+    }
+
+    let console_ref = heap.alloc(console);
+    heap.get_mut(Heap::GLOBAL)
+        .set_hidden("console", console_ref)?;
+    Ok(())
+}
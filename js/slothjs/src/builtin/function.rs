@@ -1,22 +1,64 @@
-use crate::error::TypeError;
-use crate::object::HostClass;
+use crate::ast::{Expr, ExpressionStatement, Program, Statement, Stmt};
+use crate::error::{ParseError, TypeError};
+use crate::function::BoundFunction;
+use crate::object::{HostClass, ObjectValue};
 /// The implementation of the builtin Function object.
 use crate::prelude::*;
-use crate::{function::CallContext, Exception, Heap, Interpreted, JSResult};
+use crate::{function::CallContext, Exception, Heap, Interpreted, JSObject, JSResult, JSON};
 
 pub static CLASS: HostClass = HostClass {
     name: "Function",
     constructor: function_constructor,
     methods: &[
-        ("call", function_proto_call),
         ("apply", function_proto_apply),
+        ("bind", function_proto_bind),
+        ("call", function_proto_call),
+        ("toString", function_proto_toString),
     ],
     static_methods: &[],
 };
 
-fn function_constructor(_call: CallContext, _heap: &mut Heap) -> JSResult<Interpreted> {
-    // TODO: eval arguments
-    todo!()
+/// `Function(arg1, .., argN, body)` parses its arguments as a function expression with
+/// [`Heap::parse()`] (see [`anonymous_function()`]) and runs it in the global scope,
+/// so the resulting function only sees globals.
+fn function_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let mut params = Vec::new();
+    for i in 0..call.arguments.len() {
+        let param = call.arg_value(i, heap)?.stringify(heap)?;
+        params.push(param.to_string());
+    }
+    let body = params.pop().unwrap_or_default();
+    let params = params.join(",");
+
+    // the parameters and the body must each be valid on their own,
+    // so that neither can close the function early, e.g. `Function("}); f(); (function(){")`
+    if !params.is_empty() {
+        anonymous_function(&params, "", heap)?;
+        anonymous_function("", &body, heap)?;
+    }
+    let program = anonymous_function(&params, &body, heap)?;
+
+    // a function expression on its own captures nothing
+    let result = heap.run(&program)?;
+    let funcref = result.to_ref(heap)?;
+    Ok(Interpreted::from(funcref))
+}
+
+/// Parses `(function anonymous(params) { body })`,
+/// which must stay a single function expression.
+fn anonymous_function(params: &str, body: &str, heap: &mut Heap) -> JSResult<Program> {
+    let code = format!("(function anonymous({}\n) {{\n{}\n}})", params, body);
+    let program = heap.parse(&code)?;
+    match program.body.body.as_slice() {
+        [Statement {
+            stmt: Stmt::Expr(ExpressionStatement { expression }),
+            ..
+        }] if matches!(expression.expr, Expr::Function(_)) => Ok(program),
+        _ => {
+            let error = ParseError::want("a single function", JSON::from(code));
+            Err(Exception::Syntax(error))
+        }
+    }
 }
 
 fn function_proto_call(mut call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
@@ -24,34 +66,91 @@ fn function_proto_call(mut call: CallContext, heap: &mut Heap) -> JSResult<Inter
         call.arguments.push(Interpreted::VOID);
     }
     call.arguments.rotate_left(1); // [this, arg1, .. , argN] => [arg1, .. , argN, this]
-    let this_arg = call.arguments.pop().unwrap();
-    let bound_this = this_arg.to_value(heap)?.objectify(heap); // Must wrap primitives too.
+    let this_arg = call.arguments.pop().unwrap().to_value(heap)?;
     let funcref = call.this_ref;
-    call.this_ref = bound_this;
 
-    heap.execute(funcref, call)
+    heap.execute(funcref, call.with_this_value(this_arg))
 }
 
 fn function_proto_apply(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
-    let bound_this = call.arg_value(0, heap)?.to_ref()?;
-    let call_args = match call.arguments.get(1) {
-        // TODO: convert from everything array-like.
-        Some(object) => {
-            let objref = object.to_ref(heap)?;
-            let array = (heap.get(objref))
-                .as_array()
-                .ok_or_else(|| Exception::type_error(TypeError::NOT_ARRAYLIKE, object.clone()))?;
-            (array.storage.iter())
-                .map(|val| Interpreted::Value(val.clone()))
-                .collect()
-        }
-        None => Vec::new(),
-    };
+    let bound_this = call.arg_value(0, heap)?;
+    let call_args = arraylike_values(&call.arg_value(1, heap)?, heap)?;
+    let call_args = call_args
+        .into_iter()
+        .map(Interpreted::from)
+        .collect::<Vec<_>>();
 
     heap.execute(
         call.this_ref,
         CallContext::from(call_args)
-            .with_this(bound_this)
+            .with_this_value(bound_this)
             .with_name(call.method_name),
     )
 }
+
+/// The values of anything array-like: `length` and indexed properties.
+/// `undefined` and `null` give nothing.
+pub(crate) fn arraylike_values(value: &JSValue, heap: &mut Heap) -> JSResult<Vec<JSValue>> {
+    let objref = match value.unboxed() {
+        Unboxed::Undefined | Unboxed::Ref(Heap::NULL) => return Ok(Vec::new()),
+        Unboxed::Ref(objref) => objref,
+        _ => {
+            return Err(Exception::type_error(
+                TypeError::NOT_ARRAYLIKE,
+                value.clone(),
+            ))
+        }
+    };
+    let length = heap.get_member(objref, &JSString::from("length"))?;
+    let length = length.numberify(heap).unwrap_or(0.0);
+    let length = if length > 0.0 { length as usize } else { 0 };
+    (0..length)
+        .map(|i| heap.get_member(objref, &JSString::from(i.to_string())))
+        .collect()
+}
+
+fn function_proto_bind(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let target = call.this_ref;
+    if !heap.get(target).is_callable() {
+        return Err(Exception::type_error(TypeError::NOT_CALLABLE, target));
+    }
+    let this = call.arg_value(0, heap)?;
+    let arguments = (call.arguments.iter().skip(1))
+        .map(|arg| arg.to_value(heap))
+        .collect::<JSResult<Vec<_>>>()?;
+
+    let target_object = heap.get(target);
+    let name = (target_object.get_own_value("name"))
+        .and_then(|name| name.as_string())
+        .unwrap_or_default();
+    let length = (target_object.get_own_value("length"))
+        .and_then(|len| len.numberify(heap))
+        .unwrap_or(0.0);
+    let length = (length - arguments.len() as f64).max(0.0) as usize;
+    let proto = target_object.proto;
+
+    let bound = BoundFunction {
+        target,
+        this,
+        arguments,
+    };
+    let mut bound_object = JSObject::from_bound_function(bound, proto);
+    bound_object.set_function_signature(&format!("bound {}", name), length)?;
+    Ok(Interpreted::from(heap.alloc(bound_object)))
+}
+
+#[allow(non_snake_case)]
+fn function_proto_toString(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let function_object = heap.get(call.this_ref);
+    let source = match &function_object.value {
+        ObjectValue::Closure(closure) => closure.function.to_source_text(),
+        ObjectValue::HostFn(_) => {
+            let name = function_object.get_own_value("name");
+            let name = name.unwrap_or(JSValue::UNDEFINED).stringify(heap)?;
+            format!("function {}() {{ [native code] }}", name)
+        }
+        ObjectValue::BoundFunction(_) => "function () { [native code] }".to_string(),
+        _ => return Err(Exception::instance_required(call.this_ref, "Function")),
+    };
+    Ok(Interpreted::from(source.as_str()))
+}
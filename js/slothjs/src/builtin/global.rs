@@ -1,54 +1,237 @@
+use crate::builtin::error;
 use crate::prelude::*;
-use crate::{CallContext, Heap, Interpreted, JSObject, JSResult, JSValue};
+use crate::value::{decimal_prefix, is_str_whitespace, number_to_int32};
+use crate::{CallContext, Exception, Heap, HostFn, Interpreted, JSObject, JSResult, JSValue};
 
 /*
- *  parseInt
+ *  parseInt/parseFloat
  */
-#[allow(clippy::manual_range_contains)]
+
+/// `parseInt(string, radix)` parses the longest prefix of integer digits.
 fn parse_int(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
-    let argument = call.arguments.first().unwrap_or(&Interpreted::VOID);
-    let value = argument.to_value(heap)?;
-    if let JSValue::Number(_) = value {
-        return Ok(Interpreted::Value(value));
-    }
-    let value = value.stringify(heap)?;
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    let radix = call.arg_value(1, heap)?.to_number(heap)?;
 
-    let radix = call.arguments.get(1).unwrap_or(&Interpreted::VOID);
-    let radix = radix.to_value(heap)?.numberify(heap);
-    let mut radix = radix.unwrap_or(0.0) as u32;
+    let s = input.trim_start_matches(is_str_whitespace);
+    let (sign, mut s) = match s.as_bytes().first() {
+        Some(b'-') => (-1.0, &s[1..]),
+        Some(b'+') => (1.0, &s[1..]),
+        _ => (1.0, s),
+    };
+
+    let mut radix = number_to_int32(radix);
+    let strip_prefix = radix == 0 || radix == 16;
     if radix == 0 {
-        let (c0, c1) = {
-            let mut cs = value.chars();
-            let c0 = cs.next().unwrap_or('\0');
-            let c1 = cs.next().unwrap_or('\0');
-            (c0, c1)
-        };
-        match (c0, c1) {
-            ('0', 'x') | ('0', 'X') => radix = 16,
-            ('0', _) => radix = 8,
-            _ => radix = 10,
+        radix = 10;
+    } else if !(2..=36).contains(&radix) {
+        return Ok(Interpreted::NAN);
+    }
+    if strip_prefix {
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            s = hex;
+            radix = 16;
         }
     }
-    if radix < 2 || radix > 36 {
+
+    let radix = radix as u32;
+    let end = (s.find(|c: char| !c.is_digit(radix))).unwrap_or(s.len());
+    let digits = &s[..end];
+    if digits.is_empty() {
         return Ok(Interpreted::NAN);
     }
+    let value = if radix == 10 {
+        // correctly rounded even for long inputs
+        digits.parse::<JSNumber>().unwrap_or(f64::NAN)
+    } else {
+        (digits.chars()).fold(0.0, |acc, c| {
+            acc * radix as f64 + c.to_digit(radix).unwrap() as f64
+        })
+    };
+    Ok(Interpreted::from(sign * value))
+}
 
-    let result = i64::from_str_radix(&value, radix)
-        .map(|num| num as f64)
-        .unwrap_or(f64::NAN);
+/// `parseFloat(string)` parses the longest prefix that is a decimal literal.
+#[allow(non_snake_case)]
+fn global_parseFloat(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    let s = input.trim_start_matches(is_str_whitespace);
+    let result = decimal_prefix(s).map_or(f64::NAN, |(n, _)| n);
     Ok(Interpreted::from(result))
 }
 
 #[allow(non_snake_case)]
-fn global_parseFloat(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
-    let argument = call.arguments.first().unwrap_or(&Interpreted::VOID);
-    let value = argument.to_value(heap)?.stringify(heap)?;
+fn global_isNaN(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let n = call.arg_value(0, heap)?.to_number(heap)?;
+    Ok(Interpreted::from(n.is_nan()))
+}
+
+#[allow(non_snake_case)]
+fn global_isFinite(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let n = call.arg_value(0, heap)?.to_number(heap)?;
+    Ok(Interpreted::from(n.is_finite()))
+}
+
+/*
+ *  URI encoding
+ */
+
+const URI_RESERVED: &str = ";/?:@&=+$,#";
+const URI_MARK: &str = "-_.!~*'()";
+
+/// Makes a `URIError` to throw.
+fn uri_error(heap: &mut Heap) -> JSResult<Exception> {
+    let arguments = vec![Interpreted::from("URI malformed")];
+    let error = error::error_constructor(CallContext::from(arguments), heap)?;
+    let error_ref = error.to_ref(heap)?;
+    heap.get_mut(error_ref).set_hidden("name", "URIError")?;
+    Ok(Exception::UserThrown(JSValue::from(error_ref)))
+}
+
+/// Percent-encodes UTF-8 bytes of everything except ASCII letters, digits and `unescaped`.
+fn uri_encode(s: &str, unescaped: &str) -> String {
+    let mut result = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || unescaped.contains(c) {
+            result.push(c);
+            continue;
+        }
+        let mut buf = [0; 4];
+        for byte in c.encode_utf8(&mut buf).bytes() {
+            write!(result, "%{:02X}", byte).unwrap();
+        }
+    }
+    result
+}
+
+/// Decodes percent-encoded UTF-8 sequences, except the ones that give a character from `reserved`.
+fn uri_decode(s: &str, reserved: &str, heap: &mut Heap) -> JSResult<String> {
+    fn hex_byte(bytes: &[u8], at: usize) -> Option<u8> {
+        let hex = bytes.get(at + 1..at + 3)?;
+        if bytes[at] != b'%' || !hex.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+        u8::from_str_radix(core::str::from_utf8(hex).ok()?, 16).ok()
+    }
+
+    let bytes = s.as_bytes();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            let next = s[i..].find('%').map_or(s.len(), |n| i + n);
+            result.push_str(&s[i..next]);
+            i = next;
+            continue;
+        }
 
-    // TODO: proper implementation
-    let result = f64::from_str(&value).unwrap_or(f64::NAN);
+        let start = i;
+        let first = match hex_byte(bytes, i) {
+            Some(byte) => byte,
+            None => return Err(uri_error(heap)?),
+        };
+        let len = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(uri_error(heap)?),
+        };
+        let mut utf8 = vec![first];
+        i += 3;
+        while utf8.len() < len {
+            match hex_byte(bytes, i) {
+                Some(byte) if byte & 0xc0 == 0x80 => utf8.push(byte),
+                _ => return Err(uri_error(heap)?),
+            }
+            i += 3;
+        }
+        // rejects overlong encodings and surrogates
+        let decoded = match core::str::from_utf8(&utf8) {
+            Ok(decoded) => decoded,
+            Err(_) => return Err(uri_error(heap)?),
+        };
+        if reserved.contains(decoded) {
+            result.push_str(&s[start..i]);
+        } else {
+            result.push_str(decoded);
+        }
+    }
+    Ok(result)
+}
+
+#[allow(non_snake_case)]
+fn global_encodeURI(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    let unescaped = [URI_RESERVED, URI_MARK].concat();
+    Ok(Interpreted::from(uri_encode(&input, &unescaped)))
+}
+
+#[allow(non_snake_case)]
+fn global_encodeURIComponent(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    Ok(Interpreted::from(uri_encode(&input, URI_MARK)))
+}
+
+#[allow(non_snake_case)]
+fn global_decodeURI(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    Ok(Interpreted::from(uri_decode(&input, URI_RESERVED, heap)?))
+}
+
+#[allow(non_snake_case)]
+fn global_decodeURIComponent(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    Ok(Interpreted::from(uri_decode(&input, "", heap)?))
+}
+
+/*
+ *  escape/unescape
+ */
+
+fn global_escape(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    let mut result = String::new();
+    for unit in input.encode_utf16() {
+        match char::from_u32(unit as u32) {
+            Some(c) if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) => result.push(c),
+            _ if unit < 0x100 => write!(result, "%{:02X}", unit).unwrap(),
+            _ => write!(result, "%u{:04X}", unit).unwrap(),
+        }
+    }
     Ok(Interpreted::from(result))
 }
 
+fn global_unescape(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let input = call.arg_value(0, heap)?.stringify(heap)?;
+    let units = input.encode_utf16().collect::<Vec<u16>>();
+    let hex = |units: &[u16]| -> Option<u16> {
+        let s = String::from_utf16(units).ok()?;
+        if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        u16::from_str_radix(&s, 16).ok()
+    };
+
+    let mut result = Vec::with_capacity(units.len());
+    let mut i = 0;
+    while i < units.len() {
+        let percent = units[i] == b'%' as u16;
+        let u = units.get(i + 1) == Some(&(b'u' as u16));
+        if let (true, true, Some(unit)) = (percent, u, units.get(i + 2..i + 6).and_then(hex)) {
+            result.push(unit);
+            i += 6;
+        } else if let (true, Some(unit)) = (percent, units.get(i + 1..i + 3).and_then(hex)) {
+            result.push(unit);
+            i += 3;
+        } else {
+            result.push(units[i]);
+            i += 1;
+        }
+    }
+    // TODO: JSString cannot hold lone surrogates yet
+    Ok(Interpreted::from(String::from_utf16_lossy(&result)))
+}
+
 /*
  *  init
  */
@@ -57,13 +240,27 @@ pub fn init(heap: &mut Heap) -> JSResult<()> {
     let mut global = JSObject::new();
 
     global.set_system("NaN", f64::NAN)?;
-    global.set_system("undefined", JSValue::Undefined)?;
+    global.set_system("Infinity", f64::INFINITY)?;
+    global.set_system("undefined", JSValue::UNDEFINED)?;
 
     global.set_hidden("global", Heap::GLOBAL)?;
-    global.set_system(Heap::SCOPE_THIS, Heap::GLOBAL)?;
+    global.set_hidden("globalThis", Heap::GLOBAL)?;
 
-    global.set_hidden("parseInt", heap.alloc_func(parse_int))?;
-    global.set_hidden("parseFloat", heap.alloc_func(global_parseFloat))?;
+    let functions: [(&str, HostFn); 10] = [
+        ("parseInt", parse_int),
+        ("parseFloat", global_parseFloat),
+        ("isNaN", global_isNaN),
+        ("isFinite", global_isFinite),
+        ("encodeURI", global_encodeURI),
+        ("encodeURIComponent", global_encodeURIComponent),
+        ("decodeURI", global_decodeURI),
+        ("decodeURIComponent", global_decodeURIComponent),
+        ("escape", global_escape),
+        ("unescape", global_unescape),
+    ];
+    for (name, func) in functions {
+        global.set_hidden(name, heap.alloc_named_func(name, func))?;
+    }
 
     *heap.get_mut(Heap::GLOBAL) = global;
 
@@ -0,0 +1,287 @@
+/// The implementation of the builtin Map object and of Map/Set iterators.
+use crate::error::TypeError;
+use crate::object::{HostClass, JSMap, ObjectValue};
+use crate::prelude::*;
+use crate::{CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult};
+
+pub static CLASS: HostClass = HostClass {
+    name: "Map",
+    constructor: map_constructor,
+    methods: &[
+        ("clear", map_proto_clear),
+        ("delete", map_proto_delete),
+        ("entries", map_proto_entries),
+        ("forEach", map_proto_forEach),
+        ("get", map_proto_get),
+        ("has", map_proto_has),
+        ("keys", map_proto_keys),
+        ("set", map_proto_set),
+        ("values", map_proto_values),
+    ],
+    static_methods: &[],
+};
+
+fn map_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if !heap.smells_fresh(call.this_ref) {
+        return Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            "Map",
+        ));
+    }
+
+    let mut map = JSMap::new();
+    let iterable = call.arg_value(0, heap)?;
+    for item in iterate(&iterable, heap)? {
+        let entry = heap.get(item.to_ref()?);
+        let key = entry.lookup_value("0", heap).unwrap_or(JSValue::UNDEFINED);
+        let value = entry.lookup_value("1", heap).unwrap_or(JSValue::UNDEFINED);
+        map.insert(key, value);
+    }
+
+    heap.get_mut(call.this_ref).value = ObjectValue::Map(map);
+    Ok(Interpreted::VOID)
+}
+
+fn this_map(this_ref: JSRef, heap: &Heap) -> JSResult<&JSMap> {
+    (heap.get(this_ref).as_map()).ok_or_else(|| Exception::instance_required(this_ref, "Map"))
+}
+
+fn this_map_mut(this_ref: JSRef, heap: &mut Heap) -> JSResult<&mut JSMap> {
+    (heap.get_mut(this_ref).as_map_mut())
+        .ok_or_else(|| Exception::instance_required(this_ref, "Map"))
+}
+
+pub(crate) fn map_proto_size(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let size = this_map(call.this_ref, heap)?.len();
+    Ok(Interpreted::from(size as i64))
+}
+
+fn map_proto_clear(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_map_mut(call.this_ref, heap)?.clear();
+    Ok(Interpreted::VOID)
+}
+
+fn map_proto_delete(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = call.arg_value(0, heap)?;
+    let found = this_map_mut(call.this_ref, heap)?.remove(&key);
+    Ok(Interpreted::from(found))
+}
+
+fn map_proto_get(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = call.arg_value(0, heap)?;
+    let value = this_map(call.this_ref, heap)?.get(&key).cloned();
+    Ok(Interpreted::from(value.unwrap_or(JSValue::UNDEFINED)))
+}
+
+fn map_proto_has(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = call.arg_value(0, heap)?;
+    let found = this_map(call.this_ref, heap)?.contains(&key);
+    Ok(Interpreted::from(found))
+}
+
+fn map_proto_set(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = call.arg_value(0, heap)?;
+    let value = call.arg_value(1, heap)?;
+    this_map_mut(call.this_ref, heap)?.insert(key, value);
+    Ok(Interpreted::from(call.this_ref))
+}
+
+fn map_proto_entries(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_map(call.this_ref, heap)?;
+    iterator_new(call.this_ref, IteratorKind::Entries, heap)
+}
+
+fn map_proto_keys(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_map(call.this_ref, heap)?;
+    iterator_new(call.this_ref, IteratorKind::Keys, heap)
+}
+
+fn map_proto_values(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_map(call.this_ref, heap)?;
+    iterator_new(call.this_ref, IteratorKind::Values, heap)
+}
+
+#[allow(non_snake_case)]
+fn map_proto_forEach(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_map(call.this_ref, heap)?;
+    let (funcref, this_arg) = callback_args(&call, heap)?;
+
+    // entries added during the iteration are visited as well
+    let mut position = 0;
+    while let Some((at, key, value)) = this_map(call.this_ref, heap)?.next_entry(position) {
+        position = at + 1;
+        let arguments = vec![
+            Interpreted::from(value),
+            Interpreted::from(key),
+            Interpreted::from(call.this_ref),
+        ];
+        let callback = CallContext::from(arguments).with_this_value(this_arg.clone());
+        heap.execute(funcref, callback.with_name("<callback>".into()))?;
+    }
+    Ok(Interpreted::VOID)
+}
+
+/// Validates `(callback, thisArg)` arguments of `forEach` methods.
+pub(crate) fn callback_args(call: &CallContext, heap: &mut Heap) -> JSResult<(JSRef, JSValue)> {
+    let callback = call.arg_value(0, heap)?;
+    let funcref = match callback.as_objref() {
+        Some(funcref) if heap.get(funcref).is_callable() => funcref,
+        _ => return Err(Exception::type_error(TypeError::NOT_CALLABLE, callback)),
+    };
+    let this_arg = call.arg_value(1, heap)?;
+    Ok((funcref, this_arg))
+}
+
+/// Collects values produced by an iterable: an Array, a String, a Map, a Set
+/// or an object with a `.next()` method.
+pub(crate) fn iterate(iterable: &JSValue, heap: &mut Heap) -> JSResult<Vec<JSValue>> {
+    let objref = match iterable.unboxed() {
+        Unboxed::Undefined | Unboxed::Ref(Heap::NULL) => return Ok(Vec::new()),
+        Unboxed::String(s) => return Ok(s.chars().map(|c| JSValue::from(c.to_string())).collect()),
+        Unboxed::Ref(objref) => objref,
+        _ => {
+            return Err(Exception::type_error(
+                TypeError::NOT_ITERABLE,
+                iterable.clone(),
+            ))
+        }
+    };
+
+    match &heap.get(objref).value {
+        ObjectValue::Array(array) => return Ok(array.to_vec()),
+        ObjectValue::TypedArray(element, view) => {
+            let values = (0..view.len(*element)).filter_map(|i| view.element(*element, i));
+            return Ok(values.map(JSValue::from).collect());
+        }
+        ObjectValue::Set(set) => return Ok(set.iter().map(|(k, _)| k.clone()).collect()),
+        ObjectValue::Map(map) => {
+            let entries = (map.iter())
+                .map(|(k, v)| vec![k.clone(), v.clone()])
+                .collect::<Vec<_>>();
+            let values = (entries.into_iter())
+                .map(|entry| JSValue::from(heap.alloc(JSObject::from_array(entry))))
+                .collect();
+            return Ok(values);
+        }
+        _ => (),
+    }
+
+    let next_ref = match heap
+        .get(objref)
+        .lookup_value("next", heap)
+        .and_then(|v| v.as_objref())
+    {
+        Some(next_ref) if heap.get(next_ref).is_callable() => next_ref,
+        _ => return Err(Exception::type_error(TypeError::NOT_ITERABLE, objref)),
+    };
+    let mut values = Vec::new();
+    loop {
+        let call = CallContext::from(vec![]).with_this(objref);
+        let result = heap.execute(next_ref, call.with_name("next".into()))?;
+        let result = result.to_ref(heap)?;
+        let result = heap.get(result);
+        let done = (result.lookup_value("done", heap)).is_some_and(|done| done.boolify(heap));
+        if done {
+            break;
+        }
+        values.push(
+            result
+                .lookup_value("value", heap)
+                .unwrap_or(JSValue::UNDEFINED),
+        );
+    }
+    Ok(values)
+}
+
+/*
+ *  Map/Set iterators
+ */
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum IteratorKind {
+    Keys,
+    Values,
+    Entries,
+}
+
+impl IteratorKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IteratorKind::Keys => "keys",
+            IteratorKind::Values => "values",
+            IteratorKind::Entries => "entries",
+        }
+    }
+}
+
+const ITERATED: &str = "[[iterated]]";
+const ITERATED_POSITION: &str = "[[iterated_position]]";
+const ITERATOR_KIND: &str = "[[iterator_kind]]";
+
+/// Creates an iterator over `iterated` (which must be a Map or a Set).
+pub(crate) fn iterator_new(
+    iterated: JSRef,
+    kind: IteratorKind,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let mut iterator = JSObject::new();
+    iterator.proto = Heap::ITERATOR_PROTO;
+    iterator.set_system(ITERATED, iterated)?;
+    iterator.set_system(ITERATED_POSITION, 0)?;
+    iterator.set_system(ITERATOR_KIND, kind.as_str())?;
+    Ok(Interpreted::from(heap.alloc(iterator)))
+}
+
+fn iterator_result(value: JSValue, done: bool, heap: &mut Heap) -> JSResult<Interpreted> {
+    let mut result = JSObject::new();
+    result.set_property("value", value)?;
+    result.set_property("done", done)?;
+    Ok(Interpreted::from(heap.alloc(result)))
+}
+
+pub(crate) fn iterator_proto_next(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let iterator = heap.get(call.this_ref);
+    let iterated = match iterator.get_own_value(ITERATED).and_then(|v| v.as_objref()) {
+        Some(Heap::NULL) => {
+            return iterator_result(JSValue::UNDEFINED, true, heap);
+        }
+        Some(iterated) => iterated,
+        _ => return Err(Exception::instance_required(call.this_ref, "Iterator")),
+    };
+    let position = (iterator.get_own_value(ITERATED_POSITION))
+        .and_then(|pos| pos.numberify(heap))
+        .unwrap_or(0.0) as usize;
+    let kind = iterator.get_own_value(ITERATOR_KIND);
+    let kind = match kind.as_ref().and_then(JSValue::as_str) {
+        Some("keys") => IteratorKind::Keys,
+        Some("values") => IteratorKind::Values,
+        _ => IteratorKind::Entries,
+    };
+
+    let (storage, is_set) = match &heap.get(iterated).value {
+        ObjectValue::Map(map) => (map, false),
+        ObjectValue::Set(set) => (set, true),
+        _ => return Err(Exception::instance_required(iterated, "Map")),
+    };
+    let (at, key, value) = match storage.next_entry(position) {
+        Some(entry) => entry,
+        None => {
+            let iterator = heap.get_mut(call.this_ref);
+            iterator.set_even_nonwritable(ITERATED, Heap::NULL)?;
+            return iterator_result(JSValue::UNDEFINED, true, heap);
+        }
+    };
+    // a Set entry is its element
+    let value = if is_set { key.clone() } else { value };
+
+    let iterator = heap.get_mut(call.this_ref);
+    iterator.set_even_nonwritable(ITERATED_POSITION, JSValue::from((at + 1) as i64))?;
+
+    let result = match kind {
+        IteratorKind::Keys => key,
+        IteratorKind::Values => value,
+        IteratorKind::Entries => JSValue::from(heap.alloc(JSObject::from_array(vec![key, value]))),
+    };
+    iterator_result(result, false, heap)
+}
@@ -1,10 +1,19 @@
 pub mod array;
+pub mod bigint;
 pub mod boolean;
+pub mod buffer;
+pub mod console;
 pub mod error;
 pub mod function;
 pub mod global;
+pub mod map;
+pub mod number;
 pub mod object;
+pub mod proxy;
+pub mod reflect;
+pub mod set;
 pub mod string;
+pub mod weak;
 
 use crate::{Heap, JSResult};
 
@@ -25,12 +34,28 @@ pub fn init(heap: &mut Heap) -> JSResult<()> {
     heap.init_class(Heap::FUNCTION_PROTO, &function::CLASS)?;
     heap.init_class(Heap::ARRAY_PROTO, &array::CLASS)?;
     heap.init_class(Heap::BOOLEAN_PROTO, &boolean::CLASS)?;
+    heap.init_class(Heap::NUMBER_PROTO, &number::CLASS)?;
     heap.init_class(Heap::STRING_PROTO, &string::CLASS)?;
+    heap.init_class(Heap::BIGINT_PROTO, &bigint::CLASS)?;
     heap.init_class(Heap::ERROR_PROTO, &error::CLASS)?;
     {
         let error_proto = heap.get_mut(Heap::ERROR_PROTO);
         error_proto.set_hidden("name", "Error")?;
         error_proto.set_hidden("message", "")?;
     }
+    heap.init_class(Heap::MAP_PROTO, &map::CLASS)?;
+    heap.init_getter(Heap::MAP_PROTO, "size", map::map_proto_size)?;
+    heap.init_class(Heap::SET_PROTO, &set::CLASS)?;
+    heap.init_getter(Heap::SET_PROTO, "size", set::set_proto_size)?;
+    {
+        let next = heap.alloc_named_func("next", map::iterator_proto_next);
+        heap.get_mut(Heap::ITERATOR_PROTO)
+            .set_hidden("next", next)?;
+    }
+    buffer::init(heap)?;
+    proxy::init(heap)?;
+    reflect::init(heap)?;
+    weak::init(heap)?;
+    console::init(heap)?;
     Ok(())
 }
@@ -0,0 +1,98 @@
+use crate::prelude::*;
+
+use crate::error::RangeError;
+use crate::value::{number_to_string, PrimitiveHint};
+use crate::{
+    object::HostClass, BigInt, CallContext, Exception, Heap, Interpreted, JSObject, JSRef,
+    JSResult, JSValue,
+};
+
+pub static CLASS: HostClass = HostClass {
+    name: "Number",
+    constructor: number_constructor,
+    methods: &[
+        ("toString", number_proto_toString),
+        ("valueOf", number_proto_valueOf),
+    ],
+    static_methods: &[],
+};
+
+/// `Number(value)` converts, `new Number(value)` wraps.
+fn number_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let n = match call.arguments.is_empty() {
+        true => 0.0,
+        false => {
+            let value = call.arg_value(0, heap)?;
+            let value = value.to_primitive(PrimitiveHint::Number, heap)?;
+            match value.as_bigint() {
+                Some(n) => n.to_f64(),
+                None => value.to_number(heap)?,
+            }
+        }
+    };
+
+    if !heap.smells_fresh(call.this_ref) {
+        return Ok(Interpreted::from(n));
+    }
+
+    *heap.get_mut(call.this_ref) = JSObject::from_number(n);
+    Ok(Interpreted::VOID)
+}
+
+fn object_to_number(this_ref: JSRef, heap: &Heap) -> JSResult<f64> {
+    match heap
+        .get(this_ref)
+        .to_primitive()
+        .and_then(|v| v.as_number())
+    {
+        Some(n) => Ok(n),
+        None => Err(Exception::instance_required(this_ref, "Number")),
+    }
+}
+
+/// The digits of `n` in `radix`, the fraction is cut after 52 digits.
+fn number_to_string_radix(n: f64, radix: u32) -> String {
+    if radix == 10 || !n.is_finite() {
+        return number_to_string(n);
+    }
+    let integer = BigInt::from_f64(n.trunc()).unwrap_or_default();
+    let mut s = integer.to_string_radix(radix);
+    if n < 0.0 && n > -1.0 {
+        s.insert(0, '-');
+    }
+    let mut fraction = n.abs().fract();
+    if fraction > 0.0 {
+        s.push('.');
+        for _ in 0..52 {
+            fraction *= radix as f64;
+            let digit = fraction.trunc() as u32;
+            s.push(char::from_digit(digit, radix).unwrap());
+            fraction = fraction.fract();
+            if fraction == 0.0 {
+                break;
+            }
+        }
+    }
+    s
+}
+
+#[allow(non_snake_case)]
+fn number_proto_toString(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let n = object_to_number(call.this_ref, heap)?;
+    let radix = call.arg_value(0, heap)?;
+    let radix = match radix.is_undefined() {
+        true => 10.0,
+        false => radix.to_number(heap)?.trunc(),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(Exception::range_error(RangeError::INVALID_RADIX, radix));
+    }
+    let s = number_to_string_radix(n, radix as u32);
+    Ok(Interpreted::from(s))
+}
+
+#[allow(non_snake_case)]
+fn number_proto_valueOf(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let n = object_to_number(call.this_ref, heap)?;
+    Ok(Interpreted::from(JSValue::from(n)))
+}
@@ -1,8 +1,8 @@
 use crate::error::TypeError;
 use crate::prelude::*;
 use crate::{
-    object::Access, object::HostClass, CallContext, Exception, Heap, Interpreted, JSObject, JSRef,
-    JSResult,
+    object::Access, object::Content, object::HostClass, object::Property, CallContext, Exception,
+    Heap, Interpreted, JSObject, JSRef, JSResult,
 };
 
 pub static CLASS: HostClass = HostClass {
@@ -21,7 +21,11 @@ pub static CLASS: HostClass = HostClass {
             "getOwnPropertyDescriptor",
             object_object_getOwnPropertyDescriptor,
         ),
+        ("getPrototypeOf", object_object_getPrototypeOf),
         ("is", object_object_is),
+        ("isExtensible", object_object_isExtensible),
+        ("keys", object_object_keys),
+        ("preventExtensions", object_object_preventExtensions),
         ("setPrototypeOf", object_object_setPrototypeOf),
     ],
 };
@@ -38,8 +42,7 @@ fn object_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interprete
 #[allow(non_snake_case)]
 fn object_proto_hasOwnProperty(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
     let propname = call.arg_value(0, heap)?.stringify(heap)?;
-    // TODO: avoid calling getters, if any
-    let found = heap.get(call.this_ref).get_own_value(&propname).is_some();
+    let found = heap.own_property(call.this_ref, &propname)?.is_some();
     Ok(Interpreted::from(found))
 }
 
@@ -82,26 +85,46 @@ fn object_object_create(call: CallContext, heap: &mut Heap) -> JSResult<Interpre
 }
 
 fn object_object_is(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
-    use JSValue::*;
-
     let left = call.arg_value(0, heap)?;
     let right = call.arg_value(1, heap)?;
+    Ok(Interpreted::from(left.same_value(&right)))
+}
 
-    let answer = match (left, right) {
-        (Undefined, Undefined) => true,
-        (String(lstr), String(rstr)) if lstr == rstr => true,
-        (Bool(lb), Bool(rb)) if lb == rb => true,
-        (Number(lnum), Number(rnum)) => {
-            if f64::abs(lnum) == 0.0 && f64::abs(rnum) == 0.0 {
-                f64::is_sign_positive(lnum) == f64::is_sign_positive(rnum)
-            } else {
-                (f64::is_nan(lnum) && f64::is_nan(rnum)) || lnum == rnum
-            }
-        }
-        (Ref(lref), Ref(rref)) => lref == rref,
-        _ => false,
+/// The object argument `index` of `call`.
+pub(crate) fn object_arg(call: &CallContext, index: usize, heap: &mut Heap) -> JSResult<JSRef> {
+    let value = call.arg_value(index, heap)?;
+    match value.as_objref() {
+        Some(objref) if objref != Heap::NULL => Ok(objref),
+        _ => Err(Exception::not_an_object(value)),
+    }
+}
+
+/// Makes a descriptor object for `property`, `undefined` if there is none.
+pub(crate) fn property_descriptor(
+    property: Option<Property>,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let Some(prop) = property else {
+        return Ok(Interpreted::VOID);
     };
-    Ok(Interpreted::from(answer))
+    let mut descriptor_object = JSObject::new();
+    match prop.content {
+        Content::Value(value) => {
+            descriptor_object.set_property("value", value)?;
+            descriptor_object.set_property("writable", prop.access.writable())?;
+        }
+        Content::Accessor { get, set } => {
+            let get = get.map_or(JSValue::UNDEFINED, JSValue::from);
+            let set = set.map_or(JSValue::UNDEFINED, JSValue::from);
+            descriptor_object.set_property("get", get)?;
+            descriptor_object.set_property("set", set)?;
+        }
+    }
+    descriptor_object.set_property("configurable", prop.access.configurable())?;
+    descriptor_object.set_property("enumerable", prop.access.enumerable())?;
+
+    let descriptor_ref = heap.alloc(descriptor_object);
+    Ok(Interpreted::from(descriptor_ref))
 }
 
 #[allow(non_snake_case)]
@@ -109,40 +132,70 @@ fn object_object_getOwnPropertyDescriptor(
     call: CallContext,
     heap: &mut Heap,
 ) -> JSResult<Interpreted> {
-    let inspected = call.arg_value(0, heap)?;
-    let inspected_ref = inspected.to_ref().map_err(|_| {
-        let inspected = Interpreted::from(inspected);
-        Exception::not_an_object(inspected)
-    })?;
-
+    let inspected_ref = object_arg(&call, 0, heap)?;
     let propname = call.arg_value(1, heap)?.stringify(heap)?;
+    let property = heap.own_property(inspected_ref, &propname)?;
+    property_descriptor(property, heap)
+}
 
-    let inspected_object = heap.get(inspected_ref);
-    let prop = match inspected_object.properties.get(propname.as_str()) {
-        Some(prop) => prop.clone(),
-        None => return Ok(Interpreted::VOID),
-    };
+#[allow(non_snake_case)]
+fn object_object_getPrototypeOf(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = call.arg_value(0, heap)?;
+    let objref = value.objectify(heap);
+    if objref == Heap::NULL {
+        return Err(Exception::not_an_object(value));
+    }
+    Ok(Interpreted::from(heap.prototype_of(objref)?))
+}
 
-    let value = prop.content.to_value()?;
+#[allow(non_snake_case)]
+fn object_object_isExtensible(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    // primitives are not extensible
+    let extensible = match call.arg_value(0, heap)?.as_objref() {
+        Some(objref) if objref != Heap::NULL => heap.is_extensible(objref)?,
+        _ => false,
+    };
+    Ok(Interpreted::from(extensible))
+}
 
-    let mut descriptor_object = JSObject::new();
-    descriptor_object.set_property("value", value)?;
-    descriptor_object.set_property("configurable", prop.access.configurable())?;
-    descriptor_object.set_property("enumerable", prop.access.enumerable())?;
-    descriptor_object.set_property("writable", prop.access.writable())?;
+#[allow(non_snake_case)]
+fn object_object_preventExtensions(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = call.arg_value(0, heap)?;
+    let objref = match value.as_objref() {
+        Some(objref) if objref != Heap::NULL => objref,
+        _ => return Ok(Interpreted::from(value)),
+    };
+    if !heap.prevent_extensions(objref)? {
+        return Err(Exception::type_error(TypeError::PROXY_REFUSED, objref));
+    }
+    Ok(Interpreted::from(objref))
+}
 
-    let descriptor_ref = heap.alloc(descriptor_object);
-    Ok(Interpreted::from(descriptor_ref))
+/// `Object.keys(obj)`: the own enumerable keys of `obj`.
+fn object_object_keys(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = call.arg_value(0, heap)?;
+    let objref = value.objectify(heap);
+    if objref == Heap::NULL {
+        return Err(Exception::not_an_object(value));
+    }
+    let mut keys = Vec::new();
+    for key in heap.own_keys(objref)? {
+        let property = heap.own_property(objref, &key)?;
+        if property.is_some_and(|property| property.access.enumerable()) {
+            keys.push(JSValue::from(key));
+        }
+    }
+    Ok(Interpreted::from(heap.alloc(JSObject::from_array(keys))))
 }
 
-fn define_property(
+pub(crate) fn define_property(
     objref: JSRef,
     propname: JSString,
     descref: JSRef,
     heap: &mut Heap,
 ) -> JSResult<()> {
     let get_value =
-        |object: &JSObject, name: &str| object.get_own_value(name).unwrap_or(JSValue::Undefined);
+        |object: &JSObject, name: &str| object.get_own_value(name).unwrap_or(JSValue::UNDEFINED);
     let get_bool = |object: &JSObject, name: &str| get_value(object, name).boolify(heap);
 
     let descriptor = heap.get(descref);
@@ -160,15 +213,29 @@ fn define_property(
             return Err(Exception::type_error(TypeError::INVALID_DESCRIPTOR, what));
         }
 
-        todo!()
+        let accessor = |name| {
+            let value = get_value(descriptor, name);
+            match value.as_objref() {
+                Some(func_ref) if heap.get(func_ref).is_callable() => Ok(Some(func_ref)),
+                _ if value.is_undefined() => Ok(None),
+                _ => Err(Exception::type_error(TypeError::NOT_CALLABLE, value)),
+            }
+        };
+        let get = accessor("get")?;
+        let set = accessor("set")?;
+        let access = Access::new(configurable, enumerable, false);
+        heap.define_accessor(objref, propname.as_str(), get, set, access)?;
     } else {
         let value = get_value(descriptor, "value");
         let writable = get_bool(descriptor, "writable");
         let access = Access::new(configurable, enumerable, writable);
+        let value = heap.array_length_value(objref, propname.as_str(), value)?;
 
         let object = heap.get_mut(objref);
         object.define_own_property(propname.as_str(), access)?;
-        object.set_even_nonwritable(propname.as_str(), value)?;
+        if has_value {
+            object.set_even_nonwritable(propname.as_str(), value)?;
+        }
     }
     Ok(())
 }
@@ -179,7 +246,13 @@ fn object_object_defineProperty(call: CallContext, heap: &mut Heap) -> JSResult<
     let prop = call.arg_value(1, heap)?.stringify(heap)?;
     let descref = call.arg_value(2, heap)?.to_ref()?;
 
-    define_property(objref, prop, descref, heap)?;
+    if !heap.define_member(objref, &prop, descref)? {
+        return Err(Exception::attr_type_error(
+            TypeError::PROXY_REFUSED,
+            objref,
+            prop,
+        ));
+    }
     Ok(Interpreted::from(objref))
 }
 
@@ -193,12 +266,18 @@ fn define_properties(objref: JSRef, descs_ref: JSRef, heap: &mut Heap) -> JSResu
                     return Err(Exception::type_error(TypeError::INVALID_DESCRIPTOR, value));
                 }
             };
-            Ok((prop.clone(), descref))
+            Ok((prop, descref))
         })
         .collect::<JSResult<_>>()?;
 
     while let Some((prop, descref)) = pairs.pop() {
-        define_property(objref, prop, descref, heap)?;
+        if !heap.define_member(objref, &prop, descref)? {
+            return Err(Exception::attr_type_error(
+                TypeError::PROXY_REFUSED,
+                objref,
+                prop,
+            ));
+        }
     }
     Ok(())
 }
@@ -218,8 +297,9 @@ fn object_object_setPrototypeOf(call: CallContext, heap: &mut Heap) -> JSResult<
 
     let proto_arg = call.arg_value(1, heap)?;
     if let Ok(protoref) = proto_arg.to_ref() {
-        let object = heap.get_mut(objref);
-        object.proto = protoref;
+        if !heap.set_prototype(objref, protoref)? {
+            return Err(Exception::type_error(TypeError::INVALID_PROTO, proto_arg));
+        }
     }
 
     Ok(Interpreted::from(objref))
@@ -0,0 +1,390 @@
+//! `Proxy` objects: their internal methods (see [`Heap::lookup_member()`] and below)
+//! call the traps of a handler object or fall back to the target.
+use crate::builtin::function::arraylike_values;
+use crate::error::TypeError;
+use crate::function::BoundFunction;
+use crate::object::{Access, Content, ObjectValue, Property};
+use crate::prelude::*;
+use crate::{CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult};
+
+/// The target and the handler of a Proxy; both are `Heap::NULL` once it is revoked.
+#[derive(Clone, Copy, Debug)]
+pub struct JSProxy {
+    pub target: JSRef,
+    pub handler: JSRef,
+    /// a proxy of a function can be called
+    pub callable: bool,
+}
+
+impl JSProxy {
+    /// Finds the trap `name` of the handler, `None` if the handler does not have it.
+    fn trap(&self, name: &str, heap: &mut Heap) -> JSResult<Option<JSRef>> {
+        if self.handler == Heap::NULL {
+            return Err(Exception::attr_type_error(
+                TypeError::PROXY_REVOKED,
+                Interpreted::VOID,
+                name,
+            ));
+        }
+        let trap = heap.get_member(self.handler, &JSString::from(name))?;
+        match trap.as_objref() {
+            _ if trap.is_undefined() => Ok(None),
+            Some(Heap::NULL) => Ok(None),
+            Some(trapref) if heap.get(trapref).is_callable() => Ok(Some(trapref)),
+            _ => Err(Exception::attr_type_error(
+                TypeError::NOT_CALLABLE,
+                Interpreted::member(self.handler, name),
+                name,
+            )),
+        }
+    }
+
+    /// Calls `trap` with the handler as `this`.
+    fn call(&self, trap: JSRef, arguments: Vec<JSValue>, heap: &mut Heap) -> JSResult<JSValue> {
+        let arguments = arguments
+            .into_iter()
+            .map(Interpreted::from)
+            .collect::<Vec<_>>();
+        let call = CallContext::from(arguments).with_this(self.handler);
+        // the code around a trap keeps the target on the Rust stack
+        heap.without_collection(|heap| heap.execute(trap, call)?.to_value(heap))
+    }
+}
+
+fn invariant(trap: &str) -> Exception {
+    Exception::attr_type_error(TypeError::PROXY_INVARIANT, Interpreted::VOID, trap)
+}
+
+pub(crate) fn get(
+    proxy: JSProxy,
+    name: &JSString,
+    receiver: JSRef,
+    heap: &mut Heap,
+) -> JSResult<JSValue> {
+    let Some(trap) = proxy.trap("get", heap)? else {
+        let value = heap.lookup_member(proxy.target, name, receiver)?;
+        return Ok(value.unwrap_or(JSValue::UNDEFINED));
+    };
+    let arguments = vec![proxy.target.into(), name.clone().into(), receiver.into()];
+    let value = proxy.call(trap, arguments, heap)?;
+
+    // a non-configurable, non-writable property cannot be reported differently
+    if let Some(property) = heap.own_property(proxy.target, name)? {
+        let access = property.access;
+        let fixed = property.content.to_value()?;
+        if !access.configurable() && !access.writable() && !value.same_value(&fixed) {
+            return Err(invariant("get"));
+        }
+    }
+    Ok(value)
+}
+
+pub(crate) fn set(
+    proxy: JSProxy,
+    name: &JSString,
+    value: JSValue,
+    receiver: JSRef,
+    heap: &mut Heap,
+) -> JSResult<()> {
+    let Some(trap) = proxy.trap("set", heap)? else {
+        return heap.set_member(proxy.target, name, value);
+    };
+    let arguments = vec![
+        proxy.target.into(),
+        name.clone().into(),
+        value,
+        receiver.into(),
+    ];
+    let done = proxy.call(trap, arguments, heap)?.boolify(heap);
+    if !done {
+        let what = Interpreted::member(receiver, name);
+        return Err(Exception::attr_type_error(
+            TypeError::SET_READONLY,
+            what,
+            name.clone(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn has(proxy: JSProxy, name: &JSString, heap: &mut Heap) -> JSResult<bool> {
+    let Some(trap) = proxy.trap("has", heap)? else {
+        return heap.has_member(proxy.target, name);
+    };
+    let arguments = vec![proxy.target.into(), name.clone().into()];
+    let found = proxy.call(trap, arguments, heap)?.boolify(heap);
+    if !found {
+        // a non-configurable property or any property of a non-extensible target cannot hide
+        if let Some(property) = heap.own_property(proxy.target, name)? {
+            if !property.access.configurable() || !heap.is_extensible(proxy.target)? {
+                return Err(invariant("has"));
+            }
+        }
+    }
+    Ok(found)
+}
+
+pub(crate) fn delete(proxy: JSProxy, name: &JSString, heap: &mut Heap) -> JSResult<bool> {
+    let Some(trap) = proxy.trap("deleteProperty", heap)? else {
+        return heap.delete_member(proxy.target, name);
+    };
+    let arguments = vec![proxy.target.into(), name.clone().into()];
+    let deleted = proxy.call(trap, arguments, heap)?.boolify(heap);
+    if deleted {
+        if let Some(property) = heap.own_property(proxy.target, name)? {
+            if !property.access.configurable() {
+                return Err(invariant("deleteProperty"));
+            }
+        }
+    }
+    Ok(deleted)
+}
+
+pub(crate) fn own_keys(proxy: JSProxy, heap: &mut Heap) -> JSResult<Vec<JSString>> {
+    let Some(trap) = proxy.trap("ownKeys", heap)? else {
+        return heap.own_keys(proxy.target);
+    };
+    let result = proxy.call(trap, vec![proxy.target.into()], heap)?;
+    let keys = (arraylike_values(&result, heap)?.into_iter())
+        .map(|key| key.as_string().ok_or_else(|| invariant("ownKeys")))
+        .collect::<JSResult<Vec<JSString>>>()?;
+
+    // non-configurable properties cannot be hidden
+    for key in heap.own_keys(proxy.target)? {
+        let property = heap.own_property(proxy.target, &key)?;
+        let fixed = property.is_some_and(|property| !property.access.configurable());
+        if fixed && !keys.contains(&key) {
+            return Err(invariant("ownKeys"));
+        }
+    }
+    Ok(keys)
+}
+
+pub(crate) fn own_property(
+    proxy: JSProxy,
+    name: &JSString,
+    heap: &mut Heap,
+) -> JSResult<Option<Property>> {
+    let Some(trap) = proxy.trap("getOwnPropertyDescriptor", heap)? else {
+        return heap.own_property(proxy.target, name);
+    };
+    let arguments = vec![proxy.target.into(), name.clone().into()];
+    let descriptor = proxy.call(trap, arguments, heap)?;
+    if descriptor.is_undefined() {
+        return Ok(None);
+    }
+    let descref = match descriptor.as_objref() {
+        Some(descref) if descref != Heap::NULL => descref,
+        _ => return Err(invariant("getOwnPropertyDescriptor")),
+    };
+    let mut field = |name: &str| heap.get_member(descref, &JSString::from(name));
+    let value = field("value")?;
+    let configurable = field("configurable")?;
+    let enumerable = field("enumerable")?;
+    let writable = field("writable")?;
+    let access = Access::new(
+        configurable.boolify(heap),
+        enumerable.boolify(heap),
+        writable.boolify(heap),
+    );
+    Ok(Some(Property {
+        content: Content::from(value),
+        access,
+    }))
+}
+
+pub(crate) fn define(
+    proxy: JSProxy,
+    name: &JSString,
+    descref: JSRef,
+    heap: &mut Heap,
+) -> JSResult<bool> {
+    let Some(trap) = proxy.trap("defineProperty", heap)? else {
+        return heap.define_member(proxy.target, name, descref);
+    };
+    let arguments = vec![proxy.target.into(), name.clone().into(), descref.into()];
+    let defined = proxy.call(trap, arguments, heap)?.boolify(heap);
+    if defined && heap.own_property(proxy.target, name)?.is_none() {
+        // a new property cannot appear on a non-extensible target
+        if !heap.is_extensible(proxy.target)? {
+            return Err(invariant("defineProperty"));
+        }
+    }
+    Ok(defined)
+}
+
+pub(crate) fn prototype_of(proxy: JSProxy, heap: &mut Heap) -> JSResult<JSRef> {
+    let Some(trap) = proxy.trap("getPrototypeOf", heap)? else {
+        return heap.prototype_of(proxy.target);
+    };
+    let proto = proxy.call(trap, vec![proxy.target.into()], heap)?;
+    let proto = proto
+        .as_objref()
+        .ok_or_else(|| invariant("getPrototypeOf"))?;
+    // the prototype of a non-extensible target is fixed
+    if !heap.is_extensible(proxy.target)? && proto != heap.prototype_of(proxy.target)? {
+        return Err(invariant("getPrototypeOf"));
+    }
+    Ok(proto)
+}
+
+pub(crate) fn set_prototype(proxy: JSProxy, proto: JSRef, heap: &mut Heap) -> JSResult<bool> {
+    let Some(trap) = proxy.trap("setPrototypeOf", heap)? else {
+        return heap.set_prototype(proxy.target, proto);
+    };
+    let arguments = vec![proxy.target.into(), proto.into()];
+    let done = proxy.call(trap, arguments, heap)?.boolify(heap);
+    if done && !heap.is_extensible(proxy.target)? && proto != heap.prototype_of(proxy.target)? {
+        return Err(invariant("setPrototypeOf"));
+    }
+    Ok(done)
+}
+
+pub(crate) fn is_extensible(proxy: JSProxy, heap: &mut Heap) -> JSResult<bool> {
+    let Some(trap) = proxy.trap("isExtensible", heap)? else {
+        return heap.is_extensible(proxy.target);
+    };
+    let extensible = proxy
+        .call(trap, vec![proxy.target.into()], heap)?
+        .boolify(heap);
+    if extensible != heap.is_extensible(proxy.target)? {
+        return Err(invariant("isExtensible"));
+    }
+    Ok(extensible)
+}
+
+pub(crate) fn prevent_extensions(proxy: JSProxy, heap: &mut Heap) -> JSResult<bool> {
+    let Some(trap) = proxy.trap("preventExtensions", heap)? else {
+        return heap.prevent_extensions(proxy.target);
+    };
+    let done = proxy
+        .call(trap, vec![proxy.target.into()], heap)?
+        .boolify(heap);
+    if done && heap.is_extensible(proxy.target)? {
+        return Err(invariant("preventExtensions"));
+    }
+    Ok(done)
+}
+
+pub(crate) fn apply(proxy: JSProxy, call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if !proxy.callable {
+        let what = Interpreted::from(call.this_ref);
+        return Err(Exception::attr_type_error(
+            TypeError::NOT_CALLABLE,
+            what,
+            call.method_name,
+        ));
+    }
+    let Some(trap) = proxy.trap("apply", heap)? else {
+        return heap.execute(proxy.target, call);
+    };
+    let arguments = (call.arguments.iter())
+        .map(|arg| arg.to_value(heap))
+        .collect::<JSResult<Vec<_>>>()?;
+    let arguments = heap.alloc(JSObject::from_array(arguments));
+    let arguments = vec![proxy.target.into(), call.this.clone(), arguments.into()];
+    proxy.call(trap, arguments, heap).map(Interpreted::from)
+}
+
+pub(crate) fn construct(
+    proxy: JSProxy,
+    proxyref: JSRef,
+    arguments: Vec<Interpreted>,
+    heap: &mut Heap,
+) -> JSResult<JSRef> {
+    if !proxy.callable {
+        return Err(Exception::type_error(TypeError::NOT_CALLABLE, proxyref));
+    }
+    let Some(trap) = proxy.trap("construct", heap)? else {
+        return heap.construct(proxy.target, arguments);
+    };
+    let arguments = (arguments.iter())
+        .map(|arg| arg.to_value(heap))
+        .collect::<JSResult<Vec<_>>>()?;
+    let arguments = heap.alloc(JSObject::from_array(arguments));
+    let arguments = vec![proxy.target.into(), arguments.into(), proxyref.into()];
+    let result = proxy.call(trap, arguments, heap)?;
+    match result.as_objref() {
+        Some(objref) if objref != Heap::NULL => Ok(objref),
+        _ => Err(invariant("construct")),
+    }
+}
+
+/*
+ *  Proxy
+ */
+
+/// The target or the handler given to `Proxy`, both must be objects.
+fn proxy_arg(call: &CallContext, index: usize, heap: &mut Heap) -> JSResult<JSRef> {
+    let value = call.arg_value(index, heap)?;
+    match value.as_objref() {
+        Some(objref) if objref != Heap::NULL => Ok(objref),
+        _ => Err(Exception::type_error(TypeError::PROXY_NOT_OBJECT, value)),
+    }
+}
+
+fn proxy_new(call: &CallContext, heap: &mut Heap) -> JSResult<JSRef> {
+    let target = proxy_arg(call, 0, heap)?;
+    let handler = proxy_arg(call, 1, heap)?;
+    let proxy = JSProxy {
+        target,
+        handler,
+        callable: heap.get(target).is_callable(),
+    };
+    let mut object = JSObject::new();
+    object.proto = Heap::NULL;
+    object.value = ObjectValue::Proxy(proxy);
+    Ok(heap.alloc(object))
+}
+
+fn proxy_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if !heap.smells_fresh(call.this_ref) {
+        return Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            "Proxy",
+        ));
+    }
+    Ok(Interpreted::from(proxy_new(&call, heap)?))
+}
+
+/// `Proxy.revocable(target, handler)` gives `{proxy, revoke}`.
+fn proxy_revocable(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let proxy = proxy_new(&call, heap)?;
+    // `revoke` is `proxy_revoke` bound to the proxy
+    let revoke = BoundFunction {
+        target: heap.alloc_named_func("revoke", proxy_revoke),
+        this: JSValue::from(proxy),
+        arguments: Vec::new(),
+    };
+    let mut revoke = JSObject::from_bound_function(revoke, Heap::FUNCTION_PROTO);
+    revoke.set_function_signature("", 0)?;
+    let revoke = heap.alloc(revoke);
+
+    let mut result = JSObject::new();
+    result.set_property("proxy", proxy)?;
+    result.set_property("revoke", revoke)?;
+    Ok(Interpreted::from(heap.alloc(result)))
+}
+
+fn proxy_revoke(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if let ObjectValue::Proxy(proxy) = &mut heap.get_mut(call.this_ref).value {
+        proxy.target = Heap::NULL;
+        proxy.handler = Heap::NULL;
+    }
+    Ok(Interpreted::VOID)
+}
+
+pub fn init(heap: &mut Heap) -> JSResult<()> {
+    let mut constructor = JSObject::from_func(proxy_constructor);
+    constructor.set_function_signature("Proxy", 2)?;
+    // `new` needs a prototype, but proxies do not use it
+    constructor.set_system("prototype", Heap::NULL)?;
+    let revocable = heap.alloc_named_func("revocable", proxy_revocable);
+    constructor.set_hidden("revocable", revocable)?;
+
+    let constructor = heap.alloc(constructor);
+    heap.get_mut(Heap::GLOBAL)
+        .set_hidden("Proxy", constructor)?;
+    Ok(())
+}
@@ -0,0 +1,156 @@
+/// The implementation of the builtin Reflect namespace: the internal methods of objects
+/// as functions, see [`crate::builtin::proxy`].
+use crate::builtin::function::arraylike_values;
+use crate::builtin::object::{object_arg, property_descriptor};
+use crate::error::{ignore_set_readonly, TypeError};
+use crate::prelude::*;
+use crate::{CallContext, Exception, Heap, HostFn, Interpreted, JSObject, JSResult};
+
+static FUNCTIONS: &[(&str, HostFn)] = &[
+    ("apply", reflect_apply),
+    ("construct", reflect_construct),
+    ("defineProperty", reflect_defineProperty),
+    ("deleteProperty", reflect_deleteProperty),
+    ("get", reflect_get),
+    ("getOwnPropertyDescriptor", reflect_getOwnPropertyDescriptor),
+    ("getPrototypeOf", reflect_getPrototypeOf),
+    ("has", reflect_has),
+    ("isExtensible", reflect_isExtensible),
+    ("ownKeys", reflect_ownKeys),
+    ("preventExtensions", reflect_preventExtensions),
+    ("set", reflect_set),
+    ("setPrototypeOf", reflect_setPrototypeOf),
+];
+
+fn property_key(call: &CallContext, index: usize, heap: &mut Heap) -> JSResult<JSString> {
+    call.arg_value(index, heap)?.stringify(heap)
+}
+
+/// `Reflect.apply(func, thisArg, args)`
+fn reflect_apply(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let funcref = object_arg(&call, 0, heap)?;
+    if !heap.get(funcref).is_callable() {
+        return Err(Exception::type_error(TypeError::NOT_CALLABLE, funcref));
+    }
+    let this = call.arg_value(1, heap)?;
+    let arguments = arraylike_values(&call.arg_value(2, heap)?, heap)?;
+    let arguments = arguments
+        .into_iter()
+        .map(Interpreted::from)
+        .collect::<Vec<_>>();
+    heap.execute(funcref, CallContext::from(arguments).with_this_value(this))
+}
+
+/// `Reflect.construct(func, args)`
+fn reflect_construct(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let funcref = object_arg(&call, 0, heap)?;
+    if !heap.get(funcref).is_callable() {
+        return Err(Exception::type_error(TypeError::NOT_CALLABLE, funcref));
+    }
+    let arguments = arraylike_values(&call.arg_value(1, heap)?, heap)?;
+    let arguments = arguments
+        .into_iter()
+        .map(Interpreted::from)
+        .collect::<Vec<_>>();
+    Ok(Interpreted::from(heap.construct(funcref, arguments)?))
+}
+
+#[allow(non_snake_case)]
+fn reflect_defineProperty(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let name = property_key(&call, 1, heap)?;
+    let descref = object_arg(&call, 2, heap)?;
+    let defined = (heap.define_member(objref, &name, descref))
+        .or_else(|e| ignore_set_readonly(e).map(|()| false))?;
+    Ok(Interpreted::from(defined))
+}
+
+#[allow(non_snake_case)]
+fn reflect_deleteProperty(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let name = property_key(&call, 1, heap)?;
+    Ok(Interpreted::from(heap.delete_member(objref, &name)?))
+}
+
+/// `Reflect.get(obj, key, receiver = obj)`
+fn reflect_get(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let name = property_key(&call, 1, heap)?;
+    let receiver = match call.arguments.len() {
+        0..=2 => objref,
+        _ => object_arg(&call, 2, heap)?,
+    };
+    let value = heap.lookup_member(objref, &name, receiver)?;
+    Ok(Interpreted::from(value.unwrap_or(JSValue::UNDEFINED)))
+}
+
+#[allow(non_snake_case)]
+fn reflect_getOwnPropertyDescriptor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let name = property_key(&call, 1, heap)?;
+    let property = heap.own_property(objref, &name)?;
+    property_descriptor(property, heap)
+}
+
+#[allow(non_snake_case)]
+fn reflect_getPrototypeOf(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    Ok(Interpreted::from(heap.prototype_of(objref)?))
+}
+
+fn reflect_has(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let name = property_key(&call, 1, heap)?;
+    Ok(Interpreted::from(heap.has_member(objref, &name)?))
+}
+
+#[allow(non_snake_case)]
+fn reflect_isExtensible(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    Ok(Interpreted::from(heap.is_extensible(objref)?))
+}
+
+#[allow(non_snake_case)]
+fn reflect_ownKeys(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let keys = heap.own_keys(objref)?;
+    let keys = keys.into_iter().map(JSValue::from).collect::<Vec<_>>();
+    Ok(Interpreted::from(heap.alloc(JSObject::from_array(keys))))
+}
+
+#[allow(non_snake_case)]
+fn reflect_preventExtensions(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    Ok(Interpreted::from(heap.prevent_extensions(objref)?))
+}
+
+/// `Reflect.set(obj, key, value)` gives `false` instead of throwing
+/// when the property cannot be set.
+fn reflect_set(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let name = property_key(&call, 1, heap)?;
+    let value = call.arg_value(2, heap)?;
+    let done = (heap.set_member(objref, &name, value).map(|()| true))
+        .or_else(|e| ignore_set_readonly(e).map(|()| false))?;
+    Ok(Interpreted::from(done))
+}
+
+#[allow(non_snake_case)]
+fn reflect_setPrototypeOf(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let objref = object_arg(&call, 0, heap)?;
+    let proto = call.arg_value(1, heap)?;
+    let protoref = (proto.as_objref())
+        .ok_or_else(|| Exception::type_error(TypeError::INVALID_PROTO, proto))?;
+    Ok(Interpreted::from(heap.set_prototype(objref, protoref)?))
+}
+
+pub fn init(heap: &mut Heap) -> JSResult<()> {
+    let mut reflect = JSObject::new();
+    for (name, func) in FUNCTIONS.iter() {
+        let funcref = heap.alloc_named_func(name, *func);
+        reflect.set_hidden(name, funcref)?;
+    }
+    let reflect = heap.alloc(reflect);
+    heap.get_mut(Heap::GLOBAL).set_hidden("Reflect", reflect)?;
+    Ok(())
+}
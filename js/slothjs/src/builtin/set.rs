@@ -0,0 +1,107 @@
+/// The implementation of the builtin Set object.
+use crate::builtin::map::{callback_args, iterate, iterator_new, IteratorKind};
+use crate::error::TypeError;
+use crate::object::{HostClass, JSMap, ObjectValue};
+use crate::prelude::*;
+use crate::{CallContext, Exception, Heap, Interpreted, JSRef, JSResult};
+
+pub static CLASS: HostClass = HostClass {
+    name: "Set",
+    constructor: set_constructor,
+    methods: &[
+        ("add", set_proto_add),
+        ("clear", set_proto_clear),
+        ("delete", set_proto_delete),
+        ("entries", set_proto_entries),
+        ("forEach", set_proto_forEach),
+        ("has", set_proto_has),
+        ("keys", set_proto_values),
+        ("values", set_proto_values),
+    ],
+    static_methods: &[],
+};
+
+fn set_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    if !heap.smells_fresh(call.this_ref) {
+        return Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            "Set",
+        ));
+    }
+
+    let mut set = JSMap::new();
+    let iterable = call.arg_value(0, heap)?;
+    for item in iterate(&iterable, heap)? {
+        set.insert(item, JSValue::UNDEFINED);
+    }
+
+    heap.get_mut(call.this_ref).value = ObjectValue::Set(set);
+    Ok(Interpreted::VOID)
+}
+
+fn this_set(this_ref: JSRef, heap: &Heap) -> JSResult<&JSMap> {
+    (heap.get(this_ref).as_set()).ok_or_else(|| Exception::instance_required(this_ref, "Set"))
+}
+
+fn this_set_mut(this_ref: JSRef, heap: &mut Heap) -> JSResult<&mut JSMap> {
+    (heap.get_mut(this_ref).as_set_mut())
+        .ok_or_else(|| Exception::instance_required(this_ref, "Set"))
+}
+
+pub(crate) fn set_proto_size(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let size = this_set(call.this_ref, heap)?.len();
+    Ok(Interpreted::from(size as i64))
+}
+
+fn set_proto_add(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = call.arg_value(0, heap)?;
+    this_set_mut(call.this_ref, heap)?.insert(value, JSValue::UNDEFINED);
+    Ok(Interpreted::from(call.this_ref))
+}
+
+fn set_proto_clear(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_set_mut(call.this_ref, heap)?.clear();
+    Ok(Interpreted::VOID)
+}
+
+fn set_proto_delete(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = call.arg_value(0, heap)?;
+    let found = this_set_mut(call.this_ref, heap)?.remove(&value);
+    Ok(Interpreted::from(found))
+}
+
+fn set_proto_has(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let value = call.arg_value(0, heap)?;
+    let found = this_set(call.this_ref, heap)?.contains(&value);
+    Ok(Interpreted::from(found))
+}
+
+fn set_proto_entries(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_set(call.this_ref, heap)?;
+    iterator_new(call.this_ref, IteratorKind::Entries, heap)
+}
+
+fn set_proto_values(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_set(call.this_ref, heap)?;
+    iterator_new(call.this_ref, IteratorKind::Values, heap)
+}
+
+#[allow(non_snake_case)]
+fn set_proto_forEach(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    this_set(call.this_ref, heap)?;
+    let (funcref, this_arg) = callback_args(&call, heap)?;
+
+    let mut position = 0;
+    while let Some((at, value, _)) = this_set(call.this_ref, heap)?.next_entry(position) {
+        position = at + 1;
+        let arguments = vec![
+            Interpreted::from(value.clone()),
+            Interpreted::from(value),
+            Interpreted::from(call.this_ref),
+        ];
+        let callback = CallContext::from(arguments).with_this_value(this_arg.clone());
+        heap.execute(funcref, callback.with_name("<callback>".into()))?;
+    }
+    Ok(Interpreted::VOID)
+}
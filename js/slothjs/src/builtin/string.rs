@@ -154,7 +154,7 @@ fn string_proto_replace(call: CallContext, heap: &mut Heap) -> JSResult<Interpre
     let string = heap.ref_to_string(call.this_ref)?;
 
     let search = call.arg_value(0, heap)?;
-    if let JSValue::Ref(r) = search {
+    if let Some(r) = search.as_objref() {
         if r.has_proto(Heap::REGEXP_PROTO, heap) {
             todo!("String.prototype.replace(regexp, ...)");
         }
@@ -167,7 +167,7 @@ fn string_proto_replace(call: CallContext, heap: &mut Heap) -> JSResult<Interpre
     };
 
     let replace = call.arg_value(1, heap)?;
-    if let JSValue::Ref(r) = replace {
+    if let Some(r) = replace.as_objref() {
         if r.has_proto(Heap::FUNCTION_PROTO, heap) {
             todo!("String.prototype.replace(.. , function)");
         }
@@ -0,0 +1,350 @@
+//! `WeakMap`, `WeakSet`, `WeakRef` and `FinalizationRegistry`: they refer to objects
+//! without keeping them alive, see [`Heap::collect()`].
+use crate::builtin::map::iterate;
+use crate::error::TypeError;
+use crate::object::{HostClass, ObjectValue};
+use crate::prelude::*;
+use crate::{CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult};
+
+/// The entries of a WeakMap (or of a WeakSet, with `undefined` values).
+///
+/// A value is alive only while both its key and the map are:
+/// the collector traces it when it finds the key, and drops entries with dead keys.
+#[derive(Clone, Debug, Default)]
+pub struct WeakEntries(HashMap<JSRef, JSValue>);
+
+impl WeakEntries {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The values of the keys that `is_alive`.
+    pub(crate) fn reachable_values<'a>(
+        &'a self,
+        is_alive: &'a dyn Fn(JSRef) -> bool,
+    ) -> impl Iterator<Item = &'a JSValue> + 'a {
+        self.0
+            .iter()
+            .filter(move |(&key, _)| is_alive(key))
+            .map(|(_, value)| value)
+    }
+
+    fn sweep(&mut self, is_alive: &dyn Fn(JSRef) -> bool) {
+        self.0.retain(|&key, _| is_alive(key));
+    }
+}
+
+/// A target of a FinalizationRegistry and what its callback gets when the target dies.
+#[derive(Clone, Debug)]
+pub struct FinalizationCell {
+    pub target: JSRef,
+    pub held: JSValue,
+    /// a weak reference too: `unregister()` is pointless once it is dead
+    pub token: Option<JSRef>,
+}
+
+/// The cleanup callback and the registered targets of a FinalizationRegistry.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    pub cleanup: JSRef,
+    pub cells: Vec<FinalizationCell>,
+}
+
+impl ObjectValue {
+    /// Drops the references to objects that are not `is_alive` after a collection.
+    /// Gives the `(cleanup, held)` pairs of a FinalizationRegistry whose targets died.
+    pub(crate) fn sweep_weak(&mut self, is_alive: &dyn Fn(JSRef) -> bool) -> Vec<(JSRef, JSValue)> {
+        let mut finalized = Vec::new();
+        match self {
+            ObjectValue::WeakMap(entries) | ObjectValue::WeakSet(entries) => {
+                entries.sweep(is_alive)
+            }
+            ObjectValue::WeakRef(target) if !is_alive(*target) => *target = Heap::NULL,
+            ObjectValue::FinalizationRegistry(registry) => {
+                let cleanup = registry.cleanup;
+                registry.cells.retain_mut(|cell| {
+                    if cell.token.is_some_and(|token| !is_alive(token)) {
+                        cell.token = None;
+                    }
+                    if is_alive(cell.target) {
+                        return true;
+                    }
+                    finalized.push((cleanup, cell.held.clone()));
+                    false
+                });
+            }
+            _ => (),
+        }
+        finalized
+    }
+}
+
+/// Only objects can be held weakly.
+fn weak_target(value: &JSValue, of: &str) -> JSResult<JSRef> {
+    match value.as_objref() {
+        Some(objref) if objref != Heap::NULL => Ok(objref),
+        _ => Err(Exception::attr_type_error(
+            TypeError::WEAK_TARGET,
+            value.clone(),
+            of,
+        )),
+    }
+}
+
+fn require_new(call: &CallContext, heap: &Heap, name: &str) -> JSResult<()> {
+    match heap.smells_fresh(call.this_ref) {
+        true => Ok(()),
+        false => Err(Exception::attr_type_error(
+            TypeError::NEW_REQUIRED,
+            call.this_ref,
+            name,
+        )),
+    }
+}
+
+// ==============================================
+// WeakMap and WeakSet
+
+pub static WEAK_MAP_CLASS: HostClass = HostClass {
+    name: "WeakMap",
+    constructor: weak_map_constructor,
+    methods: &[
+        ("delete", weak_map_proto_delete),
+        ("get", weak_map_proto_get),
+        ("has", weak_map_proto_has),
+        ("set", weak_map_proto_set),
+    ],
+    static_methods: &[],
+};
+
+pub static WEAK_SET_CLASS: HostClass = HostClass {
+    name: "WeakSet",
+    constructor: weak_set_constructor,
+    methods: &[
+        ("add", weak_set_proto_add),
+        ("delete", weak_set_proto_delete),
+        ("has", weak_set_proto_has),
+    ],
+    static_methods: &[],
+};
+
+fn weak_map_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    require_new(&call, heap, "WeakMap")?;
+    let mut entries = WeakEntries::default();
+    let iterable = call.arg_value(0, heap)?;
+    for item in iterate(&iterable, heap)? {
+        let entry = heap.get(item.to_ref()?);
+        let key = entry.lookup_value("0", heap).unwrap_or(JSValue::UNDEFINED);
+        let value = entry.lookup_value("1", heap).unwrap_or(JSValue::UNDEFINED);
+        entries.0.insert(weak_target(&key, "WeakMap")?, value);
+    }
+    heap.get_mut(call.this_ref).value = ObjectValue::WeakMap(entries);
+    Ok(Interpreted::VOID)
+}
+
+fn weak_set_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    require_new(&call, heap, "WeakSet")?;
+    let mut entries = WeakEntries::default();
+    let iterable = call.arg_value(0, heap)?;
+    for item in iterate(&iterable, heap)? {
+        entries
+            .0
+            .insert(weak_target(&item, "WeakSet")?, JSValue::UNDEFINED);
+    }
+    heap.get_mut(call.this_ref).value = ObjectValue::WeakSet(entries);
+    Ok(Interpreted::VOID)
+}
+
+fn this_entries<'a>(
+    this_ref: JSRef,
+    of: &str,
+    heap: &'a mut Heap,
+) -> JSResult<&'a mut WeakEntries> {
+    match (&mut heap.get_mut(this_ref).value, of) {
+        (ObjectValue::WeakMap(entries), "WeakMap") => Ok(entries),
+        (ObjectValue::WeakSet(entries), "WeakSet") => Ok(entries),
+        _ => Err(Exception::instance_required(this_ref, of)),
+    }
+}
+
+/// The key argument; `None` if it is not an object and so cannot be in the collection.
+fn key_arg(call: &CallContext, heap: &mut Heap) -> JSResult<Option<JSRef>> {
+    let key = call.arg_value(0, heap)?;
+    Ok(key.as_objref().filter(|&objref| objref != Heap::NULL))
+}
+
+fn weak_map_proto_delete(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = key_arg(&call, heap)?;
+    let entries = this_entries(call.this_ref, "WeakMap", heap)?;
+    let found = key.is_some_and(|key| entries.0.remove(&key).is_some());
+    Ok(Interpreted::from(found))
+}
+
+fn weak_map_proto_get(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = key_arg(&call, heap)?;
+    let entries = this_entries(call.this_ref, "WeakMap", heap)?;
+    let value = key.and_then(|key| entries.0.get(&key).cloned());
+    Ok(Interpreted::from(value.unwrap_or(JSValue::UNDEFINED)))
+}
+
+fn weak_map_proto_has(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = key_arg(&call, heap)?;
+    let entries = this_entries(call.this_ref, "WeakMap", heap)?;
+    let found = key.is_some_and(|key| entries.0.contains_key(&key));
+    Ok(Interpreted::from(found))
+}
+
+fn weak_map_proto_set(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = weak_target(&call.arg_value(0, heap)?, "WeakMap")?;
+    let value = call.arg_value(1, heap)?;
+    this_entries(call.this_ref, "WeakMap", heap)?
+        .0
+        .insert(key, value);
+    Ok(Interpreted::from(call.this_ref))
+}
+
+fn weak_set_proto_add(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = weak_target(&call.arg_value(0, heap)?, "WeakSet")?;
+    (this_entries(call.this_ref, "WeakSet", heap)?.0).insert(key, JSValue::UNDEFINED);
+    Ok(Interpreted::from(call.this_ref))
+}
+
+fn weak_set_proto_delete(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = key_arg(&call, heap)?;
+    let entries = this_entries(call.this_ref, "WeakSet", heap)?;
+    let found = key.is_some_and(|key| entries.0.remove(&key).is_some());
+    Ok(Interpreted::from(found))
+}
+
+fn weak_set_proto_has(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let key = key_arg(&call, heap)?;
+    let entries = this_entries(call.this_ref, "WeakSet", heap)?;
+    let found = key.is_some_and(|key| entries.0.contains_key(&key));
+    Ok(Interpreted::from(found))
+}
+
+// ==============================================
+// WeakRef
+
+pub static WEAK_REF_CLASS: HostClass = HostClass {
+    name: "WeakRef",
+    constructor: weak_ref_constructor,
+    methods: &[("deref", weak_ref_proto_deref)],
+    static_methods: &[],
+};
+
+fn weak_ref_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    require_new(&call, heap, "WeakRef")?;
+    let target = weak_target(&call.arg_value(0, heap)?, "WeakRef")?;
+    heap.keep_during_job(target);
+    heap.get_mut(call.this_ref).value = ObjectValue::WeakRef(target);
+    Ok(Interpreted::VOID)
+}
+
+/// The target, or `undefined` once it has been collected.
+fn weak_ref_proto_deref(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    let target = match heap.get(call.this_ref).value {
+        ObjectValue::WeakRef(target) => target,
+        _ => return Err(Exception::instance_required(call.this_ref, "WeakRef")),
+    };
+    if target == Heap::NULL {
+        return Ok(Interpreted::VOID);
+    }
+    heap.keep_during_job(target);
+    Ok(Interpreted::from(target))
+}
+
+// ==============================================
+// FinalizationRegistry
+
+pub static FINALIZATION_REGISTRY_CLASS: HostClass = HostClass {
+    name: "FinalizationRegistry",
+    constructor: finalization_registry_constructor,
+    methods: &[
+        ("register", finalization_registry_proto_register),
+        ("unregister", finalization_registry_proto_unregister),
+    ],
+    static_methods: &[],
+};
+
+fn finalization_registry_constructor(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+    require_new(&call, heap, "FinalizationRegistry")?;
+    let cleanup = call.arg_value(0, heap)?;
+    let cleanup = match cleanup.as_objref() {
+        Some(objref) if heap.get(objref).is_callable() => objref,
+        _ => return Err(Exception::type_error(TypeError::NOT_CALLABLE, cleanup)),
+    };
+    let registry = Registry {
+        cleanup,
+        cells: Vec::new(),
+    };
+    heap.get_mut(call.this_ref).value = ObjectValue::FinalizationRegistry(registry);
+    Ok(Interpreted::VOID)
+}
+
+fn this_registry(this_ref: JSRef, heap: &mut Heap) -> JSResult<&mut Registry> {
+    match &mut heap.get_mut(this_ref).value {
+        ObjectValue::FinalizationRegistry(registry) => Ok(registry),
+        _ => Err(Exception::instance_required(
+            this_ref,
+            "FinalizationRegistry",
+        )),
+    }
+}
+
+/// `register(target, heldValue, unregisterToken)`
+fn finalization_registry_proto_register(
+    call: CallContext,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let target = weak_target(&call.arg_value(0, heap)?, "register")?;
+    let held = call.arg_value(1, heap)?;
+    if held.as_objref() == Some(target) {
+        return Err(Exception::attr_type_error(
+            TypeError::WEAK_TARGET,
+            held,
+            "register",
+        ));
+    }
+    let token = call.arg_value(2, heap)?;
+    let token = match token.is_undefined() {
+        true => None,
+        false => Some(weak_target(&token, "register")?),
+    };
+    let cell = FinalizationCell {
+        target,
+        held,
+        token,
+    };
+    this_registry(call.this_ref, heap)?.cells.push(cell);
+    Ok(Interpreted::VOID)
+}
+
+/// `unregister(token)`: were there cells registered with it?
+fn finalization_registry_proto_unregister(
+    call: CallContext,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let token = weak_target(&call.arg_value(0, heap)?, "unregister")?;
+    let registry = this_registry(call.this_ref, heap)?;
+    let count = registry.cells.len();
+    registry.cells.retain(|cell| cell.token != Some(token));
+    Ok(Interpreted::from(registry.cells.len() != count))
+}
+
+pub fn init(heap: &mut Heap) -> JSResult<()> {
+    for class in [
+        &WEAK_MAP_CLASS,
+        &WEAK_SET_CLASS,
+        &WEAK_REF_CLASS,
+        &FINALIZATION_REGISTRY_CLASS,
+    ] {
+        let proto = heap.alloc(JSObject::new());
+        heap.init_class(proto, class)?;
+    }
+    Ok(())
+}
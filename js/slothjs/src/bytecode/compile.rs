@@ -0,0 +1,921 @@
+//! Compiles [`Program`]s and [`Function`]s to [`Code`].
+
+use crate::prelude::*;
+
+use crate::ast::*; // yes, EVERYTHING
+use crate::shape::PropertyCache;
+use crate::{Exception, JSResult, JSValue};
+
+use super::{Code, Op, Var};
+
+/// Compiles a [`Program`], including all the functions defined in it.
+pub fn compile(program: &Program) -> JSResult<Code> {
+    let mut compiler = Compiler::default();
+    compiler.code.layout = Rc::clone(&program.layout);
+    for var in program.variables.iter() {
+        let name = compiler.name(&var.0);
+        compiler.emit(Op::Declare(name));
+    }
+    for func in program.functions.iter() {
+        let name = compiler.name(&func.id.0);
+        compiler.emit(Op::Declare(name));
+    }
+    compiler.emit(Op::EnterProgram);
+    compiler.declare_functions(program.functions.iter())?;
+    compiler.block(&program.body, true)?;
+    compiler.emit(Op::GetCompletion);
+    compiler.emit(Op::Return);
+    compiler.finish()
+}
+
+/// Compiles a [`Function`], including all the functions defined in it.
+pub fn compile_function(function: &Function) -> JSResult<Code> {
+    let mut compiler = Compiler::default();
+    compiler.code.layout = Rc::clone(&function.layout);
+    compiler.emit(Op::Arguments);
+    compiler.emit(Op::SaveCaller);
+    compiler.emit(Op::DropArguments);
+    compiler.declare_functions(function.functions.iter())?;
+    compiler.block(&function.body, false)?;
+    compiler.emit(Op::Undefined);
+    compiler.emit(Op::Return);
+    compiler.finish()
+}
+
+/// A jump target: an index into `Compiler::labels` until the code is finished.
+#[derive(Clone, Copy)]
+struct Label(usize);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Breakable {
+    Loop,
+    ForIn,
+    Switch,
+    Label,
+}
+
+/// What the statement being compiled is nested in.
+/// Jumping out of it might require some cleanup.
+enum Control<'a> {
+    /// a target for `break`/`continue`
+    Breakable {
+        kind: Breakable,
+        labels: Vec<JSString>,
+        break_to: Label,
+        continue_to: Option<Label>,
+    },
+    /// an exception handler with its `finally` block, if any
+    Handler(Option<&'a BlockStatement>),
+    /// a `finally` block running with a pending exception
+    Pending,
+}
+
+#[derive(Default)]
+struct Compiler<'a> {
+    code: Code,
+    name_index: HashMap<JSString, u32>,
+    labels: Vec<Option<usize>>,
+    control: Vec<Control<'a>>,
+}
+
+impl<'a> Compiler<'a> {
+    fn finish(mut self) -> JSResult<Code> {
+        for op in self.code.ops.iter_mut() {
+            match op {
+                Op::Jump(target)
+                | Op::JumpIfFalse(target)
+                | Op::JumpIfTrue(target)
+                | Op::JumpIfFalseOrPop(target)
+                | Op::JumpIfTrueOrPop(target)
+                | Op::JumpIfNotNullishOrPop(target)
+                | Op::JumpIfNullish(target)
+                | Op::TryCatch(target)
+                | Op::TryFinally(target)
+                | Op::ForInNext(target) => {
+                    *target = self.labels[*target].expect("unbound label");
+                }
+                _ => (),
+            }
+        }
+        Ok(self.code)
+    }
+
+    fn emit(&mut self, op: Op) {
+        self.code.ops.push(op);
+    }
+
+    fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    fn bind(&mut self, label: Label) {
+        self.labels[label.0] = Some(self.code.ops.len());
+    }
+
+    /// Emits a jump-like `op` to be resolved to `label` in `finish()`.
+    fn jump(&mut self, op: fn(usize) -> Op, label: Label) {
+        self.emit(op(label.0));
+    }
+
+    fn name(&mut self, name: &JSString) -> u32 {
+        if let Some(&index) = self.name_index.get(name) {
+            return index;
+        }
+        let index = self.code.names.len() as u32;
+        self.code.names.push(name.clone());
+        self.name_index.insert(name.clone(), index);
+        index
+    }
+
+    /// A new [`PropertyCache`] for an access to property `name`, if it can be cached.
+    fn cache(&mut self, name: u32) -> Option<u32> {
+        if !PropertyCache::applies(&self.code.names[name as usize]) {
+            return None;
+        }
+        self.code.caches.push(PropertyCache::default());
+        Some((self.code.caches.len() - 1) as u32)
+    }
+
+    fn constant(&mut self, value: JSValue) -> u32 {
+        self.code.constants.push(value);
+        (self.code.constants.len() - 1) as u32
+    }
+
+    fn function(&mut self, function: &Rc<Function>) -> JSResult<u32> {
+        let code = compile_function(function)?;
+        (self.code.functions).push((Rc::clone(function), Rc::new(code)));
+        Ok((self.code.functions.len() - 1) as u32)
+    }
+
+    fn var(&mut self, name: &Identifier, slot: Slot) -> Var {
+        let name = self.name(&name.0);
+        Var { name, slot }
+    }
+
+    /// The operand of a variable reference: a resolved [`Variable`] or a bare [`Identifier`].
+    fn variable(&mut self, expr: &Expr) -> Option<Var> {
+        match expr {
+            Expr::Identifier(id) => Some(self.var(id, Slot::Dynamic)),
+            Expr::Variable(var) => Some(self.var(&var.name, var.slot)),
+            _ => None,
+        }
+    }
+
+    fn declare_functions(
+        &mut self,
+        functions: impl Iterator<Item = &'a FunctionDeclaration>,
+    ) -> JSResult<()> {
+        for func in functions {
+            let var = self.var(&func.id, func.slot);
+            let function = self.function(&func.function.func)?;
+            self.emit(Op::DeclareFunction { var, function });
+        }
+        Ok(())
+    }
+
+    fn set_completion_void(&mut self, completion: bool) {
+        if completion {
+            self.emit(Op::Undefined);
+            self.emit(Op::SetCompletion);
+        }
+    }
+
+    /// Compiles a jump out of all the `control` entries above `depth`:
+    /// pops handlers, runs `finally` blocks.
+    /// The operand stack is left alone when `returning`.
+    fn exit_to(&mut self, depth: usize, returning: bool) -> JSResult<()> {
+        for i in (depth..self.control.len()).rev() {
+            match &self.control[i] {
+                Control::Pending => self.emit(Op::DropPending),
+                Control::Handler(finalizer) => {
+                    let finalizer = *finalizer;
+                    self.emit(Op::PopHandler);
+                    if let Some(finalizer) = finalizer {
+                        let outer = self.control.split_off(i);
+                        self.block(finalizer, false)?;
+                        self.control.extend(outer);
+                    }
+                }
+                Control::Breakable { kind, .. } => match kind {
+                    Breakable::Switch if !returning => self.emit(Op::Pop),
+                    Breakable::ForIn => self.emit(Op::ForInEnd),
+                    _ => (),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the `control` entry that `break`/`continue` jumps to.
+    fn find_target(
+        &self,
+        label: &Option<Identifier>,
+        continuing: bool,
+    ) -> JSResult<(usize, Label)> {
+        for (i, control) in self.control.iter().enumerate().rev() {
+            let (kind, labels, break_to, continue_to) = match control {
+                Control::Breakable {
+                    kind,
+                    labels,
+                    break_to,
+                    continue_to,
+                } => (*kind, labels, *break_to, *continue_to),
+                _ => continue,
+            };
+            let matches = match label {
+                Some(label) => labels.contains(&label.0),
+                None if continuing => continue_to.is_some(),
+                None => kind != Breakable::Label,
+            };
+            if !matches {
+                continue;
+            }
+            return match (continuing, continue_to) {
+                (false, _) => Ok((i, break_to)),
+                (true, Some(continue_to)) => Ok((i, continue_to)),
+                (true, None) => break,
+            };
+        }
+        let label = label.clone().unwrap_or_else(|| Identifier::from(""));
+        Err(Exception::no_loop_for_continue_label(label))
+    }
+
+    /*
+     *  statements
+     */
+
+    /// Compiles `block`. In `completion` mode, every statement stores its value
+    /// as the completion value of the program.
+    fn block(&mut self, block: &'a BlockStatement, completion: bool) -> JSResult<()> {
+        self.set_completion_void(completion);
+        for &slot in block.slots.iter() {
+            self.emit(Op::ResetVar(slot));
+        }
+        for stmt in block.body.iter() {
+            self.statement(stmt, completion)?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &'a Statement, completion: bool) -> JSResult<()> {
+        let location = self.code.locations.len() as u32;
+        self.code.locations.push(stmt.loc.clone());
+        self.emit(Op::Statement(location));
+
+        match &stmt.stmt {
+            Stmt::Empty | Stmt::Function(_) => self.set_completion_void(completion),
+            Stmt::Expr(stmt) => {
+                self.expression(&stmt.expression)?;
+                self.emit(if completion {
+                    Op::SetCompletion
+                } else {
+                    Op::Pop
+                });
+            }
+            Stmt::Block(block) => self.block(block, completion)?,
+            Stmt::If(stmt) => self.if_statement(stmt, completion)?,
+            Stmt::Switch(stmt) => self.switch(stmt, Vec::new(), completion)?,
+            Stmt::For(stmt) => self.for_loop(stmt, Vec::new(), completion)?,
+            Stmt::ForIn(stmt) => self.for_in(stmt, Vec::new(), completion)?,
+            Stmt::Label(stmt) => self.labeled(stmt, completion)?,
+            Stmt::Break(BreakStatement(label)) => {
+                let (depth, target) = self.find_target(label, false)?;
+                self.exit_to(depth + 1, false)?;
+                self.jump(Op::Jump, target);
+            }
+            Stmt::Continue(ContinueStatement(label)) => {
+                let (depth, target) = self.find_target(label, true)?;
+                self.exit_to(depth + 1, false)?;
+                self.jump(Op::Jump, target);
+            }
+            Stmt::Return(ReturnStatement(argument)) => {
+                match argument {
+                    Some(argument) => self.expression(argument)?,
+                    None => self.emit(Op::Undefined),
+                }
+                self.exit_to(0, true)?;
+                self.emit(Op::Return);
+            }
+            Stmt::Throw(ThrowStatement(argument)) => {
+                self.expression(argument)?;
+                self.emit(Op::Throw);
+            }
+            Stmt::Try(stmt) => self.try_statement(stmt, completion)?,
+            Stmt::Variable(vardecl) => {
+                for decl in vardecl.declarations.iter() {
+                    if let Some(init) = decl.init.as_ref() {
+                        self.expression(init)?;
+                        let var = self.var(&decl.name, decl.slot);
+                        self.emit(Op::InitVar(var));
+                    }
+                }
+                self.set_completion_void(completion);
+            }
+        }
+        Ok(())
+    }
+
+    fn if_statement(&mut self, stmt: &'a IfStatement, completion: bool) -> JSResult<()> {
+        let otherwise = self.label();
+        let end = self.label();
+        self.expression(&stmt.test)?;
+        self.jump(Op::JumpIfFalse, otherwise);
+        self.statement(&stmt.consequent, completion)?;
+        self.jump(Op::Jump, end);
+        self.bind(otherwise);
+        match stmt.alternate.as_ref() {
+            Some(alternate) => self.statement(alternate, completion)?,
+            None => self.set_completion_void(completion),
+        }
+        self.bind(end);
+        Ok(())
+    }
+
+    fn switch(
+        &mut self,
+        stmt: &'a SwitchStatement,
+        labels: Vec<JSString>,
+        completion: bool,
+    ) -> JSResult<()> {
+        let end = self.label();
+        let bodies = (stmt.cases.iter())
+            .map(|_| self.label())
+            .collect::<Vec<_>>();
+
+        self.expression(&stmt.discriminant)?;
+        let mut default = None;
+        for (case, &body) in stmt.cases.iter().zip(bodies.iter()) {
+            match &case.test {
+                None => default = Some(body),
+                Some(test) => {
+                    self.emit(Op::Dup);
+                    self.expression(test)?;
+                    self.emit(Op::Binary(BinOp::EqEqEq));
+                    self.jump(Op::JumpIfTrue, body);
+                }
+            }
+        }
+        self.jump(Op::Jump, default.unwrap_or(end));
+
+        self.control.push(Control::Breakable {
+            kind: Breakable::Switch,
+            labels,
+            break_to: end,
+            continue_to: None,
+        });
+        for (case, &body) in stmt.cases.iter().zip(bodies.iter()) {
+            self.bind(body);
+            for stmt in case.consequent.iter() {
+                self.statement(stmt, false)?;
+            }
+        }
+        self.control.pop();
+
+        self.bind(end);
+        self.emit(Op::Pop);
+        self.set_completion_void(completion);
+        Ok(())
+    }
+
+    fn for_loop(
+        &mut self,
+        stmt: &'a ForStatement,
+        labels: Vec<JSString>,
+        completion: bool,
+    ) -> JSResult<()> {
+        let top = self.label();
+        let next = self.label();
+        let exit = self.label();
+        self.control.push(Control::Breakable {
+            kind: Breakable::Loop,
+            labels,
+            break_to: exit,
+            continue_to: Some(next),
+        });
+
+        // the body of a do-while loop is its init
+        self.statement(&stmt.init, false)?;
+        self.bind(top);
+        if let Some(test) = stmt.test.as_ref() {
+            self.expression(test)?;
+            self.jump(Op::JumpIfFalse, exit);
+        }
+        self.statement(&stmt.body, false)?;
+        self.bind(next);
+        if let Some(update) = stmt.update.as_ref() {
+            self.expression(update)?;
+            self.emit(Op::Pop);
+        }
+        self.jump(Op::Jump, top);
+
+        self.control.pop();
+        self.bind(exit);
+        self.set_completion_void(completion);
+        Ok(())
+    }
+
+    fn for_in(
+        &mut self,
+        stmt: &'a ForInStatement,
+        labels: Vec<JSString>,
+        completion: bool,
+    ) -> JSResult<()> {
+        let next = self.label();
+        let exit = self.label();
+
+        self.expression(&stmt.right)?;
+        self.emit(Op::ForInStart);
+        self.bind(next);
+        self.jump(Op::ForInNext, exit);
+        match &stmt.left {
+            ForInTarget::Expr(target) => self.assign_to(target, None)?,
+            ForInTarget::Var(vardecl) => {
+                debug_assert_eq!(vardecl.declarations.len(), 1);
+                let decl = &vardecl.declarations[0];
+                let var = self.var(&decl.name, decl.slot);
+                self.emit(Op::AssignVar { var, op: None });
+            }
+        }
+        self.emit(Op::Pop);
+
+        self.control.push(Control::Breakable {
+            kind: Breakable::ForIn,
+            labels,
+            break_to: exit,
+            continue_to: Some(next),
+        });
+        self.statement(&stmt.body, false)?;
+        self.control.pop();
+        self.jump(Op::Jump, next);
+
+        self.bind(exit);
+        self.emit(Op::ForInEnd);
+        self.set_completion_void(completion);
+        Ok(())
+    }
+
+    fn labeled(&mut self, stmt: &'a LabelStatement, completion: bool) -> JSResult<()> {
+        let LabelStatement(label, body) = stmt;
+        let mut body = body;
+        let mut labels = vec![label.0.clone()];
+        while let Stmt::Label(stmt) = &body.stmt {
+            let LabelStatement(label, inner) = stmt.as_ref();
+            labels.push(label.0.clone());
+            body = inner;
+        }
+
+        match &body.stmt {
+            Stmt::For(stmt) => self.for_loop(stmt, labels, completion),
+            Stmt::ForIn(stmt) => self.for_in(stmt, labels, completion),
+            Stmt::Switch(stmt) => self.switch(stmt, labels, completion),
+            _ => {
+                let exit = self.label();
+                self.control.push(Control::Breakable {
+                    kind: Breakable::Label,
+                    labels,
+                    break_to: exit,
+                    continue_to: None,
+                });
+                self.statement(body, completion)?;
+                self.control.pop();
+                if completion {
+                    // `break label` makes the completion value undefined
+                    let end = self.label();
+                    self.jump(Op::Jump, end);
+                    self.bind(exit);
+                    self.set_completion_void(completion);
+                    self.bind(end);
+                } else {
+                    self.bind(exit);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn try_statement(&mut self, stmt: &'a TryStatement, completion: bool) -> JSResult<()> {
+        let finalizer = stmt.finalizer.as_ref();
+        let catch = self.label();
+        let rethrow = self.label();
+        let end = self.label();
+
+        match stmt.handler {
+            Some(_) => self.jump(Op::TryCatch, catch),
+            None => self.jump(Op::TryFinally, rethrow),
+        }
+        self.control.push(Control::Handler(finalizer));
+        self.block(&stmt.block, completion)?;
+        self.control.pop();
+        self.emit(Op::PopHandler);
+        if let Some(finalizer) = finalizer {
+            self.block(finalizer, false)?;
+        }
+        self.jump(Op::Jump, end);
+
+        if let Some(handler) = stmt.handler.as_ref() {
+            // the caught value is on the stack
+            self.bind(catch);
+            if finalizer.is_some() {
+                self.jump(Op::TryFinally, rethrow);
+                self.control.push(Control::Handler(finalizer));
+            }
+            self.emit(Op::ResetVar(handler.slot));
+            let param = self.var(&handler.param, handler.slot);
+            self.emit(Op::InitVar(param));
+            self.block(&handler.body, completion)?;
+            if let Some(finalizer) = finalizer {
+                self.control.pop();
+                self.emit(Op::PopHandler);
+                self.block(finalizer, false)?;
+            }
+            self.jump(Op::Jump, end);
+        }
+
+        if let Some(finalizer) = finalizer {
+            // the exception is pending
+            self.bind(rethrow);
+            self.control.push(Control::Pending);
+            self.block(finalizer, false)?;
+            self.control.pop();
+            self.emit(Op::Rethrow);
+        }
+
+        self.bind(end);
+        Ok(())
+    }
+
+    /*
+     *  expressions
+     */
+
+    /// Compiles `expr` to push exactly one value.
+    fn expression(&mut self, expr: &'a Expression) -> JSResult<()> {
+        match &expr.expr {
+            Expr::Literal(lit) => {
+                let index = self.constant(lit.to_value());
+                self.emit(Op::Const(index));
+            }
+            Expr::Identifier(_) | Expr::Variable(_) => {
+                let var = self.variable(&expr.expr).unwrap();
+                self.emit(Op::GetVar(var));
+            }
+            Expr::This => self.emit(Op::This),
+            Expr::BinaryOp(binexpr) => {
+                let BinaryExpression(left, op, right) = binexpr.as_ref();
+                self.expression(left)?;
+                self.expression(right)?;
+                self.emit(Op::Binary(*op));
+            }
+            Expr::LogicalOp(logexpr) => {
+                let LogicalExpression(left, op, right) = logexpr.as_ref();
+                let end = self.label();
+                self.expression(left)?;
+                self.jump(short_circuit(*op), end);
+                self.expression(right)?;
+                self.bind(end);
+            }
+            Expr::Conditional(condexpr) => {
+                let otherwise = self.label();
+                let end = self.label();
+                self.expression(&condexpr.condexpr)?;
+                self.jump(Op::JumpIfFalse, otherwise);
+                self.expression(&condexpr.thenexpr)?;
+                self.jump(Op::Jump, end);
+                self.bind(otherwise);
+                self.expression(&condexpr.elseexpr)?;
+                self.bind(end);
+            }
+            Expr::Sequence(SequenceExpression(exprs)) => {
+                if exprs.is_empty() {
+                    self.emit(Op::Undefined);
+                }
+                for (i, expr) in exprs.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(Op::Pop);
+                    }
+                    self.expression(expr)?;
+                }
+            }
+            Expr::Member(membexpr) => match self.member(membexpr)? {
+                Some(name) => {
+                    let cache = self.cache(name);
+                    self.emit(Op::GetProp { name, cache });
+                }
+                None => self.emit(Op::GetElem),
+            },
+            Expr::Chain(chain) => self.chain(chain, false)?,
+            Expr::Assign(assign) => {
+                let AssignmentExpression(target, op, value) = assign.as_ref();
+                self.expression(value)?;
+                self.assign_to(target, *op)?;
+            }
+            Expr::LogicalAssign(assign) => self.logical_assign(assign)?,
+            Expr::Unary(unary) => self.unary(unary)?,
+            Expr::Update(update) => {
+                let UpdateExpression(op, prefix, target) = update.as_ref();
+                let (op, prefix) = (*op, *prefix);
+                match &target.expr {
+                    Expr::Identifier(_) | Expr::Variable(_) => {
+                        let var = self.variable(&target.expr).unwrap();
+                        self.emit(Op::UpdateVar { var, op, prefix });
+                    }
+                    Expr::Member(membexpr) => match self.member(membexpr)? {
+                        Some(name) => self.emit(Op::UpdateProp { name, op, prefix }),
+                        None => self.emit(Op::UpdateElem { op, prefix }),
+                    },
+                    _ => {
+                        self.expression(target)?;
+                        self.emit(Op::NotAssignable);
+                    }
+                }
+            }
+            Expr::Object(ObjectExpression(properties)) => {
+                self.emit(Op::Object);
+                for property in properties.iter() {
+                    let (value, kind) = (&property.value, &property.kind);
+                    match &property.key {
+                        ObjectKey::Identifier(key) => {
+                            let name = self.name(key);
+                            self.expression(value)?;
+                            self.emit(Op::InitProp(name, *kind));
+                        }
+                        ObjectKey::Computed(key) => {
+                            self.expression(key)?;
+                            self.emit(Op::ToKey);
+                            self.expression(value)?;
+                            self.emit(Op::InitElem(*kind));
+                        }
+                    }
+                }
+            }
+            Expr::Array(ArrayExpression(exprs)) => {
+                for expr in exprs.iter() {
+                    match expr {
+                        Some(expr) => self.expression(expr)?,
+                        None => self.emit(Op::Undefined),
+                    }
+                }
+                self.emit(Op::Array(exprs.len() as u32));
+                for (index, _) in exprs.iter().enumerate().filter(|(_, e)| e.is_none()) {
+                    self.emit(Op::ArrayHole(index as u32));
+                }
+            }
+            Expr::Function(funcexpr) => {
+                let function = self.function(&funcexpr.func)?;
+                self.emit(Op::Closure(function));
+            }
+            Expr::Call(callexpr) => {
+                let CallExpression(callee, arguments) = callexpr.as_ref();
+                if let Expr::Chain(chain) = &callee.expr {
+                    // `(o?.f)()` calls `f` on `o`, like `o.f()`
+                    self.chain(chain, true)?;
+                    for argument in arguments.iter() {
+                        self.expression(argument)?;
+                    }
+                    let argc = arguments.len() as u32;
+                    self.emit(Op::CallWithThis { argc });
+                    return Ok(());
+                }
+                for argument in arguments.iter() {
+                    self.expression(argument)?;
+                }
+                let argc = arguments.len() as u32;
+                match &callee.expr {
+                    Expr::Identifier(_) | Expr::Variable(_) => {
+                        let var = self.variable(&callee.expr).unwrap();
+                        self.emit(Op::CallVar { var, argc });
+                    }
+                    Expr::Member(membexpr) => match self.member(membexpr)? {
+                        Some(name) => {
+                            let cache = self.cache(name);
+                            self.emit(Op::CallProp { name, argc, cache });
+                        }
+                        None => self.emit(Op::CallElem { argc }),
+                    },
+                    _ => {
+                        self.expression(callee)?;
+                        self.emit(Op::CallValue { argc });
+                    }
+                }
+            }
+            Expr::New(newexpr) => {
+                let NewExpression(callee, arguments) = newexpr.as_ref();
+                for argument in arguments.iter() {
+                    self.expression(argument)?;
+                }
+                self.expression(callee)?;
+                let argc = arguments.len() as u32;
+                self.emit(Op::New { argc });
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes the parts of a member reference: a computed key (if any), then the object.
+    /// Returns the name of a non-computed property.
+    fn member(&mut self, membexpr: &'a MemberExpression) -> JSResult<Option<u32>> {
+        let MemberExpression(object, property, computed, _) = membexpr;
+        let name = if *computed {
+            self.expression(property)?;
+            self.emit(Op::ToKey);
+            None
+        } else {
+            match &property.expr {
+                Expr::Identifier(name) => Some(self.name(&name.0)),
+                _ => panic!("Member(computed=false) property is not an identifier"),
+            }
+        };
+        self.expression(object)?;
+        self.emit(Op::ToObject);
+        Ok(name)
+    }
+
+    /// Assigns the value on the stack to `target`, leaving the assigned value on the stack.
+    fn assign_to(&mut self, target: &'a Expression, op: Option<BinOp>) -> JSResult<()> {
+        match &target.expr {
+            Expr::Identifier(_) | Expr::Variable(_) => {
+                let var = self.variable(&target.expr).unwrap();
+                self.emit(Op::AssignVar { var, op });
+            }
+            Expr::Member(membexpr) => match self.member(membexpr)? {
+                Some(name) => {
+                    let cache = self.cache(name);
+                    self.emit(Op::AssignProp { name, op, cache });
+                }
+                None => self.emit(Op::AssignElem { op }),
+            },
+            _ => {
+                self.expression(target)?;
+                self.emit(Op::NotAssignable);
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps `[this, function]` on the stack before a call link, the value otherwise.
+    /// A short-circuit drops them and pushes `undefined`.
+    /// A `callee` chain leaves `[this, function]` for a call after it.
+    fn chain(&mut self, chain: &'a ChainExpression, callee: bool) -> JSResult<()> {
+        let ChainExpression(object, links) = chain;
+        let short_two = self.label();
+        let short_one = self.label();
+        let end = self.label();
+
+        self.expression(object)?;
+        for (i, link) in links.iter().enumerate() {
+            let before_call = match links.get(i + 1) {
+                Some(next) => matches!(next.kind, LinkKind::Call(_)),
+                None => callee,
+            };
+            match &link.kind {
+                LinkKind::Property(property) => {
+                    if link.optional {
+                        self.jump(Op::JumpIfNullish, short_one);
+                    }
+                    self.emit(Op::ToObject);
+                    if before_call {
+                        self.emit(Op::Dup);
+                    }
+                    let name = self.name(&property.0);
+                    self.emit(Op::GetProp { name, cache: None });
+                }
+                LinkKind::Computed(key) => {
+                    if link.optional {
+                        self.jump(Op::JumpIfNullish, short_one);
+                    }
+                    self.emit(Op::ToObject);
+                    if before_call {
+                        self.emit(Op::Dup);
+                    }
+                    self.expression(key)?;
+                    self.emit(Op::ToKey);
+                    self.emit(Op::Insert(1));
+                    self.emit(Op::GetElem);
+                }
+                LinkKind::Call(arguments) => {
+                    let method = i > 0 && !matches!(links[i - 1].kind, LinkKind::Call(_));
+                    if link.optional {
+                        let short = if method { short_two } else { short_one };
+                        self.jump(Op::JumpIfNullish, short);
+                    }
+                    if !method {
+                        self.emit(Op::Undefined);
+                        self.emit(Op::Insert(1));
+                    }
+                    for argument in arguments.iter() {
+                        self.expression(argument)?;
+                    }
+                    let argc = arguments.len() as u32;
+                    self.emit(Op::CallWithThis { argc });
+                }
+            }
+        }
+        if callee && matches!(links.last().map(|link| &link.kind), Some(LinkKind::Call(_))) {
+            self.emit(Op::Undefined);
+            self.emit(Op::Insert(1));
+        }
+        self.jump(Op::Jump, end);
+        self.bind(short_two);
+        self.emit(Op::Pop);
+        self.bind(short_one);
+        self.emit(Op::Pop);
+        self.emit(Op::Undefined);
+        if callee {
+            self.emit(Op::Undefined);
+        }
+        self.bind(end);
+        Ok(())
+    }
+
+    /// Reads the target once, assigns to it only if the operator does not short-circuit.
+    fn logical_assign(&mut self, assign: &'a LogicalAssignmentExpression) -> JSResult<()> {
+        let LogicalAssignmentExpression(target, op, value) = assign;
+        let end = self.label();
+        match &target.expr {
+            Expr::Identifier(_) | Expr::Variable(_) => {
+                let var = self.variable(&target.expr).unwrap();
+                self.emit(Op::GetVar(var));
+                self.jump(short_circuit(*op), end);
+                self.expression(value)?;
+                self.emit(Op::AssignVar { var, op: None });
+            }
+            Expr::Member(membexpr) => {
+                // the reference stays under the old value
+                let name = self.member(membexpr)?;
+                let size = match name {
+                    Some(name) => {
+                        self.emit(Op::Dup);
+                        let cache = self.cache(name);
+                        self.emit(Op::GetProp { name, cache });
+                        1
+                    }
+                    None => {
+                        self.emit(Op::Dup2);
+                        self.emit(Op::GetElem);
+                        2
+                    }
+                };
+                let skip = self.label();
+                self.jump(short_circuit(*op), skip);
+                self.expression(value)?;
+                self.emit(Op::Insert(size));
+                match name {
+                    Some(name) => {
+                        let cache = self.cache(name);
+                        self.emit(Op::AssignProp {
+                            name,
+                            op: None,
+                            cache,
+                        });
+                    }
+                    None => self.emit(Op::AssignElem { op: None }),
+                }
+                self.jump(Op::Jump, end);
+                self.bind(skip);
+                self.emit(Op::Insert(size));
+                for _ in 0..size {
+                    self.emit(Op::Pop);
+                }
+            }
+            _ => {
+                self.expression(target)?;
+                self.emit(Op::NotAssignable);
+            }
+        }
+        self.bind(end);
+        Ok(())
+    }
+
+    fn unary(&mut self, unary: &'a UnaryExpression) -> JSResult<()> {
+        let UnaryExpression(op, argument) = unary;
+        match (op, &argument.expr) {
+            (UnOp::Typeof, Expr::Identifier(_) | Expr::Variable(_)) => {
+                let var = self.variable(&argument.expr).unwrap();
+                self.emit(Op::TypeofVar(var));
+            }
+            (UnOp::Delete, Expr::Identifier(_) | Expr::Variable(_)) => {
+                let var = self.variable(&argument.expr).unwrap();
+                self.emit(Op::DeleteVar(var));
+            }
+            (UnOp::Delete, Expr::Member(membexpr)) => match self.member(membexpr)? {
+                Some(name) => self.emit(Op::DeleteProp(name)),
+                None => self.emit(Op::DeleteElem),
+            },
+            _ => {
+                self.expression(argument)?;
+                self.emit(Op::Unary(*op));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The jump of `left op right` over `right`: it keeps `left` or pops it.
+fn short_circuit(op: BoolOp) -> fn(usize) -> Op {
+    match op {
+        BoolOp::And => Op::JumpIfFalseOrPop,
+        BoolOp::Or => Op::JumpIfTrueOrPop,
+        BoolOp::Nullish => Op::JumpIfNotNullishOrPop,
+    }
+}
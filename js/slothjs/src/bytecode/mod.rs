@@ -0,0 +1,284 @@
+//! An alternative execution backend: compiles [`Program`]s to bytecode and runs it on
+//! a stack machine.
+//!
+//! [`compile`] turns a [`Program`] or an [`ast::Function`] into [`Code`]: a flat vector of
+//! [`Op`]s with resolved jumps, plus tables of constants, names and nested functions.
+//! `break`/`continue`/`return` become jumps (with `finally` blocks inlined on the way),
+//! exceptions are dispatched through a stack of handlers.
+//!
+//! The machine shares the object model with the tree walker: variables live in the same
+//! heap frames and are accessed by their resolved [`Slot`]s, closures capture the same
+//! cells (see [`crate::resolve`]). Calls between
+//! bytecode closures push a frame instead of recursing on the Rust stack,
+//! so deep JavaScript recursion only grows the heap. A call in tail position of strict
+//! mode code replaces the frame of its caller.
+//!
+//! Select the backend with [`Heap::set_backend()`]; the tree walker is the default.
+
+mod compile;
+mod vm;
+
+use crate::prelude::*;
+
+use crate::ast::{self, BinOp, Program, PropertyKind, Slot, UnOp, UpdOp};
+use crate::shape::PropertyCache;
+use crate::{source, CallContext, Heap, Interpreted, JSRef, JSResult, JSValue};
+
+pub use self::compile::{compile, compile_function};
+
+/// How [`Heap::run()`] executes programs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Walk the AST with [`crate::Interpretable`]
+    #[default]
+    TreeWalker,
+    /// Compile to [`Code`] and run it on the bytecode machine
+    Bytecode,
+}
+
+/// A variable operand: its name in [`Code`] and its resolved [`Slot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Var {
+    pub name: u32,
+    pub slot: Slot,
+}
+
+/// A bytecode instruction.
+///
+/// Operands are indexes into the tables of [`Code`] (`names`, `constants`, `functions`),
+/// variables or jump targets (instruction indexes). Property references take their object
+/// from the top of the stack; a computed key (a string or an array index) is just below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    // the operand stack
+    Undefined,
+    Const(u32),
+    Pop,
+    Dup,
+    /// duplicates the two values on top, e.g. a computed property reference
+    Dup2,
+    /// moves the value on top under the `n` values below it
+    Insert(u32),
+    This,
+
+    // the completion value of a program
+    SetCompletion,
+    GetCompletion,
+
+    // control flow
+    Jump(usize),
+    JumpIfFalse(usize),
+    JumpIfTrue(usize),
+    /// jump keeping the value if it is falsy, pop it otherwise (`&&`)
+    JumpIfFalseOrPop(usize),
+    /// jump keeping the value if it is truthy, pop it otherwise (`||`)
+    JumpIfTrueOrPop(usize),
+    /// jump keeping the value if it is not `null`/`undefined`, pop it otherwise (`??`)
+    JumpIfNotNullishOrPop(usize),
+    /// jump keeping the value if it is `null`/`undefined` (`?.`)
+    JumpIfNullish(usize),
+    /// the start of a statement at `locations[i]`, a safe point for garbage collection
+    Statement(u32),
+
+    // scopes and variables
+    /// declares a variable by name in the current scope
+    Declare(u32),
+    /// enters the frame of the program
+    EnterProgram,
+    DeclareFunction {
+        var: Var,
+        function: u32,
+    },
+    /// binds `arguments` and the parameters to the arguments of the frame
+    Arguments,
+    SaveCaller,
+    DropArguments,
+    /// gives a `let` binding a fresh value on entering its block
+    ResetVar(Slot),
+    GetVar(Var),
+    TypeofVar(Var),
+    /// pops a value into a variable
+    InitVar(Var),
+    AssignVar {
+        var: Var,
+        op: Option<BinOp>,
+    },
+    UpdateVar {
+        var: Var,
+        op: UpdOp,
+        prefix: bool,
+    },
+    DeleteVar(Var),
+
+    // properties
+    /// makes a computed property key: an array index stays a number, anything else a string
+    ToKey,
+    /// turns a value into an object reference for member access
+    ToObject,
+    /// `cache` is the index of the [`PropertyCache`] of the site, if it has one
+    GetProp {
+        name: u32,
+        cache: Option<u32>,
+    },
+    GetElem,
+    AssignProp {
+        name: u32,
+        op: Option<BinOp>,
+        cache: Option<u32>,
+    },
+    AssignElem {
+        op: Option<BinOp>,
+    },
+    UpdateProp {
+        name: u32,
+        op: UpdOp,
+        prefix: bool,
+    },
+    UpdateElem {
+        op: UpdOp,
+        prefix: bool,
+    },
+    DeleteProp(u32),
+    DeleteElem,
+    /// throws a TypeError for an assignment to a value
+    NotAssignable,
+
+    // operators and literals
+    Unary(UnOp),
+    Binary(BinOp),
+    Object,
+    /// defines a property of the object literal under the value on top
+    InitProp(u32, PropertyKind),
+    /// the same with a computed key between them
+    InitElem(PropertyKind),
+    Array(u32),
+    /// makes a hole at the index of the array on top of the stack
+    ArrayHole(u32),
+    Closure(u32),
+
+    // calls
+    CallVar {
+        var: Var,
+        argc: u32,
+    },
+    CallProp {
+        name: u32,
+        argc: u32,
+        cache: Option<u32>,
+    },
+    CallElem {
+        argc: u32,
+    },
+    CallValue {
+        argc: u32,
+    },
+    /// calls a function under its `argc` arguments with `this` under it
+    /// (the global object if `undefined`)
+    CallWithThis {
+        argc: u32,
+    },
+    New {
+        argc: u32,
+    },
+    Return,
+
+    // exceptions
+    Throw,
+    /// installs a handler that pushes the caught value and jumps
+    TryCatch(usize),
+    /// installs a handler that saves the exception for `Rethrow` and jumps
+    TryFinally(usize),
+    PopHandler,
+    Rethrow,
+    DropPending,
+
+    // for-in loops
+    ForInStart,
+    /// pushes the next key or jumps when there are no more keys
+    ForInNext(usize),
+    ForInEnd,
+}
+
+/// A compiled [`Program`] or [`ast::Function`].
+#[derive(Debug, Default)]
+pub struct Code {
+    pub(crate) layout: Rc<ast::Layout>,
+    pub(crate) ops: Vec<Op>,
+    pub(crate) constants: Vec<JSValue>,
+    pub(crate) names: Vec<JSString>,
+    pub(crate) functions: Vec<(Rc<ast::Function>, Rc<Code>)>,
+    pub(crate) locations: Vec<Option<Box<source::Location>>>,
+    pub(crate) caches: Vec<PropertyCache>,
+}
+
+impl Code {
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+}
+
+/// Disassembly, e.g. for debugging.
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (pc, op) in self.ops.iter().enumerate() {
+            write!(f, "{:>5}  {:?}", pc, op)?;
+            match op {
+                Op::Const(i) => write!(f, "\t; {:?}", self.constants[*i as usize])?,
+                Op::Declare(i)
+                | Op::GetVar(Var { name: i, .. })
+                | Op::TypeofVar(Var { name: i, .. })
+                | Op::InitVar(Var { name: i, .. })
+                | Op::DeleteVar(Var { name: i, .. })
+                | Op::GetProp { name: i, .. }
+                | Op::DeleteProp(i)
+                | Op::InitProp(i, _)
+                | Op::DeclareFunction {
+                    var: Var { name: i, .. },
+                    ..
+                }
+                | Op::AssignVar {
+                    var: Var { name: i, .. },
+                    ..
+                }
+                | Op::UpdateVar {
+                    var: Var { name: i, .. },
+                    ..
+                }
+                | Op::AssignProp { name: i, .. }
+                | Op::UpdateProp { name: i, .. }
+                | Op::CallVar {
+                    var: Var { name: i, .. },
+                    ..
+                }
+                | Op::CallProp { name: i, .. } => write!(f, "\t; {}", self.names[*i as usize])?,
+                _ => (),
+            }
+            writeln!(f)?;
+        }
+        for (i, (function, code)) in self.functions.iter().enumerate() {
+            let name = function.id.as_ref().map(|id| id.as_str()).unwrap_or("");
+            writeln!(f, "\nfunction #{} {}:", i, name)?;
+            write!(f, "{}", code)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compiles and runs `program` in the current scope.
+pub fn run(program: &Program, heap: &mut Heap) -> JSResult<Interpreted> {
+    let code = compile(program)?;
+    let value = vm::Machine::new().run_program(Rc::new(code), heap)?;
+    Ok(Interpreted::from(value))
+}
+
+/// Calls a closure compiled to `code`.
+pub(crate) fn call(
+    code: &Rc<Code>,
+    captured: &Rc<[JSRef]>,
+    call: CallContext,
+    heap: &mut Heap,
+) -> JSResult<Interpreted> {
+    let captured = Rc::clone(captured);
+    let value = vm::Machine::new().run_closure(Rc::clone(code), captured, call, heap)?;
+    Ok(Interpreted::from(value))
+}
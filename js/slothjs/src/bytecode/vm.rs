@@ -0,0 +1,936 @@
+//! The bytecode machine: runs [`Code`] on an operand stack with a stack of frames.
+
+use crate::prelude::*;
+
+use crate::ast::{BinOp, BoolOp, CatchClause, Identifier, UpdOp};
+use crate::error::TypeError;
+use crate::function::{this_from_ref, Closure};
+use crate::interpret::member;
+use crate::object::ObjectValue;
+use crate::{
+    source, CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult, JSValue,
+};
+
+use super::{Code, Op, Var};
+
+/// An activation of [`Code`].
+struct Frame {
+    code: Rc<Code>,
+    pc: usize,
+    /// where the operand stack of the frame starts, its arguments come first
+    base: usize,
+    argc: usize,
+    /// the scope to restore on return
+    saved_scope: JSRef,
+    /// where the handlers and iterators of the frame start
+    handlers: usize,
+    iterators: usize,
+    /// the object being made by `new`
+    constructed: Option<JSRef>,
+    completion: JSValue,
+    /// exceptions waiting for their `finally` blocks
+    pending: Vec<Exception>,
+    caller: Option<Box<source::Location>>,
+}
+
+/// An exception handler installed by `Op::TryCatch`/`Op::TryFinally`.
+struct Handler {
+    target: usize,
+    catch: bool,
+    stack: usize,
+    iterators: usize,
+    scope: JSRef,
+}
+
+/// The state of a `for-in` loop: the keys of `objref` yet to visit.
+struct ForIn {
+    objref: JSRef,
+    keys: Vec<JSString>,
+    visited: HashSet<JSString>,
+}
+
+impl ForIn {
+    fn new(objref: JSRef, heap: &mut Heap) -> JSResult<ForIn> {
+        let mut it = ForIn {
+            objref,
+            keys: Vec::new(),
+            visited: HashSet::new(),
+        };
+        it.load_keys(heap)?;
+        Ok(it)
+    }
+
+    fn load_keys(&mut self, heap: &mut Heap) -> JSResult<()> {
+        if self.objref == Heap::NULL {
+            return Ok(());
+        }
+        self.keys = heap.own_keys(self.objref)?;
+        // TODO: strings iteration
+        self.keys.reverse();
+        Ok(())
+    }
+
+    fn next_key(&mut self, heap: &mut Heap) -> JSResult<Option<JSValue>> {
+        while self.objref != Heap::NULL {
+            let propname = match self.keys.pop() {
+                Some(propname) => propname,
+                None => {
+                    self.objref = heap.prototype_of(self.objref)?;
+                    self.load_keys(heap)?;
+                    continue;
+                }
+            };
+            if !self.visited.insert(propname.clone()) {
+                continue;
+            }
+
+            match heap.own_property(self.objref, &propname)? {
+                Some(p) if p.access.enumerable() => (),
+                Some(_) => continue, // not enumerable, skip
+                None => continue,    // the property has disappeared!
+            };
+
+            return Ok(Some(match propname.parse::<usize>() {
+                Ok(p) => JSValue::from(p as f64),
+                _ => JSValue::from(propname.as_str()),
+            }));
+        }
+        Ok(None)
+    }
+}
+
+fn is_object(value: &JSValue) -> bool {
+    value.as_objref().is_some_and(|r| r != Heap::NULL)
+}
+
+pub(crate) struct Machine {
+    stack: Vec<JSValue>,
+    frames: Vec<Frame>,
+    handlers: Vec<Handler>,
+    iterators: Vec<ForIn>,
+}
+
+impl Machine {
+    pub(crate) fn new() -> Machine {
+        Machine {
+            stack: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
+            iterators: Vec::new(),
+        }
+    }
+
+    /// Runs `code` of a program in the current scope.
+    pub(crate) fn run_program(&mut self, code: Rc<Code>, heap: &mut Heap) -> JSResult<JSValue> {
+        let saved_scope = heap.scope_ref();
+        self.push_frame(code, 0, saved_scope, None, None);
+        self.run(heap)
+    }
+
+    /// Runs `code` of a closure called from the host.
+    pub(crate) fn run_closure(
+        &mut self,
+        code: Rc<Code>,
+        captured: Rc<[JSRef]>,
+        call: CallContext,
+        heap: &mut Heap,
+    ) -> JSResult<JSValue> {
+        for argument in call.arguments.iter() {
+            let value = argument.to_value(heap)?;
+            self.stack.push(value);
+        }
+        let argc = call.arguments.len();
+        self.enter(code, captured, call.this, argc, None, call.loc, heap);
+        self.run(heap)
+    }
+
+    fn push_frame(
+        &mut self,
+        code: Rc<Code>,
+        argc: usize,
+        saved_scope: JSRef,
+        constructed: Option<JSRef>,
+        caller: Option<Box<source::Location>>,
+    ) {
+        self.frames.push(Frame {
+            code,
+            pc: 0,
+            base: self.stack.len() - argc,
+            argc,
+            saved_scope,
+            handlers: self.handlers.len(),
+            iterators: self.iterators.len(),
+            constructed,
+            completion: JSValue::UNDEFINED,
+            pending: Vec::new(),
+            caller,
+        });
+    }
+
+    /// Starts a call of a bytecode closure with `argc` arguments on the stack.
+    #[allow(clippy::too_many_arguments)]
+    fn enter(
+        &mut self,
+        code: Rc<Code>,
+        captured: Rc<[JSRef]>,
+        this: JSValue,
+        argc: usize,
+        constructed: Option<JSRef>,
+        caller: Option<Box<source::Location>>,
+        heap: &mut Heap,
+    ) {
+        let saved_scope = heap.scope_ref();
+        let this = heap.bind_this(&code.layout, this);
+        heap.push_frame(&code.layout, this, captured, Heap::GLOBAL);
+        self.push_frame(code, argc, saved_scope, constructed, caller);
+    }
+
+    /// Ends the current call, gives the result if it was the outermost one.
+    fn leave(&mut self, heap: &mut Heap) -> JSResult<Option<JSValue>> {
+        let value = self.pop();
+        let frame = self.frames.pop().expect("no frame to return from");
+        heap.set_local_scope(frame.saved_scope);
+        self.stack.truncate(frame.base);
+        self.handlers.truncate(frame.handlers);
+        self.iterators.truncate(frame.iterators);
+
+        let value = match frame.constructed {
+            Some(objref) if !is_object(&value) => JSValue::from(objref),
+            _ => value,
+        };
+        if self.frames.is_empty() {
+            return Ok(Some(value));
+        }
+        heap.leave_vm_call();
+        self.stack.push(value);
+        Ok(None)
+    }
+
+    fn run(&mut self, heap: &mut Heap) -> JSResult<JSValue> {
+        loop {
+            match self.execute(heap) {
+                Ok(value) => return Ok(value),
+                Err(exc) => self.unwind(exc, heap)?,
+            }
+        }
+    }
+
+    /// Transfers control to the innermost handler of `exc`, unwinding frames on the way.
+    /// Gives `exc` back if there is none.
+    fn unwind(&mut self, mut exc: Exception, heap: &mut Heap) -> JSResult<()> {
+        loop {
+            let frame = match self.frames.last_mut() {
+                Some(frame) => frame,
+                None => return Err(exc),
+            };
+            if self.handlers.len() > frame.handlers {
+                let handler = self.handlers.pop().unwrap();
+                if !exc.is_catchable() {
+                    continue;
+                }
+                self.stack.truncate(handler.stack);
+                self.iterators.truncate(handler.iterators);
+                heap.set_local_scope(handler.scope);
+                frame.pc = handler.target;
+                if !handler.catch {
+                    frame.pending.push(exc);
+                    return Ok(());
+                }
+                match CatchClause::caught_value(&exc, heap) {
+                    Ok(value) => {
+                        self.stack.push(value);
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        exc = e;
+                        continue;
+                    }
+                }
+            }
+
+            let frame = self.frames.pop().unwrap();
+            heap.set_local_scope(frame.saved_scope);
+            self.stack.truncate(frame.base);
+            self.iterators.truncate(frame.iterators);
+            if !self.frames.is_empty() {
+                heap.leave_vm_call();
+            }
+        }
+    }
+
+    /// Everything the machine keeps alive outside of the heap.
+    fn roots(&self) -> Vec<JSRef> {
+        let mut roots = Vec::new();
+        let mut add = |value: &JSValue| {
+            if let Some(objref) = value.as_objref() {
+                roots.push(objref);
+            }
+        };
+        self.stack.iter().for_each(&mut add);
+        for frame in self.frames.iter() {
+            add(&frame.completion);
+            if let Some(objref) = frame.constructed {
+                add(&JSValue::from(objref));
+            }
+            for exc in frame.pending.iter() {
+                if let Exception::UserThrown(value) = exc {
+                    add(value);
+                }
+            }
+        }
+        for it in self.iterators.iter() {
+            add(&JSValue::from(it.objref));
+        }
+        roots
+    }
+
+    fn frame(&self) -> &Frame {
+        self.frames.last().expect("no frame")
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("no frame")
+    }
+
+    fn name(&self, index: u32) -> JSString {
+        self.frame().code.names[index as usize].clone()
+    }
+
+    /// Gets `objref.name`, through the cache of the site if it has one.
+    fn get_prop(
+        &self,
+        objref: JSRef,
+        name: u32,
+        cache: Option<u32>,
+        heap: &mut Heap,
+    ) -> JSResult<JSValue> {
+        let code = &self.frame().code;
+        let name = &code.names[name as usize];
+        match cache {
+            Some(cache) => code.caches[cache as usize].get(objref, name, heap),
+            None => member(objref, name.clone(), heap).to_value(heap),
+        }
+    }
+
+    fn variable(&self, var: Var, heap: &Heap) -> Interpreted {
+        let name = &self.frame().code.names[var.name as usize];
+        heap.variable(name, var.slot)
+    }
+
+    fn assignable_variable(&self, var: Var, heap: &Heap) -> JSResult<Interpreted> {
+        let name = &self.frame().code.names[var.name as usize];
+        heap.assignable_variable(name, var.slot)
+    }
+
+    fn pop(&mut self) -> JSValue {
+        self.stack.pop().expect("stack underflow")
+    }
+
+    fn pop_ref(&mut self) -> JSRef {
+        let value = self.pop();
+        match value.as_objref() {
+            Some(objref) => objref,
+            None => panic!("expected an object, got {:?}", value),
+        }
+    }
+
+    /// Pops a key from [`Op::ToKey`] as a string.
+    fn pop_key(&mut self) -> JSString {
+        let value = self.pop();
+        if let Some(key) = value.as_string() {
+            return key;
+        }
+        match value.to_index() {
+            Some(index) => JSString::from(index.to_string()),
+            None => panic!("expected a property key, got {:?}", value),
+        }
+    }
+
+    fn peek_ref(&self) -> JSRef {
+        let value = self.stack.last();
+        match value.and_then(JSValue::as_objref) {
+            Some(objref) => objref,
+            None => panic!("expected an object, got {:?}", value),
+        }
+    }
+
+    /// Jumps to `target`; a backward jump ends a loop iteration and spends fuel.
+    fn jump(&mut self, target: usize, heap: &mut Heap) -> JSResult<()> {
+        let frame = self.frame_mut();
+        let backward = target < frame.pc;
+        frame.pc = target;
+        if backward {
+            heap.tick()?;
+        }
+        Ok(())
+    }
+
+    fn jump_if(&mut self, condition: bool, target: usize, heap: &mut Heap) -> JSResult<()> {
+        if condition {
+            self.jump(target, heap)?;
+        }
+        Ok(())
+    }
+
+    /// Executes instructions until the outermost frame returns or an exception is thrown.
+    fn execute(&mut self, heap: &mut Heap) -> JSResult<JSValue> {
+        loop {
+            let frame = self.frames.last_mut().expect("no frame");
+            let op = frame.code.ops[frame.pc];
+            frame.pc += 1;
+
+            match op {
+                Op::Undefined => self.stack.push(JSValue::UNDEFINED),
+                Op::Const(index) => {
+                    let value = self.frame().code.constants[index as usize].clone();
+                    self.stack.push(value);
+                }
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Dup => {
+                    let value = self.stack.last().expect("stack underflow").clone();
+                    self.stack.push(value);
+                }
+                Op::Dup2 => {
+                    let start = self.stack.len() - 2;
+                    self.stack.extend_from_within(start..);
+                }
+                Op::Insert(n) => {
+                    let value = self.pop();
+                    let at = self.stack.len() - n as usize;
+                    self.stack.insert(at, value);
+                }
+                Op::This => self.stack.push(heap.this_value()),
+
+                Op::SetCompletion => {
+                    let value = self.pop();
+                    self.frame_mut().completion = value;
+                }
+                Op::GetCompletion => {
+                    let value = self.frame().completion.clone();
+                    self.stack.push(value);
+                }
+
+                Op::Jump(target) => self.jump(target, heap)?,
+                Op::JumpIfFalse(target) => {
+                    let value = self.pop();
+                    self.jump_if(!value.boolify(heap), target, heap)?;
+                }
+                Op::JumpIfTrue(target) => {
+                    let value = self.pop();
+                    self.jump_if(value.boolify(heap), target, heap)?;
+                }
+                Op::JumpIfFalseOrPop(target)
+                | Op::JumpIfTrueOrPop(target)
+                | Op::JumpIfNotNullishOrPop(target) => {
+                    let boolop = match op {
+                        Op::JumpIfFalseOrPop(_) => BoolOp::And,
+                        Op::JumpIfTrueOrPop(_) => BoolOp::Or,
+                        _ => BoolOp::Nullish,
+                    };
+                    let value = self.stack.last().expect("stack underflow");
+                    if boolop.short_circuits(value, heap) {
+                        self.frame_mut().pc = target;
+                    } else {
+                        self.pop();
+                    }
+                }
+                Op::JumpIfNullish(target) => {
+                    let value = self.stack.last().expect("stack underflow");
+                    if value.is_nullish() {
+                        self.frame_mut().pc = target;
+                    }
+                }
+                Op::Statement(location) => {
+                    heap.loc = self.frame().code.locations[location as usize].clone();
+                    if heap.should_collect() {
+                        let roots = self.roots();
+                        heap.collect_with(&roots);
+                    }
+                    heap.check_memory()?;
+                }
+
+                Op::Declare(name) => {
+                    heap.declare_variable(&Identifier(self.name(name)))?;
+                }
+                Op::EnterProgram => {
+                    let this = heap.this_value();
+                    let parent = heap.scope_ref();
+                    let layout = Rc::clone(&self.frame().code.layout);
+                    heap.push_frame(&layout, this, Rc::from([]), parent);
+                }
+                Op::DeclareFunction { var, function } => {
+                    let function_ref = self.closure(function, heap)?;
+                    (self.variable(var, heap)).put_value(JSValue::from(function_ref), heap)?;
+                }
+                Op::Arguments => {
+                    let frame = self.frame();
+                    let argv = self.stack[frame.base..frame.base + frame.argc].to_vec();
+                    let layout = Rc::clone(&frame.code.layout);
+                    let arguments_ref = heap.alloc(JSObject::from_array(argv.clone()));
+                    heap.init_slot(layout.arguments, JSValue::from(arguments_ref));
+                    for (index, &slot) in layout.params.iter().enumerate() {
+                        let value = argv.get(index).cloned().unwrap_or(JSValue::UNDEFINED);
+                        heap.init_slot(slot, value);
+                    }
+                }
+                Op::SaveCaller => {
+                    let caller = self.frame().caller.clone();
+                    let _ = source::save_caller(caller, heap);
+                }
+                Op::DropArguments => {
+                    let base = self.frame().base;
+                    self.stack.truncate(base);
+                }
+                Op::ResetVar(slot) => heap.reset_slot(slot),
+                Op::GetVar(var) => {
+                    let value = self.variable(var, heap).to_value(heap)?;
+                    self.stack.push(value);
+                }
+                Op::TypeofVar(var) => {
+                    let typ = (self.variable(var, heap).to_value(heap))
+                        .map(|value| value.type_of(heap))
+                        .unwrap_or("undefined");
+                    self.stack.push(JSValue::from(typ));
+                }
+                Op::InitVar(var) => {
+                    let value = self.pop();
+                    (self.variable(var, heap))
+                        .put_value(value, heap)
+                        .or_else(|e| heap.ignore_set_readonly(e))?;
+                }
+                Op::AssignVar { var, op } => {
+                    let place = self.assignable_variable(var, heap)?;
+                    self.assign(place, op, heap)?;
+                }
+                Op::UpdateVar { var, op, prefix } => {
+                    let place = self.variable(var, heap);
+                    self.update(place, op, prefix, heap)?;
+                }
+                Op::DeleteVar(var) => {
+                    let place = self.variable(var, heap);
+                    let deleted = place.delete(heap)?;
+                    self.stack.push(JSValue::from(deleted));
+                }
+
+                Op::ToKey => {
+                    let key = self.pop().to_property_key(heap)?;
+                    self.stack.push(key);
+                }
+                Op::ToObject => {
+                    let value = self.pop();
+                    if value.is_undefined() {
+                        return Err(Exception::not_an_object(value));
+                    }
+                    let objref = value.objectify(heap);
+                    self.stack.push(JSValue::from(objref));
+                }
+                Op::GetProp { name, cache } => {
+                    let objref = self.pop_ref();
+                    let value = self.get_prop(objref, name, cache, heap)?;
+                    self.stack.push(value);
+                }
+                Op::GetElem => {
+                    let objref = self.pop_ref();
+                    let index = self.stack.last().and_then(JSValue::to_index);
+                    let value = match index.and_then(|i| heap.get(objref).get_element(i)) {
+                        Some(value) => {
+                            self.pop();
+                            value
+                        }
+                        None => member(objref, self.pop_key(), heap).to_value(heap)?,
+                    };
+                    self.stack.push(value);
+                }
+                Op::AssignProp {
+                    name,
+                    op,
+                    cache: Some(cache),
+                } => {
+                    let objref = self.pop_ref();
+                    let value = self.pop();
+                    let newvalue = match op {
+                        None => value,
+                        Some(op) => {
+                            let oldvalue = self.get_prop(objref, name, Some(cache), heap)?;
+                            op.compute(&oldvalue, &value, heap)?
+                        }
+                    };
+                    let code = Rc::clone(&self.frame().code);
+                    let name = &code.names[name as usize];
+                    (code.caches[cache as usize])
+                        .put(objref, name, newvalue.clone(), heap)
+                        .or_else(|e| heap.ignore_set_readonly(e))?;
+                    self.stack.push(newvalue);
+                }
+                Op::AssignProp {
+                    name,
+                    op,
+                    cache: None,
+                } => {
+                    let objref = self.pop_ref();
+                    let place = member(objref, self.name(name), heap);
+                    self.assign(place, op, heap)?;
+                }
+                Op::AssignElem { op } => {
+                    let objref = self.pop_ref();
+                    let index = self.stack.last().and_then(JSValue::to_index);
+                    let array = heap.get_mut(objref).as_growable_array();
+                    if let (None, Some(index), Some(array)) = (op, index, array) {
+                        self.pop();
+                        let value = self.pop();
+                        (array.set(index, value.clone()))
+                            .or_else(|e| heap.ignore_set_readonly(e))?;
+                        self.stack.push(value);
+                    } else {
+                        let key = self.pop_key();
+                        let place = member(objref, key, heap);
+                        self.assign(place, op, heap)?;
+                    }
+                }
+                Op::UpdateProp { name, op, prefix } => {
+                    let objref = self.pop_ref();
+                    let place = member(objref, self.name(name), heap);
+                    self.update(place, op, prefix, heap)?;
+                }
+                Op::UpdateElem { op, prefix } => {
+                    let objref = self.pop_ref();
+                    let key = self.pop_key();
+                    let place = member(objref, key, heap);
+                    self.update(place, op, prefix, heap)?;
+                }
+                Op::DeleteProp(name) => {
+                    let objref = self.pop_ref();
+                    let place = member(objref, self.name(name), heap);
+                    let deleted = place.delete(heap)?;
+                    self.stack.push(JSValue::from(deleted));
+                }
+                Op::DeleteElem => {
+                    let objref = self.pop_ref();
+                    let key = self.pop_key();
+                    let place = member(objref, key, heap);
+                    let deleted = place.delete(heap)?;
+                    self.stack.push(JSValue::from(deleted));
+                }
+                Op::NotAssignable => {
+                    let target = self.pop();
+                    return Err(Exception::type_error(
+                        TypeError::CANNOT_SET_PROPERTY,
+                        target,
+                    ));
+                }
+
+                Op::Unary(op) => {
+                    let value = self.pop();
+                    let result = op.compute(&value, heap)?;
+                    self.stack.push(result);
+                }
+                Op::Binary(op) => {
+                    let rval = self.pop();
+                    let lval = self.pop();
+                    let result = op.compute(&lval, &rval, heap)?;
+                    self.stack.push(result);
+                }
+                Op::Object => {
+                    let objref = heap.alloc(JSObject::new());
+                    self.stack.push(JSValue::from(objref));
+                }
+                Op::InitProp(name, kind) => {
+                    let value = self.pop();
+                    let objref = self.peek_ref();
+                    let name = self.name(name);
+                    heap.init_property(objref, name.as_str(), value, kind)?;
+                }
+                Op::InitElem(kind) => {
+                    let value = self.pop();
+                    let key = self.pop_key();
+                    let objref = self.peek_ref();
+                    heap.init_property(objref, key.as_str(), value, kind)?;
+                }
+                Op::Array(count) => {
+                    let start = self.stack.len() - count as usize;
+                    let storage = self.stack.split_off(start);
+                    let objref = heap.alloc(JSObject::from_array(storage));
+                    self.stack.push(JSValue::from(objref));
+                }
+                Op::ArrayHole(index) => {
+                    let objref = self.stack.last().unwrap().to_ref()?;
+                    let array = heap.get_mut(objref).as_array_mut().unwrap();
+                    array.remove(index as usize);
+                }
+                Op::Closure(function) => {
+                    let function_ref = self.closure(function, heap)?;
+                    self.stack.push(JSValue::from(function_ref));
+                }
+
+                Op::CallVar { var, argc } => {
+                    let callee = self.variable(var, heap);
+                    // functions called by name get an undefined `this`, see `Heap::bind_this()`
+                    let (func_ref, _, name) = callee.resolve_call(heap)?;
+                    let this = JSValue::UNDEFINED;
+                    self.invoke(func_ref, this, name, argc as usize, None, heap)?;
+                }
+                Op::CallProp { name, argc, cache } => {
+                    let objref = self.pop_ref();
+                    let method = match cache {
+                        Some(_) => match self.get_prop(objref, name, cache, heap)?.as_objref() {
+                            Some(func_ref) if objref != Heap::NULL => Some(func_ref),
+                            _ => None,
+                        },
+                        None => None,
+                    };
+                    match method {
+                        Some(func_ref) => {
+                            // methods of scopes are functions called by name
+                            let this = match heap.is_scope(objref) {
+                                true => JSValue::from(Heap::GLOBAL),
+                                false => JSValue::from(objref),
+                            };
+                            let name = self.name(name);
+                            self.invoke(func_ref, this, name, argc as usize, None, heap)?;
+                        }
+                        None => {
+                            let callee = member(objref, self.name(name), heap);
+                            self.call(callee, argc as usize, heap)?;
+                        }
+                    }
+                }
+                Op::CallElem { argc } => {
+                    let objref = self.pop_ref();
+                    let key = self.pop_key();
+                    let callee = member(objref, key, heap);
+                    self.call(callee, argc as usize, heap)?;
+                }
+                Op::CallValue { argc } => {
+                    let callee = Interpreted::Value(self.pop());
+                    self.call(callee, argc as usize, heap)?;
+                }
+                Op::CallWithThis { argc } => {
+                    let start = self.stack.len() - argc as usize;
+                    let mut parts = self.stack.drain(start - 2..start);
+                    let (this, func) = (parts.next().unwrap(), parts.next().unwrap());
+                    drop(parts);
+                    let func_ref = (func.as_objref())
+                        .ok_or_else(|| Exception::type_error(TypeError::NOT_CALLABLE, func))?;
+                    let name = JSString::from("<anonymous>");
+                    self.invoke(func_ref, this, name, argc as usize, None, heap)?;
+                }
+                Op::New { argc } => {
+                    let callee = self.pop();
+                    self.construct(callee, argc as usize, heap)?;
+                }
+                Op::Return => {
+                    if let Some(value) = self.leave(heap)? {
+                        return Ok(value);
+                    }
+                }
+
+                Op::Throw => {
+                    let value = self.pop();
+                    return heap.throw(Exception::UserThrown(value));
+                }
+                Op::TryCatch(target) | Op::TryFinally(target) => {
+                    self.handlers.push(Handler {
+                        target,
+                        catch: matches!(op, Op::TryCatch(_)),
+                        stack: self.stack.len(),
+                        iterators: self.iterators.len(),
+                        scope: heap.scope_ref(),
+                    });
+                }
+                Op::PopHandler => {
+                    self.handlers.pop();
+                }
+                Op::Rethrow => {
+                    let exc = self.frame_mut().pending.pop();
+                    return Err(exc.expect("no pending exception"));
+                }
+                Op::DropPending => {
+                    self.frame_mut().pending.pop();
+                }
+
+                Op::ForInStart => {
+                    let objref = self.pop().objectify(heap);
+                    let it = ForIn::new(objref, heap)?;
+                    self.iterators.push(it);
+                }
+                Op::ForInNext(target) => {
+                    let it = self.iterators.last_mut().expect("no for-in iterator");
+                    match it.next_key(heap)? {
+                        Some(key) => self.stack.push(key),
+                        None => self.frame_mut().pc = target,
+                    }
+                }
+                Op::ForInEnd => {
+                    self.iterators.pop();
+                }
+            }
+        }
+    }
+
+    /// Makes a closure of a nested function `index` of the current code.
+    fn closure(&self, index: u32, heap: &mut Heap) -> JSResult<JSRef> {
+        let (function, code) = &self.frame().code.functions[index as usize];
+        let closure = Closure {
+            function: Rc::clone(function),
+            captured: heap.capture(&function.layout),
+            code: Some(Rc::clone(code)),
+            home: None,
+        };
+        heap.alloc_closure(closure)
+    }
+
+    /// Assigns the value on the stack (combined with the old value by `op`, if any) to `place`.
+    fn assign(&mut self, place: Interpreted, op: Option<BinOp>, heap: &mut Heap) -> JSResult<()> {
+        let value = self.pop();
+        let newvalue = match op {
+            None => value,
+            Some(op) => {
+                let oldvalue = place.to_value(heap)?;
+                op.compute(&oldvalue, &value, heap)?
+            }
+        };
+        place
+            .put_value(newvalue.clone(), heap)
+            .or_else(|e| heap.ignore_set_readonly(e))?;
+        self.stack.push(newvalue);
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        place: Interpreted,
+        op: UpdOp,
+        prefix: bool,
+        heap: &mut Heap,
+    ) -> JSResult<()> {
+        let oldvalue = place.to_value(heap)?;
+        let (oldvalue, newvalue) = op.compute(&oldvalue, heap)?;
+        place
+            .put_value(newvalue.clone(), heap)
+            .or_else(|e| heap.ignore_set_readonly(e))?;
+        let result = if prefix { newvalue } else { oldvalue };
+        self.stack.push(result);
+        Ok(())
+    }
+
+    /// Is the call being made in tail position of strict mode code? Its result would be
+    /// returned right away, maybe after a jump out of a conditional branch.
+    fn in_tail_position(&self) -> bool {
+        let frame = self.frame();
+        if !frame.code.layout.strict || frame.constructed.is_some() {
+            return false;
+        }
+        let mut pc = frame.pc;
+        loop {
+            match frame.code.ops[pc] {
+                Op::Return => return true,
+                Op::Jump(target) if target > pc => pc = target,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Leaves the current frame before a tail call, keeps `argc` arguments on the stack.
+    fn drop_frame(&mut self, argc: usize, heap: &mut Heap) {
+        let frame = self.frames.pop().expect("no frame to leave");
+        heap.set_local_scope(frame.saved_scope);
+        let start = self.stack.len() - argc;
+        self.stack.drain(frame.base..start);
+    }
+
+    /// Calls `callee` with `argc` arguments on the stack.
+    fn call(&mut self, callee: Interpreted, argc: usize, heap: &mut Heap) -> JSResult<()> {
+        let (func_ref, this_ref, name) = callee.resolve_call(heap)?;
+        self.invoke(func_ref, this_from_ref(this_ref), name, argc, None, heap)
+    }
+
+    /// `new callee(...)` with `argc` arguments on the stack.
+    fn construct(&mut self, callee: JSValue, mut argc: usize, heap: &mut Heap) -> JSResult<()> {
+        let mut funcref = match callee.as_objref() {
+            Some(funcref) => funcref,
+            None => return Err(Exception::not_an_object(callee)),
+        };
+        loop {
+            match &heap.get(funcref).value {
+                // `new` ignores the bound `this` of a bound function, but not its bound arguments
+                ObjectValue::BoundFunction(bound) => {
+                    let start = self.stack.len() - argc;
+                    let bound_arguments = bound.arguments.iter().cloned();
+                    self.stack.splice(start..start, bound_arguments);
+                    argc += bound.arguments.len();
+                    funcref = bound.target;
+                }
+                ObjectValue::Proxy(_) => {
+                    let start = self.stack.len() - argc;
+                    let arguments = (self.stack.drain(start..))
+                        .map(Interpreted::from)
+                        .collect::<Vec<_>>();
+                    let object_ref = heap.construct(funcref, arguments)?;
+                    self.stack.push(JSValue::from(object_ref));
+                    return Ok(());
+                }
+                _ => break,
+            }
+        }
+        let prototype_ref = (heap.get(funcref))
+            .get_own_value("prototype")
+            .ok_or_else(|| {
+                Exception::attr_type_error(TypeError::CANNOT_GET_PROPERTY, callee, "prototype")
+            })?
+            .to_ref()?;
+
+        let mut object = JSObject::new();
+        object.proto = prototype_ref;
+        let object_ref = heap.alloc(object);
+
+        let name = JSString::from("<constructor>");
+        let this = JSValue::from(object_ref);
+        self.invoke(funcref, this, name, argc, Some(object_ref), heap)
+    }
+
+    /// Calls `func_ref`: pushes a frame for a bytecode closure, calls anything else
+    /// through [`Heap::execute()`].
+    fn invoke(
+        &mut self,
+        func_ref: JSRef,
+        this: JSValue,
+        name: JSString,
+        argc: usize,
+        constructed: Option<JSRef>,
+        heap: &mut Heap,
+    ) -> JSResult<()> {
+        if let ObjectValue::Closure(closure) = &heap.get(func_ref).value {
+            if let Some(code) = closure.code.as_ref() {
+                let code = Rc::clone(code);
+                let captured = Rc::clone(&closure.captured);
+                let caller = heap.loc.clone();
+                heap.tick()?;
+                if constructed.is_none() && self.in_tail_position() {
+                    // the callee takes the place of the current frame
+                    self.drop_frame(argc, heap);
+                } else {
+                    heap.enter_vm_call()?;
+                }
+                self.enter(code, captured, this, argc, constructed, caller, heap);
+                return Ok(());
+            }
+        }
+
+        let start = self.stack.len() - argc;
+        let arguments = (self.stack.drain(start..))
+            .map(Interpreted::from)
+            .collect::<Vec<_>>();
+        let call = CallContext::from(arguments)
+            .with_this_value(this)
+            .with_name(name);
+        let result = heap.execute(func_ref, call)?.to_value(heap)?;
+        let result = match constructed {
+            Some(objref) if !is_object(&result) => JSValue::from(objref),
+            _ => result,
+        };
+        self.stack.push(result);
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 use std::io;
 
 use crate::prelude::*;
-use crate::{ast::Identifier, Interpreted, JSValue, JSON};
+use crate::{ast::Identifier, function::TailCall, Interpreted, JSValue, JSON};
 
 pub type JSResult<T> = Result<T, Exception>;
 
@@ -22,6 +22,12 @@ pub enum Exception {
 
     /// TypeError
     Type(TypeError),
+
+    /// RangeError
+    Range(RangeError),
+
+    /// the script was stopped from outside; `try/catch` cannot stop this
+    Abort(Abort),
 }
 
 // TODO: impl Display for Exception
@@ -29,6 +35,11 @@ pub enum Exception {
 // TODO: capture JavaScript stack trace in Exception
 
 impl Exception {
+    /// Can a `catch`/`finally` block of the script see this?
+    pub fn is_catchable(&self) -> bool {
+        !matches!(self, Exception::Abort(_))
+    }
+
     pub fn instance_required<V>(arg: V, of: &str) -> Exception
     where
         Interpreted: From<V>,
@@ -81,6 +92,16 @@ impl Exception {
             attr: JSString::from(""),
         })
     }
+
+    pub(crate) fn range_error<V>(tag: &'static str, what: V) -> Exception
+    where
+        Interpreted: From<V>,
+    {
+        Self::Range(RangeError {
+            tag,
+            value: Interpreted::from(what),
+        })
+    }
 }
 
 impl From<ParseError> for Exception {
@@ -94,14 +115,14 @@ impl From<Exception> for io::Error {
     fn from(exc: Exception) -> io::Error {
         // TODO: impl Display for Exception
         let msg = format!("{:?}", exc);
-        io::Error::new(io::ErrorKind::Other, msg)
+        io::Error::other(msg)
     }
 }
 
 pub fn ignore_set_readonly(e: Exception) -> JSResult<()> {
     match e {
         Exception::Type(TypeError {
-            tag: TypeError::SET_READONLY,
+            tag: TypeError::SET_READONLY | TypeError::NOT_EXTENSIBLE,
             ..
         }) => Ok(()),
         _ => Err(e),
@@ -111,10 +132,21 @@ pub fn ignore_set_readonly(e: Exception) -> JSResult<()> {
 #[derive(Debug, PartialEq)]
 pub enum Jump {
     Return(Interpreted),
+    /// `return` of a call in tail position of strict mode code, see [`TailCall`]
+    TailCall(Box<TailCall>),
     Break(Option<Identifier>),
     Continue(Option<Identifier>),
 }
 
+/// Why a script was stopped, see [`crate::Heap::set_fuel()`],
+/// [`crate::Heap::interrupt_handle()`] and [`crate::Heap::set_memory_limit()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Abort {
+    OutOfFuel,
+    Interrupted,
+    OutOfMemory,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct TypeError {
     tag: &'static str,
@@ -133,6 +165,20 @@ impl TypeError {
     pub const INSTANCE_REQUIRED: &'static str = "an instance required";
     pub const INVALID_DESCRIPTOR: &'static str = "invalid descriptor";
     pub const INVALID_PROTO: &'static str = "invalid prototype";
+    pub const NEW_REQUIRED: &'static str = "constructor requires 'new'";
+    pub const NOT_CONSTRUCTOR: &'static str = "not a constructor";
+    pub const NOT_ITERABLE: &'static str = "not iterable";
+    pub const NO_PRIMITIVE: &'static str = "cannot convert object to primitive value";
+    pub const NOT_EXTENSIBLE: &'static str = "cannot add a property to a non-extensible object";
+    pub const PROXY_REVOKED: &'static str = "cannot use a revoked proxy";
+    pub const PROXY_INVARIANT: &'static str = "proxy trap result contradicts its target";
+    pub const PROXY_REFUSED: &'static str = "proxy trap refused the operation";
+    pub const PROXY_NOT_OBJECT: &'static str = "proxy target and handler must be objects";
+    pub const BIGINT_MIX: &'static str = "cannot mix BigInt and other types";
+    pub const BIGINT_TO_NUMBER: &'static str = "cannot convert a BigInt to a number";
+    pub const BIGINT_UNSIGNED_SHIFT: &'static str = "BigInts have no unsigned right shift";
+    pub const NOT_BIGINT: &'static str = "cannot convert to a BigInt";
+    pub const WEAK_TARGET: &'static str = "invalid value to be held weakly";
 }
 
 #[derive(Debug, PartialEq)]
@@ -158,6 +204,28 @@ impl ReferenceError {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub struct RangeError {
+    tag: &'static str,
+    value: Interpreted,
+}
+
+impl RangeError {
+    pub const INVALID_LENGTH: &'static str = "invalid array length";
+    pub const STACK_OVERFLOW: &'static str = "Maximum call stack size exceeded";
+    pub const OUT_OF_BOUNDS: &'static str = "offset is out of bounds";
+    pub const MISALIGNED: &'static str = "offset or length is not a multiple of the element size";
+    pub const DIVISION_BY_ZERO: &'static str = "Division by zero";
+    pub const BIGINT_TOO_BIG: &'static str = "Maximum BigInt size exceeded";
+    pub const NOT_INTEGER: &'static str = "the number is not an integer";
+    pub const INVALID_RADIX: &'static str = "radix must be between 2 and 36";
+    pub const NEGATIVE_EXPONENT: &'static str = "Exponent must be non-negative";
+
+    pub fn message(&self) -> &'static str {
+        self.tag
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ParseError {
     InvalidJSON { err: String },
@@ -167,6 +235,8 @@ pub enum ParseError {
     BindingRedeclared {},
     ForInMultipleVar(),
     ContinueLabelNotALoop(Identifier),
+    InvalidBigInt(String),
+    DuplicateParameter(Identifier), // in a strict mode function
 }
 
 impl ParseError {
@@ -1,8 +1,9 @@
 use crate::prelude::*;
 
+use crate::object::ObjectValue;
 use crate::{
-    ast, source, Exception, Heap, Interpretable, Interpreted, JSObject, JSRef, JSResult, JSValue,
-    Jump,
+    ast, bytecode, source, Exception, Heap, Interpretable, Interpreted, JSObject, JSRef, JSResult,
+    JSValue, Jump,
 };
 
 /// Call context information (e.g. arguments) for [`Heap::execute()`].
@@ -10,7 +11,7 @@ use crate::{
 /// Whenever you want to call a JS function, it needs to know:
 ///
 /// - `arguments`
-/// - `this_ref`: what `this` is for this call
+/// - `this`: what `this` is for this call, `this_ref` is its object (if any)
 ///
 /// The regular usage is:
 /// ```
@@ -18,17 +19,20 @@ use crate::{
 /// # let mut heap = Heap::new();
 /// let func_ref: JSRef = heap
 ///     .lookup_var("parseInt").expect("parseInt")
-///     .to_ref(&heap).expect("to_ref");
+///     .to_ref(&mut heap).expect("to_ref");
 ///
 /// let arguments = vec![ Interpreted::from("42") ];
 /// let result = heap.execute( func_ref, CallContext::from(arguments)).expect("execute");
-/// let result = result.to_value(&heap).unwrap();
+/// let result = result.to_value(&mut heap).unwrap();
 ///
 /// assert_eq!(result, JSValue::from(42));
 /// ```
 ///
 pub struct CallContext {
+    /// the object `this` refers to, [`Heap::NULL`] if `this` is not an object
     pub this_ref: JSRef,
+    /// `this` as given by the caller: strict mode code sees it unchanged
+    pub this: JSValue,
     pub method_name: JSString,
     pub arguments: Vec<Interpreted>,
     pub loc: Option<Box<source::Location>>,
@@ -37,6 +41,14 @@ pub struct CallContext {
 impl CallContext {
     pub fn with_this(mut self, this_ref: JSRef) -> Self {
         self.this_ref = this_ref;
+        self.this = this_from_ref(this_ref);
+        self
+    }
+
+    /// Like [`CallContext::with_this()`] for any `this`, e.g. the `thisArg` of `call()`.
+    pub fn with_this_value(mut self, this: JSValue) -> Self {
+        self.this_ref = this.as_objref().unwrap_or(Heap::NULL);
+        self.this = this;
         self
     }
 
@@ -52,7 +64,7 @@ impl CallContext {
             .to_value(heap)
     }
 
-    pub fn arg_as_number(&self, argnum: usize, heap: &Heap) -> JSResult<Option<i64>> {
+    pub fn arg_as_number(&self, argnum: usize, heap: &mut Heap) -> JSResult<Option<i64>> {
         let arg = match self.arguments.get(argnum) {
             Some(arg) => arg.to_value(heap)?,
             None => return Ok(None),
@@ -68,13 +80,26 @@ impl From<Vec<Interpreted>> for CallContext {
             method_name: JSString::from(""),
             loc: None,
             this_ref: Heap::NULL,
+            this: JSValue::UNDEFINED,
         }
     }
 }
 
+/// `this` for `this_ref`, where [`Heap::NULL`] stands for no `this` at all.
+pub(crate) fn this_from_ref(this_ref: JSRef) -> JSValue {
+    if this_ref == Heap::NULL {
+        JSValue::UNDEFINED
+    } else {
+        JSValue::from(this_ref)
+    }
+}
+
 /// What a host function callable from JS looks like.
 pub type HostFn = fn(ctx: CallContext, heap: &'_ mut Heap) -> JSResult<Interpreted>;
 
+/// Parses source code built at run time, e.g. by `Function()`, see [`Heap::set_parse_func()`].
+pub type ParseFn = fn(code: &str, heap: &'_ mut Heap) -> JSResult<ast::Program>;
+
 /// A wrapper for [`HostFn`] to give it `fmt::Debug`.
 #[derive(Clone)]
 pub struct HostFunc(HostFn);
@@ -100,38 +125,89 @@ impl fmt::Debug for HostFunc {
     }
 }
 
+/// A function made by `Function.prototype.bind()`: calls `target` with
+/// the bound `this` and `arguments` prepended to its own arguments.
+#[derive(Clone, Debug)]
+pub struct BoundFunction {
+    pub target: JSRef,
+    pub this: JSValue,
+    pub arguments: Vec<JSValue>,
+}
+
+impl BoundFunction {
+    pub fn call(&self, mut call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+        call.arguments = self.with_arguments(call.arguments);
+        heap.execute(self.target, call.with_this_value(self.this.clone()))
+    }
+
+    /// Prepends the bound arguments to `arguments`.
+    pub fn with_arguments(&self, mut arguments: Vec<Interpreted>) -> Vec<Interpreted> {
+        let mut bound = (self.arguments.iter().cloned())
+            .map(Interpreted::from)
+            .collect::<Vec<_>>();
+        bound.append(&mut arguments);
+        bound
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Closure {
     pub function: Rc<ast::Function>,
-    pub captured_scope: JSRef, // TODO: capture free variables only
+    /// the cells of the free variables of `function`, see [`ast::Layout::captures`]
+    pub captured: Rc<[JSRef]>,
+    /// the compiled `function` for closures made by [`bytecode`]
+    pub code: Option<Rc<bytecode::Code>>,
+    /// the object literal a method, getter or setter is defined in, ES: \[\[HomeObject\]\]
+    pub home: Option<JSRef>,
 }
 
 impl Closure {
     pub fn call(&self, call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
-        let result = heap.enter_new_scope(call.this_ref, self.captured_scope, |heap| {
+        let mut result = self.run(call, heap);
+        let mark = heap.temporaries_mark();
+        // calls in tail position are made here, after the frame of their caller is gone
+        while let Err(Exception::Jump(Jump::TailCall(tail))) = result {
+            let (func_ref, mut call) = tail.into_call();
+            heap.release_temporaries(mark);
+            heap.root_temporary(func_ref);
+            call.loc = heap.loc.clone();
+            result = match &heap.get(func_ref).value {
+                ObjectValue::Closure(closure) => closure.clone().run(call, heap),
+                _ => heap.execute(func_ref, call),
+            };
+        }
+        result
+    }
+
+    /// Runs a call in a new frame, a call in tail position is left to [`Closure::call()`].
+    fn run(&self, call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
+        heap.tick()?;
+        if let Some(code) = self.code.as_ref() {
+            return bytecode::call(code, &self.captured, call, heap);
+        }
+        let function = &self.function;
+        let captured = Rc::clone(&self.captured);
+        let result = heap.enter_frame(&function.layout, call.this.clone(), captured, |heap| {
             // `arguments`
             let argv = (call.arguments.iter())
                 .map(|v| v.to_value(heap))
                 .collect::<JSResult<Vec<JSValue>>>()?;
             let arguments_ref = heap.alloc(JSObject::from_array(argv));
-            heap.scope_mut().set_nonconf("arguments", arguments_ref)?;
+            heap.init_slot(function.layout.arguments, arguments_ref.into());
 
             // set each argument
-            for (i, param) in self.function.params.iter().enumerate() {
+            for (i, &slot) in function.layout.params.iter().enumerate() {
                 let value = (call.arguments.get(i))
                     .unwrap_or(&Interpreted::VOID)
                     .to_value(heap)?;
-                heap.scope_mut().set_nonconf(param.0.as_str(), value)?;
+                heap.init_slot(slot, value);
             }
 
             let _ = source::save_caller(call.loc.clone(), heap);
 
-            heap.declare(
-                self.function.variables.iter(),
-                self.function.functions.iter(),
-            )?;
+            heap.declare_functions(function.functions.iter())?;
 
-            self.function.body.interpret(heap)
+            function.body.interpret(heap)
         });
         match result {
             Ok(_) => Ok(Interpreted::VOID), // BlockStatement result
@@ -140,3 +216,58 @@ impl Closure {
         }
     }
 }
+
+/// A call in tail position of strict mode code: the caller returns it as a
+/// [`Jump::TailCall`] and [`Closure::call()`] makes it without growing the Rust stack.
+#[derive(Debug, PartialEq)]
+pub struct TailCall {
+    pub func_ref: JSRef,
+    pub this: JSValue,
+    pub name: JSString,
+    pub arguments: Vec<JSValue>,
+}
+
+impl TailCall {
+    pub(crate) fn into_call(self) -> (JSRef, CallContext) {
+        let arguments = (self.arguments.into_iter())
+            .map(Interpreted::from)
+            .collect::<Vec<_>>();
+        let call = CallContext::from(arguments)
+            .with_this_value(self.this)
+            .with_name(self.name);
+        (self.func_ref, call)
+    }
+}
+
+/// A frame of variables: the scope of a function call or of a program.
+/// Lives on the heap as the value of a scope object, see [`Heap::enter_frame()`].
+#[derive(Clone, Debug)]
+pub struct Scope {
+    pub layout: Rc<ast::Layout>,
+    /// the values of the slots; a boxed slot holds a reference to its cell
+    pub slots: Vec<JSValue>,
+    /// the cells captured by the closure being called
+    pub captured: Rc<[JSRef]>,
+    /// `this` of the frame, already coerced to an object for sloppy mode functions
+    pub this: JSValue,
+    /// the scope to restore when leaving this one
+    pub saved: JSRef,
+    /// where to continue looking up names after this scope:
+    /// the caller of `eval()` for a program, the global scope for a function
+    pub parent: JSRef,
+}
+
+impl Scope {
+    /// Gives the index of the innermost slot named `name`.
+    pub(crate) fn find_slot(&self, name: &str) -> Option<usize> {
+        self.layout
+            .names
+            .iter()
+            .rposition(|slot| slot.as_str() == name)
+    }
+
+    /// Gives the index of the captured cell named `name`.
+    pub(crate) fn find_captured(&self, name: &str) -> Option<usize> {
+        (self.layout.captures.iter()).position(|(captured, _)| captured.as_str() == name)
+    }
+}
@@ -1,28 +1,81 @@
-use crate::ast::{FunctionDeclaration, Identifier};
-use crate::error::TypeError;
-use crate::function::{CallContext, HostFn};
+use alloc::rc::Weak;
+use alloc::sync::Arc;
+use core::cell::{Ref, RefCell};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ast::{FunctionDeclaration, Identifier, Layout, Program, PropertyKind, Slot};
+use crate::builtin::console::{Console, ConsoleSink};
+use crate::builtin::proxy;
+use crate::bytecode::{self, Backend};
+use crate::error::{self, Abort, RangeError, TypeError};
+use crate::function::{CallContext, Closure, HostFn, ParseFn, Scope};
 use crate::object::HostClass;
 use crate::prelude::*;
+use crate::shape::{Properties, Shape};
 use crate::{
-    builtin, object::ObjectValue, source, Exception, Interpretable, Interpreted, JSObject,
-    JSResult, JSValue, JSON,
+    builtin,
+    object::{Access, Content, JSArray, ObjectValue, Property},
+    source, Exception, Interpretable, Interpreted, JSObject, JSResult, JSValue, JSON,
 };
 
-/// A heap reference: a Heap index.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct JSRef(usize);
+/// A heap reference: a Heap index and the generation of its slot.
+///
+/// A slot gets a new generation when the garbage collector frees an object in it,
+/// so a `JSRef` to a freed object never aliases an object allocated there later:
+/// [`Heap::get()`] panics on it and [`Heap::try_get()`] gives `None`.
+/// A slot that runs out of generations is retired instead of being reused.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JSRef {
+    index: u32,
+    generation: u16,
+}
 
 impl JSRef {
+    /// Packs `self` into the 48 bits of a boxed [`JSValue`].
+    pub(crate) const fn to_bits(self) -> u64 {
+        (self.generation as u64) << 32 | self.index as u64
+    }
+
+    pub(crate) const fn from_bits(bits: u64) -> JSRef {
+        JSRef {
+            index: bits as u32,
+            generation: (bits >> 32) as u16,
+        }
+    }
+
+    const fn fixed(index: u32) -> JSRef {
+        JSRef {
+            index,
+            generation: 0,
+        }
+    }
+
+    /// The index of the slot on the heap.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
     pub fn has_proto(&self, protoref: JSRef, heap: &Heap) -> bool {
         (heap.get(*self).protochain(heap)).any(|pref| pref == protoref)
     }
 
-    pub fn isinstance(&self, constructor: JSRef, heap: &Heap) -> JSResult<bool> {
+    pub fn isinstance(&self, constructor: JSRef, heap: &mut Heap) -> JSResult<bool> {
+        if let ObjectValue::BoundFunction(bound) = &heap.get(constructor).value {
+            return self.isinstance(bound.target, heap);
+        }
         let protoval = heap.get(constructor).get_own_value("prototype");
         let protoval =
             protoval.ok_or_else(|| Exception::type_error(TypeError::NOT_CALLABLE, constructor))?;
         let protoref = protoval.to_ref()?;
-        Ok(self.has_proto(protoref, heap))
+        // the prototype chain may go through proxies
+        let mut objref = heap.prototype_of(*self)?;
+        while objref != Heap::NULL {
+            if objref == protoref {
+                return Ok(true);
+            }
+            objref = heap.prototype_of(objref)?;
+        }
+        Ok(false)
     }
 
     /// Check if the object behind the reference `self` has a prototype of `constructor`.
@@ -30,9 +83,9 @@ impl JSRef {
     /// # use slothjs::{JSObject, Heap};
     /// # let mut heap = Heap::new();
     /// let array_ref = heap.alloc(JSObject::from_array(vec![]));
-    /// array_ref.expect_instance("Array", &heap).unwrap();
+    /// array_ref.expect_instance("Array", &mut heap).unwrap();
     /// ```
-    pub fn expect_instance(&self, constructor: &str, heap: &Heap) -> JSResult<()> {
+    pub fn expect_instance(&self, constructor: &str, heap: &mut Heap) -> JSResult<()> {
         let ctrval = heap
             .lookup_var(constructor)
             .ok_or_else(|| Exception::no_reference(Identifier::from(constructor)))?;
@@ -48,35 +101,193 @@ impl JSRef {
     }
 }
 
+impl fmt::Debug for JSRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.generation {
+            0 => write!(f, "JSRef({})", self.index),
+            gen => write!(f, "JSRef({}/{})", self.index, gen),
+        }
+    }
+}
+
+/// A handle that keeps an object (and everything reachable from it) alive
+/// across garbage collections until all its clones are dropped, see [`Heap::root()`].
+#[derive(Clone)]
+pub struct Rooted {
+    objref: JSRef,
+    _root: Rc<()>,
+}
+
+impl Rooted {
+    pub fn objref(&self) -> JSRef {
+        self.objref
+    }
+}
+
+impl fmt::Debug for Rooted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rooted({:?})", self.objref)
+    }
+}
+
+/// Aborts the script running on a [`Heap`] from another thread,
+/// see [`Heap::interrupt_handle()`].
+#[derive(Clone, Debug)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    /// Makes the running script (or the next one) abort with [`Abort::Interrupted`]
+    /// at its next loop iteration or function call.
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Approximate memory usage of a [`Heap`], see [`Heap::memory_stats()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// the number of objects that are not freed yet
+    pub objects: usize,
+    /// bytes taken by the objects, their properties and elements
+    pub object_bytes: usize,
+    /// bytes taken by the strings that objects refer to
+    pub string_bytes: usize,
+    /// see [`Heap::set_memory_limit()`]
+    pub limit: Option<usize>,
+}
+
+impl MemoryStats {
+    /// The total of `object_bytes` and `string_bytes`, as checked against `limit`.
+    pub fn bytes(&self) -> usize {
+        self.object_bytes + self.string_bytes
+    }
+}
+
+/// An address on the native stack to measure how deep the recursion is.
+fn stack_address() -> usize {
+    let marker = 0u8;
+    core::hint::black_box(&marker) as *const u8 as usize
+}
+
 /// Runtime heap
+///
+/// The heap is garbage-collected: [`Heap::collect()`] frees all objects that are not
+/// reachable from the fixed slots (e.g. [`Heap::GLOBAL`]), from the current scope
+/// or from [`Rooted`] handles. A `JSRef` to a freed object is stale,
+/// its slot is reused by later allocations.
+///
+/// Collections also happen automatically when enough objects have been allocated
+/// since the last one, at a statement boundary. The tree-walking interpreter roots
+/// the intermediate values it keeps on the Rust stack (see [`Heap::root_temporary()`]),
+/// host functions do not: no automatic collection happens while one of them is running.
+/// An embedder that keeps a `JSRef` across evaluations must root it.
+///
+/// The heap keeps an estimate of the memory it holds, which can be limited
+/// with [`Heap::set_memory_limit()`].
 #[derive(Debug)]
 pub struct Heap {
     objects: Vec<JSObject>,
+    /// the current generation of each slot, might outlive `objects` after shrinking
+    generations: Vec<u16>,
     pub loc: Option<Box<source::Location>>,
+    console: Console,
+    backend: Backend,
+    /// the parser of source code built at run time, see [`Heap::set_parse_func()`]
+    parse_func: Option<ParseFn>,
+
+    /// freed slots to reuse
+    free: Vec<usize>,
+    /// the number of slots that ran out of generations, see [`Heap::RETIRED`]
+    retired: usize,
+    /// extra roots for the collector: alive while their `Rooted` handles are
+    roots: Vec<(JSRef, Weak<()>)>,
+    /// allocations since the last collection
+    allocated: usize,
+    /// the number of allocations that triggers an automatic collection
+    gc_threshold: usize,
+    /// the number of nested `Heap::execute()` calls
+    call_depth: usize,
+    /// the number of running calls that keep values where the collector does not see them:
+    /// all calls but those of closures run by the tree-walking interpreter and bound functions
+    native_calls: usize,
+    /// the values that running statements of the tree-walking interpreter keep on the
+    /// Rust stack, see [`Heap::root_temporary()`]
+    temporaries: Vec<JSRef>,
+    /// the number of running statements of the tree-walking interpreter
+    statements: usize,
+    /// the approximate bytes of each slot as of its last measurement
+    sizes: Vec<usize>,
+    /// slots that might have changed since their last measurement, under a memory limit
+    dirty: Vec<usize>,
+    /// the sum of `sizes`
+    object_bytes: usize,
+    /// strings counted at the last collection and charged since then
+    string_bytes: usize,
+    memory_limit: Option<usize>,
+    /// the memory use that triggers the next automatic collection under a limit
+    gc_memory_threshold: usize,
+    /// the number of nested calls inside of bytecode VMs
+    vm_depth: usize,
+    /// the limit of nested calls, see [`Heap::set_max_call_depth()`]
+    max_call_depth: usize,
+    /// a native stack address at the start of the outermost [`Heap::run()`], 0 outside of it
+    stack_base: usize,
+    /// the native stack available from `stack_base`, see [`Heap::set_stack_limit()`]
+    stack_limit: usize,
+    /// the current scope: a frame or `Heap::GLOBAL`
+    scope: JSRef,
+    /// the root of the shapes of objects on the heap
+    empty_shape: Rc<Shape>,
+    /// the steps left to run, see [`Heap::set_fuel()`]
+    fuel: Option<u64>,
+    /// the flag of [`InterruptHandle`]s
+    interrupt: Arc<AtomicBool>,
+    /// callbacks waiting for the current script to finish, see [`Heap::run_jobs()`]
+    jobs: Vec<Job>,
+    /// targets of `WeakRef`s used by the current job, see [`Heap::keep_during_job()`]
+    kept_alive: Vec<JSRef>,
+}
+
+/// A function call scheduled to run after the current script, e.g. a cleanup callback
+/// of a `FinalizationRegistry`.
+#[derive(Debug)]
+struct Job {
+    func: JSRef,
+    arguments: Vec<JSValue>,
 }
 
 impl Heap {
     // A set of fixed slots on the heap.
     // This untangles the builtins intialization and avoids frequent lookups
     // for e.g. `Array.prototype`.
-    pub const NULL: JSRef = JSRef(0);
-    pub const GLOBAL: JSRef = JSRef(1);
-    pub const OBJECT_PROTO: JSRef = JSRef(2);
-    pub const FUNCTION_PROTO: JSRef = JSRef(3);
-    pub const ARRAY_PROTO: JSRef = JSRef(4);
-    pub const BOOLEAN_PROTO: JSRef = JSRef(5);
-    //pub const NUMBER_PROTO: JSRef = JSRef(6);
-    pub const STRING_PROTO: JSRef = JSRef(7);
-    pub const REGEXP_PROTO: JSRef = JSRef(8);
+    pub const NULL: JSRef = JSRef::fixed(0);
+    pub const GLOBAL: JSRef = JSRef::fixed(1);
+    pub const OBJECT_PROTO: JSRef = JSRef::fixed(2);
+    pub const FUNCTION_PROTO: JSRef = JSRef::fixed(3);
+    pub const ARRAY_PROTO: JSRef = JSRef::fixed(4);
+    pub const BOOLEAN_PROTO: JSRef = JSRef::fixed(5);
+    pub const NUMBER_PROTO: JSRef = JSRef::fixed(6);
+    pub const STRING_PROTO: JSRef = JSRef::fixed(7);
+    pub const REGEXP_PROTO: JSRef = JSRef::fixed(8);
 
-    pub const ERROR_PROTO: JSRef = JSRef(9);
+    pub const ERROR_PROTO: JSRef = JSRef::fixed(9);
+    pub const MAP_PROTO: JSRef = JSRef::fixed(10);
+    pub const SET_PROTO: JSRef = JSRef::fixed(11);
+    pub const ITERATOR_PROTO: JSRef = JSRef::fixed(12);
+    pub const ARRAY_BUFFER_PROTO: JSRef = JSRef::fixed(13);
+    pub const BIGINT_PROTO: JSRef = JSRef::fixed(14);
 
-    const USERSTART: usize = 10;
+    const USERSTART: usize = 15;
 
-    const LOCAL_SCOPE: &'static str = "[[local_scope]]";
-    const CAPTURED_SCOPE: &'static str = "[[captured_scope]]";
-    pub(crate) const SCOPE_THIS: &'static str = "[[this]]";
-    pub(crate) const SAVED_SCOPE: &'static str = "[[saved_scope]]";
+    /// The generation of a slot that is never reused: otherwise the next generation
+    /// would wrap around and stale references to the slot would become valid again.
+    const RETIRED: u16 = u16::MAX;
+
+    /// The minimal number of allocations between automatic collections.
+    const GC_MIN_THRESHOLD: usize = 8192;
+
+    const DEFAULT_MAX_CALL_DEPTH: usize = 200_000;
+    const DEFAULT_STACK_LIMIT: usize = 1 << 20;
 
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
@@ -85,31 +296,543 @@ impl Heap {
             objects.push(JSObject::new());
         }
 
-        let mut heap = Heap { objects, loc: None };
+        let mut heap = Heap {
+            generations: vec![0; objects.len()],
+            objects,
+            loc: None,
+            console: Console::default(),
+            parse_func: None,
+            backend: Backend::default(),
+            free: Vec::new(),
+            retired: 0,
+            roots: Vec::new(),
+            allocated: 0,
+            gc_threshold: Self::GC_MIN_THRESHOLD,
+            call_depth: 0,
+            native_calls: 0,
+            temporaries: Vec::new(),
+            statements: 0,
+            sizes: vec![0; Self::USERSTART],
+            dirty: Vec::new(),
+            object_bytes: 0,
+            string_bytes: 0,
+            memory_limit: None,
+            gc_memory_threshold: usize::MAX,
+            vm_depth: 0,
+            max_call_depth: Self::DEFAULT_MAX_CALL_DEPTH,
+            stack_base: 0,
+            stack_limit: Self::DEFAULT_STACK_LIMIT,
+            scope: Heap::GLOBAL,
+            empty_shape: Shape::root(),
+            fuel: None,
+            interrupt: Arc::default(),
+            jobs: Vec::new(),
+            kept_alive: Vec::new(),
+        };
         builtin::init(&mut heap).expect("failed to initialize builtin objects");
+        heap.measure();
         heap
     }
 
+    /// Sends the output of `console` to `sink`.
+    pub fn set_console(&mut self, sink: Box<dyn ConsoleSink>) {
+        self.console = Console::new(sink);
+    }
+
+    /// Lets `Function()` parse its source with `parse_func`,
+    /// [`crate::runtime::Runtime`] sets the one of its parser.
+    pub fn set_parse_func(&mut self, parse_func: ParseFn) {
+        self.parse_func = Some(parse_func);
+    }
+
+    /// Parses `code` with the function from [`Heap::set_parse_func()`].
+    pub(crate) fn parse(&mut self, code: &str) -> JSResult<Program> {
+        let parse_func = (self.parse_func).ok_or_else(|| Exception::no_reference("eval"))?;
+        parse_func(code, self)
+    }
+
+    pub(crate) fn console_mut(&mut self) -> &mut Console {
+        &mut self.console
+    }
+
+    /// Selects how [`Heap::run()`] executes programs.
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend;
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Limits how deep JavaScript calls can nest; deeper calls throw a `RangeError`.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Limits the native stack (in bytes) that [`Heap::run()`] can use: the tree walker
+    /// recurses on calls and nested expressions. Deeper recursion throws a `RangeError`.
+    /// The default fits into the 2 MiB stack of a spawned thread, raise it for bigger stacks.
+    pub fn set_stack_limit(&mut self, bytes: usize) {
+        self.stack_limit = bytes;
+    }
+
+    /// Throws `RangeError` if the native stack is about to run out.
+    pub(crate) fn check_stack(&self) -> JSResult<()> {
+        let used = self.stack_base.saturating_sub(stack_address());
+        if used > self.stack_limit {
+            return Err(Exception::range_error(
+                RangeError::STACK_OVERFLOW,
+                Interpreted::VOID,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Throws `RangeError` if one more call would be too deep.
+    pub(crate) fn check_call_depth(&self) -> JSResult<()> {
+        if self.call_depth + self.vm_depth >= self.max_call_depth {
+            return Err(Exception::range_error(
+                RangeError::STACK_OVERFLOW,
+                Interpreted::VOID,
+            ));
+        }
+        self.check_stack()
+    }
+
+    /// Counts calls that a bytecode VM makes without [`Heap::execute()`].
+    pub(crate) fn enter_vm_call(&mut self) -> JSResult<()> {
+        self.check_call_depth()?;
+        self.vm_depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave_vm_call(&mut self) {
+        self.vm_depth -= 1;
+    }
+
+    /// Limits the steps (loop iterations and function calls) left to run, `None` for no limit.
+    /// Running out of them aborts the script with [`Abort::OutOfFuel`].
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    /// The steps left to run, if limited.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Gives a handle that can stop the running script from another thread.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle(Arc::clone(&self.interrupt))
+    }
+
+    /// Spends a step of fuel, checks for an interrupt and the memory limit:
+    /// loop back-edges and function calls do this.
+    pub(crate) fn tick(&mut self) -> JSResult<()> {
+        if let Some(fuel) = self.fuel.as_mut() {
+            if *fuel == 0 {
+                return Err(Exception::Abort(Abort::OutOfFuel));
+            }
+            *fuel -= 1;
+        }
+        if self.interrupt.load(Ordering::Relaxed) {
+            self.interrupt.store(false, Ordering::Relaxed);
+            return Err(Exception::Abort(Abort::Interrupted));
+        }
+        self.check_memory()
+    }
+
     pub fn get(&self, objref: JSRef) -> &JSObject {
-        self.objects
-            .get(objref.0)
-            .unwrap_or_else(|| panic!("{:?} is invalid", objref))
+        self.try_get(objref)
+            .unwrap_or_else(|| panic!("{:?} is invalid or stale", objref))
     }
 
     pub fn get_mut(&mut self, objref: JSRef) -> &mut JSObject {
-        self.objects
-            .get_mut(objref.0)
-            .unwrap_or_else(|| panic!("{:?} is invalid", objref))
+        if !self.is_valid(objref) {
+            panic!("{:?} is invalid or stale", objref);
+        }
+        let index = objref.index();
+        if self.memory_limit.is_some() && self.dirty.last() != Some(&index) {
+            self.dirty.push(index);
+        }
+        &mut self.objects[index]
+    }
+
+    /// Like [`Heap::get()`], but gives `None` for an invalid or stale `objref`.
+    pub fn try_get(&self, objref: JSRef) -> Option<&JSObject> {
+        if !self.is_valid(objref) {
+            return None;
+        }
+        self.objects.get(objref.index())
+    }
+
+    /// Checks that `objref` points to a live object.
+    pub fn is_valid(&self, objref: JSRef) -> bool {
+        objref.index() < self.objects.len() && self.generations[objref.index()] == objref.generation
     }
 
     pub fn get_index(&self, index: usize) -> Option<&JSObject> {
         self.objects.get(index)
     }
 
-    pub fn alloc(&mut self, object: JSObject) -> JSRef {
-        let ind = self.objects.len();
-        self.objects.push(object);
-        JSRef(ind)
+    pub fn alloc(&mut self, mut object: JSObject) -> JSRef {
+        object.properties.adopt(&self.empty_shape);
+        self.allocated += 1;
+        let size = object.memory_size();
+        self.object_bytes += size;
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.objects[index] = object;
+                self.sizes[index] = size;
+                index
+            }
+            None => {
+                let index = self.objects.len();
+                self.objects.push(object);
+                self.sizes.push(size);
+                if self.generations.len() == index {
+                    self.generations.push(0);
+                }
+                index
+            }
+        };
+        let objref = JSRef {
+            index: index as u32,
+            generation: self.generations[index],
+        };
+        self.root_temporary(objref);
+        objref
+    }
+
+    /// The number of live (i.e. not freed) objects on the heap.
+    pub fn object_count(&self) -> usize {
+        self.objects.len() - self.free.len() - self.retired
+    }
+
+    /// Makes `objref` (and everything reachable from it) survive collections
+    /// while the returned handle (or any of its clones) is alive.
+    /// ```
+    /// # use slothjs::{Heap, JSObject};
+    /// let mut heap = Heap::new();
+    /// let objref = heap.alloc(JSObject::new());
+    /// let rooted = heap.root(objref);
+    ///
+    /// heap.collect();
+    /// assert!(heap.is_valid(objref));
+    ///
+    /// drop(rooted);
+    /// heap.collect();
+    /// assert!(!heap.is_valid(objref));
+    /// ```
+    pub fn root(&mut self, objref: JSRef) -> Rooted {
+        if !self.is_valid(objref) {
+            panic!("{:?} is invalid or stale", objref);
+        }
+        if self.roots.len() == self.roots.capacity() {
+            self.roots.retain(|(_, root)| root.strong_count() > 0);
+        }
+        let root = Rc::new(());
+        self.roots.push((objref, Rc::downgrade(&root)));
+        Rooted {
+            objref,
+            _root: root,
+        }
+    }
+
+    /// Roots `value` if it is a reference, see [`Heap::root()`].
+    pub(crate) fn root_value(&mut self, value: &JSValue) -> Option<Rooted> {
+        value.as_objref().map(|objref| self.root(objref))
+    }
+
+    /// Mark-and-sweep garbage collection.
+    /// The roots are the fixed slots (including the global object), the current scope
+    /// with the scopes it returns to, the temporaries of running statements and [`Rooted`]
+    /// handles. Returns the number of freed objects.
+    /// Weak collections and `WeakRef`s forget the freed objects, `FinalizationRegistry`
+    /// callbacks for them are left to [`Heap::run_jobs()`].
+    /// ```
+    /// # use slothjs::{Heap, JSObject};
+    /// let mut heap = Heap::new();
+    /// let kept = heap.alloc(JSObject::new());
+    /// let _rooted = heap.root(kept);
+    /// let garbage = heap.alloc(JSObject::new());
+    ///
+    /// assert_eq!(heap.collect(), 1);
+    /// assert!(heap.try_get(garbage).is_none());
+    /// ```
+    pub fn collect(&mut self) -> usize {
+        self.collect_with(&[])
+    }
+
+    /// Like [`Heap::collect()`], but also keeps `extra_roots` alive.
+    pub(crate) fn collect_with(&mut self, extra_roots: &[JSRef]) -> usize {
+        self.roots.retain(|(_, root)| root.strong_count() > 0);
+
+        let mut marked = vec![false; self.objects.len()];
+        let mut worklist = (0..Self::USERSTART as u32)
+            .map(JSRef::fixed)
+            .collect::<Vec<_>>();
+        worklist.extend(self.roots.iter().map(|(objref, _)| *objref));
+        worklist.extend_from_slice(extra_roots);
+        worklist.push(self.scope);
+        worklist.extend_from_slice(&self.temporaries);
+        worklist.extend_from_slice(&self.kept_alive);
+        for job in self.jobs.iter() {
+            worklist.push(job.func);
+            worklist.extend(job.arguments.iter().filter_map(JSValue::as_objref));
+        }
+        loop {
+            while let Some(objref) = worklist.pop() {
+                if !self.is_valid(objref) || marked[objref.index()] {
+                    continue;
+                }
+                marked[objref.index()] = true;
+                self.objects[objref.index()].trace(&mut |r| worklist.push(r));
+            }
+
+            // a value of a WeakMap is alive while both the map and its key are
+            let is_marked = |objref: JSRef| self.is_valid(objref) && marked[objref.index()];
+            for (index, object) in self.objects.iter().enumerate() {
+                let ObjectValue::WeakMap(entries) = &object.value else {
+                    continue;
+                };
+                if marked[index] {
+                    let values = entries.reachable_values(&is_marked);
+                    let refs = values.filter_map(JSValue::as_objref);
+                    worklist.extend(refs.filter(|&objref| !is_marked(objref)));
+                }
+            }
+            if worklist.is_empty() {
+                break;
+            }
+        }
+
+        // the survivors forget the dead objects they refer to weakly
+        let generations = &self.generations;
+        let is_alive = |objref: JSRef| {
+            let index = objref.index();
+            index < marked.len() && marked[index] && generations[index] == objref.generation
+        };
+        for (index, object) in self.objects.iter_mut().enumerate() {
+            if !marked[index] {
+                continue;
+            }
+            for (func, held) in object.value.sweep_weak(&is_alive) {
+                let arguments = vec![held];
+                self.jobs.push(Job { func, arguments });
+            }
+        }
+
+        let live_before = self.object_count();
+        let mut was_free = vec![false; self.objects.len()];
+        for &index in self.free.iter() {
+            was_free[index] = true;
+        }
+        for index in Self::USERSTART..self.objects.len() {
+            let generation = self.generations[index];
+            if marked[index] || was_free[index] || generation == Self::RETIRED {
+                continue;
+            }
+            self.objects[index] = JSObject::free_slot();
+            self.generations[index] = generation + 1;
+            if generation + 1 == Self::RETIRED {
+                self.retired += 1;
+            }
+        }
+
+        // retired slots stay, so that `alloc()` never pushes into them
+        let is_reusable = |index: usize| !marked[index] && self.generations[index] != Self::RETIRED;
+        while self.objects.len() > Self::USERSTART && is_reusable(self.objects.len() - 1) {
+            self.objects.pop();
+            self.sizes.pop();
+        }
+        self.free.clear();
+        for index in (Self::USERSTART..self.objects.len()).rev() {
+            if is_reusable(index) {
+                self.free.push(index);
+            }
+        }
+
+        let live = self.object_count();
+        self.allocated = 0;
+        self.gc_threshold = usize::max(Self::GC_MIN_THRESHOLD, live);
+        self.measure();
+        live_before - live
+    }
+
+    /// Measures every object and string anew.
+    fn measure(&mut self) {
+        self.sizes = self.objects.iter().map(JSObject::memory_size).collect();
+        self.dirty.clear();
+        self.object_bytes = self.sizes.iter().sum();
+        self.string_bytes = self.count_strings();
+        let used = self.memory_used();
+        self.gc_memory_threshold = match self.memory_limit {
+            Some(limit) => used + limit.saturating_sub(used) / 2,
+            None => usize::MAX,
+        };
+    }
+
+    /// The bytes of the strings that objects refer to, counting each string once.
+    fn count_strings(&self) -> usize {
+        let mut seen = HashSet::new();
+        let mut bytes = 0;
+        for object in self.objects.iter() {
+            object.for_each_value(&mut |value| {
+                if let Some((ident, len)) = value.string_ident() {
+                    if seen.insert(ident) {
+                        bytes += len;
+                    }
+                }
+            });
+        }
+        bytes
+    }
+
+    /// Measures the objects that might have changed since their last measurement.
+    fn measure_dirty(&mut self) {
+        for index in self.dirty.drain(..) {
+            if let Some(object) = self.objects.get(index) {
+                let size = object.memory_size();
+                self.object_bytes = self.object_bytes - self.sizes[index] + size;
+                self.sizes[index] = size;
+            }
+        }
+    }
+
+    fn memory_used(&self) -> usize {
+        self.object_bytes + self.string_bytes
+    }
+
+    /// Limits the approximate memory (in bytes) that objects and strings on the heap
+    /// can take, `None` for no limit. Going over it aborts the script
+    /// with [`Abort::OutOfMemory`]. Usage is estimated: see [`Heap::memory_stats()`].
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) {
+        self.memory_limit = limit;
+        self.measure();
+    }
+
+    /// The current estimate of the memory taken by objects (with their properties
+    /// and elements) and by the strings they refer to. This measures the whole heap.
+    pub fn memory_stats(&mut self) -> MemoryStats {
+        self.measure();
+        MemoryStats {
+            objects: self.object_count(),
+            object_bytes: self.object_bytes,
+            string_bytes: self.string_bytes,
+            limit: self.memory_limit,
+        }
+    }
+
+    /// Accounts for a new string of `bytes` before it is made:
+    /// aborts if it does not fit into the memory limit.
+    pub(crate) fn charge_string(&mut self, bytes: usize) -> JSResult<()> {
+        self.string_bytes += bytes;
+        self.check_memory()
+    }
+
+    /// Checks that `bytes` more fit into the memory limit before they are allocated,
+    /// e.g. for the contents of an ArrayBuffer; aborts if they do not.
+    pub(crate) fn check_allocation(&mut self, bytes: usize) -> JSResult<()> {
+        let Some(limit) = self.memory_limit else {
+            return Ok(());
+        };
+        self.measure_dirty();
+        if self.memory_used().saturating_add(bytes) > limit {
+            self.measure();
+            if self.memory_used().saturating_add(bytes) > limit {
+                return Err(Exception::Abort(Abort::OutOfMemory));
+            }
+        }
+        Ok(())
+    }
+
+    /// Aborts with [`Abort::OutOfMemory`] if the heap has gone over its memory limit.
+    pub(crate) fn check_memory(&mut self) -> JSResult<()> {
+        let Some(limit) = self.memory_limit else {
+            return Ok(());
+        };
+        self.measure_dirty();
+        if self.memory_used() > limit {
+            // the charged strings might have been dropped already
+            self.string_bytes = self.count_strings();
+            if self.memory_used() > limit {
+                return Err(Exception::Abort(Abort::OutOfMemory));
+            }
+        }
+        Ok(())
+    }
+
+    /// A safe point for an automatic collection: `collect()` if there was enough
+    /// allocations and no host function call is in progress.
+    pub(crate) fn maybe_collect(&mut self) {
+        if self.should_collect() {
+            self.collect();
+        }
+    }
+
+    /// Checks if an automatic collection is due at this safe point.
+    pub(crate) fn should_collect(&self) -> bool {
+        self.native_calls == 0
+            && (self.allocated >= self.gc_threshold
+                || self.memory_used() >= self.gc_memory_threshold)
+    }
+
+    /// Runs `action` like a host function: no automatic collection happens during it.
+    pub(crate) fn without_collection<T>(&mut self, action: impl FnOnce(&mut Heap) -> T) -> T {
+        self.native_calls += 1;
+        let result = action(self);
+        self.native_calls -= 1;
+        result
+    }
+
+    /// Starts a statement of the tree-walking interpreter, gives the mark to pass
+    /// to [`Heap::leave_statement()`].
+    pub(crate) fn enter_statement(&mut self) -> usize {
+        self.statements += 1;
+        self.temporaries.len()
+    }
+
+    /// Ends a statement: the temporaries it rooted are not needed anymore.
+    pub(crate) fn leave_statement(&mut self, mark: usize) {
+        self.statements -= 1;
+        self.release_temporaries(mark);
+    }
+
+    /// Keeps `objref` alive until the end of the current statement: the tree-walking
+    /// interpreter keeps intermediate values (e.g. evaluated arguments) on the Rust stack,
+    /// where the collector does not see them. Nothing is rooted outside of statements
+    /// or while a host function is running, no automatic collection happens then.
+    pub(crate) fn root_temporary(&mut self, objref: JSRef) {
+        if self.statements > 0 && self.native_calls == 0 {
+            self.temporaries.push(objref);
+        }
+    }
+
+    /// Roots `value` if it is a reference, see [`Heap::root_temporary()`].
+    pub(crate) fn root_temporary_value(&mut self, value: &JSValue) {
+        if let Some(objref) = value.as_objref() {
+            self.root_temporary(objref);
+        }
+    }
+
+    /// Roots the value or the object of the place of `result`, see [`Heap::root_temporary()`].
+    pub(crate) fn root_interpreted(&mut self, result: &Interpreted) {
+        match result {
+            Interpreted::Member { of, .. } | Interpreted::Slot { of, .. } => {
+                self.root_temporary(*of)
+            }
+            Interpreted::Value(value) => self.root_temporary_value(value),
+        }
+    }
+
+    /// The mark to release the temporaries rooted after now, e.g. in a loop.
+    pub(crate) fn temporaries_mark(&self) -> usize {
+        self.temporaries.len()
+    }
+
+    pub(crate) fn release_temporaries(&mut self, mark: usize) {
+        self.temporaries.truncate(mark);
     }
 
     pub fn alloc_func(&mut self, func: HostFn) -> JSRef {
@@ -117,23 +840,73 @@ impl Heap {
         self.alloc(func_obj)
     }
 
+    /// Like `.alloc_func()`, but also sets the `name` of the function.
+    pub fn alloc_named_func(&mut self, name: &str, func: HostFn) -> JSRef {
+        let mut func_obj = JSObject::from_func(func);
+        func_obj.set_function_signature(name, 0).unwrap();
+        self.alloc(func_obj)
+    }
+
+    /// Hands `bytes` to the heap as a new ArrayBuffer, without copying them.
+    /// ```
+    /// # use slothjs::Heap;
+    /// let mut heap = Heap::new();
+    /// let buffer = heap.alloc_array_buffer(vec![1, 2, 3]);
+    /// assert_eq!(heap.array_buffer(buffer).as_deref(), Some(&[1, 2, 3][..]));
+    /// ```
+    pub fn alloc_array_buffer(&mut self, bytes: Vec<u8>) -> JSRef {
+        let mut object = JSObject::new();
+        object.proto = Heap::ARRAY_BUFFER_PROTO;
+        object.value = ObjectValue::ArrayBuffer(Rc::new(RefCell::new(bytes)));
+        self.alloc(object)
+    }
+
+    /// The bytes of an ArrayBuffer or the bytes that a typed array or a DataView
+    /// views, `None` if `objref` is none of them.
+    pub fn array_buffer(&self, objref: JSRef) -> Option<Ref<'_, [u8]>> {
+        let (bytes, range) = match &self.get(objref).value {
+            ObjectValue::ArrayBuffer(bytes) => (bytes, 0..bytes.borrow().len()),
+            ObjectValue::TypedArray(_, view) | ObjectValue::DataView(view) => {
+                (&view.bytes, view.offset..view.offset + view.length)
+            }
+            _ => return None,
+        };
+        Some(Ref::map(bytes.borrow(), |bytes| &bytes[range]))
+    }
+
+    /// Wraps `closure` into a function object with a fresh `prototype` object.
+    pub(crate) fn alloc_closure(&mut self, closure: Closure) -> JSResult<JSRef> {
+        let function_object = JSObject::from_closure(closure);
+        let function_ref = self.alloc(function_object);
+
+        let prototype_ref = self.alloc(JSObject::new());
+        self.get_mut(function_ref)
+            .define_own_property("prototype", Access::WRITE)?;
+        self.get_mut(function_ref)
+            .set_property("prototype", prototype_ref)?;
+        self.get_mut(prototype_ref)
+            .set_hidden("constructor", function_ref)?;
+        Ok(function_ref)
+    }
+
     pub fn init_class(&mut self, proto: JSRef, class: &HostClass) -> JSResult<()> {
         let mut proto_object = JSObject::new();
 
         for &(name, func) in class.methods.iter() {
             //let name = JSString::from(name);
-            let func = self.alloc_func(func);
+            let func = self.alloc_named_func(name, func);
             proto_object.set_hidden(name, func)?;
         }
 
         *self.get_mut(proto) = proto_object;
 
         let mut ctor_object = JSObject::from_func(class.constructor);
+        ctor_object.set_function_signature(class.name, 0)?;
         ctor_object.set_system("prototype", proto)?;
 
         for &(name, func) in class.static_methods.iter() {
             //let name = JSString::from(name);
-            let func = self.alloc_func(func);
+            let func = self.alloc_named_func(name, func);
             ctor_object.set_hidden(name, func)?;
         }
 
@@ -145,6 +918,12 @@ impl Heap {
         Ok(())
     }
 
+    /// Defines `proto[name]` as an accessor property with a native getter and no setter.
+    pub fn init_getter(&mut self, proto: JSRef, name: &str, getter: HostFn) -> JSResult<()> {
+        let getter = self.alloc_named_func(&format!("get {}", name), getter);
+        self.define_accessor(proto, name, Some(getter), None, Access::HIDDEN)
+    }
+
     /// this is a hack to distingiush e.g. `new Boolean(true)` and `Boolean(true)` calls.
     #[allow(clippy::match_like_matches_macro)]
     pub(crate) fn smells_fresh(&self, objref: JSRef) -> bool {
@@ -170,98 +949,236 @@ impl Heap {
                 let value = self.object_from_json(jval);
                 object.set_property(key, value).unwrap();
             }
-            JSValue::Ref(self.alloc(object))
+            JSValue::from(self.alloc(object))
         } else if let Some(jarray) = json.as_array() {
             let storage = (jarray.iter())
                 .map(|jval| self.object_from_json(jval))
-                .collect();
+                .collect::<Vec<_>>();
             let object = JSObject::from_array(storage);
-            JSValue::Ref(self.alloc(object))
+            JSValue::from(self.alloc(object))
         } else {
             JSValue::try_from(json).expect("primitive JSON") // not Object/Array, must be primitive
         }
     }
 
-    /// Find out what `this` currently is.
-    pub fn interpret_this(&mut self) -> JSRef {
-        self.lookup_var(Self::SCOPE_THIS)
-            .expect("no this in the current scope")
-            .to_ref(self)
-            .expect("this must be JSValue::Ref")
+    /// Find out what `this` currently is, `Heap::NULL` if it is not an object.
+    pub fn interpret_this(&self) -> JSRef {
+        self.this_value().as_objref().unwrap_or(Heap::NULL)
     }
 
-    pub(crate) fn is_scope(&self, objref: JSRef) -> bool {
-        objref == Self::GLOBAL || self.get(objref).get_own_value(Heap::SAVED_SCOPE).is_some()
+    /// The value of `this`: e.g. `undefined` in a strict mode function called without one.
+    pub fn this_value(&self) -> JSValue {
+        match &self.get(self.scope).value {
+            ObjectValue::Scope(scope) => scope.this.clone(),
+            _ => JSValue::from(Heap::GLOBAL),
+        }
     }
 
-    /// If there's a local scope, return a `JSRef` to it.
-    pub(crate) fn local_scope(&self) -> Option<JSRef> {
-        match self.get(Heap::GLOBAL).get_own_value(Heap::LOCAL_SCOPE) {
-            Some(JSValue::Ref(scope_ref)) => Some(scope_ref),
-            _ => None,
+    /// What a function with `layout` sees as `this` when called with `this`:
+    /// sloppy mode code gets the global object for `null`/`undefined` and objects for primitives.
+    pub(crate) fn bind_this(&mut self, layout: &Layout, this: JSValue) -> JSValue {
+        if layout.strict {
+            this
+        } else if this.is_nullish() {
+            JSValue::from(Heap::GLOBAL)
+        } else {
+            JSValue::from(this.objectify(self))
+        }
+    }
+
+    /// Is the code of the current frame strict mode code?
+    pub fn is_strict(&self) -> bool {
+        match &self.get(self.scope).value {
+            ObjectValue::Scope(scope) => scope.layout.strict,
+            _ => false,
+        }
+    }
+
+    /// Sloppy mode code ignores assignments that fail with `TypeError::SET_READONLY`
+    /// or `TypeError::NOT_EXTENSIBLE`, strict mode code throws them.
+    pub(crate) fn ignore_set_readonly(&self, e: Exception) -> JSResult<()> {
+        match self.is_strict() {
+            true => Err(e),
+            false => error::ignore_set_readonly(e),
         }
     }
 
+    /// Checks if `objref` is the global scope or a frame.
+    pub(crate) fn is_scope(&self, objref: JSRef) -> bool {
+        objref == Self::GLOBAL || matches!(self.get(objref).value, ObjectValue::Scope(_))
+    }
+
+    /// The current scope: a frame or [`Heap::GLOBAL`].
+    pub(crate) fn scope_ref(&self) -> JSRef {
+        self.scope
+    }
+
     fn scope(&self) -> &JSObject {
-        let scope_ref = self.local_scope().unwrap_or(Heap::GLOBAL);
-        self.get(scope_ref)
+        self.get(self.scope)
     }
 
     pub fn scope_mut(&mut self) -> &mut JSObject {
-        let scope_ref = self.local_scope().unwrap_or(Heap::GLOBAL);
-        self.get_mut(scope_ref)
+        self.get_mut(self.scope)
+    }
+
+    fn frame(&self) -> &Scope {
+        match &self.scope().value {
+            ObjectValue::Scope(scope) => scope,
+            _ => panic!("no frame"), // yes, panic, this interpreter is broken.
+        }
+    }
+
+    fn frame_mut(&mut self) -> &mut Scope {
+        match &mut self.scope_mut().value {
+            ObjectValue::Scope(scope) => scope,
+            _ => panic!("no frame"),
+        }
     }
 
-    fn declare_variable(&mut self, var: &Identifier) -> JSResult<()> {
-        if !self.scope().properties.contains_key(var.as_str()) {
+    /// Declares a variable by name in the current scope, unless it is already there.
+    pub(crate) fn declare_variable(&mut self, var: &Identifier) -> JSResult<()> {
+        let scope = self.scope();
+        let declared = scope.properties.contains_key(var.as_str())
+            || matches!(&scope.value, ObjectValue::Scope(frame) if frame.find_slot(var.as_str()).is_some());
+        if !declared {
             let name = var.as_str();
-            self.scope_mut().set_nonconf(name, JSValue::Undefined)?;
+            self.scope_mut().set_nonconf(name, JSValue::UNDEFINED)?;
         }
         Ok(())
     }
 
-    pub fn declare<'a>(
+    /// Makes closures of `functions` in the current scope and assigns them to their names.
+    pub fn declare_functions<'a>(
         &mut self,
-        variables: impl Iterator<Item = &'a Identifier>,
         functions: impl Iterator<Item = &'a FunctionDeclaration>,
     ) -> JSResult<()> {
-        for var in variables {
-            self.declare_variable(var)?;
-        }
         for func in functions {
-            let name = &func.id;
-            self.declare_variable(name)?;
-
             let closure = func.function.interpret(self)?;
             let closure = closure.to_value(self)?;
-            self.scope_mut().set_property(name.as_str(), closure)?;
+            self.variable(&func.id.0, func.slot)
+                .put_value(closure, self)?;
         }
         Ok(())
     }
 
-    pub fn lookup_var(&self, name: &str) -> Option<Interpreted> {
-        if let Some(local_ref) = self.local_scope() {
-            let local = self.get(local_ref);
-            if local.get_own_value(name).is_some() {
-                return Some(Interpreted::member(local_ref, name));
+    /// The place of a variable resolved to `slot` (see [`crate::resolve`]),
+    /// `Slot::Dynamic` variables are looked up by `name`.
+    pub(crate) fn variable(&self, name: &JSString, slot: Slot) -> Interpreted {
+        match slot {
+            Slot::Dynamic => self
+                .lookup_var(name)
+                .unwrap_or_else(|| Interpreted::member(Heap::GLOBAL, name)),
+            Slot::Local(index) => Interpreted::Slot {
+                of: self.scope,
+                index,
+            },
+            Slot::Boxed(index) => Interpreted::Slot {
+                of: self.get(self.scope).slot(index).to_ref().expect("a cell"),
+                index: 0,
+            },
+            Slot::Captured(index) => Interpreted::Slot {
+                of: self.frame().captured[index as usize],
+                index: 0,
+            },
+        }
+    }
+
+    /// The place of a variable to assign to: strict mode code cannot create
+    /// a global variable by assigning an undeclared one.
+    pub(crate) fn assignable_variable(&self, name: &JSString, slot: Slot) -> JSResult<Interpreted> {
+        let place = self.variable(name, slot);
+        if let Interpreted::Member {
+            of: Heap::GLOBAL,
+            name,
+        } = &place
+        {
+            if self.is_strict() && self.get(Heap::GLOBAL).get_own_value(name).is_none() {
+                return Err(Exception::no_reference(name.clone()));
             }
+        }
+        Ok(place)
+    }
 
-            // captured scopes lookup
-            let mut scope_ref = match local.get_own_value(Self::CAPTURED_SCOPE) {
-                Some(JSValue::Ref(scope_ref)) => scope_ref,
-                _ => Heap::NULL,
-            };
-            while scope_ref != Heap::NULL {
-                let scope = self.get(scope_ref);
-                if scope.get_own_value(name).is_some() {
-                    return Some(Interpreted::member(scope_ref, name));
-                }
+    /// Sets a parameter or `arguments` of the current frame.
+    pub(crate) fn init_slot(&mut self, slot: Slot, value: JSValue) {
+        match self.variable(&JSString::default(), slot) {
+            Interpreted::Slot { of, index } => *self.get_mut(of).slot_mut(index) = value,
+            _ => panic!("{:?} is not a local variable", slot),
+        }
+    }
+
+    /// Gives a `let` binding a fresh `undefined` value (in a fresh cell, if it is boxed).
+    pub(crate) fn reset_slot(&mut self, slot: Slot) {
+        let value = match slot {
+            Slot::Local(_) => JSValue::UNDEFINED,
+            Slot::Boxed(_) => JSValue::from(self.alloc_cell()),
+            _ => panic!("{:?} is not a local variable", slot),
+        };
+        match slot {
+            Slot::Local(index) | Slot::Boxed(index) => {
+                self.frame_mut().slots[index as usize] = value;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn alloc_cell(&mut self) -> JSRef {
+        self.alloc(JSObject {
+            proto: Heap::NULL,
+            value: ObjectValue::Cell(JSValue::UNDEFINED),
+            properties: Properties::new(),
+            extensible: true,
+        })
+    }
+
+    /// The cells a closure made in the current frame captures, see [`Layout::captures`].
+    pub(crate) fn capture(&self, layout: &Layout) -> Rc<[JSRef]> {
+        (layout.captures.iter())
+            .map(
+                |(_, slot)| match self.variable(&JSString::default(), *slot) {
+                    Interpreted::Slot { of, .. } => of,
+                    _ => panic!("{:?} cannot be captured", slot),
+                },
+            )
+            .collect()
+    }
 
-                scope_ref = match scope.get_own_value(Self::CAPTURED_SCOPE) {
-                    Some(JSValue::Ref(scope_ref)) => scope_ref,
-                    _ => Heap::NULL,
+    /// Looks up a variable by name: in the frames the current one is nested in
+    /// (e.g. for `eval()`), then in the global scope. The slots of the current frame
+    /// are already resolved statically, only names declared by `eval()` are looked up in it.
+    pub fn lookup_var(&self, name: &str) -> Option<Interpreted> {
+        let mut scope_ref = self.scope;
+        while scope_ref != Heap::GLOBAL && scope_ref != Heap::NULL {
+            let scope = self.get(scope_ref);
+            if scope.get_own_value(name).is_some() {
+                return Some(Interpreted::member(scope_ref, name));
+            }
+            let frame = match &scope.value {
+                ObjectValue::Scope(frame) => frame,
+                _ => break,
+            };
+            if scope_ref == self.scope {
+                scope_ref = frame.parent;
+                continue;
+            }
+            if let Some(index) = frame.find_slot(name) {
+                let place = match frame.layout.boxed[index] {
+                    true => Interpreted::Slot {
+                        of: frame.slots[index].to_ref().expect("a cell"),
+                        index: 0,
+                    },
+                    false => Interpreted::Slot {
+                        of: scope_ref,
+                        index: index as u32,
+                    },
                 };
+                return Some(place);
+            }
+            if let Some(index) = frame.find_captured(name) {
+                let of = frame.captured[index];
+                return Some(Interpreted::Slot { of, index: 0 });
             }
+            scope_ref = frame.parent;
         }
 
         self.get(Heap::GLOBAL)
@@ -278,67 +1195,83 @@ impl Heap {
     ///     Interpreted::from(Heap::ARRAY_PROTO)
     /// );
     /// ```
-    pub fn lookup_path(&self, mut names: &[&str]) -> JSResult<Interpreted> {
-        let mut scoperef = self.local_scope().unwrap_or(Heap::GLOBAL);
+    pub fn lookup_path(&mut self, names: &[&str]) -> JSResult<Interpreted> {
+        let (&first, mut names) = match names.split_first() {
+            Some(split) => split,
+            None => return Ok(Interpreted::from(self.scope)),
+        };
+        let mut objref = (self.lookup_var(first))
+            .ok_or_else(|| Exception::no_reference(first))?
+            .to_ref(self)?;
         while let Some((&name, rest)) = names.split_first() {
             names = rest;
-            let nameval = self.get(scoperef).get_own_value(name).ok_or_else(|| {
-                Exception::attr_type_error(TypeError::CANNOT_GET_PROPERTY, scoperef, name)
+            let nameval = self.get(objref).get_own_value(name).ok_or_else(|| {
+                Exception::attr_type_error(TypeError::CANNOT_GET_PROPERTY, objref, name)
             })?;
-            scoperef = nameval.to_ref()?;
+            objref = nameval.to_ref()?;
         }
-        Ok(Interpreted::from(scoperef))
+        Ok(Interpreted::from(objref))
     }
 
-    pub fn enter_new_scope<T, F>(
+    /// Runs `action` in a new frame of a function call with `layout`.
+    pub(crate) fn enter_frame<T, F>(
         &mut self,
-        this_ref: JSRef,
-        captured_scope: JSRef,
-        mut action: F,
+        layout: &Rc<Layout>,
+        this: JSValue,
+        captured: Rc<[JSRef]>,
+        action: F,
     ) -> JSResult<T>
     where
-        F: FnMut(&mut Heap) -> JSResult<T>,
+        F: FnOnce(&mut Heap) -> JSResult<T>,
     {
-        self.push_scope(this_ref)?;
-        if captured_scope != Heap::NULL {
-            let name = Self::CAPTURED_SCOPE;
-            self.scope_mut().set_system(name, captured_scope)?;
-        }
+        let this = self.bind_this(layout, this);
+        self.push_frame(layout, this, captured, Heap::GLOBAL);
         let result = action(self);
-        self.pop_scope()?;
+        self.pop_scope();
         result
     }
 
-    fn push_scope(&mut self, this_ref: JSRef) -> JSResult<JSRef> {
-        let old_scope_ref = self.local_scope().unwrap_or(Heap::GLOBAL);
-
-        let mut scope_object = JSObject::new();
-
-        scope_object.set_system(Self::SAVED_SCOPE, old_scope_ref)?;
-        scope_object.set_system(Self::SCOPE_THIS, this_ref)?;
-
-        let new_scope_ref = self.alloc(scope_object);
-        self.get_mut(Heap::GLOBAL)
-            .set_even_nonwritable(Self::LOCAL_SCOPE, new_scope_ref)?;
-        Ok(new_scope_ref)
+    /// Makes a frame for `layout` the current scope.
+    /// Names not found in the frame are looked up in `parent`.
+    /// `this` is taken as is, see [`Heap::bind_this()`] for what a function call gets.
+    pub(crate) fn push_frame(
+        &mut self,
+        layout: &Rc<Layout>,
+        this: JSValue,
+        captured: Rc<[JSRef]>,
+        parent: JSRef,
+    ) -> JSRef {
+        let slots = (layout.boxed.iter())
+            .map(|&boxed| match boxed {
+                true => JSValue::from(self.alloc_cell()),
+                false => JSValue::UNDEFINED,
+            })
+            .collect();
+        let frame = Scope {
+            layout: Rc::clone(layout),
+            slots,
+            captured,
+            this,
+            saved: self.scope,
+            parent,
+        };
+        self.scope = self.alloc(JSObject {
+            proto: Heap::NULL,
+            value: ObjectValue::Scope(Box::new(frame)),
+            properties: Properties::new(),
+            extensible: true,
+        });
+        self.scope
     }
 
-    fn pop_scope(&mut self) -> JSResult<()> {
-        let this_scope_ref = self.local_scope().expect(".pop_scope without local scope"); // yes, panic, this interpreter is broken.
-        let this_scope_object = self.get(this_scope_ref);
-        let saved_scope_ref = (this_scope_object.properties)
-            .get(Self::SAVED_SCOPE)
-            .and_then(|prop| prop.to_ref())
-            .expect("saved scope is not a reference"); // yes, panic, this interpreter is broken.
-
-        let global = self.get_mut(Heap::GLOBAL);
-        if saved_scope_ref == Heap::GLOBAL {
-            global.properties.remove(Self::LOCAL_SCOPE);
-        } else {
-            global.set_even_nonwritable(Self::LOCAL_SCOPE, saved_scope_ref)?;
-        }
+    /// Returns to the scope that was current before the current frame.
+    pub(crate) fn pop_scope(&mut self) {
+        self.scope = self.frame().saved;
+    }
 
-        Ok(())
+    /// Makes `scope_ref` the current scope, e.g. to restore it after an exception.
+    pub(crate) fn set_local_scope(&mut self, scope_ref: JSRef) {
+        self.scope = scope_ref;
     }
 
     /// Find the location of `propname` on the prototype chain of `objref`.
@@ -355,21 +1288,468 @@ impl Heap {
         None
     }
 
+    // The internal methods of objects: ordinary objects work with their properties,
+    // proxies call the traps of their handlers, see [`crate::builtin::proxy`].
+
+    /// Looks up `name` in `objref` and its prototypes, ES: \[\[Get\]\].
+    /// `None` if it is not found, `receiver` is the `this` of a proxy `get` trap.
+    pub fn lookup_member(
+        &mut self,
+        mut objref: JSRef,
+        name: &JSString,
+        receiver: JSRef,
+    ) -> JSResult<Option<JSValue>> {
+        while objref != Heap::NULL {
+            let object = self.get(objref);
+            if let Some(proxy) = object.as_proxy() {
+                return proxy::get(proxy, name, receiver, self).map(Some);
+            }
+            if let Some(value) = object.get_own_value(name) {
+                if value.as_objref().is_some() {
+                    if let Some((getter, _)) = self.own_accessor(objref, name) {
+                        return self.call_accessor(getter, receiver, name, vec![]).map(Some);
+                    }
+                }
+                return Ok(Some(value));
+            }
+            objref = object.proto;
+        }
+        Ok(None)
+    }
+
+    /// The value of `objref[name]`, `undefined` if there is none.
+    pub fn get_member(&mut self, objref: JSRef, name: &JSString) -> JSResult<JSValue> {
+        let value = self.lookup_member(objref, name, objref)?;
+        Ok(value.unwrap_or(JSValue::UNDEFINED))
+    }
+
+    /// Assigns `objref[name]`, ES: \[\[Set\]\]. Fails with `TypeError::SET_READONLY`
+    /// or `TypeError::NOT_EXTENSIBLE` if the property cannot be set.
+    pub fn set_member(&mut self, objref: JSRef, name: &JSString, value: JSValue) -> JSResult<()> {
+        let object = self.get(objref);
+        if let Some(proxy) = object.as_proxy() {
+            return proxy::set(proxy, name, value, objref, self);
+        }
+        if object.as_typed_array().is_some() && value.as_objref().is_some() {
+            // elements are numbers: objects are converted here, where valueOf() can be called
+            let n = value.to_number(self)?;
+            return self.get_mut(objref).set_property(name, n);
+        }
+        let value = self.array_length_value(objref, name, value)?;
+        // the first `name` on the prototype chain might be an accessor
+        let mut holder = objref;
+        while holder != Heap::NULL {
+            let object = self.get(holder);
+            if object.as_proxy().is_some() {
+                break;
+            }
+            if let Some((_, setter)) = self.own_accessor(holder, name) {
+                if setter.is_none() {
+                    let what = Interpreted::from(objref);
+                    return Err(Exception::attr_type_error(
+                        TypeError::SET_READONLY,
+                        what,
+                        name.as_str(),
+                    ));
+                }
+                let argument = Interpreted::from(value);
+                return self
+                    .call_accessor(setter, objref, name, vec![argument])
+                    .map(|_| ());
+            }
+            if object.properties.contains_key(name) {
+                break;
+            }
+            holder = object.proto;
+        }
+        self.get_mut(objref).set_property(name, value)
+    }
+
+    /// Converts an object assigned to the `length` of an array to a number,
+    /// so that its `valueOf()` is called; other values are left as they are.
+    pub(crate) fn array_length_value(
+        &mut self,
+        objref: JSRef,
+        name: &str,
+        value: JSValue,
+    ) -> JSResult<JSValue> {
+        let is_array = self.get(objref).as_array().is_some();
+        if !(is_array && name == "length" && value.as_objref().is_some()) {
+            return Ok(value);
+        }
+        Ok(JSValue::from(value.to_number(self)?))
+    }
+
+    /// The getter and the setter of `objref[name]` if it is an own accessor property.
+    pub(crate) fn own_accessor(
+        &self,
+        objref: JSRef,
+        name: &str,
+    ) -> Option<(Option<JSRef>, Option<JSRef>)> {
+        let properties = &self.get(objref).properties;
+        let (slot, access) = properties.find(name)?;
+        if !access.accessor() {
+            return None;
+        }
+        let accessor_ref = properties.slot(slot).as_objref()?;
+        match self.get(accessor_ref).value {
+            ObjectValue::Accessor { get, set } => Some((get, set)),
+            _ => None,
+        }
+    }
+
+    /// Calls a getter or a setter on `this_ref`, a missing one gives `undefined`.
+    fn call_accessor(
+        &mut self,
+        accessor: Option<JSRef>,
+        this_ref: JSRef,
+        name: &JSString,
+        arguments: Vec<Interpreted>,
+    ) -> JSResult<JSValue> {
+        let Some(func_ref) = accessor else {
+            return Ok(JSValue::UNDEFINED);
+        };
+        let call = CallContext::from(arguments)
+            .with_this(this_ref)
+            .with_name(name.clone());
+        self.execute(func_ref, call)?.to_value(self)
+    }
+
+    /// Makes `name` an own accessor property of `objref`. A `None` getter or setter
+    /// keeps the one that the property has already, if it is an accessor.
+    pub(crate) fn define_accessor(
+        &mut self,
+        objref: JSRef,
+        name: &str,
+        get: Option<JSRef>,
+        set: Option<JSRef>,
+        access: Access,
+    ) -> JSResult<()> {
+        let (get, set) = match self.own_accessor(objref, name) {
+            Some((old_get, old_set)) => (get.or(old_get), set.or(old_set)),
+            None => (get, set),
+        };
+        let accessor = JSObject {
+            proto: Heap::NULL,
+            value: ObjectValue::Accessor { get, set },
+            ..JSObject::new()
+        };
+        let accessor_ref = self.alloc(accessor);
+        let access = (access - Access::WRITE) | Access::ACCESSOR;
+        let content = Content::from(accessor_ref);
+        (self.get_mut(objref)).set_maybe_nonwritable(name, content, access, true)
+    }
+
+    /// Defines the property `name` of the object literal `objref`,
+    /// `value` is the function of a method, a getter or a setter if `kind` says so.
+    pub(crate) fn init_property(
+        &mut self,
+        objref: JSRef,
+        name: &str,
+        value: JSValue,
+        kind: PropertyKind,
+    ) -> JSResult<()> {
+        if kind == PropertyKind::Init {
+            return self.get_mut(objref).init_property(name, value);
+        }
+        let func_ref = value.to_ref()?;
+        if let ObjectValue::Closure(closure) = &mut self.get_mut(func_ref).value {
+            closure.home = Some(objref);
+        }
+        let access = Access::ENUM | Access::CONF;
+        match kind {
+            PropertyKind::Get => self.define_accessor(objref, name, Some(func_ref), None, access),
+            PropertyKind::Set => self.define_accessor(objref, name, None, Some(func_ref), access),
+            _ => self.get_mut(objref).init_property(name, value),
+        }
+    }
+
+    /// Is there `name` in `objref` or its prototypes? ES: \[\[HasProperty\]\]
+    pub fn has_member(&mut self, mut objref: JSRef, name: &JSString) -> JSResult<bool> {
+        while objref != Heap::NULL {
+            let object = self.get(objref);
+            if let Some(proxy) = object.as_proxy() {
+                return proxy::has(proxy, name, self);
+            }
+            if object.get_own_value(name).is_some() {
+                return Ok(true);
+            }
+            objref = object.proto;
+        }
+        Ok(false)
+    }
+
+    /// Removes the own property `name`, gives `false` if it cannot be removed.
+    /// ES: \[\[Delete\]\]
+    pub fn delete_member(&mut self, objref: JSRef, name: &JSString) -> JSResult<bool> {
+        if let Some(proxy) = self.get(objref).as_proxy() {
+            return proxy::delete(proxy, name, self);
+        }
+        let object = self.get_mut(objref);
+        if let Some(array) = object.as_array_mut() {
+            if let Some(index) = JSArray::index(name) {
+                array.remove(index as usize);
+                return Ok(true);
+            }
+        }
+        if let (Some((element, view)), Some(index)) =
+            (object.as_typed_array(), JSArray::index(name))
+        {
+            return Ok(index as usize >= view.len(element));
+        }
+        match object.properties.find(name) {
+            Some((_, access)) if !access.configurable() => Ok(false),
+            Some(_) => {
+                object.properties.remove(name);
+                Ok(true)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// The own keys of `objref`: array indexes in order and `length`, then properties
+    /// in the order they were added. ES: \[\[OwnPropertyKeys\]\]
+    pub fn own_keys(&mut self, objref: JSRef) -> JSResult<Vec<JSString>> {
+        let object = self.get(objref);
+        if let Some(proxy) = object.as_proxy() {
+            return proxy::own_keys(proxy, self);
+        }
+        let mut keys = match (object.as_array(), object.as_typed_array()) {
+            (Some(array), _) => (array.elements())
+                .map(|(index, _)| JSString::from(index.to_string()))
+                .chain(Some(JSString::from("length")))
+                .collect(),
+            (_, Some((element, view))) => (0..view.len(element))
+                .map(|index| JSString::from(index.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        keys.extend(object.properties.keys());
+        Ok(keys)
+    }
+
+    /// The own property `name` of `objref`, if any. ES: \[\[GetOwnProperty\]\]
+    pub fn own_property(&mut self, objref: JSRef, name: &JSString) -> JSResult<Option<Property>> {
+        let object = self.get(objref);
+        if let Some(proxy) = object.as_proxy() {
+            return proxy::own_property(proxy, name, self);
+        }
+        if let Some(property) = object.properties.get(name) {
+            if let Some((get, set)) = self.own_accessor(objref, name) {
+                let content = Content::Accessor { get, set };
+                let access = property.access;
+                return Ok(Some(Property { content, access }));
+            }
+            return Ok(Some(property));
+        }
+        // array elements and properties like `length` are not in `properties`
+        let access = match (object.as_array(), JSArray::index(name)) {
+            (Some(_), Some(_)) => Access::DEFAULT,
+            (Some(array), None) => Access::new(false, false, array.is_length_writable()),
+            (None, Some(_)) if object.as_typed_array().is_some() => Access::DEFAULT,
+            _ => Access::empty(),
+        };
+        let value = object.get_own_value(name);
+        Ok(value.map(|value| Property {
+            content: Content::from(value),
+            access,
+        }))
+    }
+
+    /// Defines the own property `name` of `objref` as described by the object `descref`,
+    /// gives `false` if a proxy refuses to. ES: \[\[DefineOwnProperty\]\]
+    pub fn define_member(
+        &mut self,
+        objref: JSRef,
+        name: &JSString,
+        descref: JSRef,
+    ) -> JSResult<bool> {
+        if let Some(proxy) = self.get(objref).as_proxy() {
+            return proxy::define(proxy, name, descref, self);
+        }
+        builtin::object::define_property(objref, name.clone(), descref, self)?;
+        Ok(true)
+    }
+
+    /// ES: \[\[GetPrototypeOf\]\]
+    pub fn prototype_of(&mut self, objref: JSRef) -> JSResult<JSRef> {
+        let object = self.get(objref);
+        match object.as_proxy() {
+            Some(proxy) => proxy::prototype_of(proxy, self),
+            None => Ok(object.proto),
+        }
+    }
+
+    /// Makes `proto` the prototype of `objref`. Gives `false` if `objref` is not extensible
+    /// or this would make a cycle. ES: \[\[SetPrototypeOf\]\]
+    pub fn set_prototype(&mut self, objref: JSRef, proto: JSRef) -> JSResult<bool> {
+        let object = self.get(objref);
+        if let Some(proxy) = object.as_proxy() {
+            return proxy::set_prototype(proxy, proto, self);
+        }
+        if object.proto == proto {
+            return Ok(true);
+        }
+        if !object.is_extensible() {
+            return Ok(false);
+        }
+        let mut ancestor = proto;
+        while ancestor != Heap::NULL {
+            let ancestor_object = self.get(ancestor);
+            if ancestor == objref {
+                return Ok(false);
+            }
+            if ancestor_object.as_proxy().is_some() {
+                break;
+            }
+            ancestor = ancestor_object.proto;
+        }
+        self.get_mut(objref).proto = proto;
+        Ok(true)
+    }
+
+    /// ES: \[\[IsExtensible\]\]
+    pub fn is_extensible(&mut self, objref: JSRef) -> JSResult<bool> {
+        let object = self.get(objref);
+        match object.as_proxy() {
+            Some(proxy) => proxy::is_extensible(proxy, self),
+            None => Ok(object.is_extensible()),
+        }
+    }
+
+    /// Gives `false` if a proxy refuses to. ES: \[\[PreventExtensions\]\]
+    pub fn prevent_extensions(&mut self, objref: JSRef) -> JSResult<bool> {
+        match self.get(objref).as_proxy() {
+            Some(proxy) => proxy::prevent_extensions(proxy, self),
+            None => {
+                self.get_mut(objref).prevent_extensions();
+                Ok(true)
+            }
+        }
+    }
+
+    /// `new callee(...arguments)`, ES: \[\[Construct\]\]
+    pub fn construct(&mut self, callee: JSRef, mut arguments: Vec<Interpreted>) -> JSResult<JSRef> {
+        let mut funcref = callee;
+        loop {
+            match &self.get(funcref).value {
+                // `new` ignores the bound `this` of a bound function, but not its bound arguments
+                ObjectValue::BoundFunction(bound) => {
+                    arguments = bound.with_arguments(arguments);
+                    funcref = bound.target;
+                }
+                ObjectValue::Proxy(proxy) => {
+                    return proxy::construct(*proxy, funcref, arguments, self);
+                }
+                _ => break,
+            }
+        }
+        let prototype_ref = (self.get(funcref))
+            .get_own_value("prototype")
+            .ok_or_else(|| {
+                Exception::attr_type_error(TypeError::CANNOT_GET_PROPERTY, callee, "prototype")
+            })?
+            .to_ref()?;
+
+        let mut object = JSObject::new();
+        object.proto = prototype_ref;
+        let object_ref = self.alloc(object);
+
+        let call = CallContext::from(arguments)
+            .with_this(object_ref)
+            .with_name("<constructor>".into());
+        let result = self.execute(funcref, call)?.to_value(self)?;
+        match result.as_objref() {
+            Some(objref) if objref != Heap::NULL => Ok(objref),
+            _ => Ok(object_ref),
+        }
+    }
+
+    /// Runs `program` on the selected [`Backend`].
+    pub fn run(&mut self, program: &Program) -> JSResult<Interpreted> {
+        let outermost = self.stack_base == 0;
+        if outermost {
+            self.stack_base = stack_address();
+        }
+        let result = match self.backend {
+            Backend::TreeWalker => program.interpret(self),
+            Backend::Bytecode => bytecode::run(program, self),
+        };
+        if outermost {
+            self.stack_base = 0;
+        }
+        result
+    }
+
+    /// Runs the jobs that the finished script left, e.g. `FinalizationRegistry` callbacks
+    /// for the objects it had registered and a collection has freed,
+    /// and the jobs that they leave in turn. [`crate::runtime::Runtime::evaluate()`]
+    /// does this after each script.
+    pub fn run_jobs(&mut self) -> JSResult<()> {
+        self.kept_alive.clear();
+        let outermost = self.stack_base == 0;
+        if outermost {
+            self.stack_base = stack_address();
+        }
+        let mut result = Ok(());
+        while !self.jobs.is_empty() && result.is_ok() {
+            let job = self.jobs.remove(0);
+            let arguments: Vec<_> = job.arguments.into_iter().map(Interpreted::from).collect();
+            let call = CallContext::from(arguments).with_name("<job>".into());
+            result = self.execute(job.func, call).map(|_| ());
+            self.kept_alive.clear();
+        }
+        if outermost {
+            self.stack_base = 0;
+        }
+        result
+    }
+
+    /// Keeps `target` of a `WeakRef` alive until the current job is over,
+    /// so `deref()` gives the same object throughout a script.
+    pub(crate) fn keep_during_job(&mut self, target: JSRef) {
+        self.kept_alive.push(target);
+    }
+
     /// A shortcut for `interpretable.evaluate(&mut heap)`.
     pub fn evaluate<T: Interpretable>(&mut self, interpretable: &T) -> JSResult<JSValue> {
         interpretable.interpret(self)?.to_value(self)
     }
 
     /// Given a `func_ref` to a closure or a native call and a set of arguments,
-    /// executes the function. `this_ref` is bound as `this`.
+    /// executes the function. `call.this` is bound as `this`.
     pub fn execute(&mut self, func_ref: JSRef, mut call: CallContext) -> JSResult<Interpreted> {
         if call.loc.as_ref().is_none() {
             call.loc = self.loc.clone();
         }
+        self.check_call_depth()?;
+        // the callee and its arguments might be only on the Rust stack
+        let mark = self.temporaries_mark();
+        self.root_temporary(func_ref);
+        self.root_temporary_value(&call.this);
+        for argument in call.arguments.iter() {
+            self.root_interpreted(argument);
+        }
+        let native = match &self.get(func_ref).value {
+            ObjectValue::Closure(closure) => closure.code.is_some(),
+            ObjectValue::BoundFunction(_) => false,
+            _ => true,
+        };
+        self.native_calls += native as usize;
         // Yes, we do need a clone() to workaround borrow checker:
-        match &self.get(func_ref).value {
-            ObjectValue::HostFn(vmcall) => vmcall.clone().call(call, self),
+        self.call_depth += 1;
+        let result = match &self.get(func_ref).value {
+            ObjectValue::HostFn(vmcall) => {
+                let vmcall = vmcall.clone();
+                // host functions work on objects: a primitive `this` gets boxed
+                if call.this_ref == Heap::NULL && !call.this.is_nullish() {
+                    call.this_ref = call.this.objectify(self);
+                }
+                vmcall.call(call, self)
+            }
             ObjectValue::Closure(closure) => closure.clone().call(call, self),
+            ObjectValue::BoundFunction(bound) => bound.clone().call(call, self),
+            ObjectValue::Proxy(proxy) => proxy::apply(*proxy, call, self),
             _ => {
                 let callee = Interpreted::Member {
                     of: call.this_ref,
@@ -377,7 +1757,14 @@ impl Heap {
                 };
                 Err(Exception::type_error(TypeError::NOT_CALLABLE, callee))
             }
+        };
+        self.call_depth -= 1;
+        self.native_calls -= native as usize;
+        self.release_temporaries(mark);
+        if let Ok(result) = &result {
+            self.root_interpreted(result);
         }
+        result
     }
 
     pub fn throw<T>(&self, exc: Exception) -> JSResult<T> {
@@ -1,12 +1,14 @@
-use crate::error::TypeError;
 use crate::prelude::*;
 use crate::Jump;
 
 use crate::ast::*; // yes, EVERYTHING
 use crate::builtin;
+use crate::error::{RangeError, TypeError};
+use crate::shape::PropertyCache;
 use crate::{
-    function::Closure, object::Access, CallContext, Exception, Heap, Interpreted, JSObject,
-    JSResult, JSValue,
+    function::{Closure, TailCall},
+    object::{JSArray, ObjectValue},
+    BigInt, CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult, JSValue,
 };
 
 // ==============================================
@@ -25,8 +27,22 @@ pub trait Interpretable {
 
 impl Interpretable for Program {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
-        heap.declare(self.variables.iter(), self.functions.iter())?;
-        self.body.interpret(heap)
+        for var in self.variables.iter() {
+            heap.declare_variable(var)?;
+        }
+        for func in self.functions.iter() {
+            heap.declare_variable(&func.id)?;
+        }
+
+        // `let` bindings of the program live in its own frame
+        let this = heap.this_value();
+        let parent = heap.scope_ref();
+        heap.push_frame(&self.layout, this, Rc::from([]), parent);
+        let result = heap
+            .declare_functions(self.functions.iter())
+            .and_then(|()| self.body.interpret(heap));
+        heap.pop_scope();
+        result
     }
 }
 
@@ -35,7 +51,11 @@ impl Interpretable for Program {
 impl Interpretable for Statement {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         heap.loc = self.loc.clone();
-        match &self.stmt {
+        heap.maybe_collect();
+        heap.check_memory()?;
+        heap.check_stack()?;
+        let mark = heap.enter_statement();
+        let result = match &self.stmt {
             Stmt::Empty => Ok(Interpreted::VOID),
             Stmt::Expr(stmt) => stmt.interpret(heap),
             Stmt::Block(stmt) => stmt.interpret(heap),
@@ -51,7 +71,9 @@ impl Interpretable for Statement {
             Stmt::Try(stmt) => stmt.interpret(heap),
             Stmt::Variable(stmt) => stmt.interpret(heap),
             Stmt::Function(stmt) => stmt.interpret(heap),
-        }
+        };
+        heap.leave_statement(mark);
+        result
     }
 }
 
@@ -59,17 +81,15 @@ impl Interpretable for Statement {
 
 impl Interpretable for BlockStatement {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
-        let this_ref = heap.interpret_this();
-        let outer_scope = heap.local_scope().unwrap_or(Heap::GLOBAL);
-        heap.enter_new_scope(this_ref, outer_scope, |heap| {
-            heap.declare(self.bindings.iter(), [].into_iter())?;
+        for &slot in self.slots.iter() {
+            heap.reset_slot(slot);
+        }
 
-            let mut result = Interpreted::VOID;
-            for stmt in self.body.iter() {
-                result = stmt.interpret(heap)?;
-            }
-            Ok(result)
-        })
+        let mut result = Interpreted::VOID;
+        for stmt in self.body.iter() {
+            result = stmt.interpret(heap)?;
+        }
+        Ok(result)
     }
 }
 
@@ -132,7 +152,10 @@ impl ForStatement {
     /// `do_loop()` executes the loop except its `init` statement.
     /// `init` must be interpreted before this, if needed.
     fn do_loop(&self, heap: &mut Heap) -> Result<(), Exception> {
+        let mark = heap.temporaries_mark();
         while self.should_iterate(heap)? {
+            heap.release_temporaries(mark);
+            heap.tick()?;
             // body
             let result = self.body.interpret(heap);
             match result {
@@ -173,48 +196,21 @@ impl Interpretable for ForStatement {
     }
 }
 
-impl ForInStatement {}
-
-impl Interpretable for ForInStatement {
-    fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
-        let iteratee = self.right.evaluate(heap)?.objectify(heap);
-
-        let assignexpr = match &self.left {
-            ForInTarget::Expr(expr) => expr.clone(),
-            ForInTarget::Var(vardecl) => {
-                debug_assert_eq!(vardecl.declarations.len(), 1);
-                let ident = &vardecl.declarations[0].name;
-                let idexpr = Expr::Identifier(Identifier::from(ident.as_str()));
-                Expression {
-                    expr: idexpr,
-                    loc: None,
-                }
-            }
-        };
-
+impl ForInStatement {
+    fn iterate(&self, iteratee: JSRef, assignexpr: &Expression, heap: &mut Heap) -> JSResult<()> {
         let mut visited = HashSet::new();
         let mut objref = iteratee;
+        let mark = heap.temporaries_mark();
         while objref != Heap::NULL {
-            let object = heap.get(objref);
-            let mut keys = (object.properties.keys())
-                .cloned()
-                .collect::<HashSet<JSString>>();
-            if let Some(array) = object.as_array() {
-                let indices = 0..array.storage.len();
-                keys.extend(indices.map(|i| i.to_string().into()));
-            }
+            // the body might change the prototype chain
+            let _rooted = heap.root(objref);
             // TODO: strings iteration
-
-            for propname in keys.drain() {
-                if visited.contains(&propname) {
+            for propname in heap.own_keys(objref)? {
+                if !visited.insert(propname.clone()) {
                     continue;
                 }
-                visited.insert(propname.clone());
-
-                let object = heap.get(objref);
-                match object.properties.get(&propname) {
+                match heap.own_property(objref, &propname)? {
                     Some(p) if p.access.enumerable() => (),
-                    None if object.as_array().is_some() && propname.parse::<usize>().is_ok() => (),
                     Some(_) => continue, // not enumerable, skip
                     None => continue,    // the property has disappeared!
                 };
@@ -223,25 +219,49 @@ impl Interpretable for ForInStatement {
                     Ok(p) => JSValue::from(p as f64),
                     _ => JSValue::from(propname.as_str()),
                 };
-                assignexpr
-                    .interpret(heap)?
+                heap.release_temporaries(mark);
+                assignment_target(assignexpr, heap)?
                     .put_value(propname, heap)
-                    .or_else(crate::error::ignore_set_readonly)?;
+                    .or_else(|e| heap.ignore_set_readonly(e))?;
 
+                heap.tick()?;
                 match self.body.interpret(heap) {
                     Ok(_) => (),
                     Err(Exception::Jump(Jump::Continue(None))) => continue,
-                    Err(Exception::Jump(Jump::Break(None))) => {
-                        return Ok(Interpreted::VOID);
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
+                    Err(Exception::Jump(Jump::Break(None))) => return Ok(()),
+                    Err(e) => return Err(e),
                 }
             }
 
-            objref = heap.get(objref).proto;
+            objref = heap.prototype_of(objref)?;
         }
+        Ok(())
+    }
+}
+
+impl Interpretable for ForInStatement {
+    fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
+        let iteratee = self.right.evaluate(heap)?.objectify(heap);
+
+        let assignexpr = match &self.left {
+            ForInTarget::Expr(expr) => expr.clone(),
+            ForInTarget::Var(vardecl) => {
+                debug_assert_eq!(vardecl.declarations.len(), 1);
+                let decl = &vardecl.declarations[0];
+                let idexpr = Expr::Variable(Variable {
+                    name: decl.name.clone(),
+                    slot: decl.slot,
+                });
+                Expression {
+                    expr: idexpr,
+                    loc: None,
+                }
+            }
+        };
+
+        // the body might trigger a collection, `iteratee` might be unreachable otherwise
+        let _rooted = heap.root(iteratee);
+        self.iterate(iteratee, &assignexpr, heap)?;
         Ok(Interpreted::VOID)
     }
 }
@@ -314,12 +334,53 @@ impl Interpretable for ReturnStatement {
         let ReturnStatement(argument) = self;
         let returned = match argument {
             None => Interpreted::VOID,
+            Some(argexpr) if heap.is_strict() => interpret_tail(argexpr, heap)?,
             Some(argexpr) => argexpr.interpret(heap)?,
         };
         Err(Exception::Jump(Jump::Return(returned)))
     }
 }
 
+/// Interprets `expr` in tail position of strict mode code: a call there is not made,
+/// but returned as a [`Jump::TailCall`].
+fn interpret_tail(expr: &Expression, heap: &mut Heap) -> JSResult<Interpreted> {
+    match &expr.expr {
+        Expr::Call(callexpr) => {
+            let (func_ref, call) = callexpr.prepare(heap)?;
+            // host functions like `eval()` need the frame of their caller
+            if !matches!(heap.get(func_ref).value, ObjectValue::Closure(_)) {
+                return heap.execute(func_ref, call);
+            }
+            let arguments = (call.arguments.iter())
+                .map(|argument| argument.to_value(heap))
+                .collect::<JSResult<Vec<JSValue>>>()?;
+            let tail = TailCall {
+                func_ref,
+                this: call.this,
+                name: call.method_name,
+                arguments,
+            };
+            Err(Exception::Jump(Jump::TailCall(Box::new(tail))))
+        }
+        Expr::Conditional(condexpr) => {
+            let cond = condexpr.condexpr.evaluate(heap)?;
+            if cond.boolify(heap) {
+                interpret_tail(&condexpr.thenexpr, heap)
+            } else {
+                interpret_tail(&condexpr.elseexpr, heap)
+            }
+        }
+        Expr::Sequence(SequenceExpression(exprs)) if !exprs.is_empty() => {
+            let (last, exprs) = exprs.split_last().unwrap();
+            for expr in exprs.iter() {
+                expr.interpret(heap)?.to_value(heap)?;
+            }
+            interpret_tail(last, heap)
+        }
+        _ => expr.interpret(heap),
+    }
+}
+
 impl Interpretable for ThrowStatement {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let ThrowStatement(exc_expr) = self;
@@ -329,40 +390,78 @@ impl Interpretable for ThrowStatement {
 }
 
 impl CatchClause {
-    fn interpret(&self, exc: &Exception, heap: &mut Heap) -> JSResult<Interpreted> {
-        let this_ref = heap.interpret_this();
-        let scope_ref = heap.local_scope().unwrap_or(Heap::GLOBAL);
-
-        heap.enter_new_scope(this_ref, scope_ref, |heap| {
-            let error_value: JSValue = match exc {
-                Exception::UserThrown(errval) => errval.clone(),
-                Exception::Jump(_) => {
-                    panic!("Impossible to catch: {:?}", exc)
-                }
-                //Exception::ReferenceNotFound(ident) => { // TODO: ReferenceError
-                _ => {
-                    let message = format!("{:?}", exc);
-                    let args = vec![Interpreted::from(message)];
-                    let errval = builtin::error::error_constructor(
-                        CallContext::from(args)
-                            .with_this(this_ref)
-                            .with_name("Error".into()),
-                        heap,
-                    )?;
-                    errval.to_value(heap)?
-                }
-            };
+    /// The value that `catch` binds for `exc`: errors of the interpreter itself
+    /// become `Error` objects.
+    pub(crate) fn caught_value(exc: &Exception, heap: &mut Heap) -> JSResult<JSValue> {
+        match exc {
+            Exception::UserThrown(errval) => Ok(errval.clone()),
+            Exception::Jump(_) => {
+                panic!("Impossible to catch: {:?}", exc)
+            }
+            Exception::Range(err) => {
+                let this_ref = heap.interpret_this();
+                let args = vec![Interpreted::from(err.message())];
+                let errval = builtin::error::error_constructor(
+                    CallContext::from(args)
+                        .with_this(this_ref)
+                        .with_name("RangeError".into()),
+                    heap,
+                )?;
+                let errref = errval.to_ref(heap)?;
+                heap.get_mut(errref).set_hidden("name", "RangeError")?;
+                Ok(JSValue::from(errref))
+            }
+            //Exception::ReferenceNotFound(ident) => { // TODO: ReferenceError
+            _ => {
+                let this_ref = heap.interpret_this();
+                let message = format!("{:?}", exc);
+                let args = vec![Interpreted::from(message)];
+                let errval = builtin::error::error_constructor(
+                    CallContext::from(args)
+                        .with_this(this_ref)
+                        .with_name("Error".into()),
+                    heap,
+                )?;
+                errval.to_value(heap)
+            }
+        }
+    }
 
-            heap.scope_mut()
-                .set_nonconf(self.param.0.as_str(), error_value)?;
-            self.body.interpret(heap)
-        })
+    fn interpret(&self, exc: &Exception, heap: &mut Heap) -> JSResult<Interpreted> {
+        let error_value = CatchClause::caught_value(exc, heap)?;
+        heap.reset_slot(self.slot);
+        heap.variable(&self.param.0, self.slot)
+            .put_value(error_value, heap)?;
+        self.body.interpret(heap)
     }
 }
 
 impl TryStatement {
-    fn run_finalizer(&self, heap: &mut Heap) -> Result<(), Exception> {
+    /// Makes the call of a [`Jump::TailCall`] right away: `try` must see how it completes.
+    fn complete_tail_call(result: JSResult<Interpreted>, heap: &mut Heap) -> JSResult<Interpreted> {
+        match result {
+            Err(Exception::Jump(Jump::TailCall(tail))) => {
+                let (func_ref, call) = tail.into_call();
+                let returned = heap.execute(func_ref, call)?;
+                Err(Exception::Jump(Jump::Return(returned)))
+            }
+            _ => result,
+        }
+    }
+
+    /// Runs the `finally` block, if any, while the `pending` completion waits.
+    fn run_finalizer(&self, pending: &JSResult<Interpreted>, heap: &mut Heap) -> JSResult<()> {
         if let Some(finalizer) = self.finalizer.as_ref() {
+            // the finalizer might trigger a collection, keep the pending value alive
+            let pending = match pending {
+                Ok(Interpreted::Value(value)) => value.clone(),
+                Ok(Interpreted::Member { of, .. } | Interpreted::Slot { of, .. }) => {
+                    JSValue::from(*of)
+                }
+                Err(Exception::UserThrown(value)) => value.clone(),
+                Err(_) => JSValue::UNDEFINED,
+            };
+            let _rooted = heap.root_value(&pending);
             finalizer.interpret(heap)?;
         }
         Ok(())
@@ -372,9 +471,11 @@ impl TryStatement {
 impl Interpretable for TryStatement {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let result = self.block.interpret(heap);
+        let result = TryStatement::complete_tail_call(result, heap);
         match &result {
+            Err(exc) if !exc.is_catchable() => result,
             Ok(_) | Err(Exception::Jump(_)) => {
-                self.run_finalizer(heap)?;
+                self.run_finalizer(&result, heap)?;
                 result
             }
             Err(exc) => {
@@ -382,7 +483,11 @@ impl Interpretable for TryStatement {
                     None => result,
                     Some(catch) => catch.interpret(exc, heap),
                 };
-                self.run_finalizer(heap)?;
+                let result = TryStatement::complete_tail_call(result, heap);
+                if matches!(&result, Err(exc) if !exc.is_catchable()) {
+                    return result;
+                }
+                self.run_finalizer(&result, heap)?;
                 result
             }
         }
@@ -393,16 +498,10 @@ impl Interpretable for VariableDeclaration {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         for decl in &self.declarations {
             if let Some(initexpr) = decl.init.as_ref() {
-                let name = &decl.name.0;
                 let value = initexpr.evaluate(heap)?;
-                match heap.lookup_var(name) {
-                    Some(Interpreted::Member { of, name }) => {
-                        heap.get_mut(of)
-                            .set_property(name.as_str(), value)
-                            .or_else(crate::error::ignore_set_readonly)?;
-                    }
-                    _ => panic!("variable not declared: {}", name),
-                }
+                heap.variable(&decl.name.0, decl.slot)
+                    .put_value(value, heap)
+                    .or_else(|e| heap.ignore_set_readonly(e))?;
             }
         }
         Ok(Interpreted::VOID)
@@ -417,26 +516,51 @@ impl Interpretable for FunctionDeclaration {
 }
 
 impl Interpretable for Expression {
+    fn evaluate(&self, heap: &mut Heap) -> JSResult<JSValue> {
+        let value = match &self.expr {
+            Expr::Member(expr) => {
+                heap.loc = self.loc.clone();
+                heap.check_stack()?;
+                expr.evaluate(heap)
+            }
+            _ => self.interpret(heap)?.to_value(heap),
+        };
+        // the result of a subexpression waits on the Rust stack for its siblings
+        if let Ok(value) = &value {
+            heap.root_temporary_value(value);
+        }
+        value
+    }
+
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         heap.loc = self.loc.clone();
-        match &self.expr {
+        // nested expressions recurse on the native stack:
+        heap.check_stack()?;
+        let result = match &self.expr {
             Expr::Literal(expr) => expr.interpret(heap),
             Expr::Identifier(expr) => expr.interpret(heap),
+            Expr::Variable(expr) => expr.interpret(heap),
             Expr::BinaryOp(expr) => expr.interpret(heap),
             Expr::LogicalOp(expr) => expr.interpret(heap),
             Expr::Call(expr) => expr.interpret(heap),
             Expr::Array(expr) => expr.interpret(heap),
             Expr::Member(expr) => expr.interpret(heap),
+            Expr::Chain(expr) => expr.interpret(heap),
             Expr::Object(expr) => expr.interpret(heap),
             Expr::Assign(expr) => expr.interpret(heap),
+            Expr::LogicalAssign(expr) => expr.interpret(heap),
             Expr::Conditional(expr) => expr.interpret(heap),
             Expr::Unary(expr) => expr.interpret(heap),
             Expr::Update(expr) => expr.interpret(heap),
             Expr::Sequence(expr) => expr.interpret(heap),
             Expr::Function(expr) => expr.interpret(heap),
             Expr::New(expr) => expr.interpret(heap),
-            Expr::This => Ok(Interpreted::from(heap.interpret_this())),
+            Expr::This => Ok(Interpreted::from(heap.this_value())),
+        };
+        if let Ok(result) = &result {
+            heap.root_interpreted(result);
         }
+        result
     }
 }
 
@@ -447,13 +571,33 @@ impl Interpretable for Literal {
     }
 }
 
+/// An identifier that has not been resolved, e.g. in an AST made by hand.
 impl Interpretable for Identifier {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
-        let name = &self.0;
-        let place = heap
-            .lookup_var(name)
-            .unwrap_or_else(|| Interpreted::member(Heap::GLOBAL, name));
-        Ok(place)
+        Ok(heap.variable(&self.0, Slot::Dynamic))
+    }
+}
+
+impl Interpretable for Variable {
+    fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
+        Ok(heap.variable(&self.name.0, self.slot))
+    }
+}
+
+impl Interpretable for LogicalAssignmentExpression {
+    fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
+        let LogicalAssignmentExpression(leftexpr, op, valexpr) = self;
+
+        let assignee = leftexpr.interpret(heap)?;
+        let oldvalue = assignee.to_value(heap)?;
+        if op.short_circuits(&oldvalue, heap) {
+            return Ok(Interpreted::Value(oldvalue));
+        }
+        let value = valexpr.evaluate(heap)?;
+        assignee
+            .put_value(value.clone(), heap)
+            .or_else(|e| heap.ignore_set_readonly(e))?;
+        Ok(Interpreted::Value(value))
     }
 }
 
@@ -472,60 +616,73 @@ impl Interpretable for LogicalExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let LogicalExpression(lexpr, op, rexpr) = self;
         let lval = lexpr.evaluate(heap)?;
-        let value = match (lval.boolify(heap), op) {
-            (true, BoolOp::And) | (false, BoolOp::Or) => rexpr.evaluate(heap)?,
-            _ => lval,
+        let value = match op.short_circuits(&lval, heap) {
+            false => rexpr.evaluate(heap)?,
+            true => lval,
         };
         Ok(Interpreted::Value(value))
     }
 }
 
+impl BoolOp {
+    /// Whether `left op right` is `left` without evaluating `right`.
+    pub(crate) fn short_circuits(&self, left: &JSValue, heap: &Heap) -> bool {
+        match self {
+            BoolOp::And => !left.boolify(heap),
+            BoolOp::Or => left.boolify(heap),
+            BoolOp::Nullish => !left.is_nullish(),
+        }
+    }
+}
+
 impl BinOp {
-    fn compute(&self, lval: &JSValue, rval: &JSValue, heap: &mut Heap) -> JSResult<JSValue> {
+    pub(crate) fn compute(
+        &self,
+        lval: &JSValue,
+        rval: &JSValue,
+        heap: &mut Heap,
+    ) -> JSResult<JSValue> {
+        if let (Some(a), Some(b)) = (lval.as_int32(), rval.as_int32()) {
+            if let Some(value) = self.compute_int32(a, b) {
+                return Ok(value);
+            }
+        }
         Ok(match self {
-            BinOp::EqEq => JSValue::from(JSValue::loose_eq(lval, rval, heap)),
-            BinOp::NotEq => JSValue::from(!JSValue::loose_eq(lval, rval, heap)),
+            BinOp::EqEq => JSValue::from(JSValue::loose_eq(lval, rval, heap)?),
+            BinOp::NotEq => JSValue::from(!JSValue::loose_eq(lval, rval, heap)?),
             BinOp::EqEqEq => JSValue::from(JSValue::strict_eq(lval, rval, heap)),
             BinOp::NotEqEq => JSValue::from(!JSValue::strict_eq(lval, rval, heap)),
-            BinOp::Less => JSValue::compare(lval, rval, heap, |a, b| a < b, |a, b| a < b),
-            BinOp::Greater => JSValue::compare(lval, rval, heap, |a, b| a > b, |a, b| a > b),
-            BinOp::LtEq => JSValue::compare(lval, rval, heap, |a, b| a <= b, |a, b| a <= b),
-            BinOp::GtEq => JSValue::compare(lval, rval, heap, |a, b| a >= b, |a, b| a >= b),
+            BinOp::Less => JSValue::from(lval.is_less_than(rval, true, heap)? == Some(true)),
+            BinOp::Greater => JSValue::from(rval.is_less_than(lval, false, heap)? == Some(true)),
+            BinOp::LtEq => JSValue::from(rval.is_less_than(lval, false, heap)? == Some(false)),
+            BinOp::GtEq => JSValue::from(lval.is_less_than(rval, true, heap)? == Some(false)),
             BinOp::Plus => JSValue::plus(lval, rval, heap)?,
-            BinOp::Minus => JSValue::minus(lval, rval, heap)?,
-            BinOp::Star => JSValue::numerically(lval, rval, heap, |a, b| a * b),
-            BinOp::Slash => JSValue::numerically(lval, rval, heap, |a, b| a / b),
-            BinOp::Percent => JSValue::numerically(lval, rval, heap, |a, b| a % b),
-            BinOp::Pipe => {
-                let bitor = |a, b| (a as i32 | b as i32) as f64;
-                JSValue::numerically(lval, rval, heap, bitor)
-            }
-            BinOp::Hat => {
-                let bitxor = |a, b| (a as i32 ^ b as i32) as f64;
-                JSValue::numerically(lval, rval, heap, bitxor)
-            }
-            BinOp::Ampersand => {
-                let bitand = |a, b| (a as i32 & b as i32) as f64;
-                JSValue::numerically(lval, rval, heap, bitand)
-            }
-            BinOp::LtLt => {
-                let bitshl = |a, b| ((a as i32) << ((b as u32) & 0x1f) as i32) as f64;
-                JSValue::numerically(lval, rval, heap, bitshl)
-            }
-            BinOp::GtGt => {
-                let bitshr = |a, b| ((a as i32) >> ((b as u32) & 0x1f) as i32) as f64;
-                JSValue::numerically(lval, rval, heap, bitshr)
-            }
-            BinOp::GtGtGt => {
-                let bitshru = |a, b| ((a as u32) >> (b as u32) & 0x1f) as f64;
-                JSValue::numerically(lval, rval, heap, bitshru)
+            BinOp::Minus
+            | BinOp::Star
+            | BinOp::StarStar
+            | BinOp::Slash
+            | BinOp::Percent
+            | BinOp::Pipe
+            | BinOp::Hat
+            | BinOp::Ampersand
+            | BinOp::LtLt
+            | BinOp::GtGt
+            | BinOp::GtGtGt => {
+                let lnum = lval.to_numeric(heap)?;
+                let rnum = rval.to_numeric(heap)?;
+                match (lnum.as_bigint(), rnum.as_bigint()) {
+                    (Some(a), Some(b)) => JSValue::from(self.compute_bigint(a, b)?),
+                    (None, None) => {
+                        let (a, b) = (lnum.as_number().unwrap(), rnum.as_number().unwrap());
+                        JSValue::from(self.compute_number(a, b))
+                    }
+                    _ => return Err(Exception::type_error(TypeError::BIGINT_MIX, rnum)),
+                }
             }
             BinOp::In => {
                 let prop = lval.stringify(heap)?;
                 let objref = rval.to_ref()?;
-                let object = heap.get(objref);
-                let found = object.lookup_value(&prop, heap).is_some();
-                JSValue::from(found)
+                JSValue::from(heap.has_member(objref, &prop)?)
             }
             BinOp::InstanceOf => {
                 let constructor = rval.to_ref()?;
@@ -537,6 +694,98 @@ impl BinOp {
             }
         })
     }
+
+    /// Arithmetic and bitwise operations on numbers.
+    fn compute_number(&self, a: f64, b: f64) -> f64 {
+        match self {
+            BinOp::Minus => a - b,
+            BinOp::Star => a * b,
+            // unlike `powf()`, `1 ** NaN` and `(-1) ** Infinity` are NaN
+            BinOp::StarStar if b.is_nan() || (a.abs() == 1.0 && b.is_infinite()) => f64::NAN,
+            BinOp::StarStar => a.powf(b),
+            BinOp::Slash => a / b,
+            BinOp::Percent => a % b,
+            BinOp::Pipe => (a as i32 | b as i32) as f64,
+            BinOp::Hat => (a as i32 ^ b as i32) as f64,
+            BinOp::Ampersand => (a as i32 & b as i32) as f64,
+            BinOp::LtLt => ((a as i32) << ((b as u32) & 0x1f) as i32) as f64,
+            BinOp::GtGt => ((a as i32) >> ((b as u32) & 0x1f) as i32) as f64,
+            BinOp::GtGtGt => ((a as u32) >> (b as u32) & 0x1f) as f64,
+            _ => unreachable!("{:?} is not arithmetic", self),
+        }
+    }
+
+    /// Arithmetic and bitwise operations on BigInts.
+    fn compute_bigint(&self, a: &BigInt, b: &BigInt) -> JSResult<BigInt> {
+        let too_big =
+            || Exception::range_error(RangeError::BIGINT_TOO_BIG, JSValue::from(b.clone()));
+        let division_by_zero =
+            || Exception::range_error(RangeError::DIVISION_BY_ZERO, JSValue::from(0));
+        // a negative shift count shifts the other way
+        let shift = |left: bool| -> JSResult<BigInt> {
+            let left = left != b.is_negative();
+            let count = b.to_u64().or_else(|| (-b).to_u64()).unwrap_or(u64::MAX);
+            match left {
+                true if a.is_zero() => Ok(BigInt::zero()),
+                true if a.bits().saturating_add(count) > BigInt::MAX_BITS => Err(too_big()),
+                true => Ok(a.shift_left(count)),
+                false => Ok(a.shift_right(count)),
+            }
+        };
+        Ok(match self {
+            BinOp::Minus => a - b,
+            BinOp::Star if a.bits() + b.bits() > BigInt::MAX_BITS => return Err(too_big()),
+            BinOp::Star => a * b,
+            BinOp::StarStar if b.is_negative() => {
+                let b = JSValue::from(b.clone());
+                return Err(Exception::range_error(RangeError::NEGATIVE_EXPONENT, b));
+            }
+            BinOp::StarStar => a.checked_pow(b).ok_or_else(too_big)?,
+            BinOp::Slash => a.checked_div(b).ok_or_else(division_by_zero)?,
+            BinOp::Percent => a.checked_rem(b).ok_or_else(division_by_zero)?,
+            BinOp::Pipe => a | b,
+            BinOp::Hat => a ^ b,
+            BinOp::Ampersand => a & b,
+            BinOp::LtLt => shift(true)?,
+            BinOp::GtGt => shift(false)?,
+            BinOp::GtGtGt => {
+                let b = JSValue::from(b.clone());
+                return Err(Exception::type_error(TypeError::BIGINT_UNSIGNED_SHIFT, b));
+            }
+            _ => unreachable!("{:?} is not arithmetic", self),
+        })
+    }
+
+    /// The fast path of [`BinOp::compute()`] for int32 operands.
+    /// `None` if the result is not an int32 (e.g. on overflow or `-0`).
+    fn compute_int32(&self, a: i32, b: i32) -> Option<JSValue> {
+        let value = match self {
+            BinOp::EqEq | BinOp::EqEqEq => JSValue::from(a == b),
+            BinOp::NotEq | BinOp::NotEqEq => JSValue::from(a != b),
+            BinOp::Less => JSValue::from(a < b),
+            BinOp::Greater => JSValue::from(a > b),
+            BinOp::LtEq => JSValue::from(a <= b),
+            BinOp::GtEq => JSValue::from(a >= b),
+            BinOp::Plus => JSValue::from(a.checked_add(b)?),
+            BinOp::Minus => JSValue::from(a.checked_sub(b)?),
+            BinOp::Star => match a.checked_mul(b)? {
+                0 if a < 0 || b < 0 => return None,
+                n => JSValue::from(n),
+            },
+            BinOp::Percent => match a.checked_rem(b)? {
+                0 if a < 0 => return None,
+                n => JSValue::from(n),
+            },
+            BinOp::Pipe => JSValue::from(a | b),
+            BinOp::Hat => JSValue::from(a ^ b),
+            BinOp::Ampersand => JSValue::from(a & b),
+            BinOp::LtLt => JSValue::from(a.wrapping_shl(b as u32)),
+            BinOp::GtGt => JSValue::from(a.wrapping_shr(b as u32)),
+            BinOp::GtGtGt => JSValue::from((a as u32).wrapping_shr(b as u32) as i64),
+            _ => return None,
+        };
+        Some(value)
+    }
 }
 
 impl Interpretable for BinaryExpression {
@@ -549,51 +798,96 @@ impl Interpretable for BinaryExpression {
     }
 }
 
+impl UnOp {
+    /// Applies the operation to a value; `delete` of a value is always `true`.
+    pub(crate) fn compute(&self, value: &JSValue, heap: &mut Heap) -> JSResult<JSValue> {
+        Ok(match self {
+            UnOp::Exclamation => JSValue::from(!value.boolify(heap)),
+            UnOp::Minus => {
+                let num = value.to_numeric(heap)?;
+                match num.as_bigint() {
+                    Some(n) => JSValue::from(-n),
+                    None => JSValue::from(-num.as_number().unwrap()),
+                }
+            }
+            // unary plus does not convert BigInts, it throws
+            UnOp::Plus => JSValue::from(value.to_number(heap)?),
+            UnOp::Tilde => {
+                let num = value.to_numeric(heap)?;
+                if let Some(n) = num.as_bigint() {
+                    return Ok(JSValue::from(!n));
+                }
+                let num = num.as_number().unwrap();
+                let num = if f64::is_nan(num) { 0.0 } else { num };
+                JSValue::from(-(1.0 + num))
+            }
+            UnOp::Void => JSValue::UNDEFINED,
+            UnOp::Typeof => JSValue::from(value.type_of(heap)),
+            UnOp::Delete => JSValue::from(true),
+        })
+    }
+}
+
 impl Interpretable for UnaryExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let UnaryExpression(op, argexpr) = self;
         let arg = argexpr.interpret(heap)?;
-        let argvalue = || arg.to_value(heap);
-        let argnum = || argvalue().map(|val| val.numberify(heap).unwrap_or(f64::NAN));
         let value = match op {
-            UnOp::Exclamation => JSValue::Bool(!argvalue()?.boolify(heap)),
-            UnOp::Minus => JSValue::Number(-argnum()?),
-            UnOp::Plus => JSValue::Number(argnum()?),
-            UnOp::Tilde => {
-                let num = argnum()?;
-                let num = if f64::is_nan(num) { 0.0 } else { num };
-                JSValue::from(-(1.0 + num))
-            }
-            UnOp::Void => JSValue::Undefined,
             UnOp::Typeof => JSValue::from(
-                argvalue()
+                arg.to_value(heap)
                     .map(|val| val.type_of(heap))
                     .unwrap_or("undefined"),
             ),
-            UnOp::Delete => JSValue::from(arg.delete(heap).is_ok()),
+            UnOp::Delete => JSValue::from(arg.delete(heap)?),
+            _ => op.compute(&arg.to_value(heap)?, heap)?,
         };
         Ok(Interpreted::Value(value))
     }
 }
 
+impl UpdOp {
+    /// Gives the old value as a number and the updated value, staying in int32 if possible.
+    pub(crate) fn compute(&self, value: &JSValue, heap: &mut Heap) -> JSResult<(JSValue, JSValue)> {
+        if let Some(n) = value.as_int32() {
+            let updated = match self {
+                UpdOp::Increment => n.checked_add(1),
+                UpdOp::Decrement => n.checked_sub(1),
+            };
+            if let Some(updated) = updated {
+                return Ok((value.clone(), JSValue::from(updated)));
+            }
+        }
+        let oldvalue = value.to_numeric(heap)?;
+        if let Some(old) = oldvalue.as_bigint() {
+            let updated = match self {
+                UpdOp::Increment => old + &BigInt::from(1u64),
+                UpdOp::Decrement => old - &BigInt::from(1u64),
+            };
+            return Ok((oldvalue.clone(), JSValue::from(updated)));
+        }
+        let oldnum = oldvalue.as_number().unwrap();
+        let newnum = match self {
+            UpdOp::Increment => oldnum + 1.0,
+            UpdOp::Decrement => oldnum - 1.0,
+        };
+        Ok((JSValue::from(oldnum), JSValue::from(newnum)))
+    }
+}
+
 impl Interpretable for UpdateExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let UpdateExpression(op, prefix, argexpr) = self;
         let assignee = argexpr.interpret(heap)?;
 
         let oldvalue = assignee.to_value(heap)?;
-        let oldnum = oldvalue.numberify(heap).unwrap_or(f64::NAN);
-        let newnum = match op {
-            UpdOp::Increment => oldnum + 1.0,
-            UpdOp::Decrement => oldnum - 1.0,
-        };
+        let (oldvalue, newvalue) = op.compute(&oldvalue, heap)?;
 
         assignee
-            .put_value(JSValue::from(newnum), heap)
-            .or_else(crate::error::ignore_set_readonly)?;
+            .put_value(newvalue.clone(), heap)
+            .or_else(|e| heap.ignore_set_readonly(e))?;
 
-        let resnum = if *prefix { newnum } else { oldnum };
-        Ok(Interpreted::from(resnum))
+        let result = if *prefix { newvalue } else { oldvalue };
+        Ok(Interpreted::from(result))
     }
 }
 
@@ -601,7 +895,7 @@ impl Interpretable for SequenceExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let SequenceExpression(exprs) = self;
 
-        let mut value = JSValue::Undefined;
+        let mut value = JSValue::UNDEFINED;
         for expr in exprs.iter() {
             value = expr.interpret(heap)?.to_value(heap)?;
         }
@@ -609,59 +903,98 @@ impl Interpretable for SequenceExpression {
     }
 }
 
+impl MemberExpression {
+    /// The name of a non-computed property that [`PropertyCache`] can cache.
+    fn cached_name(&self) -> Option<&JSString> {
+        let MemberExpression(_, propexpr, computed, _) = self;
+        match &propexpr.expr {
+            Expr::Identifier(name) if !computed && PropertyCache::applies(&name.0) => Some(&name.0),
+            _ => None,
+        }
+    }
+
+    /// Gets the object reference for member computation.
+    fn object_ref(&self, heap: &mut Heap) -> JSResult<JSRef> {
+        let objresult = self.0.interpret(heap)?;
+        let objref = match objresult.to_value(heap)? {
+            value if value.is_undefined() => return Err(Exception::not_an_object(objresult)),
+            value => value.objectify(heap),
+        };
+        heap.root_temporary(objref);
+        Ok(objref)
+    }
+
+    /// Computes `object[key]` to the object reference and the property key,
+    /// which is an array index or a string.
+    fn computed(&self, heap: &mut Heap) -> JSResult<(JSRef, JSValue)> {
+        let key = self.1.evaluate(heap)?.to_property_key(heap)?;
+        let objref = self.object_ref(heap)?;
+        Ok((objref, key))
+    }
+}
+
 impl Interpretable for MemberExpression {
+    fn evaluate(&self, heap: &mut Heap) -> JSResult<JSValue> {
+        match self.cached_name() {
+            Some(name) => {
+                let objref = self.object_ref(heap)?;
+                self.3.get(objref, name, heap)
+            }
+            None if self.2 => {
+                let (objref, key) = self.computed(heap)?;
+                let element = key.to_index().and_then(|i| heap.get(objref).get_element(i));
+                match element {
+                    Some(value) => Ok(value),
+                    None => member(objref, key.stringify(heap)?, heap).to_value(heap),
+                }
+            }
+            None => self.interpret(heap)?.to_value(heap),
+        }
+    }
+
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
-        let MemberExpression(objexpr, propexpr, computed) = self;
+        let MemberExpression(_, propexpr, computed, _) = self;
 
-        // compute the name of the property:
-        let propname = if *computed {
-            let propval = propexpr.interpret(heap)?.to_value(heap)?;
-            propval.stringify(heap)?
+        let (objref, propname) = if *computed {
+            let (objref, key) = self.computed(heap)?;
+            (objref, key.stringify(heap)?)
         } else {
-            match &propexpr.expr {
+            let name = match &propexpr.expr {
                 Expr::Identifier(name) => name.0.clone(),
                 _ => panic!("Member(computed=false) property is not an identifier"),
-            }
-        };
-
-        // get the object reference for member computation:
-        let objresult = objexpr.interpret(heap)?;
-        let objref = match objresult.to_value(heap)? {
-            JSValue::Undefined => return Err(Exception::not_an_object(objresult)),
-            value => value.objectify(heap),
+            };
+            (self.object_ref(heap)?, name)
         };
+        Ok(member(objref, propname, heap))
+    }
+}
 
-        // TODO: __proto__ as (getPrototypeOf, setPrototypeOf) property
-        if propname.as_str() == "__proto__" {
-            let proto = heap.get(objref).proto;
-            return Ok(Interpreted::from(proto));
-        }
-
-        Ok(Interpreted::Member {
-            of: objref,
-            name: propname,
-        })
+/// A property reference to `objref[name]`.
+pub(crate) fn member(objref: JSRef, name: JSString, heap: &Heap) -> Interpreted {
+    // TODO: __proto__ as (getPrototypeOf, setPrototypeOf) property
+    if name.as_str() == "__proto__" {
+        return Interpreted::from(heap.get(objref).proto);
     }
+    Interpreted::Member { of: objref, name }
 }
 
 impl Interpretable for ObjectExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
-        let mut object = JSObject::new();
+        // methods refer to the object as their home object
+        let object_ref = heap.alloc(JSObject::new());
 
-        for (key, valexpr) in self.0.iter() {
-            let keyname = match key {
+        for property in self.0.iter() {
+            let keyname = match &property.key {
                 ObjectKey::Identifier(ident) => ident.clone(),
                 ObjectKey::Computed(expr) => {
                     let result = expr.interpret(heap)?.to_value(heap)?;
                     result.stringify(heap)?
                 }
             };
-            let valresult = valexpr.interpret(heap)?;
-            let value = valresult.to_value(heap)?;
-            object.set_property(keyname.as_str(), value)?;
+            let value = property.value.interpret(heap)?.to_value(heap)?;
+            heap.init_property(object_ref, keyname.as_str(), value, property.kind)?;
         }
 
-        let object_ref = heap.alloc(object);
         Ok(Interpreted::from(object_ref))
     }
 }
@@ -670,8 +1003,11 @@ impl Interpretable for ArrayExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let ArrayExpression(exprs) = self;
         let storage = (exprs.iter())
-            .map(|expr| expr.interpret(heap)?.to_value(heap))
-            .collect::<Result<Vec<JSValue>, Exception>>()?;
+            .map(|expr| match expr {
+                Some(expr) => expr.evaluate(heap).map(Some),
+                None => Ok(None),
+            })
+            .collect::<Result<JSArray, Exception>>()?;
 
         let object = JSObject::from_array(storage);
         let object_ref = heap.alloc(object);
@@ -679,50 +1015,197 @@ impl Interpretable for ArrayExpression {
     }
 }
 
+/// Evaluates the place that `expr` assigns to, see [`Heap::assignable_variable()`].
+fn assignment_target(expr: &Expression, heap: &mut Heap) -> JSResult<Interpreted> {
+    match &expr.expr {
+        Expr::Variable(var) => heap.assignable_variable(&var.name.0, var.slot),
+        _ => expr.interpret(heap),
+    }
+}
+
 impl Interpretable for AssignmentExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let AssignmentExpression(leftexpr, modop, valexpr) = self;
 
         let value = valexpr.evaluate(heap)?;
 
+        if let Expr::Member(membexpr) = &leftexpr.expr {
+            if let Some(name) = membexpr.cached_name() {
+                let objref = membexpr.object_ref(heap)?;
+                let cache = &membexpr.3;
+                let newvalue = match modop {
+                    None => value,
+                    Some(op) => {
+                        let oldvalue = cache.get(objref, name, heap)?;
+                        heap.root_temporary_value(&oldvalue);
+                        op.compute(&oldvalue, &value, heap)?
+                    }
+                };
+                (cache.put(objref, name, newvalue.clone(), heap))
+                    .or_else(|e| heap.ignore_set_readonly(e))?;
+                return Ok(Interpreted::Value(newvalue));
+            }
+            if membexpr.2 && modop.is_none() {
+                let (objref, key) = membexpr.computed(heap)?;
+                let place = match (key.to_index(), heap.get_mut(objref).as_growable_array()) {
+                    (Some(index), Some(array)) => {
+                        (array.set(index, value.clone()))
+                            .or_else(|e| heap.ignore_set_readonly(e))?;
+                        return Ok(Interpreted::Value(value));
+                    }
+                    _ => member(objref, key.stringify(heap)?, heap),
+                };
+                (place.put_value(value.clone(), heap)).or_else(|e| heap.ignore_set_readonly(e))?;
+                return Ok(Interpreted::Value(value));
+            }
+        }
+
         // This can be:
         // - Interpreted::Member{ existing object, attribute }
         // - Interpreted::Member{ scope, existing variable }
         // - Interpreted::Member{ global, non-existing variable }
+        // - Interpreted::Slot{ frame or cell, resolved variable }
         // - Interpreted::Value
-        let assignee = leftexpr.interpret(heap)?;
+        let assignee = assignment_target(leftexpr, heap)?;
 
         let newvalue = match modop {
             None => value,
             Some(op) => {
                 let oldvalue = assignee.to_value(heap)?;
+                heap.root_temporary_value(&oldvalue);
                 op.compute(&oldvalue, &value, heap)?
             }
         };
         assignee
             .put_value(newvalue.clone(), heap)
-            .or_else(crate::error::ignore_set_readonly)?;
+            .or_else(|e| heap.ignore_set_readonly(e))?;
         Ok(Interpreted::Value(newvalue))
     }
 }
 
+impl Interpretable for ChainExpression {
+    fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
+        let value = self.reference(heap)?.to_value(heap)?;
+        Ok(Interpreted::from(value))
+    }
+}
+
+impl ChainExpression {
+    /// Evaluates the chain, but does not read its last link if that is a property:
+    /// calling the member keeps its object as `this`, e.g. in `(o?.f)()`.
+    fn reference(&self, heap: &mut Heap) -> JSResult<Interpreted> {
+        let ChainExpression(object, links) = self;
+
+        let mut value = object.evaluate(heap)?;
+        // `this` and the name of a call after a property link
+        let mut this_ref = Heap::NULL;
+        let mut name = match &object.expr {
+            Expr::Variable(var) => var.name.0.clone(),
+            _ => JSString::from("<anonymous>"),
+        };
+        for (i, link) in links.iter().enumerate() {
+            if link.optional && value.is_nullish() {
+                return Ok(Interpreted::VOID);
+            }
+            let is_last = i + 1 == links.len();
+            match &link.kind {
+                LinkKind::Property(property) => {
+                    this_ref = chain_object(value, heap)?;
+                    name = property.0.clone();
+                    let reference = member(this_ref, name.clone(), heap);
+                    if is_last {
+                        return Ok(reference);
+                    }
+                    value = reference.to_value(heap)?;
+                    heap.root_temporary_value(&value);
+                }
+                LinkKind::Computed(key) => {
+                    let key = key.evaluate(heap)?.to_property_key(heap)?;
+                    this_ref = chain_object(value, heap)?;
+                    name = key.stringify(heap)?;
+                    let reference = member(this_ref, name.clone(), heap);
+                    if is_last {
+                        return Ok(reference);
+                    }
+                    value = reference.to_value(heap)?;
+                    heap.root_temporary_value(&value);
+                }
+                LinkKind::Call(argument_exprs) => {
+                    let func_ref = (value.as_objref()).ok_or_else(|| {
+                        Exception::type_error(TypeError::NOT_CALLABLE, value.clone())
+                    })?;
+                    let arguments = (argument_exprs.iter())
+                        .map(|argexpr| argexpr.interpret(heap))
+                        .collect::<Result<Vec<Interpreted>, Exception>>()?;
+                    let call = CallContext::from(arguments)
+                        .with_this(this_ref)
+                        .with_name(name.clone());
+                    value = heap.execute(func_ref, call)?.to_value(heap)?;
+                    this_ref = Heap::NULL;
+                }
+            }
+        }
+        Ok(Interpreted::from(value))
+    }
+}
+
+/// The object of a property link of a [`ChainExpression`].
+fn chain_object(value: JSValue, heap: &mut Heap) -> JSResult<JSRef> {
+    match value {
+        value if value.is_undefined() => Err(Exception::not_an_object(value)),
+        value => Ok(value.objectify(heap)),
+    }
+}
+
 impl Interpretable for CallExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
+        let (func_ref, call) = self.prepare(heap)?;
+        heap.execute(func_ref, call)
+    }
+}
+
+impl CallExpression {
+    /// Evaluates the arguments and the callee: what to call and how.
+    fn prepare(&self, heap: &mut Heap) -> JSResult<(JSRef, CallContext)> {
         let CallExpression(callee_expr, argument_exprs) = self;
 
         let arguments = (argument_exprs.iter())
             .map(|argexpr| argexpr.interpret(heap))
             .collect::<Result<Vec<Interpreted>, Exception>>()?;
 
-        let callee = callee_expr.interpret(heap)?;
-        let (func_ref, this_ref, name) = callee.resolve_call(heap)?;
+        let cached = match &callee_expr.expr {
+            Expr::Member(membexpr) => membexpr.cached_name().map(|name| (membexpr, name)),
+            _ => None,
+        };
+        let (func_ref, this_ref, name) = match cached {
+            Some((membexpr, name)) => {
+                let objref = membexpr.object_ref(heap)?;
+                match membexpr.3.get(objref, name, heap)?.as_objref() {
+                    Some(func_ref) if objref != Heap::NULL => {
+                        let this_ref = match heap.is_scope(objref) {
+                            true => Heap::GLOBAL,
+                            false => objref,
+                        };
+                        (func_ref, this_ref, name.clone())
+                    }
+                    _ => Interpreted::member(objref, name).resolve_call(heap)?,
+                }
+            }
+            None => match &callee_expr.expr {
+                Expr::Chain(chain) => chain.reference(heap)?.resolve_call(heap)?,
+                _ => callee_expr.interpret(heap)?.resolve_call(heap)?,
+            },
+        };
+        // functions called by name get an undefined `this`, see `Heap::bind_this()`
+        let (this_ref, name) = match &callee_expr.expr {
+            Expr::Variable(var) => (Heap::NULL, var.name.0.clone()),
+            _ => (this_ref, name),
+        };
 
-        heap.execute(
-            func_ref,
-            CallContext::from(arguments)
-                .with_this(this_ref)
-                .with_name(name),
-        )
+        let call = CallContext::from(arguments)
+            .with_this(this_ref)
+            .with_name(name);
+        Ok((func_ref, call))
     }
 }
 
@@ -736,30 +1219,8 @@ impl Interpretable for NewExpression {
 
         let callee = callee_expr.interpret(heap)?;
         let funcref = callee.to_ref(heap)?;
-        let prototype_ref = (heap.get_mut(funcref))
-            .get_own_value("prototype")
-            .ok_or_else(|| {
-                Exception::attr_type_error(TypeError::CANNOT_GET_PROPERTY, callee, "prototype")
-            })?
-            .to_ref()?;
-
-        // allocate the object
-        let mut object = JSObject::new();
-        object.proto = prototype_ref;
-
-        let object_ref = heap.alloc(object);
-
-        // call its constructor
-        let result = heap.execute(
-            funcref,
-            CallContext::from(arguments)
-                .with_this(object_ref)
-                .with_name("<constructor>".into()),
-        )?;
-        match result {
-            Interpreted::Value(JSValue::Ref(r)) if r != Heap::NULL => Ok(result),
-            _ => Ok(Interpreted::from(object_ref)),
-        }
+        let object_ref = heap.construct(funcref, arguments)?;
+        Ok(Interpreted::from(object_ref))
     }
 }
 
@@ -767,20 +1228,11 @@ impl Interpretable for FunctionExpression {
     fn interpret(&self, heap: &mut Heap) -> JSResult<Interpreted> {
         let closure = Closure {
             function: Rc::clone(&self.func),
-            captured_scope: heap.local_scope().unwrap_or(Heap::GLOBAL),
+            captured: heap.capture(&self.func.layout),
+            code: None,
+            home: None,
         };
-
-        let function_object = JSObject::from_closure(closure);
-        let function_ref = heap.alloc(function_object);
-
-        let prototype_ref = heap.alloc(JSObject::new());
-        heap.get_mut(function_ref)
-            .define_own_property("prototype", Access::WRITE)?;
-        heap.get_mut(function_ref)
-            .set_property("prototype", prototype_ref)?;
-        heap.get_mut(prototype_ref)
-            .set_hidden("constructor", function_ref)?;
-
+        let function_ref = heap.alloc_closure(closure)?;
         Ok(Interpreted::from(function_ref))
     }
 }
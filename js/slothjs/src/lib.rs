@@ -4,7 +4,9 @@
 extern crate alloc;
 
 pub mod ast;
+pub mod bigint;
 mod builtin;
+pub mod bytecode;
 pub mod error;
 pub mod function;
 pub mod heap;
@@ -12,6 +14,8 @@ pub mod interpret;
 pub mod object;
 mod parse;
 mod prelude;
+pub mod resolve;
+pub mod shape;
 pub mod source;
 pub mod value;
 
@@ -22,10 +26,15 @@ pub mod runtime;
 mod test;
 
 pub use ast::Program;
-pub use error::{Exception, JSResult, Jump};
-pub use function::{CallContext, HostFn, HostFunc};
-pub use heap::{Heap, JSRef};
+pub use bigint::BigInt;
+#[cfg(feature = "std")]
+pub use builtin::console::StdConsole;
+pub use builtin::console::{inspect, ConsoleSink, LogLevel};
+pub use bytecode::Backend;
+pub use error::{Abort, Exception, JSResult, Jump};
+pub use function::{CallContext, HostFn, HostFunc, ParseFn};
+pub use heap::{Heap, InterruptHandle, JSRef, MemoryStats, Rooted};
 pub use interpret::Interpretable;
 pub use object::{Interpreted, JSObject};
 pub use parse::{estree::ToESTree, HeapNode, SourceNode};
-pub use value::{JSNumber, JSString, JSValue, JSON};
+pub use value::{JSNumber, JSString, JSValue, PrimitiveHint, Unboxed, JSON};
@@ -1,22 +1,28 @@
 use bitflags::bitflags;
 use serde_json::json;
 
-use crate::error::TypeError;
+use crate::error::{RangeError, TypeError};
 use crate::prelude::*;
 
-use crate::function::{Closure, HostFn, HostFunc};
+use crate::bigint::BigInt;
+use crate::builtin::buffer::{primitive_number, ByteView, Bytes, ElementType};
+use crate::builtin::proxy::JSProxy;
+use crate::builtin::weak::{Registry, WeakEntries};
+use crate::function::{BoundFunction, Closure, HostFn, HostFunc, Scope};
+use crate::shape::Properties;
 use crate::{Exception, Heap, JSNumber, JSRef, JSResult, JSString, JSValue, JSON};
 
 /// Javascript objects.
 /// A `JSObject` always has a `proto`.
 /// It can have an optional `ObjectValue` (a primitive or array/function/closure).
-/// It has a dictionary of `properties`.
+/// It has own `properties`, laid out by their [`crate::shape::Shape`].
 #[derive(Debug, Clone)]
 pub struct JSObject {
     pub proto: JSRef,
     pub value: ObjectValue,
-    pub properties: HashMap<JSString, Property>, // TODO: StrKey
-                                                 // TODO: make fields private
+    pub properties: Properties, // TODO: make fields private
+    /// can new properties be added? See [`JSObject::prevent_extensions()`]
+    pub(crate) extensible: bool,
 }
 
 impl JSObject {
@@ -24,39 +30,69 @@ impl JSObject {
         JSObject {
             proto: Heap::OBJECT_PROTO,
             value: ObjectValue::None,
-            properties: HashMap::new(),
+            properties: Properties::new(),
+            extensible: true,
+        }
+    }
+
+    /// What a slot freed by the garbage collector holds.
+    pub(crate) fn free_slot() -> JSObject {
+        JSObject {
+            proto: Heap::NULL,
+            value: ObjectValue::None,
+            properties: Properties::new(),
+            extensible: true,
         }
     }
 
     /// Wrap the given native call into a Function.
     pub fn from_func(f: HostFn) -> JSObject {
-        JSObject {
+        let mut function_object = JSObject {
             proto: Heap::FUNCTION_PROTO,
             value: ObjectValue::from_func(f),
-            properties: HashMap::new(),
-        }
+            properties: Properties::new(),
+            extensible: true,
+        };
+        function_object.set_function_signature("", 0).unwrap();
+        function_object
     }
 
     /// Wrap the given `closure` into a Function.
     pub fn from_closure(closure: Closure) -> JSObject {
-        let params_count = closure.function.params.len() as f64;
+        let name = (closure.function.id.as_ref())
+            .map(|id| id.0.clone())
+            .unwrap_or_default();
+        let params_count = closure.function.params.len();
         let mut function_object = JSObject {
             proto: Heap::FUNCTION_PROTO,
             value: ObjectValue::Closure(closure),
-            properties: HashMap::new(),
+            properties: Properties::new(),
+            extensible: true,
         };
         function_object
-            .set_nonconf("length", Content::from(params_count))
+            .set_function_signature(&name, params_count)
             .unwrap();
         function_object
     }
 
+    /// Wrap the given `bound` function into a Function.
+    /// `proto` should be the prototype of the bound target.
+    pub fn from_bound_function(bound: BoundFunction, proto: JSRef) -> JSObject {
+        JSObject {
+            proto,
+            value: ObjectValue::BoundFunction(bound),
+            properties: Properties::new(),
+            extensible: true,
+        }
+    }
+
     /// Wrap the given vector into an Array.
-    pub fn from_array(values: Vec<JSValue>) -> JSObject {
+    pub fn from_array<A: Into<JSArray>>(values: A) -> JSObject {
         JSObject {
             proto: Heap::ARRAY_PROTO,
-            value: ObjectValue::Array(JSArray { storage: values }),
-            properties: HashMap::new(),
+            value: ObjectValue::Array(values.into()),
+            properties: Properties::new(),
+            extensible: true,
         }
     }
 
@@ -65,25 +101,42 @@ impl JSObject {
         JSObject {
             proto: Heap::BOOLEAN_PROTO,
             value: ObjectValue::Boolean(value),
-            properties: HashMap::new(),
+            properties: Properties::new(),
+            extensible: true,
+        }
+    }
+
+    /// Wrap the given number into Number
+    pub fn from_number(value: JSNumber) -> JSObject {
+        JSObject {
+            proto: Heap::NUMBER_PROTO,
+            value: ObjectValue::Number(value),
+            properties: Properties::new(),
+            extensible: true,
+        }
+    }
+
+    /// Wrap the given BigInt into BigInt
+    pub fn from_bigint(value: Rc<BigInt>) -> JSObject {
+        JSObject {
+            proto: Heap::BIGINT_PROTO,
+            value: ObjectValue::BigInt(value),
+            properties: Properties::new(),
+            extensible: true,
         }
     }
 
     /// Wrap the given string into String
     fn from_string(value: JSString) -> JSObject {
-        let mut properties = HashMap::new();
+        let mut properties = Properties::new();
         // TODO: String.prototype.length
-        properties.insert(
-            JSString::from("length"),
-            Property {
-                access: Access::empty(),
-                content: Content::from(value.as_str().chars().count() as i64),
-            },
-        );
+        let length = JSValue::from(value.as_str().chars().count() as i64);
+        properties.add("length", Access::empty(), length);
         JSObject {
             proto: Heap::STRING_PROTO,
             value: ObjectValue::String(value),
             properties,
+            extensible: true,
         }
     }
 
@@ -91,9 +144,10 @@ impl JSObject {
     pub fn to_primitive(&self) -> Option<JSValue> {
         use ObjectValue::*;
         match &self.value {
-            Boolean(b) => Some(JSValue::Bool(*b)),
-            Number(n) => Some(JSValue::Number(*n)),
-            String(s) => Some(JSValue::String(s.clone())),
+            Boolean(b) => Some(JSValue::from(*b)),
+            Number(n) => Some(JSValue::from(*n)),
+            String(s) => Some(JSValue::from(s.clone())),
+            BigInt(n) => Some(JSValue::from(n.clone())),
             _ => Option::None,
         }
     }
@@ -114,6 +168,38 @@ impl JSObject {
         }
     }
 
+    /// If `self` is a Map, give its underlying storage.
+    pub fn as_map(&self) -> Option<&JSMap> {
+        match &self.value {
+            ObjectValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// If `self` is a Map, give its underlying storage mutably.
+    pub fn as_map_mut(&mut self) -> Option<&mut JSMap> {
+        match &mut self.value {
+            ObjectValue::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// If `self` is a Set, give its underlying storage.
+    pub fn as_set(&self) -> Option<&JSMap> {
+        match &self.value {
+            ObjectValue::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
+    /// If `self` is a Set, give its underlying storage mutably.
+    pub fn as_set_mut(&mut self) -> Option<&mut JSMap> {
+        match &mut self.value {
+            ObjectValue::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
     /// If `self` is a String, get it primitive value
     pub fn as_str(&self) -> Option<&str> {
         match &self.value {
@@ -122,14 +208,152 @@ impl JSObject {
         }
     }
 
+    /// The variable in slot `index` of a scope or in a cell, see [`Interpreted::Slot`].
+    pub(crate) fn slot(&self, index: u32) -> &JSValue {
+        match &self.value {
+            ObjectValue::Scope(scope) => &scope.slots[index as usize],
+            ObjectValue::Cell(value) => value,
+            _ => panic!("not a scope or a cell: {:?}", self.value),
+        }
+    }
+
+    pub(crate) fn slot_mut(&mut self, index: u32) -> &mut JSValue {
+        match &mut self.value {
+            ObjectValue::Scope(scope) => &mut scope.slots[index as usize],
+            ObjectValue::Cell(value) => value,
+            other => panic!("not a scope or a cell: {:?}", other),
+        }
+    }
+
     #[allow(clippy::match_like_matches_macro)]
     pub fn is_callable(&self) -> bool {
         match self.value {
-            ObjectValue::HostFn(_) | ObjectValue::Closure(_) => true,
+            ObjectValue::HostFn(_) | ObjectValue::Closure(_) | ObjectValue::BoundFunction(_) => {
+                true
+            }
+            ObjectValue::Proxy(proxy) => proxy.callable,
             _ => false,
         }
     }
 
+    /// If `self` is a typed array, give its element type and the bytes it views.
+    pub fn as_typed_array(&self) -> Option<(ElementType, &ByteView)> {
+        match &self.value {
+            ObjectValue::TypedArray(element, view) => Some((*element, view)),
+            _ => None,
+        }
+    }
+
+    /// If `self` is a Proxy, give its target and handler.
+    pub fn as_proxy(&self) -> Option<JSProxy> {
+        match self.value {
+            ObjectValue::Proxy(proxy) => Some(proxy),
+            _ => None,
+        }
+    }
+
+    /// The elements of an extensible array, for fast paths that may add elements.
+    pub(crate) fn as_growable_array(&mut self) -> Option<&mut JSArray> {
+        match self.extensible {
+            true => self.as_array_mut(),
+            false => None,
+        }
+    }
+
+    pub fn is_extensible(&self) -> bool {
+        self.extensible
+    }
+
+    /// Forbids adding new properties and elements to `self`; this cannot be undone.
+    pub fn prevent_extensions(&mut self) {
+        self.extensible = false;
+    }
+
+    /// Calls `f` on every value `self` holds: properties, elements, entries and variables.
+    pub(crate) fn for_each_value(&self, f: &mut dyn FnMut(&JSValue)) {
+        self.properties.values().for_each(&mut *f);
+        match &self.value {
+            ObjectValue::String(s) => f(&JSValue::from(s.clone())),
+            ObjectValue::BoundFunction(bound) => bound.arguments.iter().for_each(f),
+            ObjectValue::Array(array) => array.elements().for_each(|(_, value)| f(value)),
+            ObjectValue::Scope(scope) => scope.slots.iter().for_each(f),
+            ObjectValue::Cell(value) => f(value),
+            ObjectValue::Map(map) | ObjectValue::Set(map) => {
+                for (key, value) in map.iter() {
+                    f(key);
+                    f(value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Calls `mark` on every object `self` refers to.
+    pub(crate) fn trace(&self, mark: &mut dyn FnMut(JSRef)) {
+        self.for_each_value(&mut |value| {
+            if let Some(objref) = value.as_objref() {
+                mark(objref);
+            }
+        });
+        match &self.value {
+            ObjectValue::Closure(closure) => {
+                closure.captured.iter().for_each(|&r| mark(r));
+                closure.home.iter().for_each(|&r| mark(r));
+            }
+            ObjectValue::BoundFunction(bound) => {
+                mark(bound.target);
+                bound.this.as_objref().into_iter().for_each(&mut *mark);
+            }
+            ObjectValue::Proxy(proxy) => {
+                mark(proxy.target);
+                mark(proxy.handler);
+            }
+            ObjectValue::TypedArray(_, view) | ObjectValue::DataView(view) => mark(view.buffer),
+            ObjectValue::Accessor { get, set } => get.iter().chain(set).for_each(|&r| mark(r)),
+            // targets are weak, values of weak maps are traced by the collector
+            ObjectValue::FinalizationRegistry(registry) => {
+                mark(registry.cleanup);
+                for cell in registry.cells.iter() {
+                    if let Some(objref) = cell.held.as_objref() {
+                        mark(objref);
+                    }
+                }
+            }
+            ObjectValue::Scope(scope) => {
+                scope.captured.iter().for_each(|&r| mark(r));
+                scope.this.as_objref().into_iter().for_each(&mut *mark);
+                mark(scope.saved);
+                mark(scope.parent);
+            }
+            _ => (),
+        }
+        mark(self.proto);
+    }
+
+    /// Approximate bytes that `self` takes, not counting the strings it refers to.
+    /// This does not look at every element, so it is cheap to measure big arrays.
+    pub(crate) fn memory_size(&self) -> usize {
+        let value_size = core::mem::size_of::<JSValue>();
+        let contents = match &self.value {
+            ObjectValue::BoundFunction(bound) => bound.arguments.len() * value_size,
+            ObjectValue::Array(array) => array.memory_size(),
+            ObjectValue::Map(map) | ObjectValue::Set(map) => map.memory_size(),
+            ObjectValue::ArrayBuffer(bytes) => bytes.borrow().len(),
+            ObjectValue::WeakMap(entries) | ObjectValue::WeakSet(entries) => {
+                entries.len() * (core::mem::size_of::<JSRef>() + value_size)
+            }
+            ObjectValue::FinalizationRegistry(registry) => {
+                registry.cells.len()
+                    * core::mem::size_of::<crate::builtin::weak::FinalizationCell>()
+            }
+            ObjectValue::Scope(scope) => {
+                core::mem::size_of::<Scope>() + scope.slots.len() * value_size
+            }
+            _ => 0,
+        };
+        core::mem::size_of::<JSObject>() + self.properties.len() * value_size + contents
+    }
+
     /// Tries to get JSValue of the own property `name`.
     /// This might call getters of the property.
     pub fn get_own_value(&self, name: &str) -> Option<JSValue> {
@@ -137,7 +361,7 @@ impl JSObject {
         if let Ok(index) = usize::from_str(name) {
             match &self.value {
                 ObjectValue::Array(array) => {
-                    if let Some(value) = array.storage.get(index) {
+                    if let Some(value) = array.get(index) {
                         return Some(value.clone());
                     }
                 }
@@ -147,25 +371,35 @@ impl JSObject {
                         return Some(JSValue::from(c.to_string()));
                     }
                 }
+                ObjectValue::TypedArray(element, view) => {
+                    return view.element(*element, index).map(JSValue::from);
+                }
                 _ => (),
             }
         } else if name == "length" {
             // TODO: make this hack a regular getter once getters are ready
             match &self.value {
-                ObjectValue::Array(array) => {
-                    return Some(JSValue::from(array.storage.len() as i64))
-                }
-                ObjectValue::Closure(closure) => {
-                    return Some(JSValue::from(closure.function.params.len() as i64))
-                }
+                ObjectValue::Array(array) => return Some(JSValue::from(array.len() as i64)),
                 ObjectValue::String(s) => return Some(JSValue::from(s.len() as i64)),
+                ObjectValue::TypedArray(element, view) => {
+                    return Some(JSValue::from(view.len(*element) as i64))
+                }
                 _ => (),
             }
         }
 
-        self.properties.get(name).map(|prop| match &prop.content {
-            Content::Value(value) => value.clone(),
-        })
+        self.properties.get_value(name).cloned()
+    }
+
+    /// An own element of an array at an integer `index`, no string key involved.
+    pub fn get_element(&self, index: u32) -> Option<JSValue> {
+        match &self.value {
+            ObjectValue::Array(array) => array.get(index as usize).cloned(),
+            ObjectValue::TypedArray(element, view) => {
+                view.element(*element, index as usize).map(JSValue::from)
+            }
+            _ => None,
+        }
     }
 
     /// Check own and all inherited properties for `name` and returns the first found value.
@@ -189,28 +423,51 @@ impl JSObject {
         }
     }
 
-    fn set_maybe_nonwritable(
+    pub(crate) fn set_maybe_nonwritable(
         &mut self,
         name: &str,
         content: Content,
         access: Access,
         even_nonwritable: bool,
     ) -> JSResult<()> {
-        if let Ok(index) = usize::from_str(name) {
-            if let Some(array) = self.as_array_mut() {
-                // TODO: a[100500] will be interesting.
-                while array.storage.len() <= index {
-                    array.storage.push(JSValue::Undefined);
+        if let ObjectValue::TypedArray(element, view) = &self.value {
+            if let Some(index) = JSArray::index(name) {
+                // elements out of bounds are never added
+                let n = primitive_number(&content.to_value()?);
+                view.set_element(*element, index as usize, n);
+                return Ok(());
+            }
+        }
+        let extensible = self.extensible;
+        if let Some(array) = self.as_array_mut() {
+            if let Some(index) = JSArray::index(name) {
+                if !(extensible || array.has(index as usize)) {
+                    return Err(Exception::attr_type_error(
+                        TypeError::NOT_EXTENSIBLE,
+                        Interpreted::VOID,
+                        name,
+                    ));
                 }
-                let value = content.to_value()?;
-                array.storage[index] = value;
+                return array.set(index, content.to_value()?);
+            }
+            if name == "length" {
+                if !(even_nonwritable || array.is_length_writable()) {
+                    let what = Interpreted::from("???"); // TODO
+                    return Err(Exception::attr_type_error(
+                        TypeError::SET_READONLY,
+                        what,
+                        name,
+                    ));
+                }
+                let length = JSArray::length_from(&content.to_value()?)?;
+                array.set_len(length);
                 return Ok(());
             }
         }
 
-        match self.properties.get_mut(name) {
-            Some(property) => {
-                if property.access != access && !property.access.configurable() {
+        match self.properties.find(name) {
+            Some((slot, current)) => {
+                if current != access && !current.configurable() {
                     return Err(Exception::attr_type_error(
                         TypeError::NONCONFIGURABLE_PROPERTY,
                         "???",
@@ -218,7 +475,7 @@ impl JSObject {
                     ));
                 }
 
-                if !(even_nonwritable || property.access.writable()) {
+                if !(even_nonwritable || current.writable()) {
                     let what = Interpreted::from("???"); // TODO
                     return Err(Exception::attr_type_error(
                         TypeError::SET_READONLY,
@@ -227,19 +484,40 @@ impl JSObject {
                     ));
                 }
 
-                property.access = access;
-                property.content = content;
+                if current != access {
+                    self.properties.set_access(slot, access);
+                }
+                *self.properties.slot_mut(slot) = content.to_value()?;
             }
-            None => {
-                let prop = Property { content, access };
-                self.properties.insert(JSString::from(name), prop);
+            None if !self.extensible => {
+                return Err(Exception::attr_type_error(
+                    TypeError::NOT_EXTENSIBLE,
+                    Interpreted::VOID,
+                    name,
+                ));
             }
+            None => self.properties.add(name, access, content.to_value()?),
         }
         Ok(())
     }
 
     pub fn define_own_property(&mut self, name: &str, access: Access) -> JSResult<()> {
-        let content = Content::from(JSValue::Undefined);
+        if let (Some(array), "length") = (self.as_array_mut(), name) {
+            // the length stays, only its writability can change
+            if access.writable() && !array.is_length_writable() {
+                let what = Interpreted::from("???"); // TODO
+                return Err(Exception::attr_type_error(
+                    TypeError::NONCONFIGURABLE_PROPERTY,
+                    what,
+                    name,
+                ));
+            }
+            if !access.writable() {
+                array.freeze_length();
+            }
+            return Ok(());
+        }
+        let content = Content::from(JSValue::UNDEFINED);
         self.set_maybe_nonwritable(name, content, access, true)
     }
 
@@ -253,7 +531,7 @@ impl JSObject {
     }
 
     /// If `name` is a number and `self` is an Array, just set the array elemnt to `value`.
-    /// Otherwise: if the own property `name` does not exist, create it with `Access::all()` and
+    /// Otherwise: if the own property `name` does not exist, create it with `Access::DEFAULT` and
     /// set to `Content::from(value)`.
     /// If the own property exists already, call `.set()` with its current access. This will fail
     /// to update non-writable properties.
@@ -262,9 +540,9 @@ impl JSObject {
     where
         Content: From<V>,
     {
-        let access = (self.properties.get(name))
-            .map(|prop| prop.access)
-            .unwrap_or(Access::all());
+        let access = (self.properties.find(name))
+            .map(|(_, access)| access)
+            .unwrap_or(Access::DEFAULT);
         self.set(name, Content::from(value), access)
     }
 
@@ -273,13 +551,22 @@ impl JSObject {
     where
         Content: From<V>,
     {
-        let access = (self.properties.get(name))
-            .map(|prop| prop.access)
-            .unwrap_or(Access::all());
+        let access = (self.properties.find(name))
+            .map(|(_, access)| access)
+            .unwrap_or(Access::DEFAULT);
         self.set_maybe_nonwritable(name, Content::from(value), access, true)
     }
 
     // are these shortcuts a good idea?
+    /// Makes `name` an own data property with `Access::DEFAULT`, like a property of an object
+    /// literal does: this replaces an accessor or a non-writable property if it is configurable.
+    pub fn init_property<V>(&mut self, name: &str, value: V) -> JSResult<()>
+    where
+        Content: From<V>,
+    {
+        self.set_maybe_nonwritable(name, Content::from(value), Access::DEFAULT, true)
+    }
+
     /// A shortcut for `define_own_property(Access::NONE)` and assigning the value.
     pub fn set_system<V>(&mut self, name: &str, value: V) -> JSResult<()>
     where
@@ -304,6 +591,13 @@ impl JSObject {
         self.set(name, Content::from(value), Access::NONCONF)
     }
 
+    /// Sets `name` and `length` of a function object: non-writable, non-enumerable, configurable.
+    pub fn set_function_signature(&mut self, name: &str, length: usize) -> JSResult<()> {
+        self.set_maybe_nonwritable("name", Content::from(name), Access::CONF, true)?;
+        let length = Content::from(length as f64);
+        self.set_maybe_nonwritable("length", length, Access::CONF, true)
+    }
+
     /// A shortcut for defining a non-writable property and setting its value.
     pub fn set_readonly<V>(&mut self, name: &str, value: V) -> JSResult<()>
     where
@@ -315,15 +609,23 @@ impl JSObject {
     /// Create a `JSON` from this `JSObject`.
     pub fn to_json(&self, heap: &Heap) -> JSResult<JSON> {
         if let Some(array) = self.as_array() {
-            let jvals = (array.storage.iter())
+            let jvals = (array.to_vec().iter())
                 .map(|v| v.to_json(heap))
                 .collect::<JSResult<Vec<_>>>()?;
             return Ok(JSON::Array(jvals));
         }
+        if let ObjectValue::TypedArray(element, view) = &self.value {
+            let mut json = json!({});
+            for i in 0..view.len(*element) {
+                json[i.to_string()] = JSON::from(view.element(*element, i).unwrap());
+            }
+            return Ok(json);
+        }
 
         let mut json = json!({});
         for (key, property) in self.properties.iter() {
-            if !property.access.enumerable() {
+            // getters cannot be called without a mutable heap
+            if !property.access.enumerable() || property.access.accessor() {
                 continue;
             }
 
@@ -336,7 +638,7 @@ impl JSObject {
     /// Create a human-readable representation of contents of an Array or an Object.
     pub fn to_string(&self, heap: &mut Heap) -> JSResult<JSString> {
         fn is_valid_identifier(s: &str) -> bool {
-            let is_start = |c: char| (c.is_alphabetic() || c == '_' || c == '$');
+            let is_start = |c: char| c.is_alphabetic() || c == '_' || c == '$';
 
             let mut it = s.chars();
             if let Some(c) = it.next() {
@@ -353,7 +655,7 @@ impl JSObject {
 
         if let Some(array) = self.as_array() {
             s.push('[');
-            for item in array.storage.iter() {
+            for item in array.to_vec().iter() {
                 empty = false;
                 let itemstr = item.to_string(heap)?;
                 s.push_str(&itemstr);
@@ -374,8 +676,8 @@ impl JSObject {
             }
 
             s.push(' ');
-            if is_valid_identifier(key) {
-                s.push_str(key);
+            if is_valid_identifier(&key) {
+                s.push_str(&key);
             } else {
                 let skey = JSON::from(key.as_str()).to_string();
                 s.push_str(&skey);
@@ -449,6 +751,7 @@ impl<'a> Iterator for ProtoChainIter<'a> {
 /// - as the primitive value of a `Number`/`Boolean`/`String` object;
 /// - as the function entry in a `Function`.
 /// - as optimizied storage in an `Array`
+/// - as native storage in a `Map`/`Set`
 #[derive(Debug, Clone)]
 pub enum ObjectValue {
     None,
@@ -457,13 +760,46 @@ pub enum ObjectValue {
     Boolean(bool),
     Number(JSNumber),
     String(JSString),
+    BigInt(Rc<BigInt>),
 
     // Function
     HostFn(HostFunc),
     Closure(Closure),
+    BoundFunction(BoundFunction),
 
     // Array
     Array(JSArray),
+
+    // Keyed collections
+    Map(JSMap),
+    Set(JSMap),
+
+    // Variables
+    /// a frame of variables, see [`Heap::enter_frame()`]
+    Scope(Box<Scope>),
+    /// a variable shared between a frame and closures
+    Cell(JSValue),
+
+    /// a Proxy, see [`crate::builtin::proxy`]
+    Proxy(JSProxy),
+
+    // Binary data, see [`crate::builtin::buffer`]
+    ArrayBuffer(Bytes),
+    TypedArray(ElementType, ByteView),
+    DataView(ByteView),
+
+    // Weak references, see [`crate::builtin::weak`]
+    WeakMap(WeakEntries),
+    WeakSet(WeakEntries),
+    /// the target of a WeakRef, `Heap::NULL` once it is collected
+    WeakRef(JSRef),
+    FinalizationRegistry(Registry),
+
+    /// the getter and the setter in the slot of an accessor property, see [`Access::ACCESSOR`]
+    Accessor {
+        get: Option<JSRef>,
+        set: Option<JSRef>,
+    },
 }
 
 impl ObjectValue {
@@ -484,9 +820,9 @@ impl Property {
     pub fn to_ref(&self) -> Option<JSRef> {
         match self {
             Property {
-                content: Content::Value(JSValue::Ref(r)),
+                content: Content::Value(value),
                 ..
-            } => Some(*r),
+            } => value.as_objref(),
             _ => None,
         }
     }
@@ -497,7 +833,11 @@ bitflags! {
         const ENUM = 0b001;
         const CONF = 0b010;
         const WRITE = 0b100;
+        /// the slot holds the getter and the setter, see [`ObjectValue::Accessor`]
+        const ACCESSOR = 0b1000;
 
+        /// what assigning a new property gives
+        const DEFAULT = Self::ENUM.bits | Self::CONF.bits | Self::WRITE.bits;
         const HIDDEN = Self::CONF.bits | Self::WRITE.bits;
         const READONLY = Self::ENUM.bits | Self::CONF.bits;
         const NONCONF = Self::ENUM.bits | Self::WRITE.bits;
@@ -528,24 +868,27 @@ impl Access {
     pub fn writable(&self) -> bool {
         self.contains(Access::WRITE)
     }
+    pub fn accessor(&self) -> bool {
+        self.contains(Access::ACCESSOR)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Content {
     Value(JSValue),
-    /*
-    Accessor{
+    Accessor {
         get: Option<JSRef>,
         set: Option<JSRef>,
     },
-    */
 }
 
 impl Content {
-    /// This might call getters of the property.
+    /// The value of a data property, `undefined` for an accessor: getters are called
+    /// by [`Heap::lookup_member()`].
     pub fn to_value(&self) -> JSResult<JSValue> {
         match self {
             Self::Value(value) => Ok(value.clone()),
+            Self::Accessor { .. } => Ok(JSValue::UNDEFINED),
         }
     }
 }
@@ -560,25 +903,374 @@ where
 }
 
 /// The underlying storage of an Array object.
+///
+/// Elements are kept in a dense vector while they are mostly present; indexes far beyond
+/// its end go into a sparse map, so `a[1e9] = 1` does not allocate a billion elements.
+/// Missing elements are holes: they are not own properties of the array.
 #[derive(Clone, Debug)]
 pub struct JSArray {
-    pub storage: Vec<JSValue>,
+    dense: Vec<Option<JSValue>>,
+    /// elements at indexes not below `dense.len()`
+    sparse: BTreeMap<u32, JSValue>,
+    length: u32,
+    length_writable: bool,
 }
 
-impl JSArray {}
+impl JSArray {
+    /// How far past the end of the dense part an element can be set
+    /// before it goes to the sparse part.
+    const MAX_DENSE_GAP: usize = 1024;
+
+    /// `2^32 - 1`, the maximum length of an array.
+    pub const MAX_LENGTH: u32 = u32::MAX;
+
+    pub fn new() -> JSArray {
+        JSArray::from(vec![])
+    }
+
+    /// Parses `name` as an array index: a canonical integer below [`JSArray::MAX_LENGTH`].
+    pub fn index(name: &str) -> Option<u32> {
+        let index = u32::from_str(name).ok()?;
+        let canonical = name.len() == 1 || !name.starts_with(['0', '+']);
+        (canonical && index < Self::MAX_LENGTH).then_some(index)
+    }
+
+    /// Converts the value assigned to `length` into a valid length.
+    pub fn length_from(value: &JSValue) -> JSResult<u32> {
+        // objects are converted by `Heap::array_length_value()`
+        let number = primitive_number(value);
+        match number as u32 {
+            length if length as f64 == number => Ok(length),
+            _ => Err(Exception::range_error(
+                RangeError::INVALID_LENGTH,
+                value.clone(),
+            )),
+        }
+    }
+
+    /// The value of `length`, including trailing holes.
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn is_length_writable(&self) -> bool {
+        self.length_writable
+    }
+
+    /// The element at `index`, `None` for holes and indexes past the end.
+    pub fn get(&self, index: usize) -> Option<&JSValue> {
+        match self.dense.get(index) {
+            Some(element) => element.as_ref(),
+            None => self.sparse.get(&u32::try_from(index).ok()?),
+        }
+    }
+
+    pub fn has(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Sets the element at `index`, extending `length` if needed.
+    pub fn set(&mut self, index: u32, value: JSValue) -> JSResult<()> {
+        if index >= self.length {
+            if !self.length_writable {
+                let what = Interpreted::from(JSValue::from(index as f64));
+                return Err(Exception::attr_type_error(
+                    TypeError::SET_READONLY,
+                    what,
+                    "length",
+                ));
+            }
+            self.length = index + 1;
+        }
+
+        let at = index as usize;
+        if at < self.dense.len() {
+            self.dense[at] = Some(value);
+        } else if at <= self.dense.len() + Self::MAX_DENSE_GAP {
+            self.dense.resize(at, None);
+            self.dense.push(Some(value));
+            // the dense part may have grown over some sparse elements
+            let rest = self.sparse.split_off(&(self.dense.len() as u32));
+            for (index, value) in core::mem::replace(&mut self.sparse, rest) {
+                self.dense[index as usize] = Some(value);
+            }
+        } else {
+            self.sparse.insert(index, value);
+        }
+        Ok(())
+    }
+
+    /// Makes a hole at `index`.
+    pub fn remove(&mut self, index: usize) -> Option<JSValue> {
+        match self.dense.get_mut(index) {
+            Some(element) => element.take(),
+            None => self.sparse.remove(&u32::try_from(index).ok()?),
+        }
+    }
+
+    pub fn push(&mut self, value: JSValue) -> JSResult<()> {
+        if self.length == Self::MAX_LENGTH {
+            let what = Interpreted::from(JSValue::from(self.length as f64));
+            return Err(Exception::range_error(RangeError::INVALID_LENGTH, what));
+        }
+        self.set(self.length, value)
+    }
+
+    /// Removes the last element; a hole or an empty array gives `None`.
+    pub fn pop(&mut self) -> JSResult<Option<JSValue>> {
+        if self.length == 0 {
+            return Ok(None);
+        }
+        if !self.length_writable {
+            let what = Interpreted::from(JSValue::from(self.length as f64));
+            return Err(Exception::attr_type_error(
+                TypeError::SET_READONLY,
+                what,
+                "length",
+            ));
+        }
+        let last = self.length - 1;
+        let value = self.remove(last as usize);
+        self.set_len(last);
+        Ok(value)
+    }
+
+    /// Truncates or extends the array with holes, like assigning to `length`.
+    /// Does not check if `length` is writable.
+    pub fn set_len(&mut self, length: u32) {
+        if length < self.length {
+            self.dense.truncate(length as usize);
+            self.sparse.split_off(&length);
+        }
+        self.length = length;
+    }
+
+    /// Makes `length` non-writable; this cannot be undone.
+    pub fn freeze_length(&mut self) {
+        self.length_writable = false;
+    }
+
+    /// The present elements with their indexes, in order.
+    pub fn elements(&self) -> impl Iterator<Item = (usize, &JSValue)> {
+        let dense = (self.dense.iter().enumerate())
+            .filter_map(|(index, element)| element.as_ref().map(|value| (index, value)));
+        let sparse = (self.sparse.iter()).map(|(&index, value)| (index as usize, value));
+        dense.chain(sparse)
+    }
+
+    /// Approximate bytes of the elements, see [`JSObject::memory_size()`].
+    fn memory_size(&self) -> usize {
+        // a node of the sparse map holds an index and a value, and about as much overhead
+        let sparse_entry = 2 * core::mem::size_of::<(u32, JSValue)>();
+        self.dense.capacity() * core::mem::size_of::<Option<JSValue>>()
+            + self.sparse.len() * sparse_entry
+    }
+
+    /// The number of present elements.
+    pub fn count(&self) -> usize {
+        self.dense.iter().flatten().count() + self.sparse.len()
+    }
+
+    /// All elements up to `length`, holes become `undefined`.
+    pub fn to_vec(&self) -> Vec<JSValue> {
+        let mut values = vec![JSValue::UNDEFINED; self.len()];
+        for (index, value) in self.elements() {
+            values[index] = value.clone();
+        }
+        values
+    }
+}
+
+impl Default for JSArray {
+    fn default() -> Self {
+        JSArray::new()
+    }
+}
+
+impl From<Vec<JSValue>> for JSArray {
+    fn from(values: Vec<JSValue>) -> Self {
+        JSArray::from_iter(values.into_iter().map(Some))
+    }
+}
+
+impl FromIterator<Option<JSValue>> for JSArray {
+    /// Collects elements and holes (`None`).
+    fn from_iter<I: IntoIterator<Item = Option<JSValue>>>(iter: I) -> Self {
+        let dense: Vec<Option<JSValue>> = iter.into_iter().collect();
+        JSArray {
+            length: dense.len() as u32,
+            dense,
+            sparse: BTreeMap::new(),
+            length_writable: true,
+        }
+    }
+}
+
+/// A `JSValue` wrapper that is hashed and compared using SameValueZero:
+/// `NaN` is equal to `NaN`, `+0` is equal to `-0`, objects are compared by `JSRef`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum MapKey {
+    Undefined,
+    Bool(bool),
+    Number(u64),
+    String(JSString),
+    BigInt(Rc<BigInt>),
+    Ref(JSRef),
+}
+
+impl From<&JSValue> for MapKey {
+    fn from(value: &JSValue) -> MapKey {
+        match value.unboxed() {
+            Unboxed::Undefined => MapKey::Undefined,
+            Unboxed::Bool(b) => MapKey::Bool(b),
+            Unboxed::Number(n) if n.is_nan() => MapKey::Number(f64::NAN.to_bits()),
+            Unboxed::Number(0.0) => MapKey::Number(0f64.to_bits()),
+            Unboxed::Number(n) => MapKey::Number(n.to_bits()),
+            Unboxed::String(s) => MapKey::String(s),
+            Unboxed::BigInt(n) => MapKey::BigInt(n),
+            Unboxed::Ref(r) => MapKey::Ref(r),
+        }
+    }
+}
+
+/// The underlying storage of Map and Set objects.
+///
+/// Entries are kept in insertion order and numbered by their position: a running count of
+/// insertions that is never reused. Iterators remember the position of the next entry, so
+/// compacting away deleted entries does not invalidate them.
+/// A Set stores its elements as keys with `undefined` values.
+#[derive(Clone, Debug, Default)]
+pub struct JSMap {
+    /// Entries sorted by position; `None` is a deleted entry.
+    entries: Vec<(usize, Option<(JSValue, JSValue)>)>,
+    /// Indices into `entries`.
+    index: HashMap<MapKey, usize>,
+    size: usize,
+    /// The position of the next inserted entry.
+    next_position: usize,
+}
+
+impl JSMap {
+    /// Deleted entries are compacted away when there are at least this many of them
+    /// and they outnumber the live ones.
+    const COMPACT_HOLES: usize = 16;
+
+    pub fn new() -> JSMap {
+        JSMap::default()
+    }
+
+    /// The number of live entries.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Approximate bytes of the entries, see [`JSObject::memory_size()`].
+    fn memory_size(&self) -> usize {
+        self.entries.capacity() * core::mem::size_of::<(usize, Option<(JSValue, JSValue)>)>()
+            + self.index.capacity() * core::mem::size_of::<(MapKey, usize)>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn get(&self, key: &JSValue) -> Option<&JSValue> {
+        let &at = self.index.get(&MapKey::from(key))?;
+        self.entries[at].1.as_ref().map(|(_, value)| value)
+    }
+
+    pub fn contains(&self, key: &JSValue) -> bool {
+        self.index.contains_key(&MapKey::from(key))
+    }
+
+    /// Updates the value of an existing entry or appends a new entry.
+    pub fn insert(&mut self, key: JSValue, value: JSValue) {
+        let mapkey = MapKey::from(&key);
+        if let Some(&at) = self.index.get(&mapkey) {
+            if let Some(entry) = self.entries[at].1.as_mut() {
+                entry.1 = value;
+            }
+            return;
+        }
+        // -0 is stored as +0, since `-0 + 0` is `+0`
+        let key = match key.as_number() {
+            Some(n) => JSValue::from(n + 0.0),
+            None => key,
+        };
+        self.index.insert(mapkey, self.entries.len());
+        self.entries.push((self.next_position, Some((key, value))));
+        self.next_position += 1;
+        self.size += 1;
+    }
+
+    /// Returns `true` if the entry existed.
+    pub fn remove(&mut self, key: &JSValue) -> bool {
+        let Some(at) = self.index.remove(&MapKey::from(key)) else {
+            return false;
+        };
+        self.entries[at].1 = None;
+        self.size -= 1;
+
+        let holes = self.entries.len() - self.size;
+        if holes >= Self::COMPACT_HOLES && holes > self.size {
+            self.compact();
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+        self.size = 0;
+    }
+
+    /// Drops deleted entries. Positions of live entries stay the same.
+    fn compact(&mut self) {
+        self.entries.retain(|(_, entry)| entry.is_some());
+        for (at, (_, entry)) in self.entries.iter().enumerate() {
+            if let Some((key, _)) = entry {
+                self.index.insert(MapKey::from(key), at);
+            }
+        }
+    }
+
+    /// Finds the first live entry at position `from` or later.
+    /// Returns its position and a copy of it.
+    pub fn next_entry(&self, from: usize) -> Option<(usize, JSValue, JSValue)> {
+        let start = self
+            .entries
+            .partition_point(|&(position, _)| position < from);
+        self.entries[start..].iter().find_map(|(position, entry)| {
+            let (key, value) = entry.as_ref()?;
+            Some((*position, key.clone(), value.clone()))
+        })
+    }
+
+    /// Live entries in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&JSValue, &JSValue)> {
+        (self.entries.iter()).filter_map(|(_, entry)| entry.as_ref().map(|(k, v)| (k, v)))
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Interpreted {
     /// An object member; might not exist yet.
     Member { of: JSRef, name: JSString },
 
+    /// A variable: slot `index` of a scope or a cell (with `index` 0).
+    Slot { of: JSRef, index: u32 },
+
     /// A value
     Value(JSValue),
 }
 
 impl Interpreted {
-    pub const VOID: Interpreted = Interpreted::Value(JSValue::Undefined);
-    pub const NAN: Interpreted = Interpreted::Value(JSValue::Number(f64::NAN));
+    pub const VOID: Interpreted = Interpreted::Value(JSValue::UNDEFINED);
+    pub const NAN: Interpreted = Interpreted::Value(JSValue::NAN);
 
     /// A convenience wrapper for Interpreted::Member{} construction
     pub fn member(of: JSRef, name: &str) -> Interpreted {
@@ -589,27 +1281,28 @@ impl Interpreted {
     }
 
     /// If Interpreted::Value, unwrap;
-    /// if Interpreted::Member{of, name}, [`JSObject::lookup_value`] of `name` in `of`.
-    pub fn to_value(&self, heap: &Heap) -> JSResult<JSValue> {
+    /// if Interpreted::Member{of, name}, [`Heap::lookup_member`] of `name` in `of`.
+    pub fn to_value(&self, heap: &mut Heap) -> JSResult<JSValue> {
         match self {
             Interpreted::Value(value) => Ok(value.clone()),
-            Interpreted::Member { of, name } => {
-                if let Some(value) = heap.get(*of).lookup_value(name, heap) {
-                    Ok(value)
-                } else if heap.is_scope(*of) {
-                    Err(Exception::no_reference(name.clone()))
-                } else {
-                    Ok(JSValue::Undefined)
-                }
-            }
+            Interpreted::Member { of, name } => match heap.lookup_member(*of, name, *of)? {
+                Some(value) => Ok(value),
+                None if heap.is_scope(*of) => Err(Exception::no_reference(name.clone())),
+                None => Ok(JSValue::UNDEFINED),
+            },
+            Interpreted::Slot { of, index } => Ok(heap.get(*of).slot(*index).clone()),
         }
     }
 
-    pub fn to_ref(&self, heap: &Heap) -> JSResult<JSRef> {
+    pub fn to_ref(&self, heap: &mut Heap) -> JSResult<JSRef> {
         match self {
-            Interpreted::Value(JSValue::Ref(r)) => Ok(*r),
-            Interpreted::Member { of, name } => match heap.get(*of).lookup_value(name, heap) {
-                Some(JSValue::Ref(r)) => Ok(r),
+            Interpreted::Value(value) if value.as_objref().is_some() => value.to_ref(),
+            Interpreted::Slot { of, index } => match heap.get(*of).slot(*index).as_objref() {
+                Some(r) => Ok(r),
+                None => Err(Exception::not_an_object(self.clone())),
+            },
+            Interpreted::Member { of, name } => match heap.lookup_member(*of, name, *of)? {
+                Some(value) if value.as_objref().is_some() => value.to_ref(),
                 None if heap.is_scope(*of) => Err(Exception::no_reference(name.clone())),
                 _ => Err(Exception::attr_type_error(
                     TypeError::CANNOT_GET_PROPERTY,
@@ -623,8 +1316,10 @@ impl Interpreted {
 
     pub fn put_value(&self, value: JSValue, heap: &mut Heap) -> JSResult<()> {
         match self {
-            Interpreted::Member { of, name } => {
-                heap.get_mut(*of).set_property(name.as_str(), value)
+            Interpreted::Member { of, name } => heap.set_member(*of, name, value),
+            Interpreted::Slot { of, index } => {
+                *heap.get_mut(*of).slot_mut(*index) = value;
+                Ok(())
             }
             _ => Err(Exception::type_error(
                 TypeError::CANNOT_SET_PROPERTY,
@@ -634,55 +1329,48 @@ impl Interpreted {
     }
 
     /// Resolve self to: a callable JSRef, `this` JSRef and the method name.
-    pub fn resolve_call(&self, heap: &Heap) -> JSResult<(JSRef, JSRef, JSString)> {
+    pub fn resolve_call(&self, heap: &mut Heap) -> JSResult<(JSRef, JSRef, JSString)> {
         match self {
             Interpreted::Member { of: this_ref, name } => {
-                let of = match heap.lookup_protochain(*this_ref, name) {
-                    Some(Interpreted::Member { of, .. }) => of,
-                    Some(_) => unreachable!(),
-                    None => {
-                        return Err(Exception::type_error(TypeError::NOT_CALLABLE, self.clone()))
-                    }
+                let not_callable = || Exception::type_error(TypeError::NOT_CALLABLE, self.clone());
+                let func_value =
+                    (heap.lookup_member(*this_ref, name, *this_ref)?).ok_or_else(not_callable)?;
+                let func_ref = func_value.as_objref().ok_or_else(not_callable)?;
+                // functions called by name get the global `this`
+                let this_ref = match heap.is_scope(*this_ref) {
+                    true => Heap::GLOBAL,
+                    false => *this_ref,
                 };
-                let func_value = (heap.get(of).get_own_value(name)).ok_or_else(|| {
-                    Exception::type_error(TypeError::NOT_CALLABLE, Interpreted::member(of, name))
-                })?;
-                let func_ref = (func_value.to_ref()).map_err(|_| {
-                    Exception::type_error(TypeError::NOT_CALLABLE, Interpreted::member(of, name))
-                })?;
-                Ok((func_ref, *this_ref, name.clone()))
-            }
-            Interpreted::Value(JSValue::Ref(func_ref)) => {
-                let this_ref = Heap::GLOBAL; // TODO: figure out what is this
-                Ok((*func_ref, this_ref, "<anonymous>".into()))
+                Ok((func_ref, this_ref, name.clone()))
+            }
+            Interpreted::Slot { .. } => {
+                let func_ref = (self.to_ref(heap))
+                    .map_err(|_| Exception::type_error(TypeError::NOT_CALLABLE, self.clone()))?;
+                Ok((func_ref, Heap::NULL, "<anonymous>".into()))
+            }
+            Interpreted::Value(value) if value.as_objref().is_some() => {
+                Ok((value.to_ref()?, Heap::NULL, "<anonymous>".into()))
             }
             _ => Err(Exception::type_error(TypeError::NOT_CALLABLE, self.clone())),
         }
     }
 
-    /// Corresponds to Javascript `delete` operator and all its weirdness.
-    /// `Ok`/`Err` correspond to `true`/`false` from `delete`.
+    /// Corresponds to Javascript `delete` operator and all its weirdness:
+    /// gives the result of `delete`, local variables cannot be deleted.
+    /// Strict mode code throws instead of failing to delete a property.
     /// <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/delete>
-    pub fn delete(&self, heap: &mut Heap) -> JSResult<()> {
+    pub fn delete(&self, heap: &mut Heap) -> JSResult<bool> {
         match self {
-            Interpreted::Member { of, name } => {
-                let object = heap.get_mut(*of);
-                let configurable = match object.properties.get(name) {
-                    Some(p) => p.access.configurable(),
-                    None => return Ok(()),
-                };
-                if configurable {
-                    object.properties.remove(name);
-                    Ok(())
-                } else {
-                    Err(Exception::attr_type_error(
-                        TypeError::NONCONFIGURABLE_PROPERTY,
-                        *of,
-                        name.clone(),
-                    ))
-                }
-            }
-            _ => Ok(()),
+            Interpreted::Member { of, name } => match heap.delete_member(*of, name)? {
+                false if heap.is_strict() => Err(Exception::attr_type_error(
+                    TypeError::NONCONFIGURABLE_PROPERTY,
+                    *of,
+                    name.clone(),
+                )),
+                deleted => Ok(deleted),
+            },
+            Interpreted::Slot { .. } => Ok(false),
+            _ => Ok(true),
         }
     }
 }
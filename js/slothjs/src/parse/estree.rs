@@ -75,6 +75,7 @@ impl ToESTree for Expression {
         match &self.expr {
             Expr::Literal(lit) => lit.to_estree(),
             Expr::Identifier(id) => id.to_estree(),
+            Expr::Variable(var) => var.name.to_estree(),
             Expr::BinaryOp(binop) => {
                 let BinaryExpression(left, op, right) = binop.as_ref();
                 let left = left.to_estree();
@@ -89,7 +90,10 @@ impl ToESTree for Expression {
 
 impl ToESTree for Literal {
     fn to_estree(&self) -> JSON {
-        json!({"type": "Literal", "value": self.to_json()})
+        match self.as_bigint() {
+            Some(n) => json!({"type": "Literal", "value": null, "bigint": n.to_string()}),
+            None => json!({"type": "Literal", "value": self.to_json()}),
+        }
     }
 }
 
@@ -78,17 +78,17 @@ impl SourceNode for HeapNode {
 
     fn get_bool(&self, property: &str) -> ParseResult<bool> {
         let value = self.property(property)?;
-        match value {
-            JSValue::Bool(b) => Ok(b),
-            _ => Err(ParseError::want("bool", self.to_error())),
+        match value.as_bool() {
+            Some(b) => Ok(b),
+            None => Err(ParseError::want("bool", self.to_error())),
         }
     }
 
     fn get_str(&self, property: &str) -> ParseResult<JSString> {
         let value = self.property(property)?;
-        match value {
-            JSValue::String(s) => Ok(s),
-            _ => Err(ParseError::want("string", self.to_error())),
+        match value.as_string() {
+            Some(s) => Ok(s),
+            None => Err(ParseError::want("string", self.to_error())),
         }
     }
 
@@ -96,12 +96,12 @@ impl SourceNode for HeapNode {
     where
         F: FnMut(&Self) -> ParseResult<T>,
     {
-        match self.property(property)? {
-            JSValue::Ref(childref) => {
+        match self.property(property)?.as_objref() {
+            Some(childref) => {
                 let child = self.with_node(childref);
                 Ok(action(&child)?)
             }
-            _ => Err(ParseError::no_attr(property, self.to_error())),
+            None => Err(ParseError::no_attr(property, self.to_error())),
         }
     }
 
@@ -114,7 +114,7 @@ impl SourceNode for HeapNode {
         let array = (self.heap.get(arrref).as_array())
             .ok_or_else(|| ParseError::want("array", self.to_error()))?;
         let mut result = vec![];
-        for item in &array.storage {
+        for item in array.to_vec() {
             let childref =
                 (item.to_ref()).map_err(|_| ParseError::want("objects", self.to_error()))?;
             let child = self.with_node(childref);
@@ -123,4 +123,25 @@ impl SourceNode for HeapNode {
         }
         Ok(result)
     }
+
+    fn map_opt_array<T, F>(&self, property: &str, mut func: F) -> ParseResult<Vec<Option<T>>>
+    where
+        F: FnMut(&Self) -> ParseResult<T>,
+    {
+        let value = self.property(property)?;
+        let arrref = (value.to_ref()).map_err(|_| ParseError::want("array", self.to_error()))?;
+        let array = (self.heap.get(arrref).as_array())
+            .ok_or_else(|| ParseError::want("array", self.to_error()))?;
+        let mut result = vec![];
+        for item in array.to_vec() {
+            let childref =
+                (item.to_ref()).map_err(|_| ParseError::want("objects", self.to_error()))?;
+            let r = match childref {
+                Heap::NULL => None,
+                _ => Some(func(&self.with_node(childref))?),
+            };
+            result.push(r);
+        }
+        Ok(result)
+    }
 }
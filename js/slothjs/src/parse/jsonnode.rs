@@ -75,4 +75,20 @@ impl SourceNode for JSON {
             (jarray.as_array()).ok_or_else(|| ParseError::want("array", JSON::Null.to_error()))?;
         array.iter().map(func).collect()
     }
+
+    fn map_opt_array<T, F>(&self, property: &str, mut func: F) -> ParseResult<Vec<Option<T>>>
+    where
+        F: FnMut(&Self) -> ParseResult<T>,
+    {
+        let jarray =
+            (self.get(property)).ok_or_else(|| ParseError::no_attr(property, self.to_error()))?;
+        let array =
+            (jarray.as_array()).ok_or_else(|| ParseError::want("array", JSON::Null.to_error()))?;
+        (array.iter())
+            .map(|item| match item.is_null() {
+                true => Ok(None),
+                false => func(item).map(Some),
+            })
+            .collect()
+    }
 }
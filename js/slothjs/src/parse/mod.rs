@@ -9,8 +9,9 @@ use crate::prelude::*;
 use crate::ast::*; // yes, EVERYTHING.
 
 use crate::error::ParseError;
+use crate::shape::PropertyCache;
 use crate::source;
-use crate::JSON;
+use crate::{BigInt, JSON};
 
 pub use self::heapnode::HeapNode;
 
@@ -24,6 +25,8 @@ pub struct ParserContext {
     pub declared_variables: HashSet<Identifier>, // var ...
 
     pub used_identifiers: HashSet<Identifier>, // note: they are not free before they leave the scope
+
+    pub strict: bool, // is it strict mode code?
 }
 
 impl ParserContext {
@@ -33,9 +36,26 @@ impl ParserContext {
             declared_bindings: HashSet::new(),
             declared_variables: HashSet::new(),
             declared_functions: Vec::new(),
+            strict: false,
         }
     }
 
+    /// Makes the context strict if the statements of `source.body` begin
+    /// with a "use strict" directive.
+    fn enter_directives<S: SourceNode>(&mut self, source: &S) -> ParseResult<()> {
+        let directives = source.map_array("body", |stmt| Ok(stmt.get_str("directive").ok()));
+        let directives = match directives {
+            Ok(directives) => directives,
+            Err(ParseError::ObjectWithout { .. }) => return Ok(()), // an expression body
+            Err(e) => return Err(e),
+        };
+        let mut prologue = directives.iter().map_while(Option::as_ref);
+        if prologue.any(|directive| directive.as_str() == "use strict") {
+            self.strict = true;
+        }
+        Ok(())
+    }
+
     fn remember_declaration(
         &mut self,
         kind: DeclarationKind,
@@ -57,6 +77,7 @@ impl ParserContext {
     {
         // inner_ctx accumulates used identifiers and declared bindings.
         let mut inner_ctx = ParserContext::new();
+        inner_ctx.strict = self.strict;
         core::mem::swap(
             &mut self.declared_variables,
             &mut inner_ctx.declared_variables,
@@ -162,6 +183,11 @@ pub trait SourceNode: Sized {
     fn map_array<T, F>(&self, property: &str, func: F) -> ParseResult<Vec<T>>
     where
         F: FnMut(&Self) -> ParseResult<T>;
+
+    /// Like [`SourceNode::map_array`], but `null` children become `None`.
+    fn map_opt_array<T, F>(&self, property: &str, func: F) -> ParseResult<Vec<Option<T>>>
+    where
+        F: FnMut(&Self) -> ParseResult<T>;
 }
 
 impl Program {
@@ -172,18 +198,9 @@ impl Program {
         source.expect_str("type", "Program")?;
 
         let mut ctx = ParserContext::new();
+        ctx.enter_directives(source)?;
         let body = BlockStatement::parse_from(source, &mut ctx)?;
-
-        let ParserContext {
-            declared_variables: variables,
-            declared_functions: functions,
-            ..
-        } = ctx;
-        Ok(Program {
-            body,
-            variables,
-            functions,
-        })
+        Ok(Program::new(body, ctx.strict))
     }
 }
 
@@ -223,7 +240,7 @@ impl ParseFrom for Statement {
             "ReturnStatement" => Stmt::Return(ReturnStatement::parse_from(source, ctx)?),
             "SwitchStatement" => Stmt::Switch(SwitchStatement::parse_from(source, ctx)?),
             "ThrowStatement" => Stmt::Throw(ThrowStatement::parse_from(source, ctx)?),
-            "TryStatement" => Stmt::Try(TryStatement::parse_from(source, ctx)?),
+            "TryStatement" => Stmt::Try(Box::new(TryStatement::parse_from(source, ctx)?)),
             "VariableDeclaration" => Stmt::Variable(VariableDeclaration::parse_from(source, ctx)?),
             _ => {
                 return Err(ParseError::UnknownNodeType {
@@ -241,7 +258,11 @@ impl ParseFrom for BlockStatement {
             source.map_array("body", |jstmt| Statement::parse_from(jstmt, ctx))
         })?;
 
-        Ok(BlockStatement { body, bindings })
+        Ok(BlockStatement {
+            body,
+            bindings,
+            slots: Vec::new(),
+        })
     }
 }
 
@@ -412,7 +433,11 @@ impl ParseFrom for TryStatement {
         let handler = source.map_opt_node("handler", |jhandler| {
             let param = jhandler.map_node("param", |jparam| Identifier::parse_from(jparam, ctx))?;
             let body = jhandler.map_node("body", |jbody| BlockStatement::parse_from(jbody, ctx))?;
-            Ok(CatchClause { param, body })
+            Ok(CatchClause {
+                param,
+                body,
+                slot: Slot::Dynamic,
+            })
         })?;
 
         let finalizer = source.map_opt_node("finalizer", |jobject| {
@@ -453,7 +478,11 @@ impl ParseFrom for VariableDeclaration {
             })?;
 
             ctx.remember_declaration(kind, &name)?;
-            Ok(VariableDeclarator { name, init })
+            Ok(VariableDeclarator {
+                name,
+                init,
+                slot: Slot::Dynamic,
+            })
         })?;
 
         Ok(VariableDeclaration { kind, declarations })
@@ -471,7 +500,11 @@ impl ParseFrom for FunctionDeclaration {
         let id = (function.func.id.clone())
             .ok_or_else(|| ParseError::no_attr("id", source.to_error()))?;
 
-        let funcdecl = FunctionDeclaration { id, function };
+        let funcdecl = FunctionDeclaration {
+            id,
+            function,
+            slot: Slot::Dynamic,
+        };
         ctx.declared_functions.push(funcdecl.clone());
         Ok(funcdecl)
     }
@@ -495,14 +528,20 @@ impl ParseFrom for Expression {
         let expr = match expr_type.as_str() {
             "ArrayExpression" => {
                 let elements =
-                    source.map_array("elements", |jelem| Expression::parse_from(jelem, ctx))?;
+                    source.map_opt_array("elements", |jelem| Expression::parse_from(jelem, ctx))?;
                 let expr = ArrayExpression(elements);
                 Expr::Array(expr)
             }
-            "AssignmentExpression" => {
-                let expr = AssignmentExpression::parse_from(source, ctx)?;
-                Expr::Assign(Box::new(expr))
-            }
+            "AssignmentExpression" => match source.get_str("operator")?.as_str() {
+                "&&=" | "||=" | "??=" => {
+                    let expr = LogicalAssignmentExpression::parse_from(source, ctx)?;
+                    Expr::LogicalAssign(Box::new(expr))
+                }
+                _ => {
+                    let expr = AssignmentExpression::parse_from(source, ctx)?;
+                    Expr::Assign(Box::new(expr))
+                }
+            },
             "BinaryExpression" => {
                 let expr = BinaryExpression::parse_from(source, ctx)?;
                 Expr::BinaryOp(Box::new(expr))
@@ -514,6 +553,10 @@ impl ParseFrom for Expression {
                     source.map_array("arguments", |jarg| Expression::parse_from(jarg, ctx))?;
                 Expr::Call(Box::new(CallExpression(callee, arguments)))
             }
+            "ChainExpression" => {
+                let expr = ChainExpression::parse_from(source, ctx)?;
+                Expr::Chain(Box::new(expr))
+            }
             "ConditionalExpression" => {
                 let condexpr =
                     source.map_node("test", |jtest| Expression::parse_from(jtest, ctx))?;
@@ -537,7 +580,14 @@ impl ParseFrom for Expression {
                 Expr::Identifier(expr)
             }
             "Literal" => {
-                let lit = source.get_literal("value")?;
+                let lit = match source.get_str("bigint") {
+                    Ok(digits) => match BigInt::parse(&digits) {
+                        Some(n) => Literal::from(n),
+                        None => return Err(ParseError::InvalidBigInt(digits.to_string())),
+                    },
+                    Err(ParseError::ObjectWithout { .. }) => source.get_literal("value")?,
+                    Err(e) => return Err(e),
+                };
                 Expr::Literal(lit)
             }
             "LogicalExpression" => {
@@ -550,7 +600,7 @@ impl ParseFrom for Expression {
                 let object = source.map_node("object", |jobj| Expression::parse_from(jobj, ctx))?;
                 let property =
                     source.map_node("property", |jprop| Expression::parse_from(jprop, ctx))?;
-                let expr = MemberExpression(object, property, computed);
+                let expr = MemberExpression(object, property, computed, PropertyCache::default());
                 Expr::Member(Box::new(expr))
             }
             "NewExpression" => {
@@ -658,6 +708,7 @@ impl ParseFrom for BinaryExpression {
             "+" => BinOp::Plus,
             "-" => BinOp::Minus,
             "*" => BinOp::Star,
+            "**" => BinOp::StarStar,
             "/" => BinOp::Slash,
             "%" => BinOp::Percent,
             "==" => BinOp::EqEq,
@@ -678,7 +729,7 @@ impl ParseFrom for BinaryExpression {
             "instanceof" => BinOp::InstanceOf,
             _ => {
                 return Err(ParseError::UnexpectedValue {
-                    want: "one of: + - * / % == === != < > <= >= instanceof | ^ & << >> >>>",
+                    want: "one of: + - * ** / % == === != < > <= >= instanceof | ^ & << >> >>>",
                     value: source.map_node("operator", |jop| Ok(jop.to_error()))?,
                 })
             }
@@ -693,9 +744,10 @@ impl ParseFrom for LogicalExpression {
         let op = match opstr.as_str() {
             "&&" => BoolOp::And,
             "||" => BoolOp::Or,
+            "??" => BoolOp::Nullish,
             _ => {
                 return Err(ParseError::UnexpectedValue {
-                    want: "&& or ||",
+                    want: "&& or || or ??",
                     value: source.map_node("operator", |jop| Ok(jop.to_error()))?,
                 })
             }
@@ -715,6 +767,7 @@ impl ParseFrom for AssignmentExpression {
             "+=" => Some(BinOp::Plus),
             "-=" => Some(BinOp::Minus),
             "*=" => Some(BinOp::Star),
+            "**=" => Some(BinOp::StarStar),
             "/=" => Some(BinOp::Slash),
             "%=" => Some(BinOp::Percent),
             "<<=" => Some(BinOp::LtLt),
@@ -725,7 +778,7 @@ impl ParseFrom for AssignmentExpression {
             "&=" => Some(BinOp::Ampersand),
             _ => {
                 return Err(ParseError::UnexpectedValue {
-                    want: "one of: = += -= *= /= %= <<= >>= >>>= |= ^= &=",
+                    want: "one of: = += -= *= **= /= %= <<= >>= >>>= |= ^= &=",
                     value: source.map_node("operator", |jop| Ok(jop.to_error()))?,
                 })
             }
@@ -738,6 +791,82 @@ impl ParseFrom for AssignmentExpression {
     }
 }
 
+impl ParseFrom for LogicalAssignmentExpression {
+    fn parse_from<S: SourceNode>(source: &S, ctx: &mut ParserContext) -> ParseResult<Self> {
+        let jop = source.get_str("operator")?;
+        let op = match jop.as_str() {
+            "&&=" => BoolOp::And,
+            "||=" => BoolOp::Or,
+            "??=" => BoolOp::Nullish,
+            _ => {
+                return Err(ParseError::UnexpectedValue {
+                    want: "one of: &&= ||= ??=",
+                    value: source.map_node("operator", |jop| Ok(jop.to_error()))?,
+                })
+            }
+        };
+
+        let right = source.map_node("right", |jright| Expression::parse_from(jright, ctx))?;
+        let left = source.map_node("left", |jleft| Expression::parse_from(jleft, ctx))?;
+
+        Ok(LogicalAssignmentExpression(left, op, right))
+    }
+}
+
+impl ParseFrom for ChainExpression {
+    fn parse_from<S: SourceNode>(source: &S, ctx: &mut ParserContext) -> ParseResult<Self> {
+        source.expect_str("type", "ChainExpression")?;
+
+        let mut links = vec![];
+        let object = source.map_node("expression", |jexpr| {
+            ChainExpression::parse_links(jexpr, &mut links, ctx)
+        })?;
+        Ok(ChainExpression(object, links))
+    }
+}
+
+impl ChainExpression {
+    /// Flattens nested member and call nodes of a chain into `links`,
+    /// returns the object of the chain.
+    fn parse_links<S: SourceNode>(
+        source: &S,
+        links: &mut Vec<ChainLink>,
+        ctx: &mut ParserContext,
+    ) -> ParseResult<Expression> {
+        let (object, kind) = match source.get_str("type")?.as_str() {
+            "MemberExpression" => {
+                let object =
+                    source.map_node("object", |jobj| Self::parse_links(jobj, links, ctx))?;
+                let kind = match source.get_bool("computed")? {
+                    true => LinkKind::Computed(
+                        source.map_node("property", |jprop| Expression::parse_from(jprop, ctx))?,
+                    ),
+                    false => LinkKind::Property(
+                        source.map_node("property", |jprop| Identifier::parse_from(jprop, ctx))?,
+                    ),
+                };
+                (object, kind)
+            }
+            "CallExpression" => {
+                let callee =
+                    source.map_node("callee", |jcallee| Self::parse_links(jcallee, links, ctx))?;
+                let arguments =
+                    source.map_array("arguments", |jarg| Expression::parse_from(jarg, ctx))?;
+                (callee, LinkKind::Call(arguments))
+            }
+            _ => return Expression::parse_from(source, ctx),
+        };
+        // only the links after the first `?.` have the attribute
+        let optional = match source.get_bool("optional") {
+            Ok(optional) => optional,
+            Err(ParseError::ObjectWithout { .. }) => false,
+            Err(e) => return Err(e),
+        };
+        links.push(ChainLink { optional, kind });
+        Ok(object)
+    }
+}
+
 impl ParseFrom for ObjectExpression {
     fn parse_from<S: SourceNode>(source: &S, ctx: &mut ParserContext) -> ParseResult<Self> {
         source.expect_str("type", "ObjectExpression")?;
@@ -751,9 +880,10 @@ impl ParseFrom for ObjectExpression {
             } else {
                 match keyexpr.expr {
                     Expr::Identifier(ident) => ObjectKey::Identifier(ident.0),
-                    Expr::Literal(lit) => match lit.to_json().as_str() {
-                        Some(val) => ObjectKey::Identifier(val.into()),
-                        None => ObjectKey::Identifier(lit.to_string().into()),
+                    Expr::Literal(lit) => match (lit.to_json().as_str(), lit.as_bigint()) {
+                        (Some(val), _) => ObjectKey::Identifier(val.into()),
+                        (None, Some(n)) => ObjectKey::Identifier(n.to_string().into()),
+                        (None, None) => ObjectKey::Identifier(lit.to_string().into()),
                     },
                     _ => {
                         return Err(ParseError::UnexpectedValue {
@@ -765,8 +895,26 @@ impl ParseFrom for ObjectExpression {
             };
 
             let value = jprop.map_node("value", |jval| Expression::parse_from(jval, ctx))?;
-
-            Ok((key, value))
+            let kind = match jprop.get_str("kind")?.as_str() {
+                "init" if jprop.get_bool("method")? => PropertyKind::Method,
+                "init" => PropertyKind::Init,
+                "get" => PropertyKind::Get,
+                "set" => PropertyKind::Set,
+                _ => {
+                    return Err(ParseError::UnexpectedValue {
+                        want: "init | get | set",
+                        value: jprop.to_error(),
+                    })
+                }
+            };
+            let shorthand = jprop.get_bool("shorthand")?;
+
+            Ok(ObjectProperty {
+                key,
+                value,
+                kind,
+                shorthand,
+            })
         })?;
 
         Ok(ObjectExpression(properties))
@@ -779,9 +927,18 @@ impl ParseFrom for FunctionExpression {
             source.map_opt_node("id", |jid| Identifier::parse_from(jid, ctx))?;
 
         let mut inner_ctx = ParserContext::new();
+        inner_ctx.strict = ctx.strict;
+        source.map_node("body", |jbody| inner_ctx.enter_directives(jbody))?;
         let params = source.map_array("params", |jparam| {
             Identifier::parse_from(jparam, &mut inner_ctx)
         })?;
+        if inner_ctx.strict {
+            for (i, param) in params.iter().enumerate() {
+                if params[..i].contains(param) {
+                    return Err(ParseError::DuplicateParameter(param.clone()));
+                }
+            }
+        }
 
         let body = source.map_node("body", |jbody| {
             BlockStatement::parse_from(jbody, &mut inner_ctx)
@@ -792,6 +949,7 @@ impl ParseFrom for FunctionExpression {
             declared_variables: variables,
             declared_functions: functions,
             declared_bindings,
+            strict: is_strict,
         } = inner_ctx;
         assert!(declared_bindings.is_empty());
 
@@ -809,9 +967,11 @@ impl ParseFrom for FunctionExpression {
             functions,
             free_variables,
             body,
+            layout: Rc::default(),
             is_generator: source.get_bool("generator").unwrap_or(false),
             is_expression: source.get_bool("expression").unwrap_or(false),
             is_async: source.get_bool("async").unwrap_or(false),
+            is_strict,
         };
         Ok(FunctionExpression {
             func: Rc::new(func),
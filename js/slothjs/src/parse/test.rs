@@ -93,3 +93,93 @@ fn test_redeclared() -> Result<(), ParseError> {
         other => panic!("want an error, got {:?}", other),
     }
 }
+
+#[test]
+fn test_resolve() -> Result<(), ParseError> {
+    // function f(a) { let b = a; var c; return function() { return b + d; }; }
+    let json_ast = json!({
+        "type": "BlockStatement",
+        "body": [{
+            "type": "FunctionDeclaration",
+            "id": {"type": "Identifier", "name": "f"},
+            "params": [{"type": "Identifier", "name": "a"}],
+            "body": { "type": "BlockStatement", "body": [
+                {   "type": "VariableDeclaration", "kind": "let",
+                    "declarations": [{
+                        "type": "VariableDeclarator",
+                        "id": {"type": "Identifier", "name": "b"},
+                        "init": {"type": "Identifier", "name": "a"},
+                    }],
+                },
+                {   "type": "VariableDeclaration", "kind": "var",
+                    "declarations": [{
+                        "type": "VariableDeclarator",
+                        "id": {"type": "Identifier", "name": "c"},
+                        "init": null,
+                    }],
+                },
+                {   "type": "ReturnStatement",
+                    "argument": {
+                        "type": "FunctionExpression",
+                        "params": [],
+                        "body": { "type": "BlockStatement", "body": [{
+                            "type": "ReturnStatement",
+                            "argument": {
+                                "type": "BinaryExpression",
+                                "operator": "+",
+                                "left": {"type": "Identifier", "name": "b"},
+                                "right": {"type": "Identifier", "name": "d"},
+                            },
+                        }]},
+                    },
+                },
+            ]},
+        }],
+    });
+    let body = BlockStatement::parse_from(&json_ast, &mut ParserContext::new())?;
+    let program = Program::from(body);
+
+    let f = &program.functions[0].function.func;
+    assert_eq!(
+        program.functions[0].slot,
+        Slot::Dynamic,
+        "functions of a program are global"
+    );
+    let names = f
+        .layout
+        .names
+        .iter()
+        .map(|name| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["a", "arguments", "c", "b"]);
+    assert_eq!(
+        f.layout.boxed,
+        [false, false, false, true],
+        "only `b` is captured"
+    );
+    assert_eq!(f.layout.params, [Slot::Local(0)]);
+
+    let inner = match &f.body.body[2].stmt {
+        Stmt::Return(ReturnStatement(Some(Expression {
+            expr: Expr::Function(funcexpr),
+            ..
+        }))) => &funcexpr.func,
+        other => panic!("want a function expression, got {:?}", other),
+    };
+    assert_eq!(
+        inner.layout.captures,
+        [(Identifier::from("b"), Slot::Boxed(3))]
+    );
+    match &inner.body.body[0].stmt {
+        Stmt::Return(ReturnStatement(Some(Expression {
+            expr: Expr::BinaryOp(binexpr),
+            ..
+        }))) => {
+            let BinaryExpression(left, _, right) = binexpr.as_ref();
+            assert!(matches!(&left.expr, Expr::Variable(var) if var.slot == Slot::Captured(0)));
+            assert!(matches!(&right.expr, Expr::Variable(var) if var.slot == Slot::Dynamic));
+        }
+        other => panic!("want a return of a sum, got {:?}", other),
+    }
+    Ok(())
+}
@@ -2,6 +2,7 @@ pub use core::{convert::TryFrom, fmt, fmt::Write, str::FromStr};
 
 pub use alloc::{
     boxed::Box,
+    collections::BTreeMap,
     format,
     rc::Rc,
     string::{String, ToString},
@@ -16,4 +17,4 @@ pub use std::collections::{HashMap, HashSet};
 pub use hashbrown::{hash_map::HashMap, hash_set::HashSet};
 
 #[allow(unused_imports)]
-pub use crate::{JSNumber, JSString, JSValue, JSON};
+pub use crate::{JSNumber, JSString, JSValue, Unboxed, JSON};
@@ -0,0 +1,551 @@
+//! Static scope resolution.
+//!
+//! [`program()`] lays out the variables of a [`Program`] and of every function in it
+//! in the slots of their frames (see [`Layout`]) and resolves every variable reference
+//! to a [`Slot`], turning it into an [`Expr::Variable`]:
+//!
+//! - parameters, `arguments`, `var`s, hoisted functions and `let` bindings of a function
+//!   live in the frame of its call, they are `Slot::Local`;
+//! - a local variable used by an inner function is `Slot::Boxed`: its slot holds a cell
+//!   that closures share;
+//! - a closure captures only the cells of its free variables, `Slot::Captured` indexes them;
+//!   one that calls `eval()` captures every variable of the enclosing functions instead;
+//! - everything else is `Slot::Dynamic` and is looked up by name: globals,
+//!   `var`s and functions of the program, variables declared by `eval()`.
+//!
+//! `let` bindings of a block get a fresh value (and a fresh cell, if boxed) every time
+//! the block is entered, so closures made in different iterations of a loop do not share
+//! them. The bindings of a function body are the exception: they live as long as the call,
+//! so that hoisted functions can use them.
+//!
+//! Whether a slot is boxed is only known after its whole function has been walked,
+//! so the resolver makes two passes.
+
+use crate::prelude::*;
+
+use crate::ast::*; // yes, EVERYTHING
+
+/// Resolves the variables of `program` and of all functions in it.
+/// Also hoists `var`s and function declarations of `program`.
+pub fn program(program: &mut Program) {
+    let (variables, functions) = hoist(&program.body.body);
+    program.variables = variables;
+    program.functions = functions;
+
+    let mut first = Resolver::default();
+    first.program(program);
+
+    let mut second = Resolver {
+        known_boxed: first.boxed,
+        ..Resolver::default()
+    };
+    second.program(program);
+}
+
+#[derive(Default)]
+struct Resolver {
+    /// the functions being resolved, the innermost last; the program is the first one
+    scopes: Vec<FunctionScope>,
+    /// the boxed slots of every function (in the order of visiting) known from the first pass
+    known_boxed: Vec<Vec<bool>>,
+    /// the boxed slots of every function found in this pass
+    boxed: Vec<Vec<bool>>,
+}
+
+#[derive(Default)]
+struct FunctionScope {
+    /// the number of the function in the order of visiting
+    index: usize,
+    names: Vec<JSString>,
+    boxed: Vec<bool>,
+    /// nested lexical scopes: names to slots, the innermost last
+    blocks: Vec<HashMap<JSString, u32>>,
+    captures: Vec<(Identifier, Slot)>,
+    captured: HashMap<JSString, u32>,
+}
+
+impl Resolver {
+    fn program(&mut self, program: &mut Program) {
+        self.enter_function();
+        // `var`s and functions of a program are global, its `let`s are not
+        self.enter_block(&mut program.body, false);
+        for funcdecl in program.functions.iter_mut() {
+            funcdecl.slot = Slot::Dynamic;
+            self.function(&mut funcdecl.function);
+        }
+        self.statements(&mut program.body.body);
+        self.leave_block();
+
+        let mut layout = self.leave_function();
+        layout.strict = program.is_strict;
+        program.layout = Rc::new(layout);
+    }
+
+    fn function(&mut self, funcexpr: &mut FunctionExpression) {
+        let function = Rc::make_mut(&mut funcexpr.func);
+        self.enter_function();
+        self.scope_mut().blocks.push(HashMap::new());
+
+        let params = (function.params.iter())
+            .map(|param| self.declare(&param.0, false))
+            .collect::<Vec<_>>();
+        let arguments = self.declare(&JSString::from("arguments"), false);
+        let mut variables = function.variables.iter().collect::<Vec<_>>();
+        variables.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        for var in variables {
+            self.declare(&var.0, false);
+        }
+        for funcdecl in function.functions.iter() {
+            self.declare(&funcdecl.id.0, false);
+        }
+        self.enter_block(&mut function.body, false);
+
+        for funcdecl in function.functions.iter_mut() {
+            funcdecl.slot = self.lookup(&funcdecl.id.0);
+            self.function(&mut funcdecl.function);
+        }
+        self.statements(&mut function.body.body);
+
+        self.leave_block();
+        self.scope_mut().blocks.pop();
+
+        let mut layout = self.leave_function();
+        layout.params = (params.into_iter())
+            .map(|index| slot(&layout.boxed, index))
+            .collect();
+        layout.arguments = slot(&layout.boxed, arguments);
+        layout.strict = function.is_strict;
+        function.layout = Rc::new(layout);
+    }
+
+    fn enter_function(&mut self) {
+        let index = self.boxed.len();
+        self.boxed.push(Vec::new());
+        self.scopes.push(FunctionScope {
+            index,
+            ..FunctionScope::default()
+        });
+    }
+
+    fn leave_function(&mut self) -> Layout {
+        let scope = self.scopes.pop().expect("no function scope");
+        self.boxed[scope.index] = scope.boxed.clone();
+        Layout {
+            names: scope.names,
+            boxed: scope.boxed,
+            captures: scope.captures,
+            params: Vec::new(),
+            arguments: Slot::Dynamic,
+            strict: false,
+        }
+    }
+
+    fn scope_mut(&mut self) -> &mut FunctionScope {
+        self.scopes.last_mut().expect("no function scope")
+    }
+
+    /// Declares `name` in the innermost lexical scope, gives its slot index.
+    /// A `var` reuses a slot with the same name.
+    fn declare(&mut self, name: &JSString, is_binding: bool) -> u32 {
+        let scope = self.scopes.last_mut().expect("no function scope");
+        let block = scope.blocks.last_mut().expect("no block scope");
+        if !is_binding {
+            if let Some(&index) = block.get(name) {
+                return index;
+            }
+        }
+        let index = scope.names.len() as u32;
+        let known = (self.known_boxed.get(scope.index))
+            .and_then(|boxed| boxed.get(index as usize).copied())
+            .unwrap_or(false);
+        scope.names.push(name.clone());
+        scope.boxed.push(known);
+        block.insert(name.clone(), index);
+        index
+    }
+
+    /// Pushes a lexical scope with the `let` bindings of `block`.
+    /// The bindings are reset on entering the block if `fresh`.
+    fn enter_block(&mut self, block: &mut BlockStatement, fresh: bool) {
+        let bindings = bindings(&block.body);
+        self.scope_mut().blocks.push(HashMap::new());
+        let indices = (bindings.iter())
+            .map(|name| self.declare(&name.0, true))
+            .collect::<Vec<_>>();
+        let slots = indices.into_iter().map(|index| self.local(index)).collect();
+        block.slots = if fresh { slots } else { Vec::new() };
+        block.bindings = bindings.into_iter().collect();
+    }
+
+    fn leave_block(&mut self) {
+        self.scope_mut().blocks.pop();
+    }
+
+    fn local(&self, index: u32) -> Slot {
+        let scope = self.scopes.last().expect("no function scope");
+        slot(&scope.boxed, index)
+    }
+
+    fn lookup(&mut self, name: &JSString) -> Slot {
+        self.lookup_at(self.scopes.len() - 1, name)
+    }
+
+    /// Resolves `name` in the function `level`, capturing it from outer functions if needed.
+    fn lookup_at(&mut self, level: usize, name: &JSString) -> Slot {
+        let scope = &self.scopes[level];
+        for block in scope.blocks.iter().rev() {
+            if let Some(&index) = block.get(name) {
+                return slot(&scope.boxed, index);
+            }
+        }
+        if let Some(&index) = scope.captured.get(name) {
+            return Slot::Captured(index);
+        }
+        if level == 0 {
+            return Slot::Dynamic;
+        }
+
+        let source = match self.lookup_at(level - 1, name) {
+            Slot::Dynamic => return Slot::Dynamic,
+            Slot::Local(index) | Slot::Boxed(index) => {
+                self.scopes[level - 1].boxed[index as usize] = true;
+                Slot::Boxed(index)
+            }
+            Slot::Captured(index) => Slot::Captured(index),
+        };
+        let scope = &mut self.scopes[level];
+        let index = scope.captures.len() as u32;
+        scope.captures.push((Identifier(name.clone()), source));
+        scope.captured.insert(name.clone(), index);
+        Slot::Captured(index)
+    }
+
+    /// Captures every variable of the enclosing functions that is visible here:
+    /// `eval()` looks names up in the frame of its caller, see [`crate::Heap::lookup_var()`].
+    fn capture_visible(&mut self) {
+        let Some((_, enclosing)) = self.scopes.split_last() else {
+            return;
+        };
+        // the variables of the program are dynamic anyway
+        let mut names = (enclosing.iter().skip(1))
+            .flat_map(|scope| scope.blocks.iter().flat_map(|block| block.keys()))
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        names.dedup();
+        for name in names {
+            self.lookup(&name);
+        }
+    }
+
+    /*
+     *  statements
+     */
+
+    fn block(&mut self, block: &mut BlockStatement) {
+        self.enter_block(block, true);
+        self.statements(&mut block.body);
+        self.leave_block();
+    }
+
+    fn statements(&mut self, stmts: &mut [Statement]) {
+        for stmt in stmts.iter_mut() {
+            self.statement(stmt);
+        }
+    }
+
+    fn statement(&mut self, stmt: &mut Statement) {
+        match &mut stmt.stmt {
+            Stmt::Empty | Stmt::Break(_) | Stmt::Continue(_) => (),
+            // resolved in the `functions` of the enclosing function
+            Stmt::Function(_) => (),
+            Stmt::Block(block) => self.block(block),
+            Stmt::Expr(stmt) => self.expression(&mut stmt.expression),
+            Stmt::If(stmt) => {
+                self.expression(&mut stmt.test);
+                self.statement(&mut stmt.consequent);
+                if let Some(alternate) = stmt.alternate.as_mut() {
+                    self.statement(alternate);
+                }
+            }
+            Stmt::Switch(stmt) => {
+                self.expression(&mut stmt.discriminant);
+                for case in stmt.cases.iter_mut() {
+                    if let Some(test) = case.test.as_mut() {
+                        self.expression(test);
+                    }
+                    self.statements(&mut case.consequent);
+                }
+            }
+            Stmt::For(stmt) => {
+                self.statement(&mut stmt.init);
+                if let Some(test) = stmt.test.as_mut() {
+                    self.expression(test);
+                }
+                if let Some(update) = stmt.update.as_mut() {
+                    self.expression(update);
+                }
+                self.statement(&mut stmt.body);
+            }
+            Stmt::ForIn(stmt) => {
+                match &mut stmt.left {
+                    ForInTarget::Var(vardecl) => self.declaration(vardecl),
+                    ForInTarget::Expr(expr) => self.expression(expr),
+                }
+                self.expression(&mut stmt.right);
+                self.statement(&mut stmt.body);
+            }
+            Stmt::Label(stmt) => self.statement(&mut stmt.1),
+            Stmt::Return(ReturnStatement(argument)) => {
+                if let Some(argument) = argument.as_mut() {
+                    self.expression(argument);
+                }
+            }
+            Stmt::Throw(ThrowStatement(argument)) => self.expression(argument),
+            Stmt::Try(stmt) => {
+                self.block(&mut stmt.block);
+                if let Some(handler) = stmt.handler.as_mut() {
+                    self.scope_mut().blocks.push(HashMap::new());
+                    let index = self.declare(&handler.param.0, true);
+                    handler.slot = self.local(index);
+                    self.block(&mut handler.body);
+                    self.leave_block();
+                }
+                if let Some(finalizer) = stmt.finalizer.as_mut() {
+                    self.block(finalizer);
+                }
+            }
+            Stmt::Variable(vardecl) => self.declaration(vardecl),
+        }
+    }
+
+    fn declaration(&mut self, vardecl: &mut VariableDeclaration) {
+        for decl in vardecl.declarations.iter_mut() {
+            decl.slot = self.lookup(&decl.name.0);
+            if let Some(init) = decl.init.as_mut() {
+                self.expression(init);
+            }
+        }
+    }
+
+    /*
+     *  expressions
+     */
+
+    fn expression(&mut self, expr: &mut Expression) {
+        match &mut expr.expr {
+            Expr::Literal(_) | Expr::This => (),
+            Expr::Identifier(id) => {
+                let slot = self.lookup(&id.0);
+                let name = id.clone();
+                expr.expr = Expr::Variable(Variable { name, slot });
+            }
+            Expr::Variable(var) => var.slot = self.lookup(&var.name.0),
+            Expr::BinaryOp(binexpr) => {
+                self.expression(&mut binexpr.0);
+                self.expression(&mut binexpr.2);
+            }
+            Expr::LogicalOp(logexpr) => {
+                self.expression(&mut logexpr.0);
+                self.expression(&mut logexpr.2);
+            }
+            Expr::Call(callexpr) => {
+                if is_eval(&callexpr.0) {
+                    self.capture_visible();
+                }
+                self.expression(&mut callexpr.0);
+                self.expressions(&mut callexpr.1);
+            }
+            Expr::New(newexpr) => {
+                self.expression(&mut newexpr.0);
+                self.expressions(&mut newexpr.1);
+            }
+            Expr::Array(ArrayExpression(exprs)) => exprs
+                .iter_mut()
+                .flatten()
+                .for_each(|expr| self.expression(expr)),
+            Expr::Sequence(SequenceExpression(exprs)) => self.expressions(exprs),
+            Expr::Object(ObjectExpression(properties)) => {
+                for property in properties.iter_mut() {
+                    if let ObjectKey::Computed(key) = &mut property.key {
+                        self.expression(key);
+                    }
+                    self.expression(&mut property.value);
+                }
+            }
+            Expr::Member(membexpr) => {
+                let MemberExpression(object, property, computed, _) = membexpr.as_mut();
+                self.expression(object);
+                if *computed {
+                    self.expression(property);
+                }
+            }
+            Expr::Chain(chain) => {
+                let ChainExpression(object, links) = chain.as_mut();
+                self.expression(object);
+                for link in links.iter_mut() {
+                    match &mut link.kind {
+                        LinkKind::Property(_) => (),
+                        LinkKind::Computed(key) => self.expression(key),
+                        LinkKind::Call(arguments) => self.expressions(arguments),
+                    }
+                }
+            }
+            Expr::Assign(assign) => {
+                self.expression(&mut assign.0);
+                self.expression(&mut assign.2);
+            }
+            Expr::LogicalAssign(assign) => {
+                self.expression(&mut assign.0);
+                self.expression(&mut assign.2);
+            }
+            Expr::Conditional(condexpr) => {
+                self.expression(&mut condexpr.condexpr);
+                self.expression(&mut condexpr.thenexpr);
+                self.expression(&mut condexpr.elseexpr);
+            }
+            Expr::Unary(unary) => self.expression(&mut unary.1),
+            Expr::Update(update) => self.expression(&mut update.2),
+            Expr::Function(funcexpr) => self.function(funcexpr),
+        }
+    }
+
+    fn expressions(&mut self, exprs: &mut [Expression]) {
+        for expr in exprs.iter_mut() {
+            self.expression(expr);
+        }
+    }
+}
+
+/// Is `callee` the name `eval`? Then the call can see the variables of its caller.
+fn is_eval(callee: &Expression) -> bool {
+    match &callee.expr {
+        Expr::Identifier(id) => id.as_str() == "eval",
+        Expr::Variable(var) => var.name.as_str() == "eval",
+        _ => false,
+    }
+}
+
+fn slot(boxed: &[bool], index: u32) -> Slot {
+    match boxed[index as usize] {
+        true => Slot::Boxed(index),
+        false => Slot::Local(index),
+    }
+}
+
+/// Collects `var`s and function declarations of `stmts`, including nested blocks,
+/// but not nested functions.
+fn hoist(stmts: &[Statement]) -> (HashSet<Identifier>, Vec<FunctionDeclaration>) {
+    fn walk(
+        stmt: &Statement,
+        variables: &mut HashSet<Identifier>,
+        functions: &mut Vec<FunctionDeclaration>,
+    ) {
+        let mut walk_all =
+            |stmts: &[Statement]| (stmts.iter()).for_each(|stmt| walk(stmt, variables, functions));
+        match &stmt.stmt {
+            Stmt::Variable(vardecl) if vardecl.kind == DeclarationKind::Var => {
+                let names = vardecl.declarations.iter().map(|decl| decl.name.clone());
+                variables.extend(names);
+            }
+            Stmt::Function(funcdecl) => {
+                // the body of a do-while loop is there twice
+                let func = &funcdecl.function.func;
+                if !functions.iter().any(|f| Rc::ptr_eq(&f.function.func, func)) {
+                    functions.push(funcdecl.clone());
+                }
+            }
+            Stmt::Block(block) => walk_all(&block.body),
+            Stmt::If(stmt) => {
+                walk(&stmt.consequent, variables, functions);
+                if let Some(alternate) = stmt.alternate.as_ref() {
+                    walk(alternate, variables, functions);
+                }
+            }
+            Stmt::Switch(stmt) => {
+                for case in stmt.cases.iter() {
+                    walk_all(&case.consequent);
+                }
+            }
+            Stmt::For(stmt) => {
+                walk(&stmt.init, variables, functions);
+                walk(&stmt.body, variables, functions);
+            }
+            Stmt::ForIn(stmt) => {
+                if let ForInTarget::Var(vardecl) = &stmt.left {
+                    if vardecl.kind == DeclarationKind::Var {
+                        let names = vardecl.declarations.iter().map(|decl| decl.name.clone());
+                        variables.extend(names);
+                    }
+                }
+                walk(&stmt.body, variables, functions);
+            }
+            Stmt::Label(stmt) => walk(&stmt.1, variables, functions),
+            Stmt::Try(stmt) => {
+                walk_all(&stmt.block.body);
+                if let Some(handler) = stmt.handler.as_ref() {
+                    walk_all(&handler.body.body);
+                }
+                if let Some(finalizer) = stmt.finalizer.as_ref() {
+                    walk_all(&finalizer.body);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut variables = HashSet::new();
+    let mut functions = Vec::new();
+    for stmt in stmts.iter() {
+        walk(stmt, &mut variables, &mut functions);
+    }
+    (variables, functions)
+}
+
+/// Collects `let`/`const` bindings that belong to the block of `stmts`, in order.
+fn bindings(stmts: &[Statement]) -> Vec<Identifier> {
+    fn walk(stmt: &Statement, names: &mut Vec<Identifier>) {
+        let mut add = |vardecl: &VariableDeclaration| {
+            if vardecl.kind != DeclarationKind::Var {
+                for decl in vardecl.declarations.iter() {
+                    if !names.contains(&decl.name) {
+                        names.push(decl.name.clone());
+                    }
+                }
+            }
+        };
+        match &stmt.stmt {
+            Stmt::Variable(vardecl) => add(vardecl),
+            Stmt::ForIn(stmt) => {
+                if let ForInTarget::Var(vardecl) = &stmt.left {
+                    add(vardecl);
+                }
+                walk(&stmt.body, names);
+            }
+            Stmt::For(stmt) => {
+                walk(&stmt.init, names);
+                walk(&stmt.body, names);
+            }
+            Stmt::If(stmt) => {
+                walk(&stmt.consequent, names);
+                if let Some(alternate) = stmt.alternate.as_ref() {
+                    walk(alternate, names);
+                }
+            }
+            Stmt::Switch(stmt) => {
+                for case in stmt.cases.iter() {
+                    case.consequent.iter().for_each(|stmt| walk(stmt, names));
+                }
+            }
+            Stmt::Label(stmt) => walk(&stmt.1, names),
+            // nested blocks have their own bindings
+            _ => (),
+        }
+    }
+
+    let mut names = Vec::new();
+    for stmt in stmts.iter() {
+        walk(stmt, &mut names);
+    }
+    names
+}
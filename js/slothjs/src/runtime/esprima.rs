@@ -2,7 +2,7 @@ use crate::{
     error::TypeError,
     runtime::{self, EvalResult, Parser},
     CallContext, Exception, Heap, HeapNode, HostFn, Interpretable, Interpreted, JSRef, JSResult,
-    Program,
+    ParseFn, Program, Rooted,
 };
 use serde_json::json;
 
@@ -12,6 +12,8 @@ pub struct EsprimaParser {
     object: JSRef,
     esparse: JSRef,
     locflag: JSRef,
+    /// keeps the objects above alive
+    roots: Vec<Rooted>,
 }
 
 impl EsprimaParser {
@@ -20,7 +22,11 @@ impl EsprimaParser {
 
 fn esprima_eval(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
     let code = call.arg_value(0, heap)?.stringify(heap)?;
+    let program = esprima_parse(&code, heap)?;
+    heap.run(&program)
+}
 
+fn esprima_parse(code: &str, heap: &mut Heap) -> JSResult<Program> {
     let esprima_ref = (heap.get(Heap::GLOBAL).get_own_value("esprima"))
         .ok_or_else(|| Exception::no_reference("esprima"))?
         .to_ref()?;
@@ -34,9 +40,10 @@ fn esprima_eval(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
         object: esprima_ref,
         esparse: parse_ref,
         locflag: Heap::NULL,
+        roots: Vec::new(),
     };
-    let program = parser.parse(&code, heap)?;
-    program.interpret(heap)
+    let program = parser.parse(code, heap)?;
+    Ok(program)
 }
 
 impl EsprimaParser {
@@ -46,6 +53,7 @@ impl EsprimaParser {
             object: Heap::NULL,
             esparse: Heap::NULL,
             locflag: Heap::NULL,
+            roots: Vec::new(),
         }
     }
 }
@@ -67,6 +75,8 @@ impl runtime::Parser for EsprimaParser {
         let locjson = json!({ "loc": true });
         let locflag = heap.object_from_json(&locjson).to_ref()?;
 
+        // the parser keeps these across evaluations
+        self.roots = vec![heap.root(object), heap.root(esparse), heap.root(locflag)];
         self.object = object;
         self.esparse = esparse;
         self.locflag = locflag;
@@ -93,4 +103,8 @@ impl runtime::Parser for EsprimaParser {
     fn eval_func(&self) -> HostFn {
         esprima_eval
     }
+
+    fn parse_func(&self) -> ParseFn {
+        esprima_parse
+    }
 }
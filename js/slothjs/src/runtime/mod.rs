@@ -4,8 +4,11 @@ mod nodejs;
 use core::str::Utf8Error;
 use std::io;
 
-use crate::function::HostFn;
-use crate::{error, Exception, Heap, JSString, JSValue, Program, JSON};
+use crate::function::{HostFn, ParseFn};
+use crate::{
+    error, Backend, ConsoleSink, Exception, Heap, InterruptHandle, JSString, JSValue, MemoryStats,
+    Program, JSON,
+};
 use crate::{prelude::*, CallContext, Interpreted, JSResult};
 
 pub use self::esprima::EsprimaParser;
@@ -73,7 +76,7 @@ impl From<EvalError> for io::Error {
     fn from(err: EvalError) -> io::Error {
         match err {
             EvalError::Io(err) => err,
-            _ => io::Error::new(io::ErrorKind::Other, err.to_string()),
+            _ => io::Error::other(err.to_string()),
         }
     }
 }
@@ -100,6 +103,9 @@ pub trait Parser: fmt::Debug {
 
     /// Get the native callback for `eval()` in JavaScript provided by this parser
     fn eval_func(&self) -> HostFn;
+
+    /// Get the native parser for code built at run time, e.g. by `Function()`
+    fn parse_func(&self) -> ParseFn;
 }
 
 /// The sljs JavaScript runtime.
@@ -135,6 +141,8 @@ pub trait Parser: fmt::Debug {
 pub struct Runtime {
     pub heap: Heap,
     parser: Box<dyn Parser>,
+    /// the fuel of each evaluation, see [`Runtime::set_fuel()`]
+    fuel: Option<u64>,
 }
 
 impl Runtime {
@@ -143,10 +151,64 @@ impl Runtime {
         let mut heap = Heap::new();
         parser.load(&mut heap)?;
 
-        let eval_ref = heap.alloc_func(parser.eval_func());
+        let eval_ref = heap.alloc_named_func("eval", parser.eval_func());
         heap.get_mut(Heap::GLOBAL).set_hidden("eval", eval_ref)?;
+        heap.set_parse_func(parser.parse_func());
+
+        Ok(Runtime {
+            heap,
+            parser,
+            fuel: None,
+        })
+    }
+
+    /// Sends the output of `console.log()` and alike to `sink` instead of stdout/stderr.
+    pub fn set_console(&mut self, sink: Box<dyn ConsoleSink>) {
+        self.heap.set_console(sink);
+    }
+
+    /// Selects how programs are executed, see [`Backend`].
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.heap.set_backend(backend);
+    }
+
+    /// Limits each [`Runtime::evaluate()`] to `fuel` steps (loop iterations and function calls),
+    /// `None` for no limit. A script that runs out of fuel is aborted with
+    /// [`crate::Abort::OutOfFuel`]; it cannot catch this.
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    /// Limits how deep JavaScript calls can nest, see [`Heap::set_max_call_depth()`].
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.heap.set_max_call_depth(depth);
+    }
 
-        Ok(Runtime { heap, parser })
+    /// Limits the memory taken by objects and strings, see [`Heap::set_memory_limit()`].
+    /// A script that goes over it is aborted with [`crate::Abort::OutOfMemory`].
+    pub fn set_memory_limit(&mut self, bytes: Option<usize>) {
+        self.heap.set_memory_limit(bytes);
+    }
+
+    /// The current estimate of memory usage, see [`Heap::memory_stats()`].
+    pub fn memory_stats(&mut self) -> MemoryStats {
+        self.heap.memory_stats()
+    }
+
+    /// Gives a handle to abort the running evaluation from another thread:
+    ///
+    /// ```
+    /// # use slothjs::{Abort, Exception};
+    /// # use slothjs::runtime::{EvalError, Runtime, NodejsParser};
+    /// # let mut sljs = Runtime::load(Box::new(NodejsParser::new())).expect("Runtime::load");
+    /// let handle = sljs.interrupt_handle();
+    /// std::thread::spawn(move || handle.interrupt());
+    ///
+    /// let result = sljs.evaluate("while (true) { try {} catch (e) {} }");
+    /// assert!(matches!(result, Err(EvalError::Exception(Exception::Abort(Abort::Interrupted)))));
+    /// ```
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.heap.interrupt_handle()
     }
 
     /// Exposes the configured parser.
@@ -154,10 +216,19 @@ impl Runtime {
         self.parser.parse(input, &mut self.heap)
     }
 
-    /// Takes an `input` and evaluates it.
+    /// Takes an `input` and evaluates it, then runs the jobs it has left,
+    /// see [`Heap::run_jobs()`].
     pub fn evaluate(&mut self, input: &str) -> EvalResult<JSValue> {
         let program = self.parse(input)?;
-        self.heap.evaluate(&program).map_err(EvalError::Exception)
+        self.heap.set_fuel(self.fuel);
+        let result = self.heap.run(&program).and_then(|result| {
+            let value = result.to_value(&mut self.heap)?;
+            let _rooted = self.heap.root_value(&value);
+            self.heap.run_jobs()?;
+            Ok(value)
+        });
+        self.heap.set_fuel(None);
+        result.map_err(EvalError::Exception)
     }
 
     /// Turn a [`JSValue`] into [`JSON`]
@@ -195,7 +266,17 @@ pub struct JSONParser;
 
 impl JSONParser {
     fn json_eval(_: CallContext, _: &mut Heap) -> JSResult<Interpreted> {
-        unimplemented!("JSONParser does not support eval()")
+        Err(Self::unsupported("eval()"))
+    }
+
+    fn json_parse(_: &str, _: &mut Heap) -> JSResult<Program> {
+        Err(Self::unsupported("Function()"))
+    }
+
+    /// A SyntaxError for source code given at run time: there is nothing to parse it.
+    fn unsupported(what: &str) -> Exception {
+        let err = format!("JSONParser does not support {}", what);
+        Exception::Syntax(error::ParseError::InvalidJSON { err })
     }
 }
 
@@ -214,4 +295,8 @@ impl Parser for JSONParser {
     fn eval_func(&self) -> HostFn {
         Self::json_eval
     }
+
+    fn parse_func(&self) -> ParseFn {
+        Self::json_parse
+    }
 }
@@ -7,19 +7,23 @@ use std::process as proc;
 
 use crate::runtime::{self, EvalError, EvalResult};
 use crate::{
-    error::ParseError, runtime::Parser, CallContext, Exception, Heap, Interpretable, Interpreted,
-    JSResult, Program, JSON,
+    error::ParseError, runtime::Parser, CallContext, Exception, Heap, Interpreted, JSResult,
+    Program, JSON,
 };
 
 fn nodejs_eval(call: CallContext, heap: &mut Heap) -> JSResult<Interpreted> {
     let code = call.arg_value(0, heap)?.stringify(heap)?;
+    let program = nodejs_parse(&code, heap)?;
+    heap.run(&program)
+}
 
+fn nodejs_parse(code: &str, heap: &mut Heap) -> JSResult<Program> {
     let tmpdir = env::temp_dir().join(NodejsParser::TMPDIRNAME);
     let espath = tmpdir.join("esparse.js");
     let parser = NodejsParser { espath };
 
-    let program = parser.parse(&code, heap)?;
-    program.interpret(heap)
+    let program = parser.parse(code, heap)?;
+    Ok(program)
 }
 
 /// [`NodejsParser`] runs Esprima in an external nodejs process, consumes JSON AST.
@@ -117,4 +121,8 @@ impl runtime::Parser for NodejsParser {
     fn eval_func(&self) -> crate::HostFn {
         nodejs_eval
     }
+
+    fn parse_func(&self) -> crate::ParseFn {
+        nodejs_parse
+    }
 }
@@ -0,0 +1,611 @@
+//! Shapes (hidden classes) of objects and inline caches of property accesses.
+//!
+//! The own properties of a [`JSObject`] are split in two: the keys with their [`Access`]
+//! live in a [`Shape`], the values live in the slots of the object ([`Properties`]).
+//! Objects that got the same keys in the same order share a shape: shapes form
+//! a transition tree rooted in the empty shape of the [`Heap`], every edge adds a key.
+//! Deleting or reconfiguring a property (or adding too many of them) gives an object
+//! its own shape, which is changed in place.
+//!
+//! Keys are interned in the [`Atoms`] of the tree, so shapes compare them as numbers.
+//! A shape shares the list of keys of its parent: the first child of a shape appends
+//! its key to that list, only other children copy it.
+//!
+//! A [`PropertyCache`] remembers where an access site found its property for the last
+//! few shapes it has seen, so that the next access to an object of the same shape is
+//! just a slot read.
+
+use core::cell::RefCell;
+
+use alloc::rc::Weak;
+
+use crate::prelude::*;
+
+use crate::object::{Access, Content, Property};
+use crate::{Heap, Interpreted, JSObject, JSRef, JSResult};
+
+/// Objects with more keys than this get their own shapes.
+const MAX_SHARED_KEYS: usize = 64;
+
+/// Shapes with more keys than this are looked up in a hash index.
+const MAX_SCANNED_KEYS: usize = 8;
+
+/// How many shapes a [`PropertyCache`] remembers.
+const MAX_CACHED_SHAPES: usize = 4;
+
+/// An interned property key, see [`Atoms`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Atom(u32);
+
+/// The keys of the shapes of a heap, each stored once and numbered by its [`Atom`].
+/// The root shape of a transition tree owns them, every shape of the tree refers to them.
+#[derive(Debug, Default)]
+pub struct Atoms {
+    names: Vec<JSString>,
+    ids: HashMap<JSString, Atom>,
+}
+
+impl Atoms {
+    /// The atom of `name`, if any key was ever `name`.
+    pub fn get(&self, name: &str) -> Option<Atom> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn intern(&mut self, name: &str) -> Atom {
+        if let Some(atom) = self.get(name) {
+            return atom;
+        }
+        let atom = Atom(self.names.len() as u32);
+        let name = JSString::from(name);
+        self.names.push(name.clone());
+        self.ids.insert(name, atom);
+        atom
+    }
+
+    pub fn name(&self, atom: Atom) -> &JSString {
+        &self.names[atom.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// The shapes made from a shape by adding a key, by the access of that key.
+type Transitions = HashMap<Atom, Vec<(Access, Weak<Shape>)>>;
+
+#[derive(Clone, Copy)]
+struct Key {
+    atom: Atom,
+    access: Access,
+}
+
+/// The keys of a line of shapes: a shape uses the first [`Shape::len()`] of them.
+/// No key is there twice, so the index of a line serves all its shapes.
+#[derive(Default)]
+struct KeyList {
+    keys: Vec<Key>,
+    /// slots by key, once there are more than `MAX_SCANNED_KEYS` keys
+    index: HashMap<Atom, u32>,
+}
+
+impl KeyList {
+    /// A copy of the first `len` keys.
+    fn prefix(&self, len: usize) -> KeyList {
+        let mut list = KeyList::default();
+        for &key in self.keys[..len].iter() {
+            list.push(key);
+        }
+        list
+    }
+
+    fn push(&mut self, key: Key) {
+        if self.keys.len() >= MAX_SCANNED_KEYS {
+            if self.index.is_empty() {
+                let indexed = self.keys.iter().enumerate();
+                self.index = indexed.map(|(i, key)| (key.atom, i as u32)).collect();
+            }
+            self.index.insert(key.atom, self.keys.len() as u32);
+        }
+        self.keys.push(key);
+    }
+
+    fn remove(&mut self, slot: usize) {
+        self.keys.remove(slot);
+        self.index.clear();
+        if self.keys.len() > MAX_SCANNED_KEYS {
+            let indexed = self.keys.iter().enumerate();
+            self.index = indexed.map(|(i, key)| (key.atom, i as u32)).collect();
+        }
+    }
+}
+
+/// The keys of an object with their [`Access`], in the order of its slots.
+#[derive(Default)]
+pub struct Shape {
+    /// the first `len` keys are the keys of this shape
+    keys: Rc<RefCell<KeyList>>,
+    len: usize,
+    atoms: Rc<RefCell<Atoms>>,
+    /// the shape this one was made from, keeps its path from the root alive
+    parent: Option<Rc<Shape>>,
+    /// the shapes made by adding a key to this one
+    transitions: RefCell<Transitions>,
+    /// belongs to one object, not in a transition tree
+    own: bool,
+}
+
+impl Shape {
+    /// A new empty root of a transition tree, with new [`Atoms`].
+    pub(crate) fn root() -> Rc<Shape> {
+        Rc::new(Shape::default())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the shape belongs to a single object.
+    pub fn is_own(&self) -> bool {
+        self.own
+    }
+
+    pub fn parent(&self) -> Option<&Rc<Shape>> {
+        self.parent.as_ref()
+    }
+
+    pub fn atoms(&self) -> &Rc<RefCell<Atoms>> {
+        &self.atoms
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = JSString> + '_ {
+        self.entries().map(|(key, _)| key)
+    }
+
+    /// The keys with their access, in the order of slots.
+    fn entries(&self) -> impl Iterator<Item = (JSString, Access)> + '_ {
+        (0..self.len).map(|slot| {
+            let Key { atom, access } = self.keys.borrow().keys[slot];
+            (self.atoms.borrow().name(atom).clone(), access)
+        })
+    }
+
+    /// The slot and the access of `name`.
+    pub fn find(&self, name: &str) -> Option<(usize, Access)> {
+        let atom = self.atoms.borrow().get(name)?;
+        self.find_atom(atom)
+    }
+
+    /// The slot and the access of the key `atom`.
+    pub fn find_atom(&self, atom: Atom) -> Option<(usize, Access)> {
+        let list = self.keys.borrow();
+        let slot = if self.len > MAX_SCANNED_KEYS {
+            // the index may know keys of longer shapes of the line
+            Some(*list.index.get(&atom)? as usize).filter(|&slot| slot < self.len)?
+        } else {
+            list.keys[..self.len]
+                .iter()
+                .position(|key| key.atom == atom)?
+        };
+        Some((slot, list.keys[slot].access))
+    }
+
+    /// Adds a key to an own shape.
+    fn push(&mut self, name: &str, access: Access) {
+        let atom = self.atoms.borrow_mut().intern(name);
+        self.keys.borrow_mut().push(Key { atom, access });
+        self.len += 1;
+    }
+
+    fn remove(&mut self, slot: usize) {
+        self.keys.borrow_mut().remove(slot);
+        self.len -= 1;
+    }
+
+    /// A copy of `self` that belongs to a single object.
+    fn detached(&self) -> Shape {
+        Shape {
+            parent: None,
+            own: true,
+            ..self.clone()
+        }
+    }
+
+    /// The shared shape with `key` added to `self`.
+    fn transition(self: &Rc<Self>, key: &str, access: Access) -> Rc<Shape> {
+        let atom = self.atoms.borrow_mut().intern(key);
+        let mut transitions = self.transitions.borrow_mut();
+        if let Some(children) = transitions.get_mut(&atom) {
+            let found = (children.iter())
+                .filter(|(child_access, _)| *child_access == access)
+                .find_map(|(_, child)| child.upgrade());
+            if let Some(child) = found {
+                return child;
+            }
+            children.retain(|(_, child)| child.strong_count() > 0);
+        }
+
+        // the line of keys goes on in the first child, other children start their own
+        let keys = match self.keys.borrow().keys.len() == self.len {
+            true => Rc::clone(&self.keys),
+            false => Rc::new(RefCell::new(self.keys.borrow().prefix(self.len))),
+        };
+        keys.borrow_mut().push(Key { atom, access });
+        let child = Rc::new(Shape {
+            keys,
+            len: self.len + 1,
+            atoms: Rc::clone(&self.atoms),
+            parent: Some(Rc::clone(self)),
+            transitions: RefCell::default(),
+            own: false,
+        });
+        let children = transitions.entry(atom).or_default();
+        children.push((access, Rc::downgrade(&child)));
+        child
+    }
+}
+
+/// Copies the keys: changing those of the copy in place leaves `self` alone.
+impl Clone for Shape {
+    fn clone(&self) -> Self {
+        Shape {
+            keys: Rc::new(RefCell::new(self.keys.borrow().prefix(self.len))),
+            len: self.len,
+            atoms: Rc::clone(&self.atoms),
+            parent: self.parent.clone(),
+            transitions: RefCell::default(),
+            own: self.own,
+        }
+    }
+}
+
+impl fmt::Debug for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.entries()).finish()
+    }
+}
+
+/// The own properties of an object: a [`Shape`] and the values in its slots.
+#[derive(Clone, Default)]
+pub struct Properties {
+    /// `None` until the first property is added or the object is put on the heap
+    shape: Option<Rc<Shape>>,
+    values: Vec<JSValue>,
+}
+
+impl Properties {
+    pub fn new() -> Properties {
+        Properties::default()
+    }
+
+    pub fn shape(&self) -> Option<&Rc<Shape>> {
+        self.shape.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The slot and the access of `name`.
+    pub fn find(&self, name: &str) -> Option<(usize, Access)> {
+        self.shape.as_ref()?.find(name)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Property> {
+        let (slot, access) = self.find(name)?;
+        let content = Content::Value(self.values[slot].clone());
+        Some(Property { content, access })
+    }
+
+    pub fn get_value(&self, name: &str) -> Option<&JSValue> {
+        let (slot, _) = self.find(name)?;
+        Some(&self.values[slot])
+    }
+
+    pub(crate) fn slot(&self, slot: usize) -> &JSValue {
+        &self.values[slot]
+    }
+
+    pub(crate) fn slot_mut(&mut self, slot: usize) -> &mut JSValue {
+        &mut self.values[slot]
+    }
+
+    /// Keys in the order they were added.
+    pub fn keys(&self) -> impl Iterator<Item = JSString> + '_ {
+        self.shape.iter().flat_map(|shape| shape.keys())
+    }
+
+    /// Properties in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (JSString, Property)> + '_ {
+        let keys = self.shape.iter().flat_map(|shape| shape.entries());
+        keys.zip(self.values.iter()).map(|((key, access), value)| {
+            let content = Content::Value(value.clone());
+            (key, Property { content, access })
+        })
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JSValue> {
+        self.values.iter()
+    }
+
+    /// Adds a new property `name`, which must not exist yet.
+    pub(crate) fn add(&mut self, name: &str, access: Access, value: JSValue) {
+        match &self.shape {
+            Some(shape) if !shape.own && shape.len() < MAX_SHARED_KEYS => {
+                self.shape = Some(shape.transition(name, access));
+            }
+            _ => self.own_shape().push(name, access),
+        }
+        self.values.push(value);
+    }
+
+    /// Changes the access of the property in `slot`.
+    pub(crate) fn set_access(&mut self, slot: usize, access: Access) {
+        self.own_shape().keys.borrow_mut().keys[slot].access = access;
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Property> {
+        let (slot, access) = self.find(name)?;
+        self.own_shape().remove(slot);
+        let content = Content::Value(self.values.remove(slot));
+        Some(Property { content, access })
+    }
+
+    /// Moves the properties to the transition tree of `root`, unless they have
+    /// too many keys; those get an own shape with the atoms of `root`.
+    pub(crate) fn adopt(&mut self, root: &Rc<Shape>) {
+        let shape = match &self.shape {
+            None => {
+                self.shape = Some(Rc::clone(root));
+                return;
+            }
+            Some(shape) if shape.own && shape.len() <= MAX_SHARED_KEYS => shape,
+            Some(shape) if Rc::ptr_eq(&shape.atoms, &root.atoms) => return,
+            Some(shape) => {
+                let mut own = root.detached();
+                for (key, access) in shape.entries() {
+                    own.push(&key, access);
+                }
+                self.shape = Some(Rc::new(own));
+                return;
+            }
+        };
+        let mut shared = Rc::clone(root);
+        for (key, access) in shape.entries() {
+            shared = shared.transition(&key, access);
+        }
+        self.shape = Some(shared);
+    }
+
+    /// The shape of this object alone, to be changed in place.
+    fn own_shape(&mut self) -> &mut Shape {
+        let shape = (self.shape).get_or_insert_with(|| Rc::new(Shape::default().detached()));
+        if !shape.own {
+            *shape = Rc::new(shape.detached());
+        }
+        // a shape held by a cache is copied, so the cache will not match it anymore
+        Rc::make_mut(shape)
+    }
+}
+
+impl fmt::Debug for Properties {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = self.shape.iter().flat_map(|shape| shape.keys());
+        let entries = keys.zip(self.values.iter());
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+/// Where a [`PropertyCache`] found a property.
+#[derive(Clone)]
+enum Place {
+    /// in an own slot
+    Own(usize),
+    /// in a slot of the prototype `holder` with `shape`
+    Proto {
+        holder: JSRef,
+        shape: Rc<Shape>,
+        slot: usize,
+    },
+    /// assigning it added a new slot, which gave the object `shape`:
+    /// no setter of `proto` was in the way
+    Added { shape: Rc<Shape>, proto: JSRef },
+}
+
+#[derive(Clone)]
+struct CacheEntry {
+    shape: Rc<Shape>,
+    place: Place,
+}
+
+/// An inline cache of a property access site, e.g. of a `MemberExpression`.
+///
+/// It is polymorphic: the places of the property are remembered for up to
+/// `MAX_CACHED_SHAPES` shapes of the objects accessed at the site.
+#[derive(Clone, Default)]
+pub struct PropertyCache {
+    entries: RefCell<Vec<CacheEntry>>,
+}
+
+impl PropertyCache {
+    /// Whether accesses to `name` can be cached: array indexes and properties
+    /// like `length` are not in slots.
+    pub fn applies(name: &str) -> bool {
+        let special = matches!(name, "length" | "__proto__");
+        let index = name.as_bytes().first().is_none_or(u8::is_ascii_digit);
+        !(special || index)
+    }
+
+    /// Gives the value of `objref.name`, like `Interpreted::Member{..}.to_value()`.
+    pub(crate) fn get(&self, objref: JSRef, name: &JSString, heap: &mut Heap) -> JSResult<JSValue> {
+        let object = heap.get(objref);
+        if let Some(shape) = object.properties.shape() {
+            let shape = Rc::as_ptr(shape);
+            for entry in self.entries.borrow().iter() {
+                if Rc::as_ptr(&entry.shape) != shape {
+                    continue;
+                }
+                match &entry.place {
+                    Place::Own(slot) => return Ok(object.properties.slot(*slot).clone()),
+                    Place::Proto {
+                        holder,
+                        shape: holder_shape,
+                        slot,
+                    } if object.proto == *holder => {
+                        let holder = heap.try_get(*holder).map(|proto| &proto.properties);
+                        if let Some(properties) = holder {
+                            let found = properties.shape().map(Rc::as_ptr);
+                            if found == Some(Rc::as_ptr(holder_shape)) {
+                                return Ok(properties.slot(*slot).clone());
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        self.fill(object, name, heap);
+        let place = Interpreted::Member {
+            of: objref,
+            name: name.clone(),
+        };
+        place.to_value(heap)
+    }
+
+    /// Remembers where `object` has `name`: in its own slot or in its prototype.
+    fn fill(&self, object: &JSObject, name: &str, heap: &Heap) {
+        let shape = match object.properties.shape() {
+            Some(shape) => shape,
+            None => return,
+        };
+        let place = match shape.find(name) {
+            // a slot remembered here can be assigned to
+            Some((slot, access)) if access.writable() => Place::Own(slot),
+            Some(_) => return,
+            None => {
+                let holder = object.proto;
+                let proto_shape = (heap.try_get(holder))
+                    .and_then(|proto| proto.properties.shape())
+                    .filter(|_| holder != Heap::NULL);
+                match proto_shape {
+                    Some(proto_shape) => match proto_shape.find(name) {
+                        // getters are called by `Heap::lookup_member()`
+                        Some((_, access)) if access.accessor() => return,
+                        Some((slot, _)) => Place::Proto {
+                            holder,
+                            shape: Rc::clone(proto_shape),
+                            slot,
+                        },
+                        None => return,
+                    },
+                    None => return,
+                }
+            }
+        };
+        self.remember(CacheEntry {
+            shape: Rc::clone(shape),
+            place,
+        });
+    }
+
+    /// Sets `objref.name` to `value`, like `Interpreted::Member{..}.put_value()`.
+    pub(crate) fn put(
+        &self,
+        objref: JSRef,
+        name: &JSString,
+        value: JSValue,
+        heap: &mut Heap,
+    ) -> JSResult<()> {
+        let object = heap.get_mut(objref);
+        if object.as_proxy().is_some() {
+            return heap.set_member(objref, name, value);
+        }
+        let extensible = object.is_extensible();
+        let properties = &mut object.properties;
+        let before = properties.shape().cloned();
+        if let Some(shape) = &before {
+            let entries = self.entries.borrow();
+            let entry = (entries.iter()).find(|entry| Rc::ptr_eq(&entry.shape, shape));
+            match entry.map(|entry| &entry.place) {
+                Some(Place::Own(slot)) => {
+                    *properties.slot_mut(*slot) = value;
+                    return Ok(());
+                }
+                Some(Place::Added { shape, proto }) if extensible && object.proto == *proto => {
+                    properties.shape = Some(Rc::clone(shape));
+                    properties.values.push(value);
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
+
+        heap.set_member(objref, name, value)?;
+
+        // remember what happened to this shape
+        let before = match before {
+            Some(before) => before,
+            None => return Ok(()),
+        };
+        let properties = &heap.get(objref).properties;
+        let (after, (slot, access)) = match (properties.shape(), properties.find(name)) {
+            (Some(after), Some(found)) => (after, found),
+            _ => return Ok(()),
+        };
+        let place = if Rc::ptr_eq(&before, after) && access.writable() {
+            Place::Own(slot)
+        } else if !after.own && after.parent().is_some_and(|p| Rc::ptr_eq(p, &before)) {
+            Place::Added {
+                shape: Rc::clone(after),
+                proto: heap.get(objref).proto,
+            }
+        } else {
+            return Ok(());
+        };
+        self.remember(CacheEntry {
+            shape: before,
+            place,
+        });
+        Ok(())
+    }
+
+    fn remember(&self, entry: CacheEntry) {
+        let mut entries = self.entries.borrow_mut();
+        if entries.len() >= MAX_CACHED_SHAPES {
+            entries.remove(0);
+        }
+        entries.push(entry);
+    }
+}
+
+impl fmt::Debug for PropertyCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PropertyCache").finish_non_exhaustive()
+    }
+}
+
+/// Caches do not change the meaning of the code they are in.
+impl PartialEq for PropertyCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for PropertyCache {}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::error::TypeError;
+use crate::object::ObjectValue;
 use crate::prelude::*;
 use crate::{error::ParseError, Exception, Heap, Interpreted, JSObject, JSValue, JSON};
 
@@ -31,12 +32,13 @@ impl Location {
 
     fn from_saved(object: &JSObject, heap: &Heap) -> Result<Location, Exception> {
         if let Some(array) = object.as_array() {
-            let line = array.storage[0].numberify(heap).unwrap() as u32;
-            let column = array.storage[1].numberify(heap).unwrap() as u32;
+            let at = |i| array.get(i).unwrap().numberify(heap).unwrap() as u32;
+            let line = at(0);
+            let column = at(1);
             let start = Position { line, column };
 
-            let line = array.storage[2].numberify(heap).unwrap() as u32;
-            let column = array.storage[3].numberify(heap).unwrap() as u32;
+            let line = at(2);
+            let column = at(3);
             let end = Position { line, column };
 
             Ok(Location { start, end })
@@ -80,8 +82,8 @@ impl<'heap> fmt::Display for Callstack<'heap> {
             Some(loc) => writeln!(f, "{:?}", loc)?,
         };
 
-        let mut scoperef = self.heap.local_scope().unwrap_or(Heap::NULL);
-        while scoperef != Heap::NULL {
+        let mut scoperef = self.heap.scope_ref();
+        while scoperef != Heap::GLOBAL {
             let loc_ref = (self.heap.get(scoperef))
                 .get_own_value(CALLER_LOCATION)
                 .and_then(|v| v.to_ref().ok())
@@ -93,9 +95,9 @@ impl<'heap> fmt::Display for Callstack<'heap> {
                 writeln!(f, "   ???")?;
             }
 
-            scoperef = match self.heap.get(scoperef).get_own_value(Heap::SAVED_SCOPE) {
-                Some(v) => v.to_ref().unwrap_or(Heap::NULL),
-                None => Heap::NULL,
+            scoperef = match &self.heap.get(scoperef).value {
+                ObjectValue::Scope(scope) => scope.saved,
+                _ => Heap::GLOBAL,
             };
         }
 
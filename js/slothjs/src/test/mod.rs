@@ -65,6 +65,8 @@ fn struct_sizes() {
     println!("size_of   Access:\t{}", size_of::<Access>());
     println!("size_of   Content:\t{}", size_of::<Content>());
     println!("============================");
+
+    assert_eq!(size_of::<JSValue>(), 8);
 }
 
 #[test]
@@ -1,5 +1,11 @@
-use core::{borrow::Borrow, convert::Infallible, ops::Deref, str::Chars};
+use core::cell::{OnceCell, RefCell};
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::{borrow::Borrow, convert::Infallible, marker::PhantomData, ops::Deref, str::Chars};
 
+use crate::bigint::BigInt;
+use crate::error::{ParseError, TypeError};
+use crate::object::JSArray;
 use crate::{prelude::*, CallContext, Exception, Heap, Interpreted, JSObject, JSRef, JSResult};
 
 pub type JSON = serde_json::Value;
@@ -12,27 +18,134 @@ pub type JSNumber = f64;
 /// - Javascript strings are immutable. There is no point in cloning a string content.
 /// - Ideally they should be a sequence of addressable 16-bit UTF-16 "code points" (potentially broken).
 ///   TODO: Indexing a Rust `String` over chars brings an unknown overhead of linear scanning.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// A `JSString` is one thin pointer, so that a [`JSValue`] can box it.
+///
+/// [`JSString::concat()`] makes a rope: it only links its two halves in O(1).
+/// A rope is flattened into one buffer the first time its contents are looked at.
+#[derive(Clone)]
 #[repr(transparent)]
-pub struct JSString(Rc<str>);
+pub struct JSString(Rc<StrNode>);
+
+/// The contents of a [`JSString`]: either flat or the two halves of a concatenation.
+struct StrNode {
+    flat: OnceCell<Box<str>>,
+    rope: RefCell<Option<(JSString, JSString)>>,
+    /// the length in bytes
+    len: usize,
+}
+
+impl StrNode {
+    /// Shorter concatenations are copied right away.
+    const MIN_ROPE_LEN: usize = 64;
+
+    fn as_str(&self) -> &str {
+        self.flat.get_or_init(|| self.flatten())
+    }
+
+    /// Collects the leaves of the rope, without recursion: ropes can be deep.
+    fn flatten(&self) -> Box<str> {
+        let mut buffer = String::with_capacity(self.len);
+        let halves = self.rope.borrow_mut().take();
+        let (left, right) = halves.expect("a rope without halves");
+        let mut stack = vec![right, left];
+        while let Some(node) = stack.pop() {
+            if let Some(flat) = node.0.flat.get() {
+                buffer.push_str(flat);
+            } else if let Some((left, right)) = node.0.rope.borrow().as_ref() {
+                stack.push(right.clone());
+                stack.push(left.clone());
+            }
+        }
+        buffer.into_boxed_str()
+    }
+}
+
+impl Drop for StrNode {
+    /// Unlinks the halves of a rope without recursion.
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        stack.extend(self.rope.get_mut().take());
+        while let Some((left, right)) = stack.pop() {
+            for half in [left, right] {
+                if let Some(mut node) = Rc::into_inner(half.0) {
+                    stack.extend(node.rope.get_mut().take());
+                }
+            }
+        }
+    }
+}
 
 impl JSString {
     pub fn as_str(&self) -> &str {
-        self.0.as_ref()
+        self.0.as_str()
+    }
+
+    /// `left + right`, without copying long strings.
+    pub fn concat(left: &JSString, right: &JSString) -> JSString {
+        let len = left.len() + right.len();
+        if right.0.len == 0 {
+            left.clone()
+        } else if left.0.len == 0 {
+            right.clone()
+        } else if len < StrNode::MIN_ROPE_LEN {
+            JSString::from([left.as_str(), right.as_str()].concat())
+        } else {
+            JSString(Rc::new(StrNode {
+                flat: OnceCell::new(),
+                rope: RefCell::new(Some((left.clone(), right.clone()))),
+                len,
+            }))
+        }
+    }
+
+    /// The length in bytes, without flattening.
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    fn into_raw(self) -> *const StrNode {
+        Rc::into_raw(self.0)
+    }
+
+    /// # Safety
+    /// `ptr` must come from [`JSString::into_raw()`] and own one strong count.
+    unsafe fn from_raw(ptr: *const StrNode) -> JSString {
+        JSString(Rc::from_raw(ptr))
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.0.len == 0
     }
 
-    pub fn chars(&self) -> Chars {
-        self.0.chars()
+    pub fn chars(&self) -> Chars<'_> {
+        self.as_str().chars()
     }
 }
 
 impl Default for JSString {
     fn default() -> Self {
-        JSString(Rc::from(""))
+        JSString::from("")
+    }
+}
+
+impl PartialEq for JSString {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for JSString {}
+
+impl Hash for JSString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl fmt::Debug for JSString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
     }
 }
 
@@ -46,7 +159,7 @@ impl Deref for JSString {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        self.0.as_ref()
+        self.as_str()
     }
 }
 
@@ -60,19 +173,29 @@ impl FromStr for JSString {
     type Err = Infallible;
 
     fn from_str(s: &str) -> Result<JSString, Self::Err> {
-        Ok(JSString(Rc::from(s)))
+        Ok(JSString::from(s))
+    }
+}
+
+impl From<Box<str>> for JSString {
+    fn from(s: Box<str>) -> JSString {
+        JSString(Rc::new(StrNode {
+            len: s.len(),
+            flat: OnceCell::from(s),
+            rope: RefCell::new(None),
+        }))
     }
 }
 
 impl From<String> for JSString {
     fn from(s: String) -> JSString {
-        JSString(Rc::from(s.into_boxed_str()))
+        JSString::from(s.into_boxed_str())
     }
 }
 
 impl From<&str> for JSString {
     fn from(s: &str) -> JSString {
-        JSString(Rc::from(s))
+        JSString::from(Box::from(s))
     }
 }
 
@@ -106,29 +229,361 @@ mod test_strings {
         let s2 = JSString::from("hello");
         assert_eq!(get_hash(&s1), get_hash(&s2));
     }
+
+    #[test]
+    fn ropes() {
+        let chunk = JSString::from("0123456789".repeat(10));
+        let mut rope = JSString::default();
+        for _ in 0..100_000 {
+            rope = JSString::concat(&rope, &chunk);
+        }
+        assert_eq!(rope.len(), 10_000_000);
+        assert!(rope.0.flat.get().is_none());
+
+        let tail = JSString::concat(&JSString::from("!"), &rope);
+        assert_eq!(&rope[..12], "012345678901");
+        assert_eq!(tail.len(), rope.len() + 1);
+        assert!(tail.ends_with("789") && tail.starts_with("!01"));
+        drop(rope);
+
+        let flat = JSString::from(tail.as_str());
+        assert_eq!(tail, flat);
+        assert_eq!(get_hash(&tail), get_hash(&flat));
+
+        let short = JSString::concat(&JSString::from("ab"), &JSString::from("cd"));
+        assert_eq!(short.0.flat.get().map(|s| s.as_ref()), Some("abcd"));
+    }
+}
+
+/// StrWhiteSpaceChar: WhiteSpace and LineTerminator (but not U+0085).
+pub(crate) fn is_str_whitespace(c: char) -> bool {
+    (c.is_whitespace() && c != '\u{85}') || c == '\u{feff}'
+}
+
+/// StringToNumber: how `Number(s)`/`+s` read a string.
+/// Surrounding whitespace is ignored, an empty string is `0`, anything invalid is `NaN`.
+pub fn string_to_number(s: &str) -> JSNumber {
+    let s = s.trim_matches(is_str_whitespace);
+    if s.is_empty() {
+        return 0.0;
+    }
+
+    let radix = match s.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let digits = &s[2..];
+        if digits.is_empty() {
+            return f64::NAN;
+        }
+        let value = digits.chars().try_fold(0.0, |acc, c| {
+            let digit = c.to_digit(radix)?;
+            Some(acc * radix as f64 + digit as f64)
+        });
+        return value.unwrap_or(f64::NAN);
+    }
+
+    match decimal_prefix(s) {
+        Some((n, len)) if len == s.len() => n,
+        _ => f64::NAN,
+    }
+}
+
+/// Finds the longest prefix of `s` that is a StrDecimalLiteral
+/// (e.g. `-12`, `1.`, `.5`, `+1.5e-3`, `-Infinity`).
+/// Gives its numeric value and length.
+pub(crate) fn decimal_prefix(s: &str) -> Option<(JSNumber, usize)> {
+    let bytes = s.as_bytes();
+    let skip_digits = |i: &mut usize| {
+        let start = *i;
+        while *i < bytes.len() && bytes[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        *i - start
+    };
+
+    let mut i = 0;
+    if let Some(b'+' | b'-') = bytes.first() {
+        i += 1;
+    }
+    if s[i..].starts_with("Infinity") {
+        let sign = if bytes[0] == b'-' { -1.0 } else { 1.0 };
+        return Some((sign * f64::INFINITY, i + "Infinity".len()));
+    }
+
+    let mut mantissa = skip_digits(&mut i);
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        mantissa += skip_digits(&mut i);
+    }
+    if mantissa == 0 {
+        return None;
+    }
+    if let Some(b'e' | b'E') = bytes.get(i) {
+        let mut exp = i + 1;
+        if let Some(b'+' | b'-') = bytes.get(exp) {
+            exp += 1;
+        }
+        if skip_digits(&mut exp) > 0 {
+            i = exp;
+        }
+    }
+    // Rust accepts every StrDecimalLiteral except `Infinity`
+    let n = s[..i].parse::<JSNumber>().ok()?;
+    Some((n, i))
+}
+
+/// ToInt32: `n` wrapped modulo 2^32 into the `i32` range, `NaN` and infinities become `0`.
+pub fn number_to_int32(n: JSNumber) -> i32 {
+    if !n.is_finite() {
+        return 0;
+    }
+    (n % 4294967296.0) as i64 as u32 as i32
+}
+
+/// Number::toString(): the shortest decimal digits that round-trip to `n`,
+/// in the positional notation for `1e-7 < |n| < 1e21` and exponential otherwise.
+pub fn number_to_string(n: JSNumber) -> String {
+    if n.is_nan() {
+        return "NaN".to_string();
+    } else if n == 0.0 {
+        return "0".to_string(); // -0 as well
+    } else if n < 0.0 {
+        return format!("-{}", number_to_string(-n));
+    } else if n.is_infinite() {
+        return "Infinity".to_string();
+    }
+
+    // Rust's `{:e}` gives the shortest round-trip digits too, e.g. "1.2345e-7"
+    let exponential = format!("{:e}", n);
+    let (mantissa, exponent) = exponential.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // the decimal point goes after `point` digits
+    let point = exponent.parse::<i32>().unwrap() + 1;
+
+    if k <= point && point <= 21 {
+        digits + &"0".repeat((point - k) as usize)
+    } else if 0 < point && point <= 21 {
+        let (int, frac) = digits.split_at(point as usize);
+        format!("{}.{}", int, frac)
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let dot = if rest.is_empty() { "" } else { "." };
+        let sign = if point > 0 { "+" } else { "-" };
+        format!("{}{}{}e{}{}", first, dot, rest, sign, (point - 1).abs())
+    }
+}
+
+/// The preferred type of [`JSValue::to_primitive()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveHint {
+    Default,
+    Number,
+    String,
 }
 
 /// A `JSValue` is either a primitive value or a reference to an object.
+///
+/// It is NaN-boxed into one 64-bit word. Numbers are stored as they are, with all NaNs
+/// made the same positive quiet NaN. This leaves the negative quiet NaNs free: their
+/// upper 16 bits tag the other kinds of values, the lower 48 bits hold
+/// a bool, a [`JSRef`] or the pointer of a [`JSString`] or of a [`BigInt`].
+///
+/// Numbers that are exact 32-bit integers (except `-0`) are always boxed as int32,
+/// so that arithmetic and array indexing have a fast path: see [`JSValue::as_int32()`].
+///
+/// Use [`JSValue::unboxed()`] to match on the kind of a value,
+/// or accessors like [`JSValue::as_number()`] to look at one kind.
+#[repr(transparent)]
+pub struct JSValue {
+    bits: u64,
+    /// a `JSValue` might own a string, which is not `Send`
+    _string: PhantomData<JSString>,
+}
+
+/// The contents of a [`JSValue`], see [`JSValue::unboxed()`].
 #[derive(Debug, Clone, PartialEq)]
-pub enum JSValue {
+pub enum Unboxed {
     Undefined,
     Bool(bool),
     Number(JSNumber),
     String(JSString),
+    BigInt(Rc<BigInt>),
     //Symbol(String)
     Ref(JSRef),
 }
 
 impl JSValue {
-    pub const NULL: JSValue = JSValue::Ref(Heap::NULL);
+    const TAG_SHIFT: u32 = 48;
+    const PAYLOAD: u64 = (1 << Self::TAG_SHIFT) - 1;
+    const CANONICAL_NAN: u64 = 0x7ff8_0000_0000_0000;
+
+    // bit patterns of negative quiet NaNs, never used by numbers
+    const TAG_UNDEFINED: u64 = 0xfff9 << Self::TAG_SHIFT;
+    const TAG_BOOL: u64 = 0xfffa << Self::TAG_SHIFT;
+    const TAG_STRING: u64 = 0xfffb << Self::TAG_SHIFT;
+    const TAG_REF: u64 = 0xfffc << Self::TAG_SHIFT;
+    const TAG_INT: u64 = 0xfffd << Self::TAG_SHIFT;
+    const TAG_BIGINT: u64 = 0xfffe << Self::TAG_SHIFT;
+
+    pub const UNDEFINED: JSValue = JSValue::from_bits(Self::TAG_UNDEFINED);
+    pub const NULL: JSValue = JSValue::from_ref(Heap::NULL);
+    pub const NAN: JSValue = JSValue::from_bits(Self::CANONICAL_NAN);
+
+    const fn from_bits(bits: u64) -> JSValue {
+        JSValue {
+            bits,
+            _string: PhantomData,
+        }
+    }
+
+    const fn from_ref(objref: JSRef) -> JSValue {
+        JSValue::from_bits(Self::TAG_REF | objref.to_bits())
+    }
+
+    fn tag(&self) -> u64 {
+        self.bits & !Self::PAYLOAD
+    }
+
+    fn payload(&self) -> u64 {
+        self.bits & Self::PAYLOAD
+    }
+
+    fn string_ptr(&self) -> Option<*const StrNode> {
+        match self.tag() {
+            Self::TAG_STRING => Some(self.payload() as usize as *const StrNode),
+            _ => None,
+        }
+    }
+
+    fn bigint_ptr(&self) -> Option<*const BigInt> {
+        match self.tag() {
+            Self::TAG_BIGINT => Some(self.payload() as usize as *const BigInt),
+            _ => None,
+        }
+    }
+
+    /// Takes the value out of its box to match on it.
+    /// Strings and BigInts are cloned, i.e. their reference count is incremented.
+    pub fn unboxed(&self) -> Unboxed {
+        if let Some(n) = self.as_number() {
+            return Unboxed::Number(n);
+        }
+        match self.tag() {
+            Self::TAG_UNDEFINED => Unboxed::Undefined,
+            Self::TAG_BOOL => Unboxed::Bool(self.payload() != 0),
+            Self::TAG_STRING => Unboxed::String(self.as_string().unwrap()),
+            Self::TAG_BIGINT => Unboxed::BigInt(self.to_bigint().unwrap()),
+            Self::TAG_REF => Unboxed::Ref(JSRef::from_bits(self.payload())),
+            _ => unreachable!("invalid JSValue bits: {:#x}", self.bits),
+        }
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        self.bits == Self::TAG_UNDEFINED
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == JSValue::NULL
+    }
+
+    /// `null` or `undefined`
+    pub fn is_nullish(&self) -> bool {
+        self.is_undefined() || self.is_null()
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        (self.tag() == Self::TAG_BOOL).then(|| self.payload() != 0)
+    }
+
+    pub fn as_number(&self) -> Option<JSNumber> {
+        if self.bits < Self::TAG_UNDEFINED {
+            Some(f64::from_bits(self.bits))
+        } else {
+            self.as_int32().map(JSNumber::from)
+        }
+    }
+
+    /// A number that is boxed as an integer.
+    pub fn as_int32(&self) -> Option<i32> {
+        (self.tag() == Self::TAG_INT).then(|| self.payload() as u32 as i32)
+    }
+
+    /// The array index of a number, without going through its string.
+    pub fn to_index(&self) -> Option<u32> {
+        if let Some(i) = self.as_int32() {
+            return u32::try_from(i).ok();
+        }
+        let n = self.as_number()?;
+        let index = n as u32;
+        (index as JSNumber == n && index < JSArray::MAX_LENGTH).then_some(index)
+    }
+
+    /// A property key: array indexes stay numbers, anything else becomes a string.
+    pub fn to_property_key(&self, heap: &mut Heap) -> JSResult<JSValue> {
+        match self.to_index() {
+            Some(_) => Ok(self.clone()),
+            None => Ok(JSValue::from(self.stringify(heap)?)),
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.tag() == Self::TAG_STRING
+    }
+
+    /// The string of a string value, without cloning it.
+    pub fn as_str(&self) -> Option<&str> {
+        // SAFETY: `self` keeps a strong count of the string while it is borrowed
+        self.string_ptr().map(|ptr| unsafe { (*ptr).as_str() })
+    }
+
+    /// The identity and the length of a string value, to count shared strings once.
+    pub(crate) fn string_ident(&self) -> Option<(usize, usize)> {
+        // SAFETY: `self` keeps a strong count of the string
+        self.string_ptr()
+            .map(|ptr| (ptr as usize, unsafe { (*ptr).len }))
+    }
+
+    pub fn as_string(&self) -> Option<JSString> {
+        self.string_ptr().map(|ptr| {
+            // SAFETY: the pointer came from `JSString::into_raw()`, `self` keeps its count
+            unsafe { Rc::increment_strong_count(ptr) };
+            unsafe { JSString::from_raw(ptr) }
+        })
+    }
+
+    /// The integer of a BigInt value, without cloning it.
+    pub fn as_bigint(&self) -> Option<&BigInt> {
+        // SAFETY: `self` keeps a strong count of the BigInt while it is borrowed
+        self.bigint_ptr().map(|ptr| unsafe { &*ptr })
+    }
+
+    fn to_bigint(&self) -> Option<Rc<BigInt>> {
+        self.bigint_ptr().map(|ptr| {
+            // SAFETY: the pointer came from `Rc::into_raw()`, `self` keeps its count
+            unsafe { Rc::increment_strong_count(ptr) };
+            unsafe { Rc::from_raw(ptr) }
+        })
+    }
+
+    /// The object reference of a reference value, `null` included.
+    pub fn as_objref(&self) -> Option<JSRef> {
+        (self.tag() == Self::TAG_REF).then(|| JSRef::from_bits(self.payload()))
+    }
 
     /// to_ref() tries to return the underlying object reference, if any.
     /// Throws Exception::ReferenceNotAnObject if it's not a reference.
     /// Checking if a JSValue is a reference: `val.to_ref().is_ok()`.
     pub fn to_ref(&self) -> JSResult<JSRef> {
-        match self {
-            JSValue::Ref(objref) => Ok(*objref),
-            _ => {
+        match self.as_objref() {
+            Some(objref) => Ok(objref),
+            None => {
                 let what = Interpreted::Value(self.clone());
                 Err(Exception::not_an_object(what))
             }
@@ -137,13 +592,15 @@ impl JSValue {
 
     #[allow(dead_code)]
     pub fn to_json(&self, heap: &Heap) -> JSResult<JSON> {
-        match self {
-            JSValue::Undefined => Ok(JSON::Null),
-            JSValue::Bool(b) => Ok(JSON::from(*b)),
-            JSValue::Number(n) => Ok(JSON::from(*n)),
-            JSValue::String(s) => Ok(JSON::from(s.as_str())),
-            JSValue::Ref(Heap::NULL) => Ok(JSON::Null),
-            JSValue::Ref(href) => heap.get(*href).to_json(heap),
+        match self.unboxed() {
+            Unboxed::Undefined => Ok(JSON::Null),
+            Unboxed::Bool(b) => Ok(JSON::from(b)),
+            Unboxed::Number(n) => Ok(JSON::from(n)),
+            Unboxed::String(s) => Ok(JSON::from(s.as_str())),
+            // JSON numbers are not precise enough
+            Unboxed::BigInt(n) => Ok(JSON::from(n.to_string())),
+            Unboxed::Ref(Heap::NULL) => Ok(JSON::Null),
+            Unboxed::Ref(href) => heap.get(href).to_json(heap),
         }
     }
 
@@ -176,14 +633,15 @@ impl JSValue {
     /// );
     /// ```
     pub fn to_string(&self, heap: &mut Heap) -> JSResult<JSString> {
-        match self {
-            JSValue::String(s) => {
+        match self.unboxed() {
+            Unboxed::String(s) => {
                 let jstr = JSON::from(s.as_str());
                 Ok(JSString::from(jstr.to_string()))
             }
-            JSValue::Ref(heapref) => {
+            Unboxed::BigInt(n) => Ok(JSString::from(format!("{}n", n))),
+            Unboxed::Ref(heapref) => {
                 // without `.clone()` `heap` cannot be borrowed in both places
-                heap.get(*heapref).clone().to_string(heap)
+                heap.get(heapref).clone().to_string(heap)
             }
             _ => self.stringify(heap),
         }
@@ -193,49 +651,105 @@ impl JSValue {
     /// used for evaluation in a string context.
     /// It corresponds to .toString() in JavaScript
     pub fn stringify(&self, heap: &mut Heap) -> JSResult<JSString> {
-        match self {
-            JSValue::Undefined => Ok("undefined".into()),
-            JSValue::Bool(b) => Ok(b.to_string().into()),
-            JSValue::Number(n) => Ok(n.to_string().into()),
-            JSValue::String(s) => Ok(s.clone()),
-            JSValue::Ref(r) if r == &Heap::NULL => Ok(JSString::from("null")),
-            JSValue::Ref(r) => match heap.lookup_protochain(*r, "toString") {
-                Some(to_string) => {
-                    let funcref = to_string.to_ref(heap)?;
-                    let result = heap.execute(
-                        funcref,
-                        CallContext {
-                            this_ref: *r,
-                            method_name: "toString".into(),
-                            arguments: vec![],
-                            loc: None,
-                        },
-                    )?;
-                    Ok(result.to_value(heap)?.stringify(heap)?)
-                }
-                None => Ok("[object Object]".into()),
+        match self.unboxed() {
+            Unboxed::Undefined => Ok("undefined".into()),
+            Unboxed::Bool(b) => Ok(b.to_string().into()),
+            Unboxed::Number(n) => Ok(number_to_string(n).into()),
+            Unboxed::String(s) => Ok(s),
+            Unboxed::BigInt(n) => Ok(JSString::from(n.to_string())),
+            Unboxed::Ref(Heap::NULL) => Ok(JSString::from("null")),
+            Unboxed::Ref(_) => self
+                .to_primitive(PrimitiveHint::String, heap)?
+                .stringify(heap),
+        }
+    }
+
+    /// ToPrimitive: objects are converted by their `.valueOf()`/`.toString()`
+    /// (in the order given by `hint`), primitive values are returned as they are.
+    pub fn to_primitive(&self, hint: PrimitiveHint, heap: &mut Heap) -> JSResult<JSValue> {
+        let objref = match self.as_objref() {
+            Some(objref) if objref != Heap::NULL => objref,
+            _ => return Ok(self.clone()),
+        };
+        let methods = match hint {
+            PrimitiveHint::String => ["toString", "valueOf"],
+            PrimitiveHint::Default | PrimitiveHint::Number => ["valueOf", "toString"],
+        };
+        for method in methods {
+            let method_value = heap.get(objref).lookup_value(method, heap);
+            let funcref = match method_value.and_then(|value| value.as_objref()) {
+                Some(funcref) if heap.get(funcref).is_callable() => funcref,
+                _ => continue,
+            };
+            let call = CallContext::from(vec![]).with_this(objref);
+            let result = heap.execute(funcref, call.with_name(method.into()))?;
+            match result.to_value(heap)? {
+                value if value.as_objref().is_some_and(|r| r != Heap::NULL) => continue,
+                primitive => return Ok(primitive),
+            }
+        }
+        Err(Exception::type_error(TypeError::NO_PRIMITIVE, objref))
+    }
+
+    /// ToNumber: the numeric value of `+value` in JavaScript.
+    pub fn to_number(&self, heap: &mut Heap) -> JSResult<JSNumber> {
+        match self.unboxed() {
+            Unboxed::Undefined => Ok(f64::NAN),
+            Unboxed::Bool(b) => Ok(if b { 1.0 } else { 0.0 }),
+            Unboxed::Number(n) => Ok(n),
+            Unboxed::String(s) => Ok(string_to_number(&s)),
+            Unboxed::BigInt(_) => Err(Exception::type_error(
+                TypeError::BIGINT_TO_NUMBER,
+                self.clone(),
+            )),
+            Unboxed::Ref(Heap::NULL) => Ok(0.0),
+            Unboxed::Ref(_) => (self.to_primitive(PrimitiveHint::Number, heap)?).to_number(heap),
+        }
+    }
+
+    /// ToNumeric: a BigInt stays one, anything else is converted to a number.
+    pub fn to_numeric(&self, heap: &mut Heap) -> JSResult<JSValue> {
+        let primitive = self.to_primitive(PrimitiveHint::Number, heap)?;
+        match primitive.as_bigint() {
+            Some(_) => Ok(primitive),
+            None => Ok(JSValue::from(primitive.to_number(heap)?)),
+        }
+    }
+
+    /// ToBigInt: numbers are not converted implicitly, strings are parsed.
+    pub fn to_bigint_value(&self, heap: &mut Heap) -> JSResult<Rc<BigInt>> {
+        let primitive = self.to_primitive(PrimitiveHint::Number, heap)?;
+        match primitive.unboxed() {
+            Unboxed::BigInt(n) => Ok(n),
+            Unboxed::Bool(b) => Ok(Rc::new(BigInt::from(b as u64))),
+            Unboxed::String(s) => match BigInt::parse(&s) {
+                Some(n) => Ok(Rc::new(n)),
+                None => Err(Exception::Syntax(ParseError::InvalidBigInt(s.to_string()))),
             },
+            _ => Err(Exception::type_error(TypeError::NOT_BIGINT, primitive)),
         }
     }
 
     /// numberify() tries to make everything into a numeric value
-    /// for evalation in a numeric context.
-    /// It is slightly more strict than `+value` in JavaScript: only
-    /// `value.numberify().unwrap_or(f64::NAN)` corresponds to `+value` in JavaScript.
+    /// for evalation in a numeric context without calling into JavaScript.
+    /// It is slightly more strict than `+value` in JavaScript, which is
+    /// [`JSValue::to_number()`].
     pub fn numberify(&self, heap: &Heap) -> Option<JSNumber> {
-        match self {
-            JSValue::Undefined => None, // Some(f64::NAN),
-            JSValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
-            JSValue::Number(n) => Some(*n),
-            JSValue::String(s) => s.as_str().parse::<JSNumber>().ok(),
-            JSValue::Ref(Heap::NULL) => Some(0.0),
-            JSValue::Ref(r) => {
-                let object = heap.get(*r);
+        match self.unboxed() {
+            Unboxed::Undefined => None, // Some(f64::NAN),
+            Unboxed::Bool(b) => Some(if b { 1.0 } else { 0.0 }),
+            Unboxed::Number(n) => Some(n),
+            Unboxed::String(s) => Some(string_to_number(&s)).filter(|n| !n.is_nan()),
+            Unboxed::BigInt(n) => Some(n.to_f64()),
+            Unboxed::Ref(Heap::NULL) => Some(0.0),
+            Unboxed::Ref(r) => {
+                let object = heap.get(r);
                 if let Some(array) = object.as_array() {
-                    match &array.storage[..] {
-                        [] => Some(0.0),              // +[]  == 0
-                        [val] => val.numberify(heap), // +[x] == x
-                        _ => None,                    // +[x, y, ..] == NaN
+                    match (array.len(), array.get(0)) {
+                        (0, _) => Some(0.0),                   // +[]  == 0
+                        (1, Some(val)) => val.numberify(heap), // +[x] == x
+                        (1, None) => Some(0.0),                // +[,] == 0
+                        _ => None,                             // +[x, y, ..] == NaN
                     }
                 } else {
                     object.to_primitive().and_then(|v| v.numberify(heap))
@@ -247,11 +761,12 @@ impl JSValue {
     /// boolify() treats everythings as a truthy value.
     /// ES5: ToBoolean
     pub fn boolify(&self, heap: &Heap) -> bool {
-        match self {
-            JSValue::Undefined => false,
-            JSValue::String(s) => !s.as_str().is_empty(),
-            JSValue::Ref(Heap::NULL) => false,
-            JSValue::Ref(_) => true,
+        match self.unboxed() {
+            Unboxed::Undefined => false,
+            Unboxed::String(s) => !s.as_str().is_empty(),
+            Unboxed::BigInt(n) => !n.is_zero(),
+            Unboxed::Ref(Heap::NULL) => false,
+            Unboxed::Ref(_) => true,
             _ => {
                 if let Some(n) = self.numberify(heap) {
                     !(n == 0.0 || f64::is_nan(n))
@@ -264,29 +779,28 @@ impl JSValue {
 
     /// objectify() wraps a primitive into its object:
     /// - `undefined` becomes `null`
-    /// - `bool`/`number`/`string` becomes `Boolean`/`Number`/`String`
+    /// - `bool`/`number`/`string`/`bigint` becomes `Boolean`/`Number`/`String`/`BigInt`
     /// - objects just return their reference.
     pub fn objectify(&self, heap: &mut Heap) -> JSRef {
-        match self {
-            JSValue::Undefined => Heap::NULL,
-            JSValue::Bool(b) => heap.alloc(JSObject::from_bool(*b)),
-            JSValue::Number(_n) => {
-                let _ = crate::source::print_callstack(heap);
-                todo!(); // TODO: Number object
-            }
-            JSValue::String(s) => heap.alloc(JSObject::from(s.clone())),
-            JSValue::Ref(r) => *r,
+        match self.unboxed() {
+            Unboxed::Undefined => Heap::NULL,
+            Unboxed::Bool(b) => heap.alloc(JSObject::from_bool(b)),
+            Unboxed::Number(n) => heap.alloc(JSObject::from_number(n)),
+            Unboxed::String(s) => heap.alloc(JSObject::from(s)),
+            Unboxed::BigInt(n) => heap.alloc(JSObject::from_bigint(n)),
+            Unboxed::Ref(r) => r,
         }
     }
 
     /// Javascript's `typeof`
     pub fn type_of(&self, heap: &Heap) -> &'static str {
-        match self {
-            JSValue::Undefined => "undefined",
-            JSValue::String(_) => "string",
-            JSValue::Number(_) => "number",
-            JSValue::Bool(_) => "boolean",
-            JSValue::Ref(r) => match heap.get(*r).is_callable() {
+        match self.unboxed() {
+            Unboxed::Undefined => "undefined",
+            Unboxed::String(_) => "string",
+            Unboxed::Number(_) => "number",
+            Unboxed::Bool(_) => "boolean",
+            Unboxed::BigInt(_) => "bigint",
+            Unboxed::Ref(r) => match heap.get(r).is_callable() {
                 true => "function",
                 false => "object",
             },
@@ -295,36 +809,33 @@ impl JSValue {
 
     /// Abstract Equality Comparison, `==`:
     /// <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Equality_comparisons_and_sameness#Loose_equality_using_>
-    pub fn loose_eq(&self, other: &JSValue, heap: &Heap) -> bool {
-        let lval = if self == &JSValue::NULL {
-            &JSValue::Undefined
-        } else {
-            self
-        };
-        let rval = if other == &JSValue::NULL {
-            &JSValue::Undefined
-        } else {
-            other
+    pub fn loose_eq(&self, other: &JSValue, heap: &mut Heap) -> JSResult<bool> {
+        use Unboxed::{BigInt, Bool, Number, Ref, String, Undefined};
+        let equal = match (self.unboxed(), other.unboxed()) {
+            (Undefined | Ref(Heap::NULL), Undefined | Ref(Heap::NULL)) => true,
+            (Undefined | Ref(Heap::NULL), _) | (_, Undefined | Ref(Heap::NULL)) => false,
+            (Number(_), Number(_))
+            | (String(_), String(_))
+            | (Bool(_), Bool(_))
+            | (BigInt(_), BigInt(_))
+            | (Ref(_), Ref(_)) => self == other,
+            (Number(n), String(s)) | (String(s), Number(n)) => n == string_to_number(&s),
+            (BigInt(b), Number(n)) | (Number(n), BigInt(b)) => {
+                b.partial_cmp_f64(n) == Some(Ordering::Equal)
+            }
+            (BigInt(b), String(s)) | (String(s), BigInt(b)) => {
+                crate::BigInt::parse(&s).is_some_and(|n| n == *b)
+            }
+            (Bool(_), _) => JSValue::from(self.to_number(heap)?).loose_eq(other, heap)?,
+            (_, Bool(_)) => self.loose_eq(&JSValue::from(other.to_number(heap)?), heap)?,
+            (Ref(_), _) => {
+                (self.to_primitive(PrimitiveHint::Default, heap)?).loose_eq(other, heap)?
+            }
+            (_, Ref(_)) => {
+                self.loose_eq(&other.to_primitive(PrimitiveHint::Default, heap)?, heap)?
+            }
         };
-        match (lval, rval) {
-            (JSValue::Undefined, JSValue::Undefined) => true,
-            (JSValue::Undefined, _) | (_, JSValue::Undefined) => false,
-            (JSValue::Number(_), JSValue::Number(_))
-            | (JSValue::String(_), JSValue::String(_))
-            | (JSValue::Bool(_), JSValue::Bool(_)) => self == other,
-            (JSValue::Ref(lref), JSValue::Ref(rref)) if lref == rref => true,
-            (JSValue::Ref(lref), JSValue::Ref(rref)) => match (
-                heap.get(*lref).to_primitive(),
-                heap.get(*rref).to_primitive(),
-            ) {
-                (Some(lval), Some(rval)) => lval == rval,
-                _ => false,
-            },
-            _ => match (self.numberify(heap), other.numberify(heap)) {
-                (Some(lnum), Some(rnum)) => lnum == rnum,
-                _ => false,
-            },
-        }
+        Ok(equal)
     }
 
     /// Strict Equality, `===`
@@ -333,80 +844,200 @@ impl JSValue {
         self == other
     }
 
-    pub fn numerically<F>(&self, other: &JSValue, heap: &Heap, op: F) -> JSValue
+    /// SameValue, `Object.is()`: like `===`, but `NaN` is itself and `0` is not `-0`.
+    pub fn same_value(&self, other: &JSValue) -> bool {
+        use Unboxed::*;
+        match (self.unboxed(), other.unboxed()) {
+            (Undefined, Undefined) => true,
+            (String(lstr), String(rstr)) => lstr == rstr,
+            (Bool(lb), Bool(rb)) => lb == rb,
+            (BigInt(lint), BigInt(rint)) => lint == rint,
+            (Number(lnum), Number(rnum)) => {
+                if f64::abs(lnum) == 0.0 && f64::abs(rnum) == 0.0 {
+                    f64::is_sign_positive(lnum) == f64::is_sign_positive(rnum)
+                } else {
+                    (f64::is_nan(lnum) && f64::is_nan(rnum)) || lnum == rnum
+                }
+            }
+            (Ref(lref), Ref(rref)) => lref == rref,
+            _ => false,
+        }
+    }
+
+    pub fn numerically<F>(&self, other: &JSValue, heap: &mut Heap, op: F) -> JSResult<JSValue>
     where
         F: Fn(f64, f64) -> f64,
     {
-        let val = match (self.numberify(heap), other.numberify(heap)) {
-            (Some(lnum), Some(rnum)) => op(lnum, rnum),
-            _ => f64::NAN,
-        };
-        JSValue::Number(val)
+        let lnum = self.to_number(heap)?;
+        let rnum = other.to_number(heap)?;
+        Ok(JSValue::from(op(lnum, rnum)))
     }
 
     /// Addition operator:
     /// <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/Addition>
     pub fn plus(&self, other: &JSValue, heap: &mut Heap) -> JSResult<JSValue> {
-        if let JSValue::String(str_self) = self {
-            let str_other = other.stringify(heap)?;
-            let mut result = String::from(str_self.as_str());
-            result.push_str(str_other.as_str());
-            return Ok(JSValue::from(result));
+        let lval = self.to_primitive(PrimitiveHint::Default, heap)?;
+        let rval = other.to_primitive(PrimitiveHint::Default, heap)?;
+        if lval.is_string() || rval.is_string() {
+            let (lstr, rstr) = (lval.stringify(heap)?, rval.stringify(heap)?);
+            heap.charge_string(lstr.len() + rstr.len())?;
+            return Ok(JSValue::from(JSString::concat(&lstr, &rstr)));
         }
-        if let JSValue::String(str_other) = other {
-            let str_self = self.stringify(heap)?;
-            let mut result = String::from(str_self.as_str());
-            result.push_str(str_other.as_str());
-            return Ok(JSValue::from(result));
+        match (lval.as_bigint(), rval.as_bigint()) {
+            (Some(a), Some(b)) => Ok(JSValue::from(a + b)),
+            (None, None) => Ok(JSValue::from(lval.to_number(heap)? + rval.to_number(heap)?)),
+            _ => Err(Exception::type_error(TypeError::BIGINT_MIX, rval)),
         }
-        let num_self = JSValue::Undefined.eq(self).then_some(f64::NAN).or_else(|| self.numberify(heap));
-        let num_other = JSValue::Undefined.eq(other).then_some(f64::NAN).or_else(|| other.numberify(heap));
-        if let (Some(lnum), Some(rnum)) = (num_self, num_other) {
-            return Ok(JSValue::from(lnum + rnum));
-        }
-        let lvalstr = self.stringify(heap)?;
-        let rvalstr = other.stringify(heap)?;
-        Ok(JSValue::from(lvalstr.to_string() + rvalstr.as_str()))
     }
 
     /// Subtraction operator:
     /// <https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Operators/Subtraction>
-    pub fn minus(&self, other: &JSValue, heap: &Heap) -> JSResult<JSValue> {
-        Ok(JSValue::numerically(self, other, heap, |a, b| a - b))
+    pub fn minus(&self, other: &JSValue, heap: &mut Heap) -> JSResult<JSValue> {
+        JSValue::numerically(self, other, heap, |a, b| a - b)
     }
 
-    pub fn compare<StrCmpFn: Fn(&str, &str) -> bool, NumCmpFn: Fn(f64, f64) -> bool>(
+    /// Abstract Relational Comparison: is `self < other`?
+    /// `None` means `undefined`, i.e. there is a `NaN` involved.
+    /// `left_first` tells if `self` is converted to a primitive before `other`.
+    pub fn is_less_than(
         &self,
         other: &JSValue,
-        heap: &Heap,
-        stringly: StrCmpFn,
-        numberly: NumCmpFn,
-    ) -> JSValue {
-        // TODO: Abstract Relational Comparison
-        if let (JSValue::String(lstr), JSValue::String(rstr)) = (self, other) {
-            return JSValue::from(stringly(lstr.as_str(), rstr.as_str()));
+        left_first: bool,
+        heap: &mut Heap,
+    ) -> JSResult<Option<bool>> {
+        let (lval, rval) = if left_first {
+            let lval = self.to_primitive(PrimitiveHint::Number, heap)?;
+            (lval, other.to_primitive(PrimitiveHint::Number, heap)?)
+        } else {
+            let rval = other.to_primitive(PrimitiveHint::Number, heap)?;
+            (self.to_primitive(PrimitiveHint::Number, heap)?, rval)
+        };
+        if let (Some(lstr), Some(rstr)) = (lval.as_str(), rval.as_str()) {
+            // strings are sequences of UTF-16 code units
+            return Ok(Some(lstr.encode_utf16().lt(rstr.encode_utf16())));
+        }
+        if lval.as_bigint().is_some() || rval.as_bigint().is_some() {
+            return Ok(JSValue::compare_bigint(&lval, &rval, heap)?.map(Ordering::is_lt));
+        }
+        let lnum = lval.to_number(heap)?;
+        let rnum = rval.to_number(heap)?;
+        if lnum.is_nan() || rnum.is_nan() {
+            return Ok(None);
+        }
+        Ok(Some(lnum < rnum))
+    }
+
+    /// Compares primitives when at least one of them is a BigInt:
+    /// the other one is compared exactly as a number or as a parsed string.
+    fn compare_bigint(
+        lval: &JSValue,
+        rval: &JSValue,
+        heap: &mut Heap,
+    ) -> JSResult<Option<Ordering>> {
+        let ordering = match (lval.unboxed(), rval.unboxed()) {
+            (Unboxed::BigInt(a), Unboxed::BigInt(b)) => Some(a.cmp(&b)),
+            (Unboxed::BigInt(a), Unboxed::String(s)) => BigInt::parse(&s).map(|b| (*a).cmp(&b)),
+            (Unboxed::String(s), Unboxed::BigInt(b)) => BigInt::parse(&s).map(|a| a.cmp(&b)),
+            (Unboxed::BigInt(a), _) => a.partial_cmp_f64(rval.to_number(heap)?),
+            (_, Unboxed::BigInt(b)) => b
+                .partial_cmp_f64(lval.to_number(heap)?)
+                .map(Ordering::reverse),
+            _ => unreachable!("no BigInt to compare"),
         };
-        let lnum = self.numberify(heap).unwrap_or(f64::NAN);
-        let rnum = other.numberify(heap).unwrap_or(f64::NAN);
-        JSValue::from(numberly(lnum, rnum))
+        Ok(ordering)
+    }
+}
+
+impl Clone for JSValue {
+    fn clone(&self) -> Self {
+        if let Some(ptr) = self.string_ptr() {
+            // SAFETY: the pointer came from `JSString::into_raw()`, `self` keeps its count
+            unsafe { Rc::increment_strong_count(ptr) };
+        } else if let Some(ptr) = self.bigint_ptr() {
+            // SAFETY: the pointer came from `Rc::into_raw()`, `self` keeps its count
+            unsafe { Rc::increment_strong_count(ptr) };
+        }
+        JSValue::from_bits(self.bits)
+    }
+}
+
+impl Drop for JSValue {
+    fn drop(&mut self) {
+        if let Some(ptr) = self.string_ptr() {
+            // SAFETY: `self` owns one strong count of the string
+            drop(unsafe { JSString::from_raw(ptr) });
+        } else if let Some(ptr) = self.bigint_ptr() {
+            // SAFETY: `self` owns one strong count of the BigInt
+            drop(unsafe { Rc::from_raw(ptr) });
+        }
+    }
+}
+
+impl PartialEq for JSValue {
+    /// Numbers are compared as numbers (`NaN != NaN`, `0 == -0`),
+    /// strings and BigInts by their contents.
+    fn eq(&self, other: &Self) -> bool {
+        match (self.as_number(), other.as_number()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) if self.bits == other.bits => true,
+            _ if self.is_string() => other.is_string() && self.as_str() == other.as_str(),
+            _ => self
+                .as_bigint()
+                .is_some_and(|a| Some(a) == other.as_bigint()),
+        }
+    }
+}
+
+impl fmt::Debug for JSValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.unboxed(), f)
+    }
+}
+
+impl From<Unboxed> for JSValue {
+    fn from(value: Unboxed) -> Self {
+        match value {
+            Unboxed::Undefined => JSValue::UNDEFINED,
+            Unboxed::Bool(b) => JSValue::from(b),
+            Unboxed::Number(n) => JSValue::from(n),
+            Unboxed::String(s) => JSValue::from(s),
+            Unboxed::BigInt(n) => JSValue::from(n),
+            Unboxed::Ref(r) => JSValue::from(r),
+        }
     }
 }
 
 impl From<bool> for JSValue {
     fn from(b: bool) -> Self {
-        JSValue::Bool(b)
+        JSValue::from_bits(Self::TAG_BOOL | b as u64)
     }
 }
 
 impl From<JSNumber> for JSValue {
     fn from(number: JSNumber) -> Self {
-        JSValue::Number(number)
+        let int = number as i32;
+        if int as JSNumber == number && (int != 0 || number.is_sign_positive()) {
+            JSValue::from(int)
+        } else if number.is_nan() {
+            JSValue::NAN
+        } else {
+            JSValue::from_bits(number.to_bits())
+        }
+    }
+}
+
+impl From<i32> for JSValue {
+    fn from(number: i32) -> Self {
+        JSValue::from_bits(Self::TAG_INT | number as u32 as u64)
     }
 }
 
 impl From<i64> for JSValue {
     fn from(number: i64) -> Self {
-        JSValue::Number(number as JSNumber)
+        match i32::try_from(number) {
+            Ok(int) => JSValue::from(int),
+            Err(_) => JSValue::from(number as JSNumber),
+        }
     }
 }
 
@@ -415,13 +1046,35 @@ where
     JSString: From<S>,
 {
     fn from(s: S) -> Self {
-        JSValue::String(JSString::from(s))
+        let ptr = JSString::from(s).into_raw() as usize as u64;
+        assert!(
+            ptr & !Self::PAYLOAD == 0,
+            "a string pointer does not fit in a JSValue"
+        );
+        JSValue::from_bits(Self::TAG_STRING | ptr)
+    }
+}
+
+impl From<Rc<BigInt>> for JSValue {
+    fn from(n: Rc<BigInt>) -> Self {
+        let ptr = Rc::into_raw(n) as usize as u64;
+        assert!(
+            ptr & !Self::PAYLOAD == 0,
+            "a BigInt pointer does not fit in a JSValue"
+        );
+        JSValue::from_bits(Self::TAG_BIGINT | ptr)
+    }
+}
+
+impl From<BigInt> for JSValue {
+    fn from(n: BigInt) -> Self {
+        JSValue::from(Rc::new(n))
     }
 }
 
 impl From<JSRef> for JSValue {
     fn from(r: JSRef) -> Self {
-        JSValue::Ref(r)
+        JSValue::from_ref(r)
     }
 }
 
@@ -452,6 +1105,107 @@ fn test_numberify() {
     assert_eq!(JSValue::from("5").numberify(&dummy), Some(5.0));
 }
 
+#[test]
+fn test_nan_boxing() {
+    for n in [
+        0.0,
+        -0.0,
+        1.5,
+        -1e300,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::MIN_POSITIVE,
+    ] {
+        let value = JSValue::from(n);
+        assert_eq!(value.as_number().map(f64::to_bits), Some(n.to_bits()));
+    }
+    let nan = JSValue::from(-f64::NAN);
+    assert!(nan.as_number().unwrap().is_nan());
+    assert_ne!(nan, JSValue::NAN);
+
+    assert_eq!(JSValue::from(true).unboxed(), Unboxed::Bool(true));
+    assert_eq!(JSValue::UNDEFINED.unboxed(), Unboxed::Undefined);
+    assert_eq!(JSValue::NULL.as_objref(), Some(Heap::NULL));
+    assert_eq!(JSValue::from(Heap::GLOBAL).as_objref(), Some(Heap::GLOBAL));
+    assert_eq!(JSValue::from(false).as_number(), None);
+
+    assert_eq!(JSValue::from(5.0).as_int32(), Some(5));
+    assert_eq!(JSValue::from(-7i64).as_int32(), Some(-7));
+    assert_eq!(JSValue::from(-0.0).as_int32(), None);
+    assert_eq!(JSValue::from(1.5).as_int32(), None);
+    assert_eq!(JSValue::from(2147483648.0).as_int32(), None);
+    assert_eq!(JSValue::from(i32::MIN).as_number(), Some(-2147483648.0));
+    assert_eq!(JSValue::from(3), JSValue::from(3.0));
+    assert_eq!(JSValue::from(4294967294.0).to_index(), Some(u32::MAX - 1));
+    assert_eq!(JSValue::from(4294967295.0).to_index(), None);
+    assert_eq!(JSValue::from(-0.0).to_index(), Some(0));
+    assert_eq!(JSValue::from(-1).to_index(), None);
+
+    let s = JSString::from("boxed");
+    let value = JSValue::from(s.clone());
+    let copy = value.clone();
+    assert_eq!(Rc::strong_count(&s.0), 3);
+    assert_eq!(copy.as_str(), Some("boxed"));
+    assert_eq!(copy, JSValue::from("boxed"));
+    drop((value, copy));
+    assert_eq!(Rc::strong_count(&s.0), 1);
+}
+
+#[test]
+fn test_string_to_number() {
+    assert_eq!(string_to_number(" 12 "), 12.0);
+    assert_eq!(string_to_number("\t\n"), 0.0);
+    assert_eq!(string_to_number(""), 0.0);
+    assert_eq!(string_to_number("0x1F"), 31.0);
+    assert_eq!(string_to_number("0o17"), 15.0);
+    assert_eq!(string_to_number("0b101"), 5.0);
+    assert_eq!(string_to_number("-Infinity"), f64::NEG_INFINITY);
+    assert_eq!(string_to_number("+1.5e3"), 1500.0);
+    assert_eq!(string_to_number("1."), 1.0);
+    assert_eq!(string_to_number(".5"), 0.5);
+    assert_eq!(string_to_number("\u{feff}7\u{2028}"), 7.0);
+
+    for nan in [
+        "inf", "NaN", "infinity", "1_000", "-0x10", "0x", "1e", ".", "12px", "\u{85}1",
+    ] {
+        assert!(string_to_number(nan).is_nan(), "{:?}", nan);
+    }
+}
+
+#[test]
+fn test_number_to_string() {
+    let cases = [
+        (0.0, "0"),
+        (-0.0, "0"),
+        (f64::NAN, "NaN"),
+        (f64::NEG_INFINITY, "-Infinity"),
+        (42.0, "42"),
+        (-1.5, "-1.5"),
+        (0.1 + 0.2, "0.30000000000000004"),
+        (1e21, "1e+21"),
+        (1e20, "100000000000000000000"),
+        (123e20, "1.23e+22"),
+        (0.000001, "0.000001"),
+        (1e-7, "1e-7"),
+        (1.5e-7, "1.5e-7"),
+        (f64::MAX, "1.7976931348623157e+308"),
+        (5e-324, "5e-324"),
+    ];
+    for (n, want) in cases {
+        assert_eq!(number_to_string(n), want);
+    }
+}
+
+#[test]
+fn test_number_to_int32() {
+    assert_eq!(number_to_int32(f64::NAN), 0);
+    assert_eq!(number_to_int32(f64::INFINITY), 0);
+    assert_eq!(number_to_int32(-1.9), -1);
+    assert_eq!(number_to_int32(2147483648.0), -2147483648);
+    assert_eq!(number_to_int32(4294967297.0), 1);
+    assert_eq!(number_to_int32(-4294967297.0), -1);
+}
+
 #[test]
 fn test_boolify() {
     let dummy = Heap::new();
@@ -4,7 +4,11 @@ mod parse {
 use serde_json::json;
 
 use slothjs::{
+    Abort,
+    Backend,
     Exception,
+    JSValue,
+    LogLevel,
     Program,
     runtime::{self, Runtime, EvalError},
     ast::{expr, stmt},
@@ -12,6 +16,9 @@ use slothjs::{
 
 type Parser = runtime::NodejsParser;
 
+/// [`assert_eval!`] and [`assert_exception!`] check that both backends agree.
+const BACKENDS: [Backend; 2] = [Backend::TreeWalker, Backend::Bytecode];
+
 fn evalbool(input: &str) -> bool {
     let parser = Parser::new();
     let mut js = Runtime::load(Box::new(parser)).unwrap();
@@ -37,14 +44,17 @@ macro_rules! assert_parse {
 /// understands).
 macro_rules! assert_eval {
     ($js:literal, $json:tt) => {
-        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
-        let expected = json!($json);
-        match js.evaluate($js) {
-            Ok(result) => {
-                assert_eq!( js.json_from(result), expected )
-            }
-            Err(exc) => {
-                panic!("\n     want value: {}\n  got exception: {:?}", expected, exc)
+        for backend in BACKENDS {
+            let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+            js.set_backend(backend);
+            let expected = json!($json);
+            match js.evaluate($js) {
+                Ok(result) => {
+                    assert_eq!( js.json_from(result), expected, "{:?}", backend )
+                }
+                Err(exc) => {
+                    panic!("\n     want value: {}\n  got exception: {:?}\n  backend: {:?}", expected, exc, backend)
+                }
             }
         }
     }
@@ -61,11 +71,14 @@ macro_rules! assert_eval {
 // ```
 macro_rules! assert_exception {
     ($js:literal, $exc:path) => {
-        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
-        match js.evaluate($js) {
-            Err(EvalError::Exception($exc(_))) => (),
-            other => {
-                panic!("\n   want {}\n   got: {:?}\n", stringify!($exc), other)
+        for backend in BACKENDS {
+            let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+            js.set_backend(backend);
+            match js.evaluate($js) {
+                Err(EvalError::Exception($exc(_))) => (),
+                other => {
+                    panic!("\n   want {}\n   got: {:?}\n   backend: {:?}\n", stringify!($exc), other, backend)
+                }
             }
         }
     };
@@ -126,6 +139,88 @@ fn test_binary_numeric_operations() {
 
     assert_eval!("12 / 3", 4.0);
     assert_eval!("14.5 % 3", 2.5);
+
+    // int32 results, overflow and -0
+    assert_eval!( "7 / 2",              3.5 );
+    assert_eval!( "-7 % 3",             (-1.0) );
+    assert_eval!( "7 % 0",              (f64::NAN) );
+    assert_eval!( "2147483647 + 1",     2147483648.0 );
+    assert_eval!( "-2147483648 - 1",    (-2147483649.0) );
+    assert_eval!( "65536 * 65536",      4294967296.0 );
+    assert_eval!( "1 / (0 * -5)",       (f64::NEG_INFINITY) );
+    assert_eval!( "1 / (-4 % 2)",       (f64::NEG_INFINITY) );
+    assert_eval!( "1 / (-2147483648 % -1)",  (f64::NEG_INFINITY) );
+    assert_eval!( "[1 << 33, -8 >> 1, -1 >>> 0, 6 & 3, 6 | 3, 6 ^ 3]",
+                  [2.0, -4.0, 4294967295.0, 2.0, 7.0, 5.0] );
+    assert_eval!( "[1 < 2, 2 <= 1, 1 == 1.0, 3 === 3, 2 !== 2]",
+                  [true, false, true, true, false] );
+
+    // StringToNumber
+    assert_eval!( "' 12 ' * 1",     12.0 );
+    assert_eval!( "'0x1F' - 0",     31.0 );
+    assert_eval!( "'' * 2",         0.0 );
+    assert_eval!( "'Infinity' * -1", (f64::NEG_INFINITY) );
+    assert_eval!( "'inf' * 1",      (f64::NAN) );
+    assert_eval!( "'1_000' * 1",    (f64::NAN) );
+
+    // ToPrimitive
+    assert_eval!( "({valueOf: function() { return 4; }}) * 3",  12.0 );
+    assert_eval!( "[5] * 2",        10.0 );
+    assert_eval!( "new Boolean(true) - 1",  0.0 );
+
+    // Number::toString
+    assert_eval!( "'' + 1e21",      "1e+21" );
+    assert_eval!( "'' + 1e20",      "100000000000000000000" );
+    assert_eval!( "'' + 0.000001",  "0.000001" );
+    assert_eval!( "'' + 1.5e-7",    "1.5e-7" );
+    assert_eval!( "'' + (0.1 + 0.2)",  "0.30000000000000004" );
+    assert_eval!( "'' + -0",        "0" );
+    assert_eval!( "String(-1/0)",   "-Infinity" );
+}
+
+#[test]
+fn test_binary_exponentiation() {
+    assert_eval!("[2 ** 10, 2 ** 3 ** 2, (2 ** 3) ** 2, (-2) ** 3, 4 ** 0.5, 2 ** -1]", [1024.0, 512.0, 64.0, -8.0, 2.0, 0.5]);
+    assert_eval!("var x = 3; x **= 2; var o = {y: 2}; o.y **= 3; [x, o.y]", [9.0, 8.0]);
+    assert_eval!("[1 ** NaN, 1 ** Infinity, NaN ** 0, '3' ** '2']", [(f64::NAN), (f64::NAN), 1.0, 9.0]);
+    assert_exception!("-2 ** 2", Exception::Syntax);
+}
+
+#[test]
+fn test_binary_plus() {
+    assert_eval!( "1 + 2",          3.0 );
+    assert_eval!( "'1' + 2",        "12" );
+    assert_eval!( "1 + '2'",        "12" );
+    assert_eval!( "true + 1",       2.0 );
+    assert_eval!( "null + 1",       1.0 );
+    assert_eval!( "undefined + 1",  (f64::NAN) );
+    assert_eval!( "[] + []",        "" );
+    assert_eval!( "[1, 2] + 3",     "1,23" );
+    assert_eval!( "({}) + 1",       "[object Object]1" );
+    assert_eval!( "({valueOf: function() { return 1; }}) + 1",  2.0 );
+    assert_eval!( "({toString: function() { return 'x'; }}) + 1",  "x1" );
+    assert_eval!(r#"
+        var o = {
+            valueOf: function() { return 42; },
+            toString: function() { return 'str'; }
+        };
+        [o + '', String(o), '' + [o]]
+    "#, ["42", "str", "str"]);
+    assert_eval!(r#"
+        var s = '', t = '';
+        for (var i = 0; i < 2000; i++) { s += 'chunk' + (i % 10); t = t + 'chunk' + (i % 10); }
+        var m = new Map(); m.set(s, 'found');
+        [s.length, s[6 * 1003 + 5], s === t, m.get(t), s.indexOf('chunk9')]
+    "#, [12000.0, "3", true, "found", 54.0]);
+    assert_exception!( "Object.create(null) + ''",  Exception::Type );
+    assert_exception!(
+        "({valueOf: function() { return {}; }, toString: function() { return {}; }}) + 1",
+        Exception::Type
+    );
+    assert_exception!(
+        "({valueOf: function() { throw 'valueOf'; }}) + 1",
+        Exception::UserThrown
+    );
 }
 
 #[test]
@@ -175,6 +270,18 @@ fn test_binary_loose_equality() {
 
     assert_eval!("Object(false) == false", true);
     assert_eval!("Object(undefined) == null", false);
+
+    assert_eval!("' 12 ' == 12", true);
+    assert_eval!("'0x10' == 16", true);
+    assert_eval!("'' == 0", true);
+    assert_eval!("'1' == true", true);
+    assert_eval!("'true' == true", false);
+    assert_eval!("[1] == '1'", true);
+    assert_eval!("[1, 2] == '1,2'", true);
+    assert_eval!("new Boolean(true) == new Boolean(true)", false);
+    assert_eval!("({valueOf: function() { return 7; }}) == 7", true);
+    assert_eval!("null == false", false);
+    assert_eval!("undefined == 0", false);
 }
 
 #[test]
@@ -200,7 +307,7 @@ fn test_binary_compare_less() {
     assert_eval!("'0' < '00'", true);
     assert_eval!("'a' < 'b'", true);
     assert_eval!("null < 1", true);
-    //assert_eval!("[1, 1] < [2]", true);
+    assert_eval!("[1, 1] < [2]", true);
 
     assert_eval!("'a' < 'a'", false);
     assert_eval!("'113' < 13", false);
@@ -215,6 +322,20 @@ fn test_binary_compare_less() {
     assert_eval!("2 <= 2", true);
     assert_eval!("2 <= 1", false);
     assert_eval!("undefined <= undefined", false);
+    assert_eval!("NaN <= 1", false);
+
+    assert_eval!("'10' < '9'", true);
+    assert_eval!("'10' < 9", false);
+    assert_eval!("' 1 ' < 2", true);
+    assert_eval!("'\u{ff61}' < '\u{1f600}'", false); // compared as UTF-16 code units
+    assert_eval!("({valueOf: function() { return 1; }}) < 2", true);
+    assert_eval!(r#"
+        var log = [];
+        var a = {valueOf: function() { log.push('a'); return 1; }};
+        var b = {valueOf: function() { log.push('b'); return 2; }};
+        a > b; a <= b;
+        log
+    "#, ["a", "b", "a", "b"]);
 }
 
 #[test]
@@ -290,6 +411,58 @@ fn test_binary_logical() {
     assert_eval!("'a' || 'b'",      "a");
     assert_eval!( "var a; (a = 0) || (a = 1)",  1.0);
     assert_eval!( "var a; (a = 1) || (a = 2)",  1.0);
+
+    assert_eval!("[null ?? 1, undefined ?? 2, 0 ?? 3, '' ?? 4, false ?? 5]", [1.0, 2.0, 0.0, "", false]);
+    assert_eval!( "var a; (a = 0) ?? (a = 1); a",  0.0);
+    assert_eval!( "(null || undefined) ?? (0 && 1)",  0.0);
+}
+
+#[test]
+fn test_logical_assignment() {
+    assert_eval!("var a = 0, b = 1, c = null; a ||= 2; b &&= 3; c ??= 4; [a, b, c]", [2.0, 3.0, 4.0]);
+    assert_eval!("var a = 1, b = 0, c = false; a ||= 2; b &&= 3; c ??= 4; [a, b, c]", [1.0, 0.0, false]);
+    assert_eval!("var o = {x: null}; [o.x ??= 'x', o.y ||= 'y', o['z'] &&= 'z', o]",
+        ["x", "y", null, {"x": "x", "y": "y"}]);
+    // the target is evaluated once, the value only if assigned
+    assert_eval!(r#"
+        var n = 0, o = {a: 1};
+        function obj() { n++; return o; }
+        function key() { n += 10; return 'a'; }
+        obj()[key()] ||= (n += 100);
+        obj().a &&= 2;
+        [n, o.a]
+    "#, [12.0, 2.0]);
+    assert_exception!("undeclared ??= 1", Exception::Reference);
+}
+
+#[test]
+fn test_optional_chaining() {
+    assert_eval!("var o = {a: {b: 1}}; [o?.a.b, o.x?.b, o.a?.['b'], o.x?.['b']]", [1.0, null, 1.0, null]);
+    assert_eval!("var o = null; o?.a.b.c", null);
+    assert_eval!("var u; [u?.a, u?.[0], u?.()]", [null, null, null]);
+    assert_eval!(r#"
+        var o = {n: 2, get: function(k) { return this.n + k; }};
+        [o.get?.(1), o?.get(2), o['get']?.(3), o.missing?.(4)]
+    "#, [3.0, 4.0, 5.0, null]);
+
+    // short-circuits the whole chain without evaluating the rest
+    assert_eval!(r#"
+        var n = 0, o = {};
+        function f() { n++; return 'a'; }
+        [o.x?.[f()], o.x?.y.z(f()), o.x?.(f()).y, n]
+    "#, [null, null, null, 0.0]);
+    // parentheses end a chain
+    assert_exception!("var o = {}; (o.x?.y).z", Exception::Reference);
+    assert_exception!("var o = {f: 1}; o.f?.()", Exception::Type);
+    assert_exception!("var o = {}; o.x.y?.z", Exception::Reference);
+
+    // a call of a parenthesized chain keeps its `this`, the getter runs once
+    assert_eval!(r#"
+        var o = {x: 7, f: function() { return this.x; }, a: {x: 8, f: function() { return this.x; }}};
+        [(o?.f)(), (o?.a.f)(), (o?.['f'])()]
+    "#, [7.0, 8.0, 7.0]);
+    assert_eval!("var n = 0, o = {get f() { n++; return function() { return this === o; }; }}; [(o?.f)(), n]", [true, 1.0]);
+    assert_exception!("var o = null; (o?.f)()", Exception::Type);
 }
 
 #[test]
@@ -321,7 +494,7 @@ fn test_assignment() {
     assert_eval!("var a = 6; a &= 9; a",    0.0);
     assert_eval!("var a = 6; a ^= 9; a",    15.0);
     assert_eval!("var a = 3; a |= 6; a",    7.0);
-    //assert_eval!("var a = 3; a **= a; a",   27.0);
+    assert_eval!("var a = 3; a **= a; a",   27.0);
 
     // Assignment of read-only variables:
     assert_eval!( "var NaN = 5; NaN != NaN", true );
@@ -344,7 +517,7 @@ fn test_assignment() {
     assert_eval!("let a = 6; a &= 9; a",    0.0);
     assert_eval!("let a = 6; a ^= 9; a",    15.0);
     assert_eval!("let a = 3; a |= 6; a",    7.0);
-    //assert_eval!("let a = 3; a **= a; a",   27.0);
+    assert_eval!("let a = 3; a **= a; a",   27.0);
 
     // const-bindings
     /*
@@ -426,7 +599,40 @@ fn test_function_scope() {
         adder(3)(4)
     "#, Exception::Reference);
 
+    // closures share the variables they capture
+    assert_eval!(r#"
+        function counter() { var n = 0; return [function() { return ++n }, function() { return n }]; }
+        var c = counter(); c[0](); c[0]();
+        c[1]()
+    "#, 2.0);
+    assert_eval!(r#"
+        var f = function(a) { var g = function() { return a }; a = 'later'; return g };
+        f('now')()
+    "#, "later");
+
+    // hoisted functions see the `let` bindings of the same body
+    assert_eval!("(function() { let x = 1; function g() { return x }; return g() })()", 1.0);
+    assert_eval!("let y = 2; function h() { return y }; h()", 2.0);
+
     // eval
+    assert_eval!("(function(a) { var b = 5; return eval('a + b') })(10)", 15.0);
+    assert_eval!("(function(a) { eval('a = 2'); return a })(1)", 2.0);
+    // eval in a closure sees the variables of the enclosing functions, even if unused otherwise
+    assert_eval!("(function(){ var x = 1; return function(){ return eval('x') } })()()", 1.0);
+    assert_eval!("(function(){ var x = 1; return function(){ eval('x = 2'); return x } })()()", 2.0);
+    assert_eval!(r#"
+        (function() {
+            var x = 'outer', y = 'y';
+            return function() {
+                var x = 'inner';
+                return function() { { let y = 'block'; return eval('x + y') } };
+            };
+        })()()()
+    "#, "innerblock");
+    assert_eval!(r#"
+        var f = (function(a) { var g = function() { return eval('a') }; a = 'later'; return g })('now');
+        f()
+    "#, "later");
     // TODO: eval can change function scope
 }
 
@@ -475,13 +681,24 @@ fn test_block_scope() {
     // TODO: eval introduces its own block scope
 
     // Closures capture let-bindings:
-    /*
     assert_eval!(r#"
         let a=0; function inca() { a += 1 };
         function callinc(f) { f(); }; callinc(inca);
         a
     "#, 1.0);
-    */
+    assert_eval!(r#"
+        // every iteration gets its own binding
+        var fs = [];
+        for (var i = 0; i < 3; i++) { let j = i; fs.push(function() { return j }); }
+        [fs[0](), fs[1](), fs[2]()]
+    "#, [0.0, 1.0, 2.0]);
+    assert_eval!(r#"
+        var fs = [];
+        for (var i = 0; i < 2; i++) {
+            try { throw i } catch (e) { fs.push(function() { return e }) }
+        }
+        [fs[0](), fs[1]()]
+    "#, [0.0, 1.0]);
     assert_eval!(r#"
         function f() {     // captures variables of the same name from different blocks
             let a = 0; var c0 = {add: function(b) { a += b }, get: function() { return a }};
@@ -834,6 +1051,10 @@ fn test_update_operations() {
     assert_eval!("var a = 1; a--", 1.0);
     assert_eval!("var a = 1; a--; a", 0.0);
 
+    assert_eval!("var a = 2147483647; ++a", 2147483648.0);
+    assert_eval!("var a = -2147483648; a--; a", (-2147483649.0));
+    assert_eval!("var a = 1.5; a++; a", 2.5);
+
     assert_eval!("var a = false; ++a",  1.0);
     assert_eval!("var a = false; ++a; a", 1.0);
     assert_eval!("var a = [2]; ++a",    3.0);
@@ -922,8 +1143,17 @@ fn test_global_methods() {
     assert_eval!( "parseInt('22', 1)",  (f64::NAN) );
     assert_eval!( "parseInt('20', 8)",  16.0 );
     assert_eval!( "parseInt('020', 10)", 20.0 );
-    assert_eval!( "parseInt('020')",     16.0 );
-    //assert_eval!( "parseInt('0x10')",   16.0 );
+    assert_eval!( "parseInt('020')",     20.0 );
+    assert_eval!( "parseInt('0x10')",   16.0 );
+    assert_eval!( "parseInt('0x10', 16)",  16.0 );
+    assert_eval!( "parseInt('0x10', 10)",  0.0 );
+    assert_eval!( "parseInt('  12px')",  12.0 );
+    assert_eval!( "parseInt('-ff', 16)", (-255.0) );
+    assert_eval!( "parseInt('z', 36)",   35.0 );
+    assert_eval!( "parseInt(15.99)",     15.0 );
+    assert_eval!( "parseInt(1e21)",      1.0 );
+    assert_eval!( "parseInt('')",        (f64::NAN) );
+    assert_eval!( "parseInt('-')",       (f64::NAN) );
     //assert_eval!( "parseInt(new Number(64))", 64.0 );
     assert_eval!( "parseInt(new String(64))", 64.0 );
     assert_eval!( "parseInt(true)", (f64::NAN));
@@ -934,9 +1164,58 @@ fn test_global_methods() {
     assert_eval!("parseFloat('0')", 0.0);
     assert_eval!("parseFloat('-0')", (-0.0));
     assert_eval!("parseFloat('.1e1')", (1.0));
-    //assert_eval!("parseFloat('1.23abc')", 1.23);
+    assert_eval!("parseFloat('1.23abc')", 1.23);
+    assert_eval!("parseFloat('  3.5e2e1')", 350.0);
+    assert_eval!("parseFloat('1e')", 1.0);
+    assert_eval!("parseFloat('-.5')", (-0.5));
+    assert_eval!("parseFloat('-Infinityx')", (f64::NEG_INFINITY));
+    assert_eval!("parseFloat('0x10')", 0.0);
     assert_eval!("parseFloat()", (f64::NAN));
     assert_eval!("parseFloat('whut')", (f64::NAN));
+    assert_eval!("parseFloat('.')", (f64::NAN));
+
+    // isNaN(), isFinite()
+    assert_eval!("isNaN(NaN)", true);
+    assert_eval!("isNaN('abc')", true);
+    assert_eval!("isNaN(undefined)", true);
+    assert_eval!("isNaN(' 12 ')", false);
+    assert_eval!("isNaN(null)", false);
+    assert_eval!("isFinite(12)", true);
+    assert_eval!("isFinite('12')", true);
+    assert_eval!("isFinite(Infinity)", false);
+    assert_eval!("isFinite(NaN)", false);
+
+    // Infinity, globalThis
+    assert_eval!("Infinity", (f64::INFINITY));
+    assert_eval!("-Infinity < -1e308", true);
+    assert_eval!("Infinity = 1; Infinity", (f64::INFINITY));
+    assert_eval!("globalThis === global", true);
+    assert_eval!("var x = 5; globalThis.x", 5.0);
+
+    // URI encoding
+    assert_eval!("encodeURIComponent('a b&c=d/é')", "a%20b%26c%3Dd%2F%C3%A9");
+    assert_eval!("encodeURI('http://x.y/a b?q=1&r=é#h')", "http://x.y/a%20b?q=1&r=%C3%A9#h");
+    assert_eval!("encodeURIComponent('\u{1F600}')", "%F0%9F%98%80");
+    assert_eval!("encodeURIComponent(\"-_.!~*'()\")", "-_.!~*'()");
+    assert_eval!("decodeURIComponent('a%20b%26c%3Dd%2F%C3%A9')", "a b&c=d/é");
+    assert_eval!("decodeURI('a%20b%26c%3Dd%2F%C3%A9')", "a b%26c%3Dd%2Fé");
+    assert_eval!("decodeURIComponent('%F0%9F%98%80')", "\u{1F600}");
+    assert_eval!("decodeURIComponent(encodeURIComponent('✓ ok'))", "✓ ok");
+    assert_eval!(r#"
+        var bad = ['%', '%2', '%zz', '%C3', '%C3%28', '%C0%AF', '%ED%A0%80', '%FF'];
+        var names = [];
+        for (var i = 0; i < bad.length; i++) {
+            try { decodeURIComponent(bad[i]); } catch (e) { names.push(e.name); }
+        }
+        names
+    "#, ["URIError", "URIError", "URIError", "URIError", "URIError", "URIError", "URIError", "URIError"]);
+    assert_eval!("try { decodeURI('%') } catch (e) { e instanceof Error }", true);
+
+    // escape(), unescape()
+    assert_eval!("escape('a b+c/@é\u{0100}')", "a%20b+c/@%E9%u0100");
+    assert_eval!("unescape('a%20b+c/@%E9%u0100')", "a b+c/@é\u{0100}");
+    assert_eval!("unescape('%u00zz%4')", "%u00zz%4");
+    assert_eval!("unescape(escape('\u{1F600}'))", "\u{1F600}");
 }
 
 #[test]
@@ -1001,7 +1280,6 @@ fn test_builtin_object() {
         var d = Object.getOwnPropertyDescriptor(obj, 'prop');
         d.enumerable && !d.writable && !d.configurable
     "#, true);
-    /*
     assert_eval!(r#"
         var obj = { val: 42 };
         Object.defineProperty(obj, 'prop', {
@@ -1021,7 +1299,6 @@ fn test_builtin_object() {
         obj.prop = true;
         obj.val
     "#, true);
-    */
     assert_exception!(r#"
         var obj = { val: 42 };
         Object.defineProperty(obj, 'prop', {
@@ -1128,16 +1405,116 @@ fn test_builtin_function() {
         "Object.getOwnPropertyDescriptor(global, 'Function')",
         {"writable": true, "enumerable": false, "configurable": true,  "value": {}}
     );
-    //assert_eval!("var sqr = Function('x', 'return x * x'); sqr(12)",  144.0);
+    assert_eval!("var sqr = Function('x', 'return x * x'); sqr(12)",  144.0);
+    assert_eval!("new Function('a', 'b', 'return a + b')(1, 2)",  3.0);
+    assert_eval!("new Function('a, b', 'return a + b').length",  2.0);
+    assert_eval!("Function('return 42')()",  42.0);
+    assert_eval!("Function().name",  "anonymous");
+    assert_eval!("var x = 'global'; (function() { var x = 'local'; return Function('return x')(); })()",  "global");
+
+    // the source cannot close the function early, nor does it go through `eval`
+    assert_exception!("var pwned; new Function('}); pwned = 1; (function(){')", Exception::Syntax);
+    assert_exception!("Function('a) { pwned = 1; }); (function(b', 'return 1')", Exception::Syntax);
+    assert_exception!("Function('/*', '*/){')", Exception::Syntax);
+    assert_eval!("var pwned = 0; try { Function('}, pwned = 1, function() {') } catch (e) {} pwned", 0.0);
+    assert_eval!("eval = function() { return function() { return 'hijacked' } }; Function('return 1')()", 1.0);
+
+    // a parser without source code support throws instead of aborting
+    let mut js = Runtime::load(Box::new(runtime::JSONParser)).expect("Runtime::load");
+    let call = r#"{"type": "Program", "body": [{"type": "ExpressionStatement", "expression": {
+        "type": "CallExpression", "callee": {"type": "Identifier", "name": "Function"},
+        "arguments": [{"type": "Literal", "value": "return 1"}]}}]}"#;
+    match js.evaluate(call) {
+        Err(EvalError::Exception(Exception::Syntax(_))) => (),
+        other => panic!("want a SyntaxError, got {:?}", other),
+    }
 
     // Function.length
     assert_eval!("var sqr = function(x) { return x*x; }; sqr.length",  1.0);
+    assert_eval!(
+        "Object.getOwnPropertyDescriptor(function(a, b) {}, 'length')",
+        {"writable": false, "enumerable": false, "configurable": true, "value": 2.0}
+    );
+    assert_eval!("parseInt.length",  0.0);
+
+    // Function.name
+    assert_eval!("(function foo(a) {}).name",  "foo");
+    assert_eval!("(function(a) {}).name",  "");
+    assert_eval!("function bar() {}; bar.name",  "bar");
+    assert_eval!("parseInt.name",  "parseInt");
+    assert_eval!("[].push.name",  "push");
+    assert_eval!("Object.name",  "Object");
+    assert_eval!(
+        "Object.getOwnPropertyDescriptor(function f() {}, 'name')",
+        {"writable": false, "enumerable": false, "configurable": true, "value": "f"}
+    );
 
     // Function.prototype.call()
     assert_eval!(r#"
         var a = [function() { return 8; }, function() { return 12; }];
         a[1].call()
     "#, 12.0);
+
+    // Function.prototype.apply()
+    assert_eval!("(function(a, b) { return a + b; }).apply(null, [1, 2])",  3.0);
+    assert_eval!("(function(a, b) { return a + b; }).apply(null, {length: 2, 0: 'a', 1: 'b'})",  "ab");
+    assert_eval!("(function() { return arguments.length; }).apply(null)",  0.0);
+    assert_eval!("(function() { return this.x; }).apply({x: 5}, [])",  5.0);
+    assert_exception!("(function() {}).apply(null, 1)",  Exception::Type);
+    assert_eval!("(function() { return typeof this; }).apply(5, [])",  "object");
+    assert_eval!("(function() { return this.valueOf(); }).call(7)",  7.0);
+
+    // Function.prototype.bind()
+    assert_eval!("(function(a, b) { return a + b; }).bind(null, 1)(2)",  3.0);
+    assert_eval!("(function() { return this.x; }).bind({x: 'bound'})()",  "bound");
+    assert_eval!("var o = {x: 1}; var f = (function() { return this.x; }).bind({x: 2}); o.f = f; o.f()",  2.0);
+    assert_eval!("(function(a, b, c) { return a + b + c; }).bind(null, 1).bind(null, 2)(3)",  6.0);
+    assert_eval!("typeof (function() {}).bind(null)",  "function");
+    assert_eval!("(function foo(a, b, c) {}).bind(null, 1).name",  "bound foo");
+    assert_eval!("(function foo(a, b, c) {}).bind(null, 1).length",  2.0);
+    assert_eval!("(function foo(a) {}).bind(null, 1, 2).length",  0.0);
+    assert_eval!(r#"
+        function Point(x, y) { this.x = x; this.y = y; }
+        var XPoint = Point.bind({x: 'ignored'}, 10);
+        var p = new XPoint(20);
+        [p.x, p.y, p instanceof Point, p instanceof XPoint]
+    "#, [10.0, 20.0, true, true]);
+    assert_exception!("Function.prototype.bind.call({})",  Exception::Type);
+    assert_eval!("(function() { return this + 1; }).bind(5)()",  6.0);
+
+    // Function.prototype.toString()
+    assert_eval!("(function f(a, b) { return a + b; }).toString()",
+        "function f(a, b) {\n    return a + b;\n}");
+    assert_eval!("String(function() {})",  "function () {\n}");
+    assert_eval!("String(function() { return {x, y: x ** 2, get z() { return 1; }}; })",
+        "function () {\n    return {\n        x,\n        y: x ** 2,\n        get z() {\n            return 1;\n        },\n    };\n}");
+    assert_eval!("parseInt.toString()",  "function parseInt() { [native code] }");
+    assert_eval!("(function() {}).bind(null).toString()",  "function () { [native code] }");
+    assert_eval!("var f = Function('a', 'return a'); f.toString()",
+        "function anonymous(a) {\n    return a;\n}");
+    assert_exception!("Function.prototype.toString.call({})",  Exception::Type);
+}
+
+#[test]
+fn test_builtin_number() {
+    // auto-objectification:
+    assert_eval!("(5).toString()",  "5");
+    assert_eval!("var a = {b: 1.5}; a.b.toString()",  "1.5");
+
+    // Number()
+    assert_eval!("Number()",         0.0);
+    assert_eval!("Number('42')",     42.0);
+    assert_eval!("Number(10n)",      10.0);
+    assert_eval!("typeof new Number(1)",  "object");
+    assert_eval!("new Number(3) + 1",     4.0);
+    assert_eval!("Object(2).valueOf()",   2.0);
+
+    // Number.prototype.toString()
+    assert_eval!("(255).toString(16)",  "ff");
+    assert_eval!("(-0.5).toString(2)",  "-0.1");
+    assert_eval!("(1e21).toString()",   "1e+21");
+    assert_exception!("(1).toString(1)",  Exception::Range);
+    assert_exception!("Number.prototype.toString.call('1')",  Exception::Type);
 }
 
 #[test]
@@ -1314,6 +1691,418 @@ fn test_builtin_error() {
     assert_eval!("new Error('just testing').toString()", "Error: just testing");
 }
 
+#[test]
+fn test_builtin_map() {
+    assert_eval!("new Map().size", 0.0);
+    assert_eval!("var m = new Map(); m.set('a', 1).set('b', 2); m.size", 2.0);
+    assert_eval!("var m = new Map([['a', 1], ['b', 2]]); m.get('b')", 2.0);
+    assert_eval!("var m = new Map([['a', 1]]); m.get('c')", null);
+    assert_eval!("var m = new Map([['a', 1]]); m.set('a', 2); [m.size, m.get('a')]", [1.0, 2.0]);
+    assert_exception!("Map()", Exception::Type);
+    assert_exception!("Map.prototype.get.call(new Set(), 1)", Exception::Type);
+
+    // size is a getter on the prototype
+    assert_eval!("var d = Object.getOwnPropertyDescriptor(Map.prototype, 'size'); [typeof d.get, d.set, d.enumerable]", ["function", null, false]);
+    assert_eval!("var m = new Map([['a', 1]]); m.size = 5; [m.size, m.hasOwnProperty('size')]", [1.0, false]);
+    assert_exception!("'use strict'; new Map().size = 5", Exception::Type);
+    assert_exception!("Map.prototype.size", Exception::Type);
+
+    // SameValueZero
+    assert_eval!("var m = new Map([[NaN, 'nan']]); m.get(NaN)", "nan");
+    assert_eval!("var m = new Map([[0, 'zero']]); m.get(-0)", "zero");
+    assert_eval!("var m = new Map([[1, 'number']]); m.has('1')", false);
+    assert_eval!("var k = {}; var m = new Map([[k, 'obj']]); [m.get(k), m.has({})]", ["obj", false]);
+
+    // Map.prototype.delete(), .clear()
+    assert_eval!("var m = new Map([['a', 1]]); [m.delete('a'), m.delete('a'), m.size]", [true, false, 0.0]);
+    assert_eval!("var m = new Map([['a', 1], ['b', 2]]); m.clear(); [m.size, m.has('a')]", [0.0, false]);
+
+    // insertion order
+    assert_eval!(r#"
+        var m = new Map([['z', 1], ['a', 2]]);
+        m.set('m', 3); m.delete('z'); m.set('z', 4);
+        var keys = [];
+        m.forEach(function(value, key, map) { keys.push(key + value + (map === m)); });
+        keys
+    "#, ["a2true", "m3true", "z4true"]);
+    assert_eval!("var s = ''; new Map([[1, 1]]).forEach(function() { s += typeof this + this; }, 7); s", "object7");
+
+    // iterators
+    assert_eval!(r#"
+        var it = new Map([['a', 1], ['b', 2]]).entries();
+        var first = it.next();
+        [first.value, first.done, it.next().value[0], it.next().done]
+    "#, [["a", 1.0], false, "b", true]);
+    assert_eval!("var it = new Map([['a', 1]]).keys(); it.next().value", "a");
+    assert_eval!("var it = new Map([['a', 1]]).values(); it.next().value", 1.0);
+    assert_eval!(r#"
+        var m = new Map([['a', 1]]);
+        var it = m.keys();
+        m.set('b', 2);
+        [it.next().value, it.next().value, it.next().done]
+    "#, ["a", "b", true]);
+    assert_eval!("var m = new Map(new Map([['a', 1]]).entries()); m.get('a')", 1.0);
+
+    // deleted entries are compacted away under live iterators
+    assert_eval!(r#"
+        var m = new Map();
+        for (var i = 0; i < 100; i++) m.set(i, i);
+        var it = m.keys(); it.next();
+        for (var i = 0; i < 96; i++) m.delete(i);
+        m.set('x', 1);
+        var rest = '';
+        for (var r = it.next(); !r.done; r = it.next()) rest += r.value;
+        rest
+    "#, "96979899x");
+    assert_eval!(r#"
+        var m = new Map();
+        for (var i = 0; i < 100; i++) m.set(i, i);
+        var seen = 0;
+        m.forEach(function(value, key) { seen++; m.delete(key); });
+        [seen, m.size]
+    "#, [100.0, 0.0]);
+    assert_eval!(r#"
+        var m = new Map([['a', 1], ['b', 2]]);
+        var it = m.keys(); it.next();
+        m.clear(); m.set('c', 3);
+        [it.next().value, m.get('c')]
+    "#, ["c", 3.0]);
+}
+
+#[test]
+fn test_builtin_set() {
+    assert_eval!("new Set().size", 0.0);
+    assert_eval!("new Set([1, 2, 1, '1']).size", 3.0);
+    assert_eval!("new Set('hello').size", 4.0);
+    assert_eval!("var s = new Set(); s.add(NaN).add(NaN).add(0).add(-0); s.size", 2.0);
+    assert_eval!("var s = new Set([1]); [s.has(1), s.has(2)]", [true, false]);
+    assert_eval!("var s = new Set([1]); [s.delete(1), s.delete(1), s.size]", [true, false, 0.0]);
+    assert_eval!("var s = new Set([1, 2]); s.clear(); s.size", 0.0);
+    assert_exception!("Set()", Exception::Type);
+    assert_exception!("Set.prototype.has.call(new Map(), 1)", Exception::Type);
+    assert_eval!("typeof Object.getOwnPropertyDescriptor(Set.prototype, 'size').get", "function");
+    assert_exception!("Object.getOwnPropertyDescriptor(Set.prototype, 'size').get.call(new Map())", Exception::Type);
+
+    assert_eval!(r#"
+        var s = new Set(['b', 'a']);
+        var seen = [];
+        s.forEach(function(value, again) {
+            if (value === 'b') s.add('c');
+            seen.push(value + again);
+        });
+        seen
+    "#, ["bb", "aa", "cc"]);
+    assert_eval!("var s = 0; new Set([1]).forEach(function(v) { s = this + v; }, 7); s", 8.0);
+    assert_eval!("var it = new Set(['a']).entries(); it.next().value", ["a", "a"]);
+    assert_eval!("var it = new Set(['a', 'b']).values(); it.next(); it.next().value", "b");
+    assert_eval!("var s = new Set(new Set([1, 2]).keys()); s.size", 2.0);
+    assert_eval!("var m = new Map([['a', 1]]); new Set(m).size", 1.0);
+}
+
+#[test]
+fn test_proxy_and_reflect() {
+    // without traps a proxy forwards everything to its target
+    assert_eval!("var t = {a: 1}; var p = new Proxy(t, {}); p.b = 2; [p.a, t.b, 'b' in p]", [1.0, 2.0, true]);
+    assert_eval!("var p = new Proxy({a: 1}, {}); delete p.a; p.a", null);
+    assert_exception!("Proxy({}, {})", Exception::Type);
+    assert_exception!("new Proxy(1, {})", Exception::Type);
+    assert_exception!("new Proxy({}, null)", Exception::Type);
+    assert_exception!("Proxy.revocable({}, 'handler')", Exception::Type);
+
+    // get, set, has, deleteProperty
+    assert_eval!(r#"
+        var p = new Proxy({a: 1}, {
+            get: function(t, k, r) { return k in t ? t[k] : k + '?'; }
+        });
+        [p.a, p.b, p['c']]
+    "#, [1.0, "b?", "c?"]);
+    assert_eval!(r#"
+        var t = {};
+        var p = new Proxy(t, {
+            set: function(t, k, v) { t[k] = v * 2; return true; }
+        });
+        p.x = 1; p['y'] = 2;
+        [t.x, t.y]
+    "#, [2.0, 4.0]);
+    assert_eval!(r#"
+        var p = new Proxy({}, { set: function() { return false; } });
+        p.x = 1;
+        Reflect.set(p, 'x', 1)
+    "#, false);
+    assert_eval!(r#"
+        var p = new Proxy({secret: 1, open: 2}, {
+            has: function(t, k) { return k !== 'secret' && k in t; }
+        });
+        ['secret' in p, 'open' in p]
+    "#, [false, true]);
+    assert_eval!(r#"
+        var log = [];
+        var p = new Proxy({a: 1}, {
+            deleteProperty: function(t, k) { log.push(k); return delete t[k]; }
+        });
+        delete p.a;
+        [log[0], Reflect.has(p, 'a')]
+    "#, ["a", false]);
+
+    // ownKeys, getOwnPropertyDescriptor, defineProperty
+    assert_eval!(r#"
+        var p = new Proxy({a: 1, b: 2}, {
+            ownKeys: function(t) { return ['b', 'a', 'c']; }
+        });
+        Reflect.ownKeys(p)
+    "#, ["b", "a", "c"]);
+    assert_eval!(r#"
+        var p = new Proxy({}, {
+            ownKeys: function() { return ['x', 'y']; },
+            getOwnPropertyDescriptor: function(t, k) {
+                return {value: k, enumerable: k === 'x', configurable: true};
+            }
+        });
+        var keys = [];
+        for (var k in p) keys.push(k);
+        [keys, Object.keys(p), Object.getOwnPropertyDescriptor(p, 'y').value]
+    "#, [["x"], ["x"], "y"]);
+    assert_eval!(r#"
+        var log = [];
+        var p = new Proxy({}, {
+            defineProperty: function(t, k, d) { log.push(k); return Reflect.defineProperty(t, k, d); }
+        });
+        Object.defineProperty(p, 'v', {value: 1});
+        [log[0], p.v]
+    "#, ["v", 1.0]);
+    assert_exception!(
+        "Object.defineProperty(new Proxy({}, {defineProperty: function() { return false; }}), 'v', {})",
+        Exception::Type
+    );
+
+    // apply, construct
+    assert_eval!(r#"
+        var p = new Proxy(function(a, b) { return a + b; }, {
+            apply: function(f, self, args) { return f(args[0], args[1]) * 10; }
+        });
+        [p(1, 2), p.call(null, 2, 3), typeof p]
+    "#, [30.0, 50.0, "function"]);
+    assert_eval!(r#"
+        function Point(x) { this.x = x; }
+        var P = new Proxy(Point, {
+            construct: function(t, args, newTarget) { return {x: args[0] + 1, same: newTarget === P}; }
+        });
+        var pt = new P(1);
+        [pt.x, pt.same, new (new Proxy(Point, {}))(5) instanceof Point]
+    "#, [2.0, true, true]);
+    assert_exception!("new Proxy({}, {})()", Exception::Type);
+    assert_exception!(
+        "var p = new Proxy(function() {}, {construct: function() { return 1; }}); new p()",
+        Exception::Type
+    );
+
+    // getPrototypeOf, setPrototypeOf, isExtensible, preventExtensions
+    assert_eval!(r#"
+        var proto = {inherited: 1};
+        var p = new Proxy({}, { getPrototypeOf: function() { return proto; } });
+        [Object.getPrototypeOf(p) === proto, Reflect.getPrototypeOf(p) === proto]
+    "#, [true, true]);
+    assert_eval!(r#"
+        var p = new Proxy({}, { setPrototypeOf: function() { return false; } });
+        Reflect.setPrototypeOf(p, {})
+    "#, false);
+    assert_eval!(r#"
+        var log = [];
+        var p = new Proxy({}, {
+            isExtensible: function(t) { log.push('is'); return Reflect.isExtensible(t); },
+            preventExtensions: function(t) { log.push('prevent'); return Reflect.preventExtensions(t); }
+        });
+        Object.preventExtensions(p);
+        [Object.isExtensible(p), log]
+    "#, [false, ["prevent", "is"]]);
+
+    // invariants
+    assert_exception!(r#"
+        var t = Object.defineProperty({}, 'fixed', {value: 1});
+        new Proxy(t, { get: function() { return 2; } }).fixed
+    "#, Exception::Type);
+    assert_exception!(r#"
+        var p = new Proxy({}, { isExtensible: function() { return false; } });
+        Object.isExtensible(p)
+    "#, Exception::Type);
+
+    // Proxy.revocable()
+    assert_eval!("var r = Proxy.revocable({a: 1}, {}); r.proxy.a", 1.0);
+    assert_exception!("var r = Proxy.revocable({a: 1}, {}); r.revoke(); r.proxy.a", Exception::Type);
+    assert_exception!("var r = Proxy.revocable({}, {}); r.revoke(); r.proxy.a = 1", Exception::Type);
+
+    // Reflect on ordinary objects
+    assert_eval!("Reflect.get({a: 1}, 'a')", 1.0);
+    assert_eval!("var o = {}; [Reflect.set(o, 'a', 1), o.a]", [true, 1.0]);
+    assert_eval!("var o = Object.defineProperty({}, 'ro', {value: 1}); Reflect.set(o, 'ro', 2)", false);
+    assert_eval!("[Reflect.has({a: 1}, 'a'), Reflect.has({}, 'toString')]", [true, true]);
+    assert_eval!("var o = {a: 1}; [Reflect.deleteProperty(o, 'a'), 'a' in o]", [true, false]);
+    assert_eval!("Reflect.ownKeys([1, 2])", ["0", "1", "length"]);
+    assert_eval!("Reflect.getOwnPropertyDescriptor({a: 1}, 'a').writable", true);
+    assert_eval!("Reflect.apply(function(a) { return this.v + a; }, {v: 1}, [2])", 3.0);
+    assert_eval!("function C(a) { this.a = a; } Reflect.construct(C, [1]).a", 1.0);
+    assert_eval!("var o = {}; Reflect.preventExtensions(o); [Reflect.isExtensible(o), Reflect.set(o, 'a', 1)]", [false, false]);
+    assert_eval!("var a = [1]; Object.preventExtensions(a); a[0] = 2; a[1] = 3; a", [2.0]);
+    assert_eval!("var a = {}, b = Object.create(a); Reflect.setPrototypeOf(a, b)", false);
+}
+
+#[test]
+fn test_typed_arrays() {
+    assert_eval!("var b = new ArrayBuffer(4); [b.byteLength, b.slice(1, -1).byteLength]", [4.0, 2.0]);
+    assert_eval!("var a = new Uint8Array([1, 2, 300, -1]); [a.length, a[2], a[3], a[4]]", [4.0, 44.0, 255.0, null]);
+    assert_eval!("var a = new Int8Array(2); a[0] = 200; a[5] = 1; [a[0], a.length, 5 in a]", [-56.0, 2.0, false]);
+    assert_eval!("new Uint8ClampedArray([1.5, 2.5, -3, 300]).toString()", "2,2,0,255");
+    assert_eval!("new Float32Array([0.5, 1e40])[1] === 1/0", true);
+    assert_eval!("[Int16Array.BYTES_PER_ELEMENT, new Float64Array(1).BYTES_PER_ELEMENT]", [2.0, 8.0]);
+    assert_eval!("Object.keys(new Uint16Array(3))", ["0", "1", "2"]);
+    assert_eval!("new Uint8Array(new Set([5, 6])).toString()", "5,6");
+    assert_exception!("Uint8Array(1)", Exception::Type);
+
+    // views share the bytes of their buffer
+    assert_eval!(r#"
+        var b = new ArrayBuffer(8);
+        var bytes = new Uint8Array(b), words = new Uint32Array(b, 4);
+        words[0] = 0x01020304;
+        [bytes[4], bytes[7], words.byteOffset, words.buffer === b]
+    "#, [4.0, 1.0, 4.0, true]);
+    assert_eval!(r#"
+        var a = new Int32Array([1, 2, 3, 4]);
+        var sub = a.subarray(1, 3), copy = a.slice(-2);
+        sub[0] = 20; copy[0] = 30;
+        [a.toString(), sub.length, copy.toString()]
+    "#, ["1,20,3,4", 2.0, "30,4"]);
+    assert_eval!("var a = new Uint8Array(5); a.fill(9, 1, -1); a.set([1, 2], 3); a.toString()", "0,9,9,1,2");
+    assert_exception!("new Uint8Array(2).set([1, 2, 3])", Exception::Range);
+    assert_exception!("new Int32Array(new ArrayBuffer(8), 2)", Exception::Range);
+    assert_exception!("new Int32Array(new ArrayBuffer(8), 4, 2)", Exception::Range);
+    assert_exception!("new ArrayBuffer(-1)", Exception::Range);
+
+    // DataView is big-endian unless asked otherwise
+    assert_eval!(r#"
+        var v = new DataView(new ArrayBuffer(8), 2);
+        v.setUint16(0, 0x0102);
+        v.setFloat32(2, 1.5, true);
+        [v.getUint8(0), v.getUint16(0, true), v.getFloat32(2, true), v.byteLength]
+    "#, [1.0, 513.0, 1.5, 6.0]);
+    assert_eval!("var v = new DataView(new ArrayBuffer(2)); v.setInt8(1, -1); v.getInt16(0)", 255.0);
+    assert_exception!("new DataView(new ArrayBuffer(2)).getInt32(0)", Exception::Range);
+    assert_exception!("DataView.prototype.getInt8.call({}, 0)", Exception::Type);
+
+    // byteLength, byteOffset and buffer are getters on the prototypes
+    assert_eval!(r#"
+        var d = Object.getOwnPropertyDescriptor(Uint8Array.prototype, 'byteOffset');
+        var v = new DataView(new ArrayBuffer(4), 1);
+        v.byteLength = 0;
+        [typeof d.get, v.byteLength, v.byteOffset, v.hasOwnProperty('buffer')]
+    "#, ["function", 3.0, 1.0, false]);
+    assert_eval!("typeof Object.getOwnPropertyDescriptor(ArrayBuffer.prototype, 'byteLength').get", "function");
+    assert_exception!("'use strict'; new ArrayBuffer(1).byteLength = 2", Exception::Type);
+
+    // embedders move bytes in and out without copying element by element
+    for backend in BACKENDS {
+        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+        js.set_backend(backend);
+        let buffer = js.heap.alloc_array_buffer(vec![1, 2, 3, 4]);
+        js.heap.get_mut(slothjs::Heap::GLOBAL).set_property("input", buffer).unwrap();
+        let result = js.evaluate("var a = new Uint8Array(input, 1, 2); a[0] += 10; a").unwrap();
+        let view = result.as_objref().unwrap();
+        assert_eq!(js.heap.array_buffer(view).as_deref(), Some(&[12, 3][..]), "{:?}", backend);
+        assert_eq!(js.heap.array_buffer(buffer).as_deref(), Some(&[1, 12, 3, 4][..]));
+
+        let used = js.memory_stats().bytes();
+        js.set_memory_limit(Some(used + (1 << 20)));
+        let result = js.evaluate("new ArrayBuffer(1 << 24)");
+        assert!(matches!(result, Err(EvalError::Exception(Exception::Abort(Abort::OutOfMemory)))));
+    }
+}
+
+#[test]
+fn test_bigint() {
+    assert_eval!("123456789012345678901234567890n * 1000000000000000000000n", "123456789012345678901234567890000000000000000000000");
+    assert_eval!("[typeof 1n, 0x1fn + 0o7n + 0b1n, -7n / 2n, -7n % 2n, 1n - 2n]", ["bigint", "39", "-3", "-1", "-1"]);
+    assert_eval!("[1n << 64n, -(1n << 64n) >> 63n, 8n >> -1n, 6n & -3n, 6n | 1n, 6n ^ 3n, ~5n, -0n]",
+        ["18446744073709551616", "-2", "16", "4", "7", "5", "-6", "0"]);
+    assert_eval!("var i = 9007199254740993n; i++; --i; ++i", "9007199254740994");
+    assert_eval!("[1n == 1, 1n === 1, 2n == '2', 2n > 1.5, 2n < '10', 1n < NaN, 0n ? 1 : 2]",
+        [true, false, true, true, true, false, 2.0]);
+    assert_eval!("9007199254740993n > 9007199254740992", true);
+    assert_eval!("'' + 12n + (-3n).toString(2) + (255n).toString(16)", "12-11ff");
+    assert_exception!("1n + 1", Exception::Type);
+    assert_exception!("1n * '2'", Exception::Type);
+    assert_exception!("+1n", Exception::Type);
+    assert_exception!("1n >>> 0n", Exception::Type);
+    assert_exception!("1n / 0n", Exception::Range);
+    assert_exception!("1n << 10000000000n", Exception::Range);
+    assert_eval!("[2n ** 64n, (-3n) ** 3n, 0n ** 0n, (-1n) ** 1000001n]", ["18446744073709551616", "-27", "1", "-1"]);
+    assert_exception!("2n ** -1n", Exception::Range);
+    assert_exception!("2n ** 2", Exception::Type);
+    assert_exception!("2n ** 10000000000n", Exception::Range);
+
+    // conversions
+    assert_eval!("[BigInt(42), BigInt(' -16 '), BigInt('0x10'), BigInt(''), BigInt(true), BigInt(Object(3n))]",
+        ["42", "-16", "16", "0", "1", "3"]);
+    assert_eval!("BigInt(1e21)", "1000000000000000000000");
+    assert_eval!("[BigInt.asIntN(8, 255n), BigInt.asIntN(8, 128n), BigInt.asUintN(8, -1n), BigInt.asUintN(64, -1n)]",
+        ["-1", "-128", "255", "18446744073709551615"]);
+    assert_eval!("[Object(1n) instanceof BigInt, (5n).valueOf() === 5n, new Set([1n, 1n]).size]", [true, true, 1.0]);
+    assert_exception!("new BigInt(1)", Exception::Type);
+    assert_exception!("BigInt(undefined)", Exception::Type);
+    assert_exception!("BigInt(1.5)", Exception::Range);
+    assert_exception!("BigInt('1.5')", Exception::Syntax);
+    assert_exception!("BigInt('-0x10')", Exception::Syntax);
+    assert_exception!("(1n).toString(37)", Exception::Range);
+}
+
+#[test]
+fn test_weak_references() {
+    assert_eval!("var k = {}; var m = new WeakMap([[k, 1]]); [m.get(k), m.has({}), m.delete(k), m.has(k), m.get(1)]",
+        [1.0, false, true, false, null]);
+    assert_eval!("var k = {}, s = new WeakSet(); s.add(k).add(k); [s.has(k), s.has(1), s.delete(k), s.has(k)]",
+        [true, false, true, false]);
+    assert_eval!("var o = {}; new WeakRef(o).deref() === o", true);
+    assert_exception!("new WeakMap().set(1, 1)", Exception::Type);
+    assert_exception!("new WeakSet().add(null)", Exception::Type);
+    assert_exception!("WeakMap()", Exception::Type);
+    assert_exception!("new WeakRef('target')", Exception::Type);
+    assert_exception!("new FinalizationRegistry(1)", Exception::Type);
+    assert_exception!("var o = {}; new FinalizationRegistry(function () {}).register(o, o)", Exception::Type);
+
+    // a collection clears what is only reachable weakly
+    for backend in BACKENDS {
+        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+        js.set_backend(backend);
+        let result = js.evaluate(r#"
+            var cleaned = [];
+            var registry = new FinalizationRegistry(function (held) { cleaned.push(held); });
+            var cache = new WeakMap(), seen = new WeakSet();
+            var kept = {}, dropped = {}, cyclic = {}, nested = {};
+            cache.set(kept, nested).set(nested, 'nested');
+            cache.set(dropped, {}).set(cyclic, {owner: cyclic});
+            seen.add(kept).add(dropped);
+            registry.register(dropped, 'dropped');
+            registry.register(cyclic, 'cyclic');
+            var token = {};
+            registry.register(kept, 'kept', token);
+            var ref = new WeakRef(dropped);
+            dropped = cyclic = nested = undefined;
+            ref.deref() !== undefined
+        "#).unwrap();
+        assert_eq!(result, JSValue::from(true), "{:?}", backend);
+
+        js.heap.collect();
+        let cache = js.evaluate("cache").unwrap().as_objref().unwrap();
+        match &js.heap.get(cache).value {
+            slothjs::object::ObjectValue::WeakMap(entries) => assert_eq!(entries.len(), 2),
+            other => panic!("not a WeakMap: {:?}", other),
+        }
+        let result = js.evaluate("[ref.deref(), cache.get(cache.get(kept)), seen.has(kept), registry.unregister(token)]").unwrap();
+        assert_eq!(js.json_from(result), json!([null, "nested", true, true]), "{:?}", backend);
+        // the callbacks have run after the last script
+        let result = js.evaluate("cleaned.toString()").unwrap();
+        assert_eq!(js.json_from(result), json!("dropped,cyclic"), "{:?}", backend);
+    }
+}
+
 #[test]
 fn test_objects() {
     assert_eval!( "var x = 'one'; var o = {[x]: 1}; o.one", 1.0);
@@ -1358,6 +2147,117 @@ fn test_objects() {
     "#, 4.0);
 }
 
+#[test]
+fn test_object_literals() {
+    assert_eval!("var x = 1, y = 'y'; ({x, y})", {"x": 1.0, "y": "y"});
+    assert_eval!("var o = {n: 2, twice(k) { return this.n * k; }}; o.twice(3)", 6.0);
+    assert_eval!("var k = 'a'; ({[k + 1]: 1, ['b']() { return 2; }}).b()", 2.0);
+
+    // getters and setters
+    assert_eval!(r#"
+        var o = {v: 1, get double() { return this.v * 2; }, set double(d) { this.v = d / 2; }};
+        var before = o.double;
+        o.double = 10;
+        [before, o.v, o.double]
+    "#, [2.0, 5.0, 10.0]);
+    assert_eval!("var o = {get x() { return 1; }}; o.x = 2; o.x", 1.0);
+    assert_eval!("var o = {get x() { return 1; }, x: 2}; o.x", 2.0);
+    assert_eval!("var o = {x: 2, get x() { return 1; }}; o.x", 1.0);
+    assert_eval!(r#"
+        var proto = {set v(value) { this.w = value + 1; }};
+        var o = Object.create(proto);
+        o.v = 1;
+        [o.w, o.hasOwnProperty('v'), 'v' in o]
+    "#, [2.0, false, true]);
+    assert_eval!(r#"
+        var d = Object.getOwnPropertyDescriptor({get g() { return 1; }}, 'g');
+        [typeof d.get, d.set, d.enumerable, d.configurable, 'value' in d, 'writable' in d]
+    "#, ["function", null, true, true, false, false]);
+    assert_eval!(r#"
+        var o = {n: 0};
+        Object.defineProperty(o, 'inc', {get: function() { return ++this.n; }});
+        o.inc; o.inc;
+        [o.n, Object.keys(o).length]
+    "#, [2.0, 1.0]);
+    assert_exception!("Object.defineProperty({}, 'x', {get: 1})", Exception::Type);
+    assert_exception!("Object.defineProperty({}, 'x', {get: function() {}, value: 1})", Exception::Type);
+}
+
+#[test]
+fn test_inline_caches() {
+    // polymorphic and megamorphic sites
+    assert_eval!(r#"
+        function get(o) { return o.v; }
+        var os = [{v: 1}, {a: 0, v: 2}, {b: 0, v: 3}, {c: 0, v: 4}, {d: 0, v: 5}, {v: 6}];
+        var sum = 0;
+        for (var i = 0; i < 12; i++) { sum += get(os[i % 6]); }
+        sum
+    "#, 42.0);
+
+    // a cached prototype method shadowed later
+    assert_eval!(r#"
+        function A() {}
+        A.prototype.f = function() { return 1; };
+        var a = new A(), r = 0;
+        for (var i = 0; i < 4; i++) {
+            r = r * 10 + a.f();
+            if (i == 1) a.f = function() { return 2; };
+        }
+        r
+    "#, 1122.0);
+    assert_eval!(r#"
+        function A() {}
+        A.prototype.f = function() { return 1; };
+        var a = new A(), r = 0;
+        for (var i = 0; i < 4; i++) {
+            r = r * 10 + a.f();
+            if (i == 1) A.prototype.f = function() { return 3; };
+        }
+        r
+    "#, 1133.0);
+
+    // deleting and re-adding properties
+    assert_eval!(r#"
+        function get(o) { return o.x; }
+        var o = {x: 1, y: 2}, r = [];
+        r.push(get(o));
+        delete o.x;
+        r.push(get(o));
+        o.x = 3;
+        r.push(get(o));
+        r
+    "#, [1.0, null, 3.0]);
+
+    // cached stores respect read-only properties
+    assert_eval!(r#"
+        var os = [{}, {}];
+        Object.defineProperty(os[1], 'x', {value: 1});
+        for (var i = 0; i < 4; i++) { os[i % 2].x = 2; }
+        os[0].x + os[1].x
+    "#, 3.0);
+    // objects built the same way share a shape
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    js.evaluate("function P(x, y) { this.x = x; this.y = y; }").unwrap();
+    let refs: Vec<_> = ["new P(1, 2)", "new P(3, 4)", "({x: 5, y: 6})"].iter()
+        .map(|code| js.evaluate(code).unwrap().to_ref().unwrap())
+        .collect();
+    let shape = |i: usize| js.heap.get(refs[i]).properties.shape().cloned().unwrap();
+    assert!(std::rc::Rc::ptr_eq(&shape(0), &shape(1)));
+    assert!(std::rc::Rc::ptr_eq(&shape(0), &shape(2)));
+    assert_eq!(shape(0).keys().map(|k| k.to_string()).collect::<Vec<_>>(), ["x", "y"]);
+    assert!(std::rc::Rc::ptr_eq(shape(0).atoms(), shape(2).parent().unwrap().atoms()));
+
+    // shapes that branch off a longer line of keys do not see its later keys
+    assert_eval!(r#"
+        var a = {}, b = {}, c = {};
+        for (var i = 0; i < 12; i++) { a['k' + i] = i; }
+        for (var i = 0; i < 10; i++) { b['k' + i] = i; c['k' + i] = i; }
+        b.z = 'z';
+        c.k11 = 'c';
+        [a.k11, b.k11, b.z, a.z, c.k10, c.k11, b.k9, Object.keys(b).length]
+    "#, [11.0, null, "z", null, null, "c", 9.0, 11.0]);
+}
+
 #[test]
 fn test_this() {
     assert_eval!("var f = function() { return this; }; f() == global", true );
@@ -1389,7 +2289,6 @@ fn test_this() {
         p.f()
     "#, 5.0 );
 
-    /*
     // bind
     assert_eval!(r#"
         var f = function(c, d) { return this.a + this.b + c + d }
@@ -1415,7 +2314,6 @@ fn test_this() {
         });
         obj.one
     "#, 1.0);
-    */
     assert_eval!(r#"
         var Class = function() { this.prop = true; }
         var obj = new Class();
@@ -1423,6 +2321,125 @@ fn test_this() {
     "#, true);
 }
 
+#[test]
+fn test_strict_mode() {
+    assert_eval!("(function() { 'use strict'; return this; })()", null);
+    assert_eval!("(function() { 'use strict'; return typeof this; })()", "undefined");
+    assert_eval!("(function() { 'use strict'; return this; }).call(undefined)", null);
+    assert_eval!("(function() { return this; }).call(undefined) == global", true);
+    assert_eval!("'use strict'; this.hasOwnProperty('undefined')", true);
+    assert_eval!("'use strict'; this == global", true);
+    assert_eval!(r#"
+        function f() { 'use strict'; return function() { return this; }; }
+        f()() === undefined
+    "#, true);
+    // only a prologue directive counts
+    assert_eval!("(function() { var a; 'use strict'; return this; })() == global", true);
+    // `thisArg` is not boxed for strict mode functions
+    assert_eval!(r#"
+        function f() { 'use strict'; return this; }
+        [f.call(null) === null, f.call() === undefined, f.call(5) === 5,
+         f.apply('s', []) === 's', typeof f.apply('s', []), f.bind(true)() === true,
+         Reflect.apply(f, 1n, []) === 1n] + ''
+    "#, "true,true,true,true,string,true,true");
+    assert_eval!(r#"
+        var seen;
+        new Map([[1, 2]]).forEach(function() { 'use strict'; seen = this; }, 7);
+        seen === 7
+    "#, true);
+    assert_eval!("(function() { return this; }).call(5) instanceof Number", true);
+    assert_eval!("(function() { return this; }).call(null) == global", true);
+    // `eval()` sees `this` of its caller, even in tail position
+    assert_eval!(r#"
+        function f() { 'use strict'; return eval('this'); }
+        [f() === undefined, f.call(7) === 7] + ''
+    "#, "true,true");
+    assert_eval!("(function() { 'use strict'; var a = 1; return eval('a'); })()", 1.0);
+
+    assert_exception!("(function() { 'use strict'; undeclared = 1; })()", Exception::Reference);
+    assert_exception!("'use strict'; undeclared = 1", Exception::Reference);
+    assert_exception!(r#"
+        'use strict';
+        for (undeclared in {a: 1}) {}
+    "#, Exception::Reference);
+    assert_eval!("(function() { undeclared = 1; })(); undeclared", 1.0);
+
+    assert_exception!(r#"
+        'use strict';
+        var o = {};
+        Object.defineProperty(o, 'x', {value: 1});
+        o.x = 2
+    "#, Exception::Type);
+    assert_exception!(r#"
+        'use strict';
+        var o = { get x() { return 1; } };
+        o.x = 2
+    "#, Exception::Type);
+    assert_exception!(r#"
+        'use strict';
+        var o = {};
+        Object.defineProperty(o, 'x', {value: 1});
+        delete o.x
+    "#, Exception::Type);
+    assert_eval!(r#"
+        var o = {};
+        Object.defineProperty(o, 'x', {value: 1});
+        o.x = 2;
+        (delete o.x) || o.x
+    "#, 1.0);
+
+    assert_exception!("function f(a, a) { 'use strict'; }", Exception::Syntax);
+    assert_exception!("'use strict'; function f(a, a) {}", Exception::Syntax);
+    assert_eval!("function f(a, a) { return a; }; f(1, 2)", 2.0);
+
+    // arguments are not mapped to parameters
+    assert_eval!(r#"
+        function f(a) { 'use strict'; a = 2; return arguments[0]; }
+        f(1)
+    "#, 1.0);
+}
+
+#[test]
+fn test_tail_calls() {
+    assert_eval!(r#"
+        'use strict';
+        function even(n) { return n == 0 ? true : odd(n - 1); }
+        function odd(n) { return n == 0 ? false : (n, even(n - 1)); }
+        even(1000000)
+    "#, true);
+    assert_eval!(r#"
+        'use strict';
+        var o = { v: 5, f: function(n) { return n == 0 ? this.v : o.f(n - 1); } };
+        o.f(10)
+    "#, 5.0);
+    assert_eval!(r#"
+        'use strict';
+        function h() { return 5; }
+        function C() { this.y = 2; return h(); }
+        new C().y
+    "#, 2.0);
+    // calls inside of `try` are not in tail position
+    assert_eval!(r#"
+        'use strict';
+        function g() { throw 1; }
+        function f() { try { return g(); } catch (e) { return 'caught'; } }
+        f()
+    "#, "caught");
+    assert_eval!(r#"
+        'use strict';
+        var log = '';
+        function g() { log += 'g'; return 1; }
+        function f() { try { return g(); } finally { log += 'finally'; } }
+        f() + log
+    "#, "1gfinally");
+
+    // only in strict mode
+    assert_exception!(r#"
+        function f(n) { return n == 0 ? 0 : f(n - 1); }
+        f(1000000)
+    "#, Exception::Range);
+}
+
 #[test]
 fn test_arrays() {
     assert_eval!( "[]",   [] );
@@ -1437,6 +2454,53 @@ fn test_arrays() {
     // .length
     assert_eval!( "var a = ['zero', 'one']; a.length", 2.0 );
     assert_eval!( "var a = ['zero', 'one']; a[2] = 'two'; a.length", 3.0 );
+    assert_eval!( "var a = [1, 2, 3]; a.length = 1; a", [1.0] );
+    assert_eval!( "var a = [1, 2, 3]; a.length = 1; a.length = 3; [a.length, 1 in a]", [3.0, false] );
+    assert_eval!( "var a = [1, 2]; a.length = '4'; a.length", 4.0 );
+    assert_exception!( "var a = []; a.length = -1", Exception::Range );
+    assert_exception!( "var a = []; a.length = 1.5", Exception::Range );
+    assert_eval!( "var a = [1, 2]; a.length = null; a.length", 0.0 );
+    assert_eval!( "var a = [1, 2]; a.length = true; a", [1.0] );
+    assert_eval!( "var a = [1, 2]; a.length = false; a.length", 0.0 );
+    assert_eval!( "var a = [1, 2]; a.length = {valueOf() { return 1 }}; a", [1.0] );
+    assert_eval!( "var a = [1]; a.length = {toString() { return '3' }}; a.length", 3.0 );
+    assert_eval!( "var a = [1, 2]; Object.defineProperty(a, 'length', {value: {valueOf() { return 0 }}}); a.length", 0.0 );
+    assert_exception!( "var a = []; a.length = undefined", Exception::Range );
+    assert_exception!( "var a = []; a.length = {valueOf() { return -1 }}", Exception::Range );
+    assert_eval!(r#"
+        var a = [1, 2];
+        Object.defineProperty(a, 'length', {writable: false});
+        a.length = 0; a[2] = 3;
+        [a.length, a[2]]
+    "#, [2.0, null]);
+    assert_exception!(r#"
+        var a = [1];
+        Object.defineProperty(a, 'length', {writable: false});
+        a.push(2)
+    "#, Exception::Type);
+
+    // integer keys
+    assert_eval!( "var a = []; for (var i = 0; i < 5; i++) a[i] = i * i; a",
+                  [0.0, 1.0, 4.0, 9.0, 16.0] );
+    assert_eval!( "var a = [1, 2]; [a[1.0], a['1'], a[-1], a[1.5], a[2]]",
+                  [2.0, 2.0, null, null, null] );
+    assert_eval!( "var a = [1, 2]; a[-1] = 'neg'; a[1.5] = 'frac'; [a.length, a['-1'], a['1.5']]",
+                  [2.0, "neg", "frac"] );
+    assert_eval!( "Object.prototype[1] = 'proto'; var a = [0, , 2]; a[1]", "proto" );
+    assert_eval!( "var a = [1, 2]; var i = 0; a[i++] += 10; a[i]++; [a, i]", [[11.0, 3.0], 1.0] );
+
+    // holes and sparse arrays
+    assert_eval!( "[1, , 3].length",  3.0 );
+    assert_eval!( "[1, ,].length",    2.0 );
+    assert_eval!( "[, 1, , null, undefined, 2].toString()", ",1,,,,2" );
+    assert_eval!( "[1 in [1, , 3], 1 in [1, undefined, 3]]", [false, true] );
+    assert_eval!( "var a = [1, 2]; delete a[0]; [0 in a, a.length]", [false, 2.0] );
+    assert_eval!( "var a = []; a[4294967294] = 1; a.length", 4294967295.0 );
+    assert_eval!( "var a = []; a[4294967295] = 1; [a.length, a[4294967295]]", [0.0, 1.0] );
+    assert_eval!( "var a = []; a[1e9] = 'x'; a[1e9] + a.length", "x1000000001" );
+    assert_eval!( "var a = []; a[1e9] = 'x'; a.length = 5; [a.length, a[1e9]]", [5.0, null] );
+    assert_eval!( "var a = [0]; a[2000] = 2; a[1000] = 1; a[2] = 0; a[1000] + a[2000]", 3.0 );
+    assert_eval!( "var n = 0, s = 0; for (var k in [1, , 3]) { n++; s += k }; [n, s]", [2.0, 2.0] );
 
     // Array.prototype.push()
     assert_eval!("var a = []; a.push(1); a", [1.0]);
@@ -1493,4 +2557,375 @@ fn test_eval() {
     assert_eval!("eval('2 + 2')",  4.0);
 }
 
+/// Evaluates `input` and collects everything it writes to `console`.
+fn console_output(input: &str) -> Vec<(LogLevel, String)> {
+    use std::{cell::RefCell, rc::Rc};
+
+    let output = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&output);
+
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    js.set_console(Box::new(move |level: LogLevel, message: &str| {
+        sink.borrow_mut().push((level, message.to_string()));
+    }));
+    js.evaluate(input).expect("evaluate");
+
+    let output = output.borrow().clone();
+    output
+}
+
+fn console_log(input: &str) -> String {
+    let lines = console_output(input).into_iter().map(|(_, line)| line).collect::<Vec<_>>();
+    lines.join("\n")
+}
+
+#[test]
+fn test_console() {
+    use LogLevel::*;
+
+    assert_eq!(console_output("console.log('hello', 'world')"), vec![(Log, "hello world".to_string())]);
+    assert_eq!(
+        console_output("console.info(1); console.warn(2); console.error(3); console.debug(4)"),
+        vec![(Info, "1".into()), (Warn, "2".into()), (Error, "3".into()), (Debug, "4".into())]
+    );
+
+    // util.inspect()
+    assert_eq!(console_log("console.log([undefined, null, -0, true, 'str'])"), "[ undefined, null, -0, true, 'str' ]");
+    assert_eq!(console_log("console.log({a: 1, 'g h': {b: [1, 2]}})"), "{ a: 1, 'g h': { b: [ 1, 2 ] } }");
+    assert_eq!(console_log("console.log({}, [], \"it's\", [\"it's\"])"), "{} [] it's [ \"it's\" ]");
+    assert_eq!(console_log("console.log({a: {b: {c: {d: 1}}}}, [[[[1]]]])"), "{ a: { b: { c: [Object] } } } [ [ [ [Array] ] ] ]");
+    assert_eq!(console_log("var o = {a: 1}; o.self = o; console.log(o)"), "{ a: 1, self: [Circular] }");
+    assert_eq!(console_log("var a = [1]; a.push(a); console.log(a)"), "[ 1, [Circular] ]");
+    assert_eq!(console_log("var a = [1, , 3, , ,]; a[1e9] = 1; console.log(a)"), "[ 1, <1 empty item>, 3, <999999997 empty items>, 1 ]");
+    assert_eq!(
+        console_log("function foo() {}; console.log(foo, function() {}, foo.bind(null))"),
+        "[Function: foo] [Function (anonymous)] [Function: bound foo]"
+    );
+    assert_eq!(console_log("console.log(new Map([['a', 1], [2, {b: 2}]]), new Set([1, 'two']))"), "Map(2) { 'a' => 1, 2 => { b: 2 } } Set(2) { 1, 'two' }");
+    assert_eq!(console_log("console.log(new Error('boom'), new Boolean(true))"), "[Error: boom] [Boolean: true]");
+    assert_eq!(console_log("function P(x) { this.x = x }; console.log(new P(1), Object.create(null))"), "P { x: 1 } [Object: null prototype] {}");
+
+    // long outputs are broken into lines
+    assert_eq!(
+        console_log("console.log({longkeyname1: 'a long string value', longkeyname2: 'another long value', k3: 3})"),
+        "{\n  k3: 3,\n  longkeyname1: 'a long string value',\n  longkeyname2: 'another long value'\n}"
+    );
+    assert_eq!(console_log("console.log([1, 2, 3, 4, 5, 6, 7, 8, 9, 10])"), "[\n  1, 2, 3, 4,  5,\n  6, 7, 8, 9, 10\n]");
+    assert_eq!(
+        console_log("console.log(['apple', 'banana', 'cherry', 'date', 'elderberry', 'fig', 'grape'])"),
+        "[ 'apple', 'banana', 'cherry', 'date', 'elderberry', 'fig', 'grape' ]"
+    );
+
+    // util.format()
+    assert_eq!(console_log("console.log('%s is %d years', 'Bob', '42', 'extra', {x: 1})"), "Bob is 42 years extra { x: 1 }");
+    assert_eq!(console_log("console.log('%i%% %f %O %s', 4.5, '1.5e1x', [1], 'rest')"), "4% 15 [ 1 ] rest");
+    assert_eq!(console_log("console.log('%s %s', 1)"), "1 %s");
+
+    // assert
+    assert_eq!(
+        console_output("console.assert(1 == 2, 'nope %s', 'x'); console.assert(false); console.assert(true, 'no')"),
+        vec![(Warn, "Assertion failed: nope x".into()), (Warn, "Assertion failed".into())]
+    );
+
+    // time/timeEnd
+    let output = console_output("console.time(); console.timeEnd(); console.timeEnd('t')");
+    assert_eq!(output.len(), 2);
+    assert!(output[0].1.starts_with("default: ") && output[0].1.ends_with("ms"), "{:?}", output[0]);
+    assert_eq!(output[1], (Warn, "Warning: No such label 't' for console.timeEnd()".into()));
+
+    // table
+    assert_eq!(console_log("console.table([{a: 1, b: 'y'}, {a: 2, c: true}, 3])"), [
+        "┌─────────┬───┬─────┬──────┬────────┐",
+        "│ (index) │ a │ b   │ c    │ Values │",
+        "├─────────┼───┼─────┼──────┼────────┤",
+        "│ 0       │ 1 │ 'y' │      │        │",
+        "│ 1       │ 2 │     │ true │        │",
+        "│ 2       │   │     │      │ 3      │",
+        "└─────────┴───┴─────┴──────┴────────┘",
+    ].join("\n"));
+    assert_eq!(console_log("console.table({x: {v: 1}, y: {v: 22}})"), [
+        "┌─────────┬────┐",
+        "│ (index) │ v  │",
+        "├─────────┼────┤",
+        "│ x       │ 1  │",
+        "│ y       │ 22 │",
+        "└─────────┴────┘",
+    ].join("\n"));
+    assert_eq!(console_log("console.table(42)"), "42");
+}
+
+#[test]
+#[should_panic(expected = "stale")]
+fn test_stale_reference() {
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    let objref = js.heap.object_from_json(&json!({})).to_ref().unwrap();
+    js.heap.collect();
+    js.heap.object_from_json(&json!({}));
+    js.heap.get(objref);
+}
+
+#[test]
+fn test_slot_generations() {
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    let first = js.heap.object_from_json(&json!({})).to_ref().unwrap();
+
+    // a slot reused over and over runs out of generations and is retired
+    let mut reused = 0;
+    for _ in 0..=u16::MAX {
+        js.heap.collect();
+        let objref = js.heap.object_from_json(&json!({})).to_ref().unwrap();
+        assert!(!js.heap.is_valid(first), "{:?} aliases {:?}", first, objref);
+        if objref.index() == first.index() {
+            reused += 1;
+        }
+    }
+    assert!(0 < reused && reused < u16::MAX, "reused {} times", reused);
+}
+
+#[test]
+fn test_garbage_collection() {
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    js.evaluate(r#"
+        var o = {a: {b: 1}};
+        var arr = [o, new Map([[{}, 'map']]), new Set(['set'])];
+        var counter = (function() { var n = 0; return function() { return ++n } })();
+        var bound = function(x) { return this.k + x }.bind({k: 'k'}, 'x');
+        counter();
+    "#).unwrap();
+
+    let freed = js.heap.collect();
+    assert!(freed > 0, "the IIFE scope is garbage");
+    assert_eq!(js.heap.collect(), 0);
+
+    let result = js.evaluate("[arr[0].a.b, arr[1].size, arr[2].has('set'), counter(), bound()].toString()").unwrap();
+    assert_eq!(js.json_from(result), json!("1,1,true,2,kx"));
+
+    // rooted objects survive
+    let objref = js.heap.object_from_json(&json!({"x": [1, 2]})).to_ref().unwrap();
+    let rooted = js.heap.root(objref);
+    js.heap.collect();
+    assert_eq!(JSValue::from(rooted.objref()).to_json(&js.heap).unwrap(), json!({"x": [1.0, 2.0]}));
+    drop(rooted);
+    assert_eq!(js.heap.collect(), 2);
+
+    // stale references do not alias new objects
+    assert!(js.heap.try_get(objref).is_none());
+    let newref = js.heap.object_from_json(&json!({})).to_ref().unwrap();
+    assert!(js.heap.try_get(objref).is_none());
+    assert!(js.heap.is_valid(newref));
+
+    // calls in a loop do not grow the heap without bound
+    let before = js.heap.object_count();
+    js.evaluate(r#"
+        function f(x) { return {x: x}; }
+        for (var i = 0; i < 20000; i++) { f(i); }
+    "#).unwrap();
+    assert!(js.heap.object_count() < before + 20000, "{} objects", js.heap.object_count());
+
+    let result = js.evaluate("counter()").unwrap();
+    assert_eq!(js.json_from(result), json!(3.0));
+
+    // closures keep only the variables they use
+    let before = js.heap.object_count();
+    js.evaluate(r#"
+        var small = (function() {
+            var big = [];
+            for (var i = 0; i < 1000; i++) { big.push({i: i}); }
+            var n = big.length;
+            return function() { return n };
+        })();
+    "#).unwrap();
+    js.heap.collect();
+    assert!(js.heap.object_count() < before + 100, "{} objects", js.heap.object_count());
+    let result = js.evaluate("small()").unwrap();
+    assert_eq!(js.json_from(result), json!(1000.0));
+}
+
+
+#[test]
+fn test_fuel_and_interrupts() {
+    fn aborted(result: Result<JSValue, EvalError>, abort: Abort) -> bool {
+        matches!(result, Err(EvalError::Exception(Exception::Abort(a))) if a == abort)
+    }
+
+    for backend in BACKENDS {
+        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+        js.set_backend(backend);
+        js.set_fuel(Some(10_000));
+
+        // neither `catch` nor `finally` see an abort
+        let result = js.evaluate(r#"
+            var caught = 0;
+            for (;;) { try { while (true) {} } catch (e) { caught++ } finally { caught++ } }
+        "#);
+        assert!(aborted(result, Abort::OutOfFuel), "{:?}", backend);
+        let caught = js.evaluate("caught").unwrap();
+        assert_eq!(js.json_from(caught), json!(0.0));
+
+        // each evaluation gets its fuel
+        for _ in 0..3 {
+            let sum = js.evaluate("var n = 0; for (var i = 0; i < 5000; i++) n += i; n").unwrap();
+            assert_eq!(js.json_from(sum), json!(12497500.0));
+        }
+
+        // calls spend fuel too
+        js.set_fuel(Some(100));
+        let result = js.evaluate("function f(n) { return n ? f(n - 1) : 0 }; f(200)");
+        assert!(aborted(result, Abort::OutOfFuel), "{:?}", backend);
+        js.set_fuel(None);
+
+        // an interrupt stops the next loop iteration once
+        js.interrupt_handle().interrupt();
+        let result = js.evaluate("do {} while (true)");
+        assert!(aborted(result, Abort::Interrupted), "{:?}", backend);
+        let result = js.evaluate("var n = 0; for (var i = 0; i < 10; i++) n += i; n").unwrap();
+        assert_eq!(js.json_from(result), json!(45.0));
+
+        let handle = js.interrupt_handle();
+        let interrupter = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            handle.interrupt();
+        });
+        let result = js.evaluate("var o = {}; for (;;) { for (var k in o) {} }");
+        interrupter.join().unwrap();
+        assert!(aborted(result, Abort::Interrupted), "{:?}", backend);
+    }
+}
+
+#[test]
+fn test_memory_limit() {
+    fn out_of_memory(result: Result<JSValue, EvalError>) -> bool {
+        matches!(result, Err(EvalError::Exception(Exception::Abort(Abort::OutOfMemory))))
+    }
+
+    for backend in BACKENDS {
+        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+        js.set_backend(backend);
+        let baseline = js.memory_stats();
+        assert_eq!(baseline.limit, None);
+        assert!(baseline.objects > 0 && baseline.bytes() > 0);
+
+        // the estimate follows objects, elements and strings
+        js.evaluate("var a = []; for (var i = 0; i < 10000; i++) a.push(i)").unwrap();
+        let stats = js.memory_stats();
+        assert!(stats.object_bytes >= baseline.object_bytes + 10000 * 8, "{:?}", stats);
+        js.evaluate("var s = 'x'; for (var i = 0; i < 16; i++) s = s + s").unwrap();
+        let stats = js.memory_stats();
+        assert!(stats.string_bytes >= baseline.string_bytes + 65536, "{:?}", stats);
+        js.evaluate("a = s = null").unwrap();
+        js.heap.collect();
+
+        let used = js.memory_stats().bytes();
+        js.set_memory_limit(Some(used + (1 << 20)));
+
+        // garbage is collected before it counts against the limit
+        let result = js.evaluate(r#"
+            for (var i = 0; i < 50000; i++) { var o = {i: i, s: 'item ' + i} }
+            o.s
+        "#).unwrap();
+        assert_eq!(js.json_from(result), json!("item 49999"), "{:?}", backend);
+        // also inside of calls, while their callers wait with arguments
+        let result = js.evaluate(r#"
+            function churn(n) { for (var i = 0; i < n; i++) { var o = {a: i, b: [1, 2, 3]} } return o.a }
+            function pair(x, y) { return x.a + y }
+            (function() { return pair({a: 1}, churn(50000)) })()
+        "#).unwrap();
+        assert_eq!(js.json_from(result), json!(50000.0), "{:?}", backend);
+
+        // neither `catch` nor `finally` see running out of memory
+        let result = js.evaluate(r#"
+            var caught = 0, kept = [];
+            try { for (;;) kept.push({}) } catch (e) { caught++ } finally { caught++ }
+        "#);
+        assert!(out_of_memory(result), "{:?}", backend);
+        assert!(out_of_memory(js.evaluate("1 + 2")), "{:?}", backend);
+
+        // the runtime is usable again when the memory is freed
+        js.set_memory_limit(None);
+        let caught = js.evaluate("caught").unwrap();
+        assert_eq!(js.json_from(caught), json!(0.0));
+        js.evaluate("kept = null").unwrap();
+        js.heap.collect();
+        js.set_memory_limit(Some(used + (1 << 20)));
+        let result = js.evaluate("function grow() { var s = 'x'; for (;;) s = s + s }; grow()");
+        assert!(out_of_memory(result), "{:?}", backend);
+        let result = js.evaluate("1 + 2").unwrap();
+        assert_eq!(js.json_from(result), json!(3.0));
+    }
+}
+
+#[test]
+fn test_call_depth() {
+    assert_eval!(r#"
+        function f() { return f() + 1 }
+        var r; try { f() } catch (e) { r = String(e) } r
+    "#, "RangeError: Maximum call stack size exceeded");
+    // the stack is usable again after unwinding
+    assert_eval!(r#"
+        var depth = 0;
+        function g() { depth++; g() }
+        try { g() } catch (e) {}
+        function h(n) { return n ? 1 + h(n - 1) : 0 }
+        depth > 50 && h(50)
+    "#, 50.0);
+
+    for backend in BACKENDS {
+        let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+        js.set_backend(backend);
+        js.set_max_call_depth(20);
+        js.evaluate("function f(n) { return n ? f(n - 1) : 0 }").unwrap();
+        assert!(js.evaluate("f(10)").is_ok(), "{:?}", backend);
+        let result = js.evaluate("f(30)");
+        assert!(matches!(result, Err(EvalError::Exception(Exception::Range(_)))), "{:?}", backend);
+    }
+
+    // the tree walker recurses on nested expressions, guarded by the native stack limit
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    js.heap.set_stack_limit(0);
+    let result = js.evaluate("1 + (2 + (3 + 4))");
+    assert!(matches!(result, Err(EvalError::Exception(Exception::Range(_)))));
+}
+
+#[test]
+fn test_bytecode() {
+    let mut js = Runtime::load(Box::new(Parser::new())).expect("Runtime::load");
+    js.set_backend(Backend::Bytecode);
+
+    // the garbage collector sees the values on the VM stack
+    let before = js.heap.object_count();
+    let result = js.evaluate(r#"
+        function f(x) { return {x: x}; }
+        var keep = [];
+        for (var i = 0; i < 20000; i++) { keep[i % 10] = f(i); }
+        keep[9].x + keep[0].x
+    "#).unwrap();
+    assert_eq!(js.json_from(result), json!(19999.0 + 19990.0));
+    assert!(js.heap.object_count() < before + 20000, "{} objects", js.heap.object_count());
+
+    // JS-to-JS calls do not recurse on the Rust stack
+    let result = js.evaluate(r#"
+        function even(n) { return n == 0 ? true : odd(n - 1); }
+        function odd(n) { return n == 0 ? false : even(n - 1); }
+        even(100000)
+    "#).unwrap();
+    assert_eq!(js.json_from(result), json!(true));
+
+    // host functions calling back into bytecode closures
+    let result = js.evaluate(r#"
+        var add = function(a, b) { return this.k + a + b };
+        add.call({k: 1}, 2, 3) + add.bind({k: 10}, 20)(30)
+    "#).unwrap();
+    assert_eq!(js.json_from(result), json!(66.0));
+
+    // jumps are resolved at compile time
+    let program = js.parse("while (true) { if (x) break; }").unwrap();
+    let code = slothjs::bytecode::compile(&program).unwrap();
+    let len = code.ops().len();
+    for op in code.ops() {
+        if let slothjs::bytecode::Op::Jump(target) | slothjs::bytecode::Op::JumpIfFalse(target) = op {
+            assert!(*target <= len, "{}", code);
+        }
+    }
+}
+
 }
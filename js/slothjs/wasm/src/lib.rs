@@ -1,7 +1,9 @@
 use core::cell::RefCell;
 use core::fmt;
 use slothjs::{
+    ConsoleSink,
     Heap,
+    LogLevel,
     Program,
     //Interpretable,
     JSON,
@@ -14,7 +16,7 @@ use wasm_bindgen::prelude::*;
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 thread_local! {
-    static HEAP: RefCell<Heap> = RefCell::new(Heap::new());
+    static HEAP: RefCell<Heap> = RefCell::new(new_heap());
 }
 
 #[wasm_bindgen]
@@ -22,6 +24,48 @@ extern "C" {
     fn alert(s: &str);
 }
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = log)]
+    fn console_log(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    fn console_info(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(s: &str);
+
+    #[wasm_bindgen(js_namespace = Date, js_name = now)]
+    fn date_now() -> f64;
+}
+
+/// Routes `console` of sljs to the browser console.
+struct BrowserConsole;
+
+impl ConsoleSink for BrowserConsole {
+    fn write(&mut self, level: LogLevel, message: &str) {
+        match level {
+            LogLevel::Log => console_log(message),
+            LogLevel::Info => console_info(message),
+            LogLevel::Warn => console_warn(message),
+            LogLevel::Error => console_error(message),
+            LogLevel::Debug => console_debug(message),
+        }
+    }
+
+    fn now(&self) -> Option<f64> {
+        Some(date_now())
+    }
+}
+
+fn new_heap() -> Heap {
+    let mut heap = Heap::new();
+    heap.set_console(Box::new(BrowserConsole));
+    heap
+}
+
 #[wasm_bindgen(start)]
 pub fn run() {
     #[cfg(feature = "console_error_panic_hook")]
@@ -37,19 +81,20 @@ pub fn interpret_string(json_ast: &str) -> Result<JsValue, JsValue> {
     let json: JSON = serde_json::from_str(json_ast).map_err(jserror)?;
     let program = Program::parse_from(&json).map_err(jserror)?;
 
-    let mut heap = Heap::new();
+    let mut heap = new_heap();
     let result = heap
         .evaluate(&program)
         .map_err(jserror)?
         // TODO: implement serde::Serializer directly for JSValue?
         .to_json(&heap)
         .map_err(jserror)?;
-    JsValue::from_serde(&result).map_err(jserror)
+    js_sys::JSON::parse(&result.to_string())
 }
 
 #[wasm_bindgen]
 pub fn interpret(jsobject: &JsValue) -> Result<JsValue, JsValue> {
-    let json: JSON = jsobject.into_serde().map_err(jserror)?;
+    let json_ast = String::from(js_sys::JSON::stringify(jsobject)?);
+    let json: JSON = serde_json::from_str(&json_ast).map_err(jserror)?;
     let program = Program::parse_from(&json).map_err(jserror)?;
     let result = HEAP
         .with(|heapcell| {
@@ -57,5 +102,5 @@ pub fn interpret(jsobject: &JsValue) -> Result<JsValue, JsValue> {
             heap.evaluate(&program)?.to_string(&mut heap)
         })
         .map_err(jserror)?;
-    JsValue::from_serde(result.as_str()).map_err(jserror)
+    Ok(JsValue::from_str(result.as_str()))
 }